@@ -173,7 +173,7 @@ export class Metrics {
   }
 
   private checkAccountErrorNextIndex(stakeAccount: StakeAccount) {
-    const nextIndex = stakeAccount.stakeAccountMetadata.nextIndex;
+    const nextIndex = stakeAccount.getNextIndex();
     for (let i = 0; i < nextIndex; i++) {
       if (!stakeAccount.stakeAccountPositionsJs.positions[i]) {
         this.accountError.set(
@@ -183,7 +183,11 @@ export class Metrics {
         return;
       }
     }
-    for (let i = nextIndex; i < wasm.Constants.MAX_POSITIONS(); i++) {
+    for (
+      let i = nextIndex;
+      i < stakeAccount.stakeAccountPositionsJs.positions.length;
+      i++
+    ) {
       if (stakeAccount.stakeAccountPositionsJs.positions[i]) {
         this.accountError.set(
           { type: "next_index", address: stakeAccount.address.toBase58() },
@@ -6,16 +6,21 @@ import { Position, wasm } from "./StakeConnection";
 export class PositionAccountJs {
   public owner: PublicKey;
   public positions: Position[];
+  // Only set for the V2 layout, V1 accounts store it in the stake account metadata
+  public nextIndex: number | undefined;
 
   constructor(buffer: Buffer, idl: Staking) {
     const coder = new BorshCoder(idl);
     let i = 8; // Skip discriminator
     this.owner = new PublicKey(buffer.slice(i, i + 32));
-    let numberOfPositions = Math.floor(
-      (buffer.length - wasm.Constants.POSITIONS_ACCOUNT_SIZE()) /
-        wasm.Constants.POSITION_BUFFER_SIZE()
-    );
     i += 32;
+    let positionBufferSize = wasm.Constants.POSITION_BUFFER_SIZE();
+    if (buffer[i] === wasm.Constants.POSITIONS_V2_VERSION()) {
+      this.nextIndex = buffer.readUInt16LE(i + 2);
+      i += wasm.Constants.POSITIONS_V2_HEADER_LEN();
+      positionBufferSize = wasm.Constants.POSITION_BUFFER_SIZE_V2();
+    }
+    let numberOfPositions = Math.floor((buffer.length - i) / positionBufferSize);
     this.positions = [];
     for (let j = 0; j < numberOfPositions; j++) {
       if (buffer[i] === 1) {
@@ -25,7 +30,7 @@ export class PositionAccountJs {
       } else {
         this.positions.push(null);
       }
-      i += wasm.Constants.POSITION_BUFFER_SIZE();
+      i += positionBufferSize;
     }
   }
 }
@@ -74,6 +74,49 @@ export type Target = IdlTypes<Staking>["targetWithParameters"];
 export type Position = IdlTypes<Staking>["position"];
 export type TargetWithParameters = IdlTypes<Staking>["targetWithParameters"];
 
+function serializeConfig(config: GlobalConfig): Buffer {
+  const coder = new BorshCoder(IDL as Staking);
+  return coder.types.encode("globalConfig", config);
+}
+
+/**
+ * Returns the epoch of `unixTime`. Governance can change the epoch duration, so
+ * the epoch depends on all the epoch parameters of the config.
+ */
+export function timeToEpoch(config: GlobalConfig, unixTime: BN): BN {
+  return new BN(
+    wasm
+      .timeToEpoch(serializeConfig(config), BigInt(unixTime.toString()))
+      .toString()
+  );
+}
+
+/**
+ * Returns the voting and integrity pool unlocking durations of the config as
+ * the wasm functions take them, `undefined` means the default duration.
+ */
+export function getUnlockingDurations(
+  config: GlobalConfig
+): [bigint | undefined, bigint | undefined] {
+  return [
+    config.votingUnlockingDuration
+      ? BigInt(config.votingUnlockingDuration.toString())
+      : undefined,
+    config.integrityPoolUnlockingDuration
+      ? BigInt(config.integrityPoolUnlockingDuration.toString())
+      : undefined,
+  ];
+}
+
+/** Returns the Unix time at which `epoch` starts */
+export function epochToTime(config: GlobalConfig, epoch: BN): BN {
+  return new BN(
+    wasm
+      .epochToTime(serializeConfig(config), BigInt(epoch.toString()))
+      .toString()
+  );
+}
+
 export class StakeConnection {
   program: Program<Staking>;
   provider: AnchorProvider;
@@ -399,7 +442,7 @@ export class StakeConnection {
     const positions = stakeAccount.stakeAccountPositionsJs.positions;
 
     const time = await this.getTime();
-    const currentEpoch = time.div(this.config.epochDuration);
+    const currentEpoch = timeToEpoch(this.config, time);
 
     const sortPositions = positions
       .map((value, index) => {
@@ -418,7 +461,8 @@ export class StakeConnection {
           [wasm.PositionState.LOCKED, wasm.PositionState.LOCKING].includes(
             stakeAccount.stakeAccountPositionsWasm.getPositionState(
               el.index,
-              BigInt(currentEpoch.toString())
+              BigInt(currentEpoch.toString()),
+              ...getUnlockingDurations(this.config)
             )
           )
       )
@@ -1021,7 +1065,7 @@ export class StakeConnection {
    * The formula is n_staked_tokens = scaling_factor * n_voter_weight
    */
   public getScalingFactor(): number {
-    let currentEpoch = new BN(Date.now() / 1000).div(this.config.epochDuration);
+    let currentEpoch = timeToEpoch(this.config, new BN(Date.now() / 1000));
     let currentAmountLocked = Number(
       this.votingAccountMetadataWasm.getCurrentAmountLocked(
         BigInt(currentEpoch.toString())
@@ -1058,11 +1102,13 @@ export class StakeConnection {
     const positionAccountWasm = new wasm.WasmPositionData(positionAccountData);
 
     const time = new BN(Date.now() / 1000);
-    const currentEpoch = time.div(this.config.epochDuration);
+    const currentEpoch = timeToEpoch(this.config, time);
     const currentEpochBI = BigInt(currentEpoch.toString());
 
-    const lockedBalanceSummary =
-      positionAccountWasm.getLockedBalanceSummary(currentEpochBI);
+    const lockedBalanceSummary = positionAccountWasm.getLockedBalanceSummary(
+      currentEpochBI,
+      ...getUnlockingDurations(this.config)
+    );
 
     const epochOfFirstStake: BN = positionAccountJs.positions.reduce(
       (prev: BN | undefined, curr) => {
@@ -1079,9 +1125,10 @@ export class StakeConnection {
     );
 
     // Default to the start of the next epoch if there are no positions
-    const timeOfFirstStake = (
+    const timeOfFirstStake = epochToTime(
+      this.config,
       epochOfFirstStake ?? currentEpoch.add(new BN(1))
-    ).mul(this.config.epochDuration);
+    );
 
     return {
       owner: positionAccountJs.owner,
@@ -1227,6 +1274,14 @@ export class StakeAccount {
     this.config = config;
   }
 
+  /** Gets the number of positions, V1 positions accounts store it in the stake account metadata */
+  public getNextIndex(): number {
+    return (
+      this.stakeAccountPositionsJs.nextIndex ??
+      this.stakeAccountMetadata.nextIndex
+    );
+  }
+
   // Withdrawable
 
   //Locked tokens :
@@ -1245,12 +1300,15 @@ export class StakeAccount {
         : undefined
     );
 
-    const currentEpoch = unixTime.div(this.config.epochDuration);
+    const currentEpoch = timeToEpoch(this.config, unixTime);
     const currentEpochBI = BigInt(currentEpoch.toString());
 
     const unvestedBN = new BN(unvestedBalance.toString());
     const lockedSummaryBI =
-      this.stakeAccountPositionsWasm.getLockedBalanceSummary(currentEpochBI);
+      this.stakeAccountPositionsWasm.getLockedBalanceSummary(
+        currentEpochBI,
+        ...getUnlockingDurations(this.config)
+      );
 
     let lockingBN = new BN(lockedSummaryBI.locking.toString());
     let lockedBN = new BN(lockedSummaryBI.locked.toString());
@@ -1363,7 +1421,7 @@ export class StakeAccount {
   }
 
   public getVoterWeight(unixTime: BN): PythBalance {
-    let currentEpoch = unixTime.div(this.config.epochDuration);
+    let currentEpoch = timeToEpoch(this.config, unixTime);
 
     const voterWeightBI = this.stakeAccountPositionsWasm.getVoterWeight(
       BigInt(currentEpoch.toString()),
@@ -1371,7 +1429,8 @@ export class StakeAccount {
         this.votingAccountMetadataWasm.getCurrentAmountLocked(
           BigInt(currentEpoch.toString())
         )
-      )
+      ),
+      ...getUnlockingDurations(this.config)
     );
 
     return new PythBalance(new BN(voterWeightBI.toString()));
@@ -1419,8 +1478,14 @@ export class StakeAccount {
     }
   }
 
+  // positions closed now are unlocked once they went through the current epoch
+  // and the voting unlocking duration, which defaults to 1 epoch
   private addUnlockingPeriod(unixTime: BN) {
-    return unixTime.add(this.config.epochDuration.mul(new BN(2)));
+    const unlockingDuration = this.config.votingUnlockingDuration ?? new BN(1);
+    return epochToTime(
+      this.config,
+      timeToEpoch(this.config, unixTime).add(new BN(1)).add(unlockingDuration)
+    );
   }
 
   public getNetExcessGovernanceAtVesting(unixTime: BN): BN {
@@ -46,6 +46,9 @@ async function main() {
   );
 
   await client.sendTransaction(tx, [AUTHORITY_KEYPAIR]);
+  console.log(
+    "The multisig needs to call acceptGovernanceAuthority to become the governance authority"
+  );
 }
 
 main();
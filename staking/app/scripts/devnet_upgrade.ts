@@ -309,7 +309,7 @@ async function upgrade(
     account.position.publicKey
   );
   stakeAccount.getBalanceSummary(await stakeConnection.getTime());
-  const nextIndex = stakeAccount.stakeAccountMetadata.nextIndex;
+  const nextIndex = stakeAccount.getNextIndex();
   if (nextIndex > 0)
     assert(
       stakeAccount.stakeAccountPositionsJs.positions[nextIndex - 1] != null
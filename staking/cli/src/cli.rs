@@ -1,6 +1,7 @@
 //! CLI options
 use {
     clap::{
+        ArgEnum,
         Parser,
         Subcommand,
     },
@@ -58,6 +59,8 @@ pub enum Action {
         pool_data_keypair:        Keypair,
         #[clap(long, help = "Y parameter")]
         y:                        u64,
+        #[clap(long, help = "Maximum delegation fee parameter")]
+        max_delegation_fee:       u64,
         #[clap(long, help = "Reward program authority parameter")]
         reward_program_authority: Pubkey,
         #[clap(long, help = "Slash custody parameter")]
@@ -79,6 +82,28 @@ pub enum Action {
         #[clap(long, help = "New fee")]
         delegation_fee: u64,
     },
+    UpdateMaxDelegationFee {
+        #[clap(long, help = "New maximum fee")]
+        max_delegation_fee: u64,
+    },
+    UpdateFeeNoticeEpochs {
+        #[clap(
+            long,
+            help = "Number of epochs of notice before a fee increase takes effect"
+        )]
+        fee_notice_epochs: u64,
+    },
+    SetPublisherDelegationFee {
+        #[clap(long, help = "Publisher")]
+        publisher:                         Pubkey,
+        #[clap(long, help = "New fee")]
+        delegation_fee:                    u64,
+        #[clap(
+            long,
+            help = "Publisher stake account positions, required if the signer is its owner"
+        )]
+        publisher_stake_account_positions: Option<Pubkey>,
+    },
     SetPublisherStakeAccount {
         #[clap(long, help = "Publisher")]
         publisher:               Pubkey,
@@ -95,6 +120,26 @@ pub enum Action {
         #[clap(long, help = "New reward program authority")]
         new_reward_program_authority: Pubkey,
     },
+    #[clap(about = "Nominate a new authority, the signer needs to be the current authority")]
+    NominateAuthority {
+        #[clap(long, arg_enum, help = "Authority to replace")]
+        authority:     Authority,
+        #[clap(long, help = "Nominated authority")]
+        new_authority: Pubkey,
+    },
+    #[clap(
+        about = "Accept a nomination, the signer needs to be the nominated authority or the reward \
+                 program authority if the pool config is nominated as the pool authority"
+    )]
+    AcceptAuthority {
+        #[clap(long, arg_enum, help = "Authority to accept")]
+        authority: Authority,
+    },
+    #[clap(about = "Cancel a nomination, the signer needs to be the current authority")]
+    CancelAuthorityTransfer {
+        #[clap(long, arg_enum, help = "Authority whose nomination is cancelled")]
+        authority: Authority,
+    },
     Slash {
         #[clap(long, help = "Publisher")]
         publisher:               Pubkey,
@@ -115,6 +160,18 @@ pub enum Action {
         #[clap(long, help = "Minimum staked tokens")]
         min_staked: u64,
     },
+    #[clap(about = "List the pending split requests")]
+    ListSplitRequests {},
+}
+
+/// Authorities that are handed over in two steps, the pool authority is nominated by the
+/// governance authority
+#[derive(ArgEnum, Clone, Copy, Debug)]
+pub enum Authority {
+    Governance,
+    Pda,
+    Pool,
+    RewardProgram,
 }
 
 pub enum SignerSource {
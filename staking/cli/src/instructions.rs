@@ -1,4 +1,5 @@
 use {
+    crate::cli::Authority,
     anchor_lang::{
         AccountDeserialize,
         Discriminator,
@@ -22,25 +23,29 @@ use {
             get_delegation_record_address,
             get_pool_config_address,
             get_pool_reward_custody_address,
+            get_reward_index_history_address,
             get_slash_event_address,
         },
         staking::pda::{
             get_config_address,
+            get_split_request_address,
             get_stake_account_custody_address,
             get_stake_account_custody_authority_address,
             get_stake_account_metadata_address,
             get_target_address,
         },
+        utils::events::parse_events,
     },
     integrity_pool::{
+        events::PoolAdvanced,
         state::{
             delegation_record::DelegationRecord,
             pool::{
                 PoolConfig,
                 PoolData,
             },
+            reward_index::RewardIndex,
         },
-        utils::clock::EPOCH_DURATION,
     },
     publisher_caps::PublisherCaps,
     pythnet_sdk::wire::v1::{
@@ -50,13 +55,18 @@ use {
     },
     reqwest::blocking::Client,
     serde_wormhole::RawMessage,
-    solana_account_decoder::UiAccountEncoding,
+    solana_account_decoder::{
+        UiAccountEncoding,
+        UiDataSliceConfig,
+    },
     solana_client::{
+        client_error::ClientError,
         nonblocking::rpc_client::RpcClient,
         rpc_config::{
             RpcAccountInfoConfig,
             RpcProgramAccountsConfig,
             RpcSendTransactionConfig,
+            RpcTransactionConfig,
         },
         rpc_filter::{
             Memcmp,
@@ -67,7 +77,10 @@ use {
     solana_sdk::{
         commitment_config::CommitmentConfig,
         compute_budget::ComputeBudgetInstruction,
-        instruction::Instruction,
+        instruction::{
+            AccountMeta,
+            Instruction,
+        },
         pubkey::Pubkey,
         rent::Rent,
         signature::{
@@ -85,6 +98,7 @@ use {
             TransactionError,
         },
     },
+    solana_transaction_status::UiTransactionEncoding,
     staking::{
         state::{
             global_config::GlobalConfig,
@@ -97,9 +111,13 @@ use {
                 Target,
                 TargetWithParameters,
             },
+            split_request::SplitRequest,
             stake_account::StakeAccountMetadataV2,
         },
-        utils::voter_weight::compute_voter_weight,
+        utils::{
+            clock::time_to_epoch,
+            voter_weight::compute_voter_weight,
+        },
     },
     std::{
         cmp::min,
@@ -306,6 +324,28 @@ pub async fn process_transaction(
     }
 }
 
+pub async fn fetch_transaction_logs(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+) -> Result<Vec<String>, ClientError> {
+    let transaction = rpc_client
+        .get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding:                          Some(UiTransactionEncoding::Json),
+                commitment:                        Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .await?;
+
+    Ok(transaction
+        .transaction
+        .meta
+        .and_then(|meta| meta.log_messages.into())
+        .unwrap_or_default())
+}
+
 pub async fn process_write_encoded_vaa(
     rpc_client: &RpcClient,
     vaa: &[u8],
@@ -498,6 +538,7 @@ pub async fn advance(rpc_client: &RpcClient, payer: &dyn Signer, publisher_caps:
         publisher_caps,
         pool_data,
         pool_reward_custody,
+        config_account: get_config_address(),
     };
 
     let instruction_data = integrity_pool::instruction::Advance {};
@@ -508,7 +549,7 @@ pub async fn advance(rpc_client: &RpcClient, payer: &dyn Signer, publisher_caps:
         data:       instruction_data.data(),
     };
 
-    process_transaction(
+    let signature = process_transaction(
         rpc_client,
         &[
             instruction,
@@ -518,6 +559,27 @@ pub async fn advance(rpc_client: &RpcClient, payer: &dyn Signer, publisher_caps:
     )
     .await
     .unwrap();
+
+    // the pool already advanced, the events are only informative
+    let logs = match fetch_transaction_logs(rpc_client, &signature).await {
+        Ok(logs) => logs,
+        Err(err) => {
+            println!("Failed to fetch the logs of {signature}: {err:?}");
+            return;
+        }
+    };
+    for PoolAdvanced {
+        epoch,
+        current_epoch,
+        y,
+        total_eligible_delegation,
+    } in parse_events::<PoolAdvanced>(&logs)
+    {
+        println!(
+            "Advanced to epoch {current_epoch}, epoch {epoch} has y {y} and a total eligible \
+             delegation of {total_eligible_delegation}"
+        );
+    }
 }
 
 pub async fn initialize_pool(
@@ -526,6 +588,7 @@ pub async fn initialize_pool(
     pool_data_keypair: &Keypair,
     reward_program_authority: Pubkey,
     y: u64,
+    max_delegation_fee: u64,
     slash_custody: Pubkey,
 ) {
     let pool_data_space: u64 = PoolData::LEN.try_into().unwrap();
@@ -549,6 +612,7 @@ pub async fn initialize_pool(
     let initialize_pool_data = integrity_pool::instruction::InitializePool {
         reward_program_authority,
         y,
+        max_delegation_fee,
     };
 
     let initialize_pool_accs = integrity_pool::accounts::InitializePool {
@@ -584,7 +648,15 @@ pub async fn get_current_time(rpc_client: &RpcClient) -> i64 {
 pub async fn get_current_epoch(rpc_client: &RpcClient) -> u64 {
     let slot = rpc_client.get_slot().await.unwrap();
     let blocktime = rpc_client.get_block_time(slot).await.unwrap();
-    blocktime as u64 / EPOCH_DURATION
+    let config = GlobalConfig::try_deserialize(
+        &mut rpc_client
+            .get_account_data(&get_config_address())
+            .await
+            .unwrap()
+            .as_slice(),
+    )
+    .unwrap();
+    time_to_epoch(&config, blocktime).unwrap()
 }
 
 pub async fn fetch_publisher_caps_and_advance(
@@ -694,6 +766,7 @@ pub async fn update_delegation_fee(
         reward_program_authority: payer.pubkey(),
         pool_config,
         pool_data,
+        config_account: get_config_address(),
         system_program: system_program::ID,
     };
 
@@ -710,6 +783,111 @@ pub async fn update_delegation_fee(
         .unwrap();
 }
 
+pub async fn update_max_delegation_fee(
+    rpc_client: &RpcClient,
+    signer: &dyn Signer,
+    max_delegation_fee: u64,
+) {
+    let pool_config = get_pool_config_address();
+
+    let PoolConfig { pool_data, .. } = PoolConfig::try_deserialize(
+        &mut rpc_client
+            .get_account_data(&pool_config)
+            .await
+            .unwrap()
+            .as_slice(),
+    )
+    .unwrap();
+
+    let accounts = integrity_pool::accounts::UpdateMaxDelegationFee {
+        reward_program_authority: signer.pubkey(),
+        pool_data,
+        pool_config,
+        config_account: get_config_address(),
+        system_program: system_program::ID,
+    };
+
+    let instruction_data =
+        integrity_pool::instruction::UpdateMaxDelegationFee { max_delegation_fee };
+
+    let instruction = Instruction {
+        program_id: integrity_pool::ID,
+        accounts:   accounts.to_account_metas(None),
+        data:       instruction_data.data(),
+    };
+
+    process_transaction(rpc_client, &[instruction], &[signer])
+        .await
+        .unwrap();
+}
+
+pub async fn update_fee_notice_epochs(
+    rpc_client: &RpcClient,
+    signer: &dyn Signer,
+    fee_notice_epochs: u64,
+) {
+    let pool_config = get_pool_config_address();
+
+    let accounts = integrity_pool::accounts::UpdateFeeNoticeEpochs {
+        reward_program_authority: signer.pubkey(),
+        pool_config,
+        system_program: system_program::ID,
+    };
+
+    let instruction_data = integrity_pool::instruction::UpdateFeeNoticeEpochs { fee_notice_epochs };
+
+    let instruction = Instruction {
+        program_id: integrity_pool::ID,
+        accounts:   accounts.to_account_metas(None),
+        data:       instruction_data.data(),
+    };
+
+    process_transaction(rpc_client, &[instruction], &[signer])
+        .await
+        .unwrap();
+}
+
+pub async fn set_publisher_delegation_fee(
+    rpc_client: &RpcClient,
+    signer: &dyn Signer,
+    publisher: &Pubkey,
+    publisher_stake_account_positions: Option<Pubkey>,
+    delegation_fee: u64,
+) {
+    let pool_config = get_pool_config_address();
+
+    let PoolConfig { pool_data, .. } = PoolConfig::try_deserialize(
+        &mut rpc_client
+            .get_account_data(&pool_config)
+            .await
+            .unwrap()
+            .as_slice(),
+    )
+    .unwrap();
+
+    let accounts = integrity_pool::accounts::SetPublisherDelegationFee {
+        signer: signer.pubkey(),
+        publisher: *publisher,
+        pool_data,
+        pool_config,
+        publisher_stake_account_positions,
+        config_account: get_config_address(),
+    };
+
+    let instruction_data =
+        integrity_pool::instruction::SetPublisherDelegationFee { delegation_fee };
+
+    let instruction = Instruction {
+        program_id: integrity_pool::ID,
+        accounts:   accounts.to_account_metas(None),
+        data:       instruction_data.data(),
+    };
+
+    process_transaction(rpc_client, &[instruction], &[signer])
+        .await
+        .unwrap();
+}
+
 pub async fn set_publisher_stake_account(
     rpc_client: &RpcClient,
     signer: &dyn Signer,
@@ -790,6 +968,7 @@ pub async fn create_slash_event(
         pool_config,
         pool_data: pool_data_address,
         slash_event: get_slash_event_address(index, *publisher),
+        config_account: get_config_address(),
         system_program: system_program::ID,
     };
 
@@ -834,6 +1013,189 @@ pub async fn update_reward_program_authority(
         .unwrap();
 }
 
+pub async fn nominate_authority(
+    rpc_client: &RpcClient,
+    signer: &dyn Signer,
+    authority: Authority,
+    new_authority: &Pubkey,
+) {
+    let config = get_config_address();
+
+    let (accounts, data) = match authority {
+        Authority::Governance => (
+            staking::accounts::UpdateGovernanceAuthority {
+                governance_authority: signer.pubkey(),
+                config,
+            }
+            .to_account_metas(None),
+            staking::instruction::UpdateGovernanceAuthority {
+                new_authority: *new_authority,
+            }
+            .data(),
+        ),
+        Authority::Pda => (
+            staking::accounts::UpdatePdaAuthority {
+                pda_authority: signer.pubkey(),
+                config,
+            }
+            .to_account_metas(None),
+            staking::instruction::UpdatePdaAuthority {
+                new_authority: *new_authority,
+            }
+            .data(),
+        ),
+        Authority::Pool => (
+            staking::accounts::UpdatePoolAuthority {
+                governance_authority: signer.pubkey(),
+                config,
+            }
+            .to_account_metas(None),
+            staking::instruction::UpdatePoolAuthority {
+                pool_authority: *new_authority,
+            }
+            .data(),
+        ),
+        Authority::RewardProgram => {
+            return update_reward_program_authority(rpc_client, signer, new_authority).await;
+        }
+    };
+
+    let instruction = Instruction {
+        program_id: staking::ID,
+        accounts,
+        data,
+    };
+
+    process_transaction(rpc_client, &[instruction], &[signer])
+        .await
+        .unwrap();
+}
+
+pub async fn accept_authority(rpc_client: &RpcClient, signer: &dyn Signer, authority: Authority) {
+    let config = get_config_address();
+
+    let instruction = match authority {
+        Authority::Governance => Instruction {
+            program_id: staking::ID,
+            accounts:   staking::accounts::AcceptGovernanceAuthority {
+                new_governance_authority: signer.pubkey(),
+                config,
+            }
+            .to_account_metas(None),
+            data:       staking::instruction::AcceptGovernanceAuthority {}.data(),
+        },
+        Authority::Pda => Instruction {
+            program_id: staking::ID,
+            accounts:   staking::accounts::AcceptPdaAuthority {
+                new_pda_authority: signer.pubkey(),
+                config,
+            }
+            .to_account_metas(None),
+            data:       staking::instruction::AcceptPdaAuthority {}.data(),
+        },
+        Authority::Pool => {
+            let global_config = GlobalConfig::try_deserialize(
+                &mut rpc_client
+                    .get_account_data(&config)
+                    .await
+                    .unwrap()
+                    .as_slice(),
+            )
+            .unwrap();
+
+            // the pool config is accepted by the reward program authority through the integrity
+            // pool program
+            if global_config.pending_pool_authority == Some(get_pool_config_address()) {
+                Instruction {
+                    program_id: integrity_pool::ID,
+                    accounts:   integrity_pool::accounts::AcceptPoolAuthority {
+                        reward_program_authority: signer.pubkey(),
+                        pool_config:              get_pool_config_address(),
+                        config_account:           config,
+                        staking_program:          staking::ID,
+                    }
+                    .to_account_metas(None),
+                    data:       integrity_pool::instruction::AcceptPoolAuthority {}.data(),
+                }
+            } else {
+                Instruction {
+                    program_id: staking::ID,
+                    accounts:   staking::accounts::AcceptPoolAuthority {
+                        new_pool_authority: signer.pubkey(),
+                        config,
+                    }
+                    .to_account_metas(None),
+                    data:       staking::instruction::AcceptPoolAuthority {}.data(),
+                }
+            }
+        }
+        Authority::RewardProgram => Instruction {
+            program_id: integrity_pool::ID,
+            accounts:   integrity_pool::accounts::AcceptRewardProgramAuthority {
+                new_reward_program_authority: signer.pubkey(),
+                pool_config:                  get_pool_config_address(),
+            }
+            .to_account_metas(None),
+            data:       integrity_pool::instruction::AcceptRewardProgramAuthority {}.data(),
+        },
+    };
+
+    process_transaction(rpc_client, &[instruction], &[signer])
+        .await
+        .unwrap();
+}
+
+pub async fn cancel_authority_transfer(
+    rpc_client: &RpcClient,
+    signer: &dyn Signer,
+    authority: Authority,
+) {
+    let config = get_config_address();
+
+    let instruction = match authority {
+        Authority::Governance => Instruction {
+            program_id: staking::ID,
+            accounts:   staking::accounts::CancelGovernanceAuthorityTransfer {
+                governance_authority: signer.pubkey(),
+                config,
+            }
+            .to_account_metas(None),
+            data:       staking::instruction::CancelGovernanceAuthorityTransfer {}.data(),
+        },
+        Authority::Pda => Instruction {
+            program_id: staking::ID,
+            accounts:   staking::accounts::CancelPdaAuthorityTransfer {
+                pda_authority: signer.pubkey(),
+                config,
+            }
+            .to_account_metas(None),
+            data:       staking::instruction::CancelPdaAuthorityTransfer {}.data(),
+        },
+        Authority::Pool => Instruction {
+            program_id: staking::ID,
+            accounts:   staking::accounts::CancelPoolAuthorityTransfer {
+                governance_authority: signer.pubkey(),
+                config,
+            }
+            .to_account_metas(None),
+            data:       staking::instruction::CancelPoolAuthorityTransfer {}.data(),
+        },
+        Authority::RewardProgram => Instruction {
+            program_id: integrity_pool::ID,
+            accounts:   integrity_pool::accounts::CancelRewardProgramAuthorityTransfer {
+                reward_program_authority: signer.pubkey(),
+                pool_config:              get_pool_config_address(),
+            }
+            .to_account_metas(None),
+            data:       integrity_pool::instruction::CancelRewardProgramAuthorityTransfer {}.data(),
+        },
+    };
+
+    process_transaction(rpc_client, &[instruction], &[signer])
+        .await
+        .unwrap();
+}
+
 pub async fn slash(
     rpc_client: &RpcClient,
     signer: &dyn Signer,
@@ -866,6 +1228,8 @@ pub async fn slash(
             DelegationRecord {
                 last_epoch:             0,
                 next_slash_event_index: 0,
+                reward_index:           RewardIndex::default(),
+                auto_compound:          false,
             }
         }
     };
@@ -1135,6 +1499,7 @@ pub async fn save_stake_accounts_snapshot(rpc_client: &RpcClient) {
                     current_epoch,
                     MAX_VOTER_WEIGHT,
                     MAX_VOTER_WEIGHT,
+                    config.get_unlocking_durations(),
                 )
                 .unwrap();
 
@@ -1142,7 +1507,18 @@ pub async fn save_stake_accounts_snapshot(rpc_client: &RpcClient) {
                     let mut amount = 0u64;
                     for i in 0..dynamic_position_array.get_position_capacity() {
                         if let Some(position) = dynamic_position_array.read_position(i).unwrap() {
-                            match position.get_current_position(current_epoch).unwrap() {
+                            match position
+                                .get_current_position(
+                                    current_epoch,
+                                    dynamic_position_array
+                                        .get_unlocking_durations(
+                                            i,
+                                            config.get_unlocking_durations(),
+                                        )
+                                        .unwrap(),
+                                )
+                                .unwrap()
+                            {
                                 PositionState::LOCKED | PositionState::PREUNLOCKING => {
                                     if !position.is_voting() {
                                         amount += position.amount;
@@ -1298,6 +1674,7 @@ pub async fn advance_delegation_records<'a>(
 
     // Process results and create instructions
     let mut instructions = Vec::new();
+    let mut batched_records = Vec::new();
     for (
         (publisher, publisher_stake_account_positions, publisher_stake_account_custody),
         delegation_record,
@@ -1305,47 +1682,95 @@ pub async fn advance_delegation_records<'a>(
         .into_iter()
         .zip(delegation_records)
     {
-        // Skip if the delegation record is already up to date
-        if let Some(delegation_record) = delegation_record {
-            if delegation_record.last_epoch == current_epoch {
-                continue;
+        let delegation_record_address =
+            get_delegation_record_address(publisher, *positions_address);
+
+        match delegation_record {
+            // Skip if the delegation record is already up to date
+            Some(delegation_record) if delegation_record.last_epoch == current_epoch => {}
+            // Existing delegation records are advanced together
+            Some(_) => {
+                batched_records.extend([
+                    AccountMeta::new_readonly(publisher, false),
+                    AccountMeta::new(delegation_record_address, false),
+                    publisher_stake_account_custody.map_or(
+                        AccountMeta::new_readonly(integrity_pool::ID, false),
+                        |custody| AccountMeta::new(custody, false),
+                    ),
+                    AccountMeta::new(get_reward_index_history_address(publisher), false),
+                ]);
+            }
+            // Missing delegation records need to be created first
+            None => {
+                let accounts = integrity_pool::accounts::AdvanceDelegationRecord {
+                    delegation_record: delegation_record_address,
+                    payer: signer.pubkey(),
+                    pool_config: *pool_config,
+                    pool_data: *pool_data_address,
+                    pool_reward_custody: get_pool_reward_custody_address(*pyth_token_mint),
+                    publisher,
+                    publisher_stake_account_positions,
+                    publisher_stake_account_custody,
+                    stake_account_positions: *positions_address,
+                    stake_account_custody: get_stake_account_custody_address(*positions_address),
+                    system_program: system_program::ID,
+                    token_program: spl_token::ID,
+                    config_account: get_config_address(),
+                    reward_index_history: get_reward_index_history_address(publisher),
+                    stake_account_metadata: get_stake_account_metadata_address(*positions_address),
+                    staking_program: staking::ID,
+                };
+
+                let data = integrity_pool::instruction::AdvanceDelegationRecord {};
+
+                instructions.push(Instruction {
+                    program_id: integrity_pool::ID,
+                    accounts:   accounts.to_account_metas(None),
+                    data:       data.data(),
+                });
             }
         }
+        // TODO: Add merge positions instruction
+    }
 
-        let accounts = integrity_pool::accounts::AdvanceDelegationRecord {
-            delegation_record: get_delegation_record_address(publisher, *positions_address),
-            payer: signer.pubkey(),
-            pool_config: *pool_config,
-            pool_data: *pool_data_address,
-            pool_reward_custody: get_pool_reward_custody_address(*pyth_token_mint),
-            publisher,
-            publisher_stake_account_positions,
-            publisher_stake_account_custody,
+    // Each instruction advances the delegation records of up to 6 publishers
+    let mut batched_instructions = Vec::new();
+    for chunk in batched_records.chunks(4 * 6) {
+        // The staking accounts are needed to track the delegation records and compound the rewards
+        let mut accounts = integrity_pool::accounts::AdvanceDelegationRecords {
+            payer:                   signer.pubkey(),
+            pool_config:             *pool_config,
+            pool_data:               *pool_data_address,
+            pool_reward_custody:     get_pool_reward_custody_address(*pyth_token_mint),
             stake_account_positions: *positions_address,
-            stake_account_custody: get_stake_account_custody_address(*positions_address),
-            system_program: system_program::ID,
-            token_program: spl_token::ID,
-        };
+            stake_account_custody:   get_stake_account_custody_address(*positions_address),
+            system_program:          system_program::ID,
+            token_program:           spl_token::ID,
+            config_account:          get_config_address(),
+            stake_account_metadata:  get_stake_account_metadata_address(*positions_address),
+            staking_program:         staking::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend_from_slice(chunk);
 
-        let data = integrity_pool::instruction::AdvanceDelegationRecord {};
+        let data = integrity_pool::instruction::AdvanceDelegationRecords {};
 
-        instructions.push(Instruction {
+        batched_instructions.push(Instruction {
             program_id: integrity_pool::ID,
-            accounts:   accounts.to_account_metas(None),
-            data:       data.data(),
+            accounts,
+            data: data.data(),
         });
-        // TODO: Add merge positions instruction
     }
 
-    // Process instructions in chunks of 5
-    if !instructions.is_empty() {
+    // Process instructions in chunks of 5, batched instructions are sent one at a time
+    if !instructions.is_empty() || !batched_instructions.is_empty() {
         println!(
             "Advancing {} delegation records for stake account {}",
-            instructions.len(),
+            instructions.len() + batched_records.len() / 4,
             positions_address,
         );
 
-        for chunk in instructions.chunks(5) {
+        for chunk in instructions.chunks(5).chain(batched_instructions.chunks(1)) {
             if process_transaction(rpc_client, chunk, &[signer])
                 .await
                 .is_err()
@@ -1503,3 +1928,104 @@ pub async fn claim_rewards(rpc_client: &RpcClient, signer: &dyn Signer, min_stak
         tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
     }
 }
+
+pub async fn list_split_requests(rpc_client: &RpcClient) {
+    // Split requests don't store their stake account, so we match them with the addresses derived
+    // from every stake account. We only need the pubkeys of the stake accounts.
+    let split_request_to_stake_account: HashMap<Pubkey, Pubkey> = rpc_client
+        .get_program_accounts_with_config(
+            &staking::ID,
+            RpcProgramAccountsConfig {
+                filters:        Some(vec![RpcFilterType::Memcmp(Memcmp::new(
+                    0,
+                    MemcmpEncodedBytes::Bytes(PositionData::discriminator().to_vec()),
+                ))]),
+                account_config: RpcAccountInfoConfig {
+                    encoding:         Some(UiAccountEncoding::Base64Zstd),
+                    data_slice:       Some(UiDataSliceConfig {
+                        offset: 0,
+                        length: 0,
+                    }),
+                    commitment:       None,
+                    min_context_slot: None,
+                },
+                with_context:   None,
+            },
+        )
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(pubkey, _)| (get_split_request_address(pubkey), pubkey))
+        .collect();
+
+    let split_requests: Vec<(Pubkey, SplitRequest)> = rpc_client
+        .get_program_accounts_with_config(
+            &staking::ID,
+            RpcProgramAccountsConfig {
+                filters:        Some(vec![RpcFilterType::Memcmp(Memcmp::new(
+                    0,
+                    MemcmpEncodedBytes::Bytes(SplitRequest::discriminator().to_vec()),
+                ))]),
+                account_config: RpcAccountInfoConfig {
+                    encoding:         Some(UiAccountEncoding::Base64Zstd),
+                    data_slice:       None,
+                    commitment:       None,
+                    min_context_slot: None,
+                },
+                with_context:   None,
+            },
+        )
+        .await
+        .unwrap()
+        .into_iter()
+        .filter_map(|(pubkey, account)| {
+            // Requests of closed stake accounts can't be accepted anymore
+            let stake_account_positions = split_request_to_stake_account.get(&pubkey)?;
+            SplitRequest::try_deserialize_legacy(&account.data)
+                .ok()
+                .filter(|split_request| split_request.amount > 0)
+                .map(|split_request| (*stake_account_positions, split_request))
+        })
+        .collect();
+
+    let current_epoch = get_current_epoch(rpc_client).await;
+    println!("Found {} pending split requests", split_requests.len());
+
+    for chunk in split_requests.chunks(100) {
+        let metadata_accounts = rpc_client
+            .get_multiple_accounts(
+                &chunk
+                    .iter()
+                    .map(|(stake_account_positions, _)| {
+                        get_stake_account_metadata_address(*stake_account_positions)
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .await
+            .unwrap();
+
+        for ((stake_account_positions, split_request), metadata_account) in
+            chunk.iter().zip(metadata_accounts)
+        {
+            let metadata = StakeAccountMetadataV2::try_deserialize(
+                &mut metadata_account.unwrap().data.as_slice(),
+            )
+            .unwrap();
+            println!(
+                "Stake account: {}, owner: {}, vesting schedule: {:?}, amount: {}, recipient: {}, \
+                 expiry epoch: {}{}",
+                stake_account_positions,
+                metadata.owner,
+                metadata.lock,
+                split_request.amount,
+                split_request.recipient,
+                split_request.expiry_epoch,
+                if split_request.is_expired(current_epoch) {
+                    " (expired)"
+                } else {
+                    ""
+                },
+            );
+        }
+    }
+}
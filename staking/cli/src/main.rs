@@ -8,6 +8,8 @@ use {
         Cli,
     },
     instructions::{
+        accept_authority,
+        cancel_authority_transfer,
         claim_rewards,
         close_all_publisher_caps,
         close_publisher_caps,
@@ -15,10 +17,15 @@ use {
         fetch_publisher_caps_and_advance,
         initialize_pool,
         initialize_reward_custody,
+        list_split_requests,
+        nominate_authority,
         save_stake_accounts_snapshot,
+        set_publisher_delegation_fee,
         set_publisher_stake_account,
         slash,
         update_delegation_fee,
+        update_fee_notice_epochs,
+        update_max_delegation_fee,
         update_reward_program_authority,
         update_y,
     },
@@ -46,6 +53,7 @@ async fn main() {
             pool_data_keypair,
             reward_program_authority,
             y,
+            max_delegation_fee,
             slash_custody,
         } => {
             initialize_pool(
@@ -54,6 +62,7 @@ async fn main() {
                 &pool_data_keypair,
                 reward_program_authority,
                 y,
+                max_delegation_fee,
                 slash_custody,
             )
             .await
@@ -71,6 +80,26 @@ async fn main() {
         Action::UpdateDelegationFee { delegation_fee } => {
             update_delegation_fee(&rpc_client, keypair.as_ref(), delegation_fee).await
         }
+        Action::UpdateMaxDelegationFee { max_delegation_fee } => {
+            update_max_delegation_fee(&rpc_client, keypair.as_ref(), max_delegation_fee).await
+        }
+        Action::UpdateFeeNoticeEpochs { fee_notice_epochs } => {
+            update_fee_notice_epochs(&rpc_client, keypair.as_ref(), fee_notice_epochs).await
+        }
+        Action::SetPublisherDelegationFee {
+            publisher,
+            delegation_fee,
+            publisher_stake_account_positions,
+        } => {
+            set_publisher_delegation_fee(
+                &rpc_client,
+                keypair.as_ref(),
+                &publisher,
+                publisher_stake_account_positions,
+                delegation_fee,
+            )
+            .await
+        }
         Action::SetPublisherStakeAccount {
             publisher,
             stake_account_positions,
@@ -97,6 +126,16 @@ async fn main() {
             )
             .await
         }
+        Action::NominateAuthority {
+            authority,
+            new_authority,
+        } => nominate_authority(&rpc_client, keypair.as_ref(), authority, &new_authority).await,
+        Action::AcceptAuthority { authority } => {
+            accept_authority(&rpc_client, keypair.as_ref(), authority).await
+        }
+        Action::CancelAuthorityTransfer { authority } => {
+            cancel_authority_transfer(&rpc_client, keypair.as_ref(), authority).await
+        }
         Action::Slash {
             publisher,
             stake_account_positions,
@@ -120,5 +159,6 @@ async fn main() {
         Action::ClaimRewards { min_staked } => {
             claim_rewards(&rpc_client, keypair.as_ref(), min_staked).await
         }
+        Action::ListSplitRequests {} => list_split_requests(&rpc_client).await,
     }
 }
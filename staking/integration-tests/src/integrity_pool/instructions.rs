@@ -5,6 +5,7 @@ use {
             get_delegation_record_address,
             get_pool_config_address,
             get_pool_reward_custody_address,
+            get_reward_index_history_address,
             get_slash_event_address,
         },
     },
@@ -15,12 +16,16 @@ use {
         },
         staking::pda::{
             get_config_address,
+            get_split_request_address,
             get_stake_account_custody_address,
             get_stake_account_custody_authority_address,
             get_stake_account_metadata_address,
             get_target_address,
         },
-        utils::constants::YIELD,
+        utils::constants::{
+            MAX_DELEGATION_FEE,
+            YIELD,
+        },
     },
     anchor_lang::{
         solana_program::system_instruction::create_account,
@@ -42,7 +47,10 @@ use {
     },
     solana_sdk::{
         compute_budget::ComputeBudgetInstruction,
-        instruction::Instruction,
+        instruction::{
+            AccountMeta,
+            Instruction,
+        },
         pubkey::Pubkey,
         signature::Keypair,
         signer::Signer,
@@ -71,6 +79,7 @@ pub fn advance(svm: &mut LiteSVM, payer: &Keypair, publisher_caps: Pubkey) -> Tr
         publisher_caps,
         pool_data,
         pool_reward_custody,
+        config_account: get_config_address(),
     };
 
     let instruction_data = integrity_pool::instruction::Advance {};
@@ -120,6 +129,7 @@ pub fn create_pool_data_account(
     let initialize_pool_data = integrity_pool::instruction::InitializePool {
         reward_program_authority,
         y: YIELD,
+        max_delegation_fee: MAX_DELEGATION_FEE,
     };
 
     // initialize slash custody
@@ -225,6 +235,86 @@ pub fn update_reward_program_authority(
     svm.send_transaction(transaction)
 }
 
+pub fn accept_reward_program_authority(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    new_reward_program_authority: &Keypair,
+) -> TransactionResult {
+    let instruction_accs = integrity_pool::accounts::AcceptRewardProgramAuthority {
+        pool_config:                  get_pool_config_address(),
+        new_reward_program_authority: new_reward_program_authority.pubkey(),
+    };
+
+    let instruction = Instruction::new_with_bytes(
+        integrity_pool::ID,
+        &integrity_pool::instruction::AcceptRewardProgramAuthority {}.data(),
+        instruction_accs.to_account_metas(None),
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[payer, new_reward_program_authority],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(transaction)
+}
+
+pub fn cancel_reward_program_authority_transfer(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    reward_program_authority: &Keypair,
+) -> TransactionResult {
+    let instruction_accs = integrity_pool::accounts::CancelRewardProgramAuthorityTransfer {
+        pool_config:              get_pool_config_address(),
+        reward_program_authority: reward_program_authority.pubkey(),
+    };
+
+    let instruction = Instruction::new_with_bytes(
+        integrity_pool::ID,
+        &integrity_pool::instruction::CancelRewardProgramAuthorityTransfer {}.data(),
+        instruction_accs.to_account_metas(None),
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[payer, reward_program_authority],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(transaction)
+}
+
+pub fn accept_pool_authority(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    reward_program_authority: &Keypair,
+) -> TransactionResult {
+    let instruction_accs = integrity_pool::accounts::AcceptPoolAuthority {
+        reward_program_authority: reward_program_authority.pubkey(),
+        pool_config:              get_pool_config_address(),
+        config_account:           get_config_address(),
+        staking_program:          staking::ID,
+    };
+
+    let instruction = Instruction::new_with_bytes(
+        integrity_pool::ID,
+        &integrity_pool::instruction::AcceptPoolAuthority {}.data(),
+        instruction_accs.to_account_metas(None),
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[payer, reward_program_authority],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(transaction)
+}
+
 pub fn update_delegation_fee(
     svm: &mut litesvm::LiteSVM,
     payer: &Keypair,
@@ -241,6 +331,7 @@ pub fn update_delegation_fee(
         pool_data,
         pool_config: pool_config_pubkey,
         reward_program_authority: reward_program_authority.pubkey(),
+        config_account: get_config_address(),
         system_program: system_program::ID,
     };
 
@@ -260,6 +351,111 @@ pub fn update_delegation_fee(
     svm.send_transaction(update_delegation_fee_tx)
 }
 
+pub fn update_max_delegation_fee(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    reward_program_authority: &Keypair,
+    max_delegation_fee: frac64,
+) -> TransactionResult {
+    let pool_config_pubkey = get_pool_config_address();
+    let pool_data = fetch_account_data::<PoolConfig>(svm, &pool_config_pubkey).pool_data;
+
+    let instruction_data =
+        integrity_pool::instruction::UpdateMaxDelegationFee { max_delegation_fee };
+
+    let instruction_accs = integrity_pool::accounts::UpdateMaxDelegationFee {
+        pool_data,
+        pool_config: pool_config_pubkey,
+        reward_program_authority: reward_program_authority.pubkey(),
+        config_account: get_config_address(),
+        system_program: system_program::ID,
+    };
+
+    let instruction = Instruction::new_with_bytes(
+        integrity_pool::ID,
+        &instruction_data.data(),
+        instruction_accs.to_account_metas(None),
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[payer, reward_program_authority],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(transaction)
+}
+
+pub fn update_fee_notice_epochs(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    reward_program_authority: &Keypair,
+    fee_notice_epochs: u64,
+) -> TransactionResult {
+    let pool_config_pubkey = get_pool_config_address();
+
+    let instruction_data = integrity_pool::instruction::UpdateFeeNoticeEpochs { fee_notice_epochs };
+
+    let instruction_accs = integrity_pool::accounts::UpdateFeeNoticeEpochs {
+        pool_config:              pool_config_pubkey,
+        reward_program_authority: reward_program_authority.pubkey(),
+        system_program:           system_program::ID,
+    };
+
+    let instruction = Instruction::new_with_bytes(
+        integrity_pool::ID,
+        &instruction_data.data(),
+        instruction_accs.to_account_metas(None),
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[payer, reward_program_authority],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(transaction)
+}
+
+pub fn set_publisher_delegation_fee(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    signer: &Keypair,
+    publisher: Pubkey,
+    publisher_stake_account_positions: Option<Pubkey>,
+    delegation_fee: frac64,
+) -> TransactionResult {
+    let pool_config = get_pool_config_address();
+    let pool_data: Pubkey = fetch_account_data::<PoolConfig>(svm, &pool_config).pool_data;
+
+    let data = integrity_pool::instruction::SetPublisherDelegationFee { delegation_fee };
+    let accs = integrity_pool::accounts::SetPublisherDelegationFee {
+        signer: signer.pubkey(),
+        publisher,
+        pool_data,
+        pool_config,
+        publisher_stake_account_positions,
+        config_account: get_config_address(),
+    };
+
+    let ix = Instruction::new_with_bytes(
+        integrity_pool::ID,
+        &data.data(),
+        accs.to_account_metas(None),
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer, &signer],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
 
 pub fn advance_delegation_record(
     svm: &mut litesvm::LiteSVM,
@@ -293,6 +489,10 @@ pub fn advance_delegation_record(
         publisher_stake_account_positions,
         token_program: spl_token::ID,
         system_program: system_program::ID,
+        config_account: get_config_address(),
+        reward_index_history: get_reward_index_history_address(publisher),
+        stake_account_metadata: get_stake_account_metadata_address(stake_account_positions),
+        staking_program: staking::ID,
     };
     let ix = Instruction::new_with_bytes(
         integrity_pool::ID,
@@ -311,6 +511,254 @@ pub fn advance_delegation_record(
     svm.send_transaction(tx)
 }
 
+pub fn advance_delegation_records(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    publishers: &[(Pubkey, Option<Pubkey>)],
+    stake_account_positions: Pubkey,
+    pyth_token_mint: Pubkey,
+    pool_data: Pubkey,
+) -> TransactionResult {
+    let data = integrity_pool::instruction::AdvanceDelegationRecords {};
+
+    let mut accounts = integrity_pool::accounts::AdvanceDelegationRecords {
+        payer: payer.pubkey(),
+        pool_config: get_pool_config_address(),
+        pool_reward_custody: get_pool_reward_custody_address(pyth_token_mint),
+        pool_data,
+        stake_account_custody: get_stake_account_custody_address(stake_account_positions),
+        stake_account_positions,
+        token_program: spl_token::ID,
+        system_program: system_program::ID,
+        config_account: get_config_address(),
+        stake_account_metadata: get_stake_account_metadata_address(stake_account_positions),
+        staking_program: staking::ID,
+    }
+    .to_account_metas(None);
+
+    for (publisher, publisher_stake_account_positions) in publishers {
+        accounts.push(AccountMeta::new_readonly(*publisher, false));
+        accounts.push(AccountMeta::new(
+            get_delegation_record_address(*publisher, stake_account_positions),
+            false,
+        ));
+        accounts.push(match publisher_stake_account_positions {
+            Some(positions) => {
+                AccountMeta::new(get_stake_account_custody_address(*positions), false)
+            }
+            None => AccountMeta::new_readonly(integrity_pool::ID, false),
+        });
+        accounts.push(AccountMeta::new(
+            get_reward_index_history_address(*publisher),
+            false,
+        ));
+    }
+
+    let ix = Instruction::new_with_bytes(integrity_pool::ID, &data.data(), accounts);
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            ix,
+            ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+}
+
+pub fn claim_rewards_to_destination(
+    svm: &mut litesvm::LiteSVM,
+    owner: &Keypair,
+    publisher: Pubkey,
+    stake_account_positions: Pubkey,
+    pyth_token_mint: Pubkey,
+    pool_data: Pubkey,
+    publisher_stake_account_positions: Option<Pubkey>,
+    destination: Pubkey,
+) -> TransactionResult {
+    let publisher_stake_account_custody =
+        publisher_stake_account_positions.map(get_stake_account_custody_address);
+
+    let data = integrity_pool::instruction::ClaimRewardsToDestination {};
+
+    let accs = integrity_pool::accounts::ClaimRewardsToDestination {
+        owner: owner.pubkey(),
+        destination,
+        advance_delegation_record: integrity_pool::accounts::AdvanceDelegationRecord {
+            payer: owner.pubkey(),
+            pool_config: get_pool_config_address(),
+            pool_reward_custody: get_pool_reward_custody_address(pyth_token_mint),
+            pool_data,
+            stake_account_custody: get_stake_account_custody_address(stake_account_positions),
+            publisher,
+            delegation_record: get_delegation_record_address(publisher, stake_account_positions),
+            stake_account_positions,
+            publisher_stake_account_custody,
+            publisher_stake_account_positions,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            config_account: get_config_address(),
+            reward_index_history: get_reward_index_history_address(publisher),
+            stake_account_metadata: get_stake_account_metadata_address(stake_account_positions),
+            staking_program: staking::ID,
+        },
+    };
+    let ix = Instruction::new_with_bytes(
+        integrity_pool::ID,
+        &data.data(),
+        accs.to_account_metas(None),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            ix,
+            ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
+        ],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+}
+
+pub fn set_auto_compound(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    owner: &Keypair,
+    publisher: Pubkey,
+    stake_account_positions: Pubkey,
+    auto_compound: bool,
+) -> TransactionResult {
+    let data = integrity_pool::instruction::SetAutoCompound { auto_compound };
+    let accs = integrity_pool::accounts::SetAutoCompound {
+        owner: owner.pubkey(),
+        publisher,
+        stake_account_positions,
+        delegation_record: get_delegation_record_address(publisher, stake_account_positions),
+        pool_config: get_pool_config_address(),
+        stake_account_metadata: get_stake_account_metadata_address(stake_account_positions),
+        config_account: get_config_address(),
+        staking_program: staking::ID,
+        system_program: system_program::ID,
+    };
+    let ix = Instruction::new_with_bytes(
+        integrity_pool::ID,
+        &data.data(),
+        accs.to_account_metas(None),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[payer, owner],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+}
+
+pub fn close_delegation_record(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    owner: &Keypair,
+    publisher: Pubkey,
+    stake_account_positions: Pubkey,
+) -> TransactionResult {
+    let data = integrity_pool::instruction::CloseDelegationRecord {};
+    let accs = integrity_pool::accounts::CloseDelegationRecord {
+        owner: owner.pubkey(),
+        publisher,
+        stake_account_positions,
+        delegation_record: get_delegation_record_address(publisher, stake_account_positions),
+        config_account: get_config_address(),
+        pool_config: get_pool_config_address(),
+        stake_account_metadata: get_stake_account_metadata_address(stake_account_positions),
+        staking_program: staking::ID,
+        system_program: system_program::ID,
+    };
+    let ix = Instruction::new_with_bytes(
+        integrity_pool::ID,
+        &data.data(),
+        accs.to_account_metas(None),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[payer, owner],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+}
+
+pub fn accept_split(
+    svm: &mut litesvm::LiteSVM,
+    pda_authority: &Keypair,
+    pyth_token_mint: Pubkey,
+    source_stake_account_positions: Pubkey,
+    new_stake_account_positions: Pubkey,
+    amount: u64,
+    recipient: Pubkey,
+    publishers: &[Pubkey],
+) -> TransactionResult {
+    let pool_config = get_pool_config_address();
+    let pool_data = fetch_account_data::<PoolConfig>(svm, &pool_config).pool_data;
+
+    let data = integrity_pool::instruction::AcceptSplit { amount, recipient };
+    let mut accounts = integrity_pool::accounts::AcceptSplit {
+        pda_authority: pda_authority.pubkey(),
+        source_stake_account_positions,
+        source_stake_account_metadata: get_stake_account_metadata_address(
+            source_stake_account_positions,
+        ),
+        source_stake_account_split_request: get_split_request_address(
+            source_stake_account_positions,
+        ),
+        source_stake_account_custody: get_stake_account_custody_address(
+            source_stake_account_positions,
+        ),
+        source_custody_authority: get_stake_account_custody_authority_address(
+            source_stake_account_positions,
+        ),
+        new_stake_account_positions,
+        new_stake_account_metadata: get_stake_account_metadata_address(new_stake_account_positions),
+        new_stake_account_custody: get_stake_account_custody_address(new_stake_account_positions),
+        new_custody_authority: get_stake_account_custody_authority_address(
+            new_stake_account_positions,
+        ),
+        config_account: get_config_address(),
+        pyth_token_mint,
+        rent: solana_sdk::sysvar::rent::ID,
+        token_program: spl_token::ID,
+        system_program: system_program::ID,
+        pool_data,
+        pool_config,
+        staking_program: staking::ID,
+    }
+    .to_account_metas(None);
+
+    for publisher in publishers {
+        accounts.push(AccountMeta::new_readonly(*publisher, false));
+        accounts.push(AccountMeta::new_readonly(
+            get_delegation_record_address(*publisher, source_stake_account_positions),
+            false,
+        ));
+        accounts.push(AccountMeta::new(
+            get_delegation_record_address(*publisher, new_stake_account_positions),
+            false,
+        ));
+    }
+
+    let ix = Instruction::new_with_bytes(integrity_pool::ID, &data.data(), accounts);
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            ix,
+            ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
+        ],
+        Some(&pda_authority.pubkey()),
+        &[&pda_authority],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx)
+}
+
 pub fn delegate(
     svm: &mut litesvm::LiteSVM,
     payer: &Keypair,
@@ -336,6 +784,7 @@ pub fn delegate(
         stake_account_custody,
         staking_program: staking::ID,
         system_program: system_program::ID,
+        reward_index_history: get_reward_index_history_address(publisher),
     };
 
     let delegate_ix = Instruction::new_with_bytes(
@@ -409,7 +858,7 @@ pub fn undelegate(
     publisher: Pubkey,
     pool_data: Pubkey,
     stake_account_positions: Pubkey,
-    position_index: u8,
+    position_index: u16,
     amount: u64,
 ) -> TransactionResult {
     let pool_config_pubkey = get_pool_config_address();
@@ -432,6 +881,7 @@ pub fn undelegate(
         stake_account_custody,
         staking_program: staking::ID,
         system_program: system_program::ID,
+        reward_index_history: get_reward_index_history_address(publisher),
     };
     let undelegate_ix = Instruction::new_with_bytes(
         integrity_pool::ID,
@@ -448,6 +898,89 @@ pub fn undelegate(
     svm.send_transaction(undelegate_tx)
 }
 
+pub fn cancel_undelegate(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    publisher: Pubkey,
+    pool_data: Pubkey,
+    stake_account_positions: Pubkey,
+    position_index: u16,
+) -> TransactionResult {
+    let cancel_undelegate_data = integrity_pool::instruction::CancelUndelegate { position_index };
+    let cancel_undelegate_accs = integrity_pool::accounts::CancelUndelegate {
+        owner: payer.pubkey(),
+        pool_data,
+        pool_config: get_pool_config_address(),
+        publisher,
+        config_account: get_config_address(),
+        stake_account_positions,
+        stake_account_metadata: get_stake_account_metadata_address(stake_account_positions),
+        stake_account_custody: get_stake_account_custody_address(stake_account_positions),
+        staking_program: staking::ID,
+    };
+    let cancel_undelegate_ix = Instruction::new_with_bytes(
+        integrity_pool::ID,
+        &cancel_undelegate_data.data(),
+        cancel_undelegate_accs.to_account_metas(None),
+    );
+    let cancel_undelegate_tx = Transaction::new_signed_with_payer(
+        &[cancel_undelegate_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(cancel_undelegate_tx)
+}
+
+pub fn redelegate(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    publisher: Pubkey,
+    new_publisher: Pubkey,
+    pool_data: Pubkey,
+    stake_account_positions: Pubkey,
+    position_index: u16,
+    amount: u64,
+) -> TransactionResult {
+    let pool_config_pubkey = get_pool_config_address();
+    let config_account = get_config_address();
+    let stake_account_metadata = get_stake_account_metadata_address(stake_account_positions);
+    let stake_account_custody = get_stake_account_custody_address(stake_account_positions);
+
+    let redelegate_data = integrity_pool::instruction::Redelegate {
+        position_index,
+        amount,
+    };
+    let redelegate_accs = integrity_pool::accounts::Redelegate {
+        owner: payer.pubkey(),
+        pool_data,
+        pool_config: pool_config_pubkey,
+        publisher,
+        new_publisher,
+        config_account,
+        stake_account_positions,
+        stake_account_metadata,
+        stake_account_custody,
+        staking_program: staking::ID,
+        system_program: system_program::ID,
+        reward_index_history: get_reward_index_history_address(publisher),
+        new_reward_index_history: get_reward_index_history_address(new_publisher),
+    };
+    let redelegate_ix = Instruction::new_with_bytes(
+        integrity_pool::ID,
+        &redelegate_data.data(),
+        redelegate_accs.to_account_metas(None),
+    );
+    let redelegate_tx = Transaction::new_signed_with_payer(
+        &[redelegate_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(redelegate_tx)
+}
+
 pub fn set_publisher_stake_account(
     svm: &mut litesvm::LiteSVM,
     payer: &Keypair,
@@ -509,6 +1042,7 @@ pub fn create_slash_event(
         pool_config,
         slash_event,
         publisher,
+        config_account: get_config_address(),
         system_program: system_program::ID,
     };
 
@@ -4,6 +4,7 @@ use {
     integrity_pool::utils::constants::{
         DELEGATION_RECORD,
         POOL_CONFIG,
+        REWARD_INDEX_HISTORY,
         SLASH_EVENT,
     },
     solana_sdk::pubkey::Pubkey,
@@ -42,3 +43,11 @@ pub fn get_slash_event_address(index: u64, publisher: Pubkey) -> Pubkey {
     )
     .0
 }
+
+pub fn get_reward_index_history_address(publisher: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[REWARD_INDEX_HISTORY.as_bytes(), publisher.as_ref()],
+        &integrity_pool::ID,
+    )
+    .0
+}
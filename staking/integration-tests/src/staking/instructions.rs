@@ -3,6 +3,7 @@ use {
         get_config_address,
         get_config_address_bump,
         get_max_voter_record_address,
+        get_split_request_address,
         get_stake_account_custody_address,
         get_stake_account_custody_authority_address,
         get_stake_account_metadata_address,
@@ -14,8 +15,12 @@ use {
             MAINNET_GOVERNANCE_PROGRAM_ID,
             MAINNET_REALM_ID,
         },
-        integrity_pool::pda::get_pool_config_address,
+        integrity_pool::pda::{
+            get_delegation_record_address,
+            get_pool_config_address,
+        },
         solana::utils::fetch_account_data,
+        utils::constants::EPOCH_DURATION,
     },
     anchor_lang::{
         prelude::AccountMeta,
@@ -25,9 +30,9 @@ use {
         ToAccountMetas,
     },
     anchor_spl::token::spl_token,
-    integrity_pool::utils::{
-        clock::EPOCH_DURATION,
-        types::frac64,
+    integrity_pool::{
+        state::pool::PoolConfig,
+        utils::types::frac64,
     },
     litesvm::types::TransactionResult,
     solana_sdk::{
@@ -65,6 +70,16 @@ pub fn init_config_account(svm: &mut litesvm::LiteSVM, payer: &Keypair, pyth_tok
             agreement_hash: [0; 32],
             mock_clock_time: 30,
             pool_authority: pool_config,
+            voting_unlocking_duration: None,
+            integrity_pool_unlocking_duration: None,
+            epoch_offset: 0,
+            epoch_duration_change_time: 0,
+            previous_epoch_duration: 0,
+            previous_epoch_offset: 0,
+            previous_epoch_duration_change_time: 0,
+            pending_governance_authority: None,
+            pending_pda_authority: None,
+            pending_pool_authority: None,
         },
     };
     let init_config_accs = staking::accounts::InitConfig {
@@ -89,27 +104,226 @@ pub fn init_config_account(svm: &mut litesvm::LiteSVM, payer: &Keypair, pyth_tok
 }
 
 
-pub fn update_pool_authority(svm: &mut litesvm::LiteSVM, payer: &Keypair, pool_authority: Pubkey) {
-    let config_account = get_config_address();
+pub fn update_governance_authority(
+    svm: &mut litesvm::LiteSVM,
+    governance_authority: &Keypair,
+    new_authority: Pubkey,
+) -> TransactionResult {
+    let accs = staking::accounts::UpdateGovernanceAuthority {
+        governance_authority: governance_authority.pubkey(),
+        config:               get_config_address(),
+    };
 
-    let update_pool_authority_data = staking::instruction::UpdatePoolAuthority { pool_authority };
-    let update_pool_authority_accs = staking::accounts::UpdatePoolAuthority {
-        config:               config_account,
-        governance_authority: payer.pubkey(),
+    let ix = Instruction::new_with_bytes(
+        staking::ID,
+        &staking::instruction::UpdateGovernanceAuthority { new_authority }.data(),
+        accs.to_account_metas(None),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&governance_authority.pubkey()),
+        &[governance_authority],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
+pub fn accept_governance_authority(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    new_governance_authority: &Keypair,
+) -> TransactionResult {
+    let accs = staking::accounts::AcceptGovernanceAuthority {
+        new_governance_authority: new_governance_authority.pubkey(),
+        config:                   get_config_address(),
     };
-    let update_pool_authority_ix = Instruction::new_with_bytes(
+
+    let ix = Instruction::new_with_bytes(
         staking::ID,
-        &update_pool_authority_data.data(),
-        update_pool_authority_accs.to_account_metas(None),
+        &staking::instruction::AcceptGovernanceAuthority {}.data(),
+        accs.to_account_metas(None),
     );
-    let update_pool_authority_tx = Transaction::new_signed_with_payer(
-        &[update_pool_authority_ix],
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
         Some(&payer.pubkey()),
-        &[&payer],
+        &[payer, new_governance_authority],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
+pub fn cancel_governance_authority_transfer(
+    svm: &mut litesvm::LiteSVM,
+    governance_authority: &Keypair,
+) -> TransactionResult {
+    let accs = staking::accounts::CancelGovernanceAuthorityTransfer {
+        governance_authority: governance_authority.pubkey(),
+        config:               get_config_address(),
+    };
+
+    let ix = Instruction::new_with_bytes(
+        staking::ID,
+        &staking::instruction::CancelGovernanceAuthorityTransfer {}.data(),
+        accs.to_account_metas(None),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&governance_authority.pubkey()),
+        &[governance_authority],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
+pub fn update_pda_authority(
+    svm: &mut litesvm::LiteSVM,
+    pda_authority: &Keypair,
+    new_authority: Pubkey,
+) -> TransactionResult {
+    let accs = staking::accounts::UpdatePdaAuthority {
+        pda_authority: pda_authority.pubkey(),
+        config:        get_config_address(),
+    };
+
+    let ix = Instruction::new_with_bytes(
+        staking::ID,
+        &staking::instruction::UpdatePdaAuthority { new_authority }.data(),
+        accs.to_account_metas(None),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&pda_authority.pubkey()),
+        &[pda_authority],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
+pub fn accept_pda_authority(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    new_pda_authority: &Keypair,
+) -> TransactionResult {
+    let accs = staking::accounts::AcceptPdaAuthority {
+        new_pda_authority: new_pda_authority.pubkey(),
+        config:            get_config_address(),
+    };
+
+    let ix = Instruction::new_with_bytes(
+        staking::ID,
+        &staking::instruction::AcceptPdaAuthority {}.data(),
+        accs.to_account_metas(None),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[payer, new_pda_authority],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
+pub fn cancel_pda_authority_transfer(
+    svm: &mut litesvm::LiteSVM,
+    pda_authority: &Keypair,
+) -> TransactionResult {
+    let accs = staking::accounts::CancelPdaAuthorityTransfer {
+        pda_authority: pda_authority.pubkey(),
+        config:        get_config_address(),
+    };
+
+    let ix = Instruction::new_with_bytes(
+        staking::ID,
+        &staking::instruction::CancelPdaAuthorityTransfer {}.data(),
+        accs.to_account_metas(None),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&pda_authority.pubkey()),
+        &[pda_authority],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
+pub fn update_pool_authority(
+    svm: &mut litesvm::LiteSVM,
+    governance_authority: &Keypair,
+    pool_authority: Pubkey,
+) -> TransactionResult {
+    let accs = staking::accounts::UpdatePoolAuthority {
+        governance_authority: governance_authority.pubkey(),
+        config:               get_config_address(),
+    };
+
+    let ix = Instruction::new_with_bytes(
+        staking::ID,
+        &staking::instruction::UpdatePoolAuthority { pool_authority }.data(),
+        accs.to_account_metas(None),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&governance_authority.pubkey()),
+        &[governance_authority],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
+pub fn accept_pool_authority(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    new_pool_authority: &Keypair,
+) -> TransactionResult {
+    let accs = staking::accounts::AcceptPoolAuthority {
+        new_pool_authority: new_pool_authority.pubkey(),
+        config:             get_config_address(),
+    };
+
+    let ix = Instruction::new_with_bytes(
+        staking::ID,
+        &staking::instruction::AcceptPoolAuthority {}.data(),
+        accs.to_account_metas(None),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[payer, new_pool_authority],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
+pub fn cancel_pool_authority_transfer(
+    svm: &mut litesvm::LiteSVM,
+    governance_authority: &Keypair,
+) -> TransactionResult {
+    let accs = staking::accounts::CancelPoolAuthorityTransfer {
+        governance_authority: governance_authority.pubkey(),
+        config:               get_config_address(),
+    };
+
+    let ix = Instruction::new_with_bytes(
+        staking::ID,
+        &staking::instruction::CancelPoolAuthorityTransfer {}.data(),
+        accs.to_account_metas(None),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&governance_authority.pubkey()),
+        &[governance_authority],
         svm.latest_blockhash(),
     );
 
-    svm.send_transaction(update_pool_authority_tx).unwrap();
+    svm.send_transaction(tx)
 }
 
 pub fn create_target_account(svm: &mut litesvm::LiteSVM, payer: &Keypair) {
@@ -201,7 +415,7 @@ pub fn close_position(
     target_with_parameters: TargetWithParameters,
     pool_authority: Option<&Keypair>,
     amount: frac64,
-    index: u8,
+    index: u16,
 ) -> TransactionResult {
     let config_pubkey = get_config_address();
     let stake_account_metadata = get_stake_account_metadata_address(stake_account_positions);
@@ -252,11 +466,78 @@ pub fn close_position(
     svm.send_transaction(close_position_tx)
 }
 
+pub fn cancel_unlock(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    stake_account_positions: Pubkey,
+    target_with_parameters: TargetWithParameters,
+    pool_authority: Option<&Keypair>,
+    index: u16,
+) -> TransactionResult {
+    let config_pubkey = get_config_address();
+    let stake_account_metadata = get_stake_account_metadata_address(stake_account_positions);
+    let stake_account_custody = get_stake_account_custody_address(stake_account_positions);
+
+    let cancel_unlock_data = staking::instruction::CancelUnlock { index };
+
+    let target_account = match target_with_parameters {
+        TargetWithParameters::Voting => Some(get_target_address()),
+        TargetWithParameters::IntegrityPool { .. } => None,
+    };
+
+    let cancel_unlock_accs = staking::accounts::CancelUnlock {
+        config: config_pubkey,
+        stake_account_metadata,
+        stake_account_positions,
+        stake_account_custody,
+        owner: payer.pubkey(),
+        target_account,
+        pool_authority: pool_authority.map(|k| k.pubkey()),
+    };
+
+    let cancel_unlock_ix = Instruction::new_with_bytes(
+        staking::ID,
+        &cancel_unlock_data.data(),
+        cancel_unlock_accs.to_account_metas(None),
+    );
+
+    let mut signing_keypairs: Vec<&Keypair> = vec![&payer];
+
+    if let Some(pool_authority) = pool_authority {
+        signing_keypairs.push(pool_authority);
+    }
+
+    let cancel_unlock_tx = Transaction::new_signed_with_payer(
+        &[cancel_unlock_ix],
+        Some(&payer.pubkey()),
+        signing_keypairs.as_slice(),
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(cancel_unlock_tx)
+}
+
 pub fn create_stake_account(
     svm: &mut litesvm::LiteSVM,
     payer: &Keypair,
     pyth_token_mint: &Keypair,
     stake_account_positions: Pubkey,
+) -> TransactionResult {
+    create_stake_account_with_lock(
+        svm,
+        payer,
+        pyth_token_mint,
+        stake_account_positions,
+        staking::state::vesting::VestingSchedule::FullyVested,
+    )
+}
+
+pub fn create_stake_account_with_lock(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    pyth_token_mint: &Keypair,
+    stake_account_positions: Pubkey,
+    lock: staking::state::vesting::VestingSchedule,
 ) -> TransactionResult {
     let stake_account_metadata = get_stake_account_metadata_address(stake_account_positions);
     let stake_account_custody = get_stake_account_custody_address(stake_account_positions);
@@ -265,7 +546,7 @@ pub fn create_stake_account(
 
     let create_stake_account_data = staking::instruction::CreateStakeAccount {
         owner: payer.pubkey(),
-        lock:  staking::state::vesting::VestingSchedule::FullyVested,
+        lock,
     };
     let create_stake_account_accs = staking::accounts::CreateStakeAccount {
         payer: payer.pubkey(),
@@ -328,6 +609,68 @@ pub fn join_dao_llc(
     svm.send_transaction(join_dao_llc_tx)
 }
 
+pub fn set_operator(
+    svm: &mut litesvm::LiteSVM,
+    owner: &Keypair,
+    stake_account_positions: Pubkey,
+    operator: Option<Pubkey>,
+) -> TransactionResult {
+    let accs = staking::accounts::SetOperator {
+        owner: owner.pubkey(),
+        stake_account_positions,
+        stake_account_metadata: get_stake_account_metadata_address(stake_account_positions),
+        config: get_config_address(),
+        system_program: system_program::ID,
+    };
+
+    let ix = Instruction::new_with_bytes(
+        staking::ID,
+        &staking::instruction::SetOperator { operator }.data(),
+        accs.to_account_metas(None),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
+pub fn withdraw_stake(
+    svm: &mut litesvm::LiteSVM,
+    owner: &Keypair,
+    stake_account_positions: Pubkey,
+    destination: Pubkey,
+    amount: u64,
+) -> TransactionResult {
+    let accs = staking::accounts::WithdrawStake {
+        owner: owner.pubkey(),
+        destination,
+        stake_account_positions,
+        stake_account_metadata: get_stake_account_metadata_address(stake_account_positions),
+        stake_account_custody: get_stake_account_custody_address(stake_account_positions),
+        custody_authority: get_stake_account_custody_authority_address(stake_account_positions),
+        config: get_config_address(),
+        token_program: spl_token::ID,
+    };
+
+    let ix = Instruction::new_with_bytes(
+        staking::ID,
+        &staking::instruction::WithdrawStake { amount }.data(),
+        accs.to_account_metas(None),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
 pub fn slash_staking(
     svm: &mut litesvm::LiteSVM,
     payer: &Keypair,
@@ -378,6 +721,67 @@ pub fn slash_staking(
     svm.send_transaction(slash_account_tx)
 }
 
+pub fn accelerate_vesting(
+    svm: &mut litesvm::LiteSVM,
+    governance_authority: &Keypair,
+    stake_account_positions: Pubkey,
+    lock: staking::state::vesting::VestingSchedule,
+) -> TransactionResult {
+    let accs = staking::accounts::AccelerateVesting {
+        governance_authority: governance_authority.pubkey(),
+        stake_account_positions,
+        stake_account_metadata: get_stake_account_metadata_address(stake_account_positions),
+        config: get_config_address(),
+    };
+
+    let ix = Instruction::new_with_bytes(
+        staking::ID,
+        &staking::instruction::AccelerateVesting { lock }.data(),
+        accs.to_account_metas(None),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&governance_authority.pubkey()),
+        &[&governance_authority],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
+pub fn clawback(
+    svm: &mut litesvm::LiteSVM,
+    governance_authority: &Keypair,
+    stake_account_positions: Pubkey,
+    treasury: Pubkey,
+) -> TransactionResult {
+    let accs = staking::accounts::Clawback {
+        governance_authority: governance_authority.pubkey(),
+        stake_account_positions,
+        stake_account_metadata: get_stake_account_metadata_address(stake_account_positions),
+        stake_account_custody: get_stake_account_custody_address(stake_account_positions),
+        custody_authority: get_stake_account_custody_authority_address(stake_account_positions),
+        config: get_config_address(),
+        governance_target_account: get_target_address(),
+        treasury,
+        token_program: spl_token::ID,
+    };
+
+    let ix = Instruction::new_with_bytes(
+        staking::ID,
+        &staking::instruction::Clawback {}.data(),
+        accs.to_account_metas(None),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&governance_authority.pubkey()),
+        &[&governance_authority],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
 pub fn get_update_voter_weight_instruction(
     payer: Pubkey,
     stake_account_positions: Pubkey,
@@ -441,6 +845,60 @@ pub fn update_token_list_time(svm: &mut litesvm::LiteSVM, payer: &Keypair, token
     svm.send_transaction(update_token_list_time_tx).unwrap();
 }
 
+pub fn update_unlocking_durations(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    voting_unlocking_duration: Option<u64>,
+    integrity_pool_unlocking_duration: Option<u64>,
+) -> TransactionResult {
+    let update_unlocking_durations_data = staking::instruction::UpdateUnlockingDurations {
+        voting_unlocking_duration,
+        integrity_pool_unlocking_duration,
+    };
+    let update_unlocking_durations_accs = staking::accounts::UpdateUnlockingDurations {
+        config:               get_config_address(),
+        governance_authority: payer.pubkey(),
+    };
+    let update_unlocking_durations_ix = Instruction::new_with_bytes(
+        staking::ID,
+        &update_unlocking_durations_data.data(),
+        update_unlocking_durations_accs.to_account_metas(None),
+    );
+    let update_unlocking_durations_tx = Transaction::new_signed_with_payer(
+        &[update_unlocking_durations_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(update_unlocking_durations_tx)
+}
+
+pub fn update_epoch_duration(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    epoch_duration: u64,
+) -> TransactionResult {
+    let update_epoch_duration_data = staking::instruction::UpdateEpochDuration { epoch_duration };
+    let update_epoch_duration_accs = staking::accounts::UpdateEpochDuration {
+        config:               get_config_address(),
+        governance_authority: payer.pubkey(),
+    };
+    let update_epoch_duration_ix = Instruction::new_with_bytes(
+        staking::ID,
+        &update_epoch_duration_data.data(),
+        update_epoch_duration_accs.to_account_metas(None),
+    );
+    let update_epoch_duration_tx = Transaction::new_signed_with_payer(
+        &[update_epoch_duration_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(update_epoch_duration_tx)
+}
+
 pub fn update_voter_weight(
     svm: &mut litesvm::LiteSVM,
     payer: &Keypair,
@@ -481,6 +939,74 @@ pub fn update_max_voter_weight(svm: &mut litesvm::LiteSVM, payer: &Keypair) {
     svm.send_transaction(tx).unwrap();
 }
 
+pub fn get_withdrawable_balance(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    stake_account_positions: Pubkey,
+) -> TransactionResult {
+    let data = staking::instruction::GetWithdrawableBalance {};
+    let accs = staking::accounts::GetWithdrawableBalance {
+        stake_account_positions,
+        stake_account_metadata: get_stake_account_metadata_address(stake_account_positions),
+        stake_account_custody: get_stake_account_custody_address(stake_account_positions),
+        config: get_config_address(),
+    };
+    let ix = Instruction::new_with_bytes(staking::ID, &data.data(), accs.to_account_metas(None));
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
+pub fn get_voter_weight_at_epoch(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    stake_account_positions: Pubkey,
+    epoch: u64,
+) -> TransactionResult {
+    let data = staking::instruction::GetVoterWeightAtEpoch { epoch };
+    let accs = staking::accounts::GetVoterWeightAtEpoch {
+        stake_account_positions,
+        stake_account_metadata: get_stake_account_metadata_address(stake_account_positions),
+        config: get_config_address(),
+        governance_target: get_target_address(),
+    };
+    let ix = Instruction::new_with_bytes(staking::ID, &data.data(), accs.to_account_metas(None));
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
+pub fn get_position_summary(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    stake_account_positions: Pubkey,
+) -> TransactionResult {
+    let data = staking::instruction::GetPositionSummary {};
+    let accs = staking::accounts::GetPositionSummary {
+        stake_account_positions,
+        config: get_config_address(),
+    };
+    let ix = Instruction::new_with_bytes(staking::ID, &data.data(), accs.to_account_metas(None));
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
 pub fn merge_target_positions(
     svm: &mut litesvm::LiteSVM,
     payer: &Keypair,
@@ -511,6 +1037,32 @@ pub fn merge_target_positions(
     svm.send_transaction(tx)
 }
 
+pub fn migrate_positions(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    stake_account_positions: Pubkey,
+) -> TransactionResult {
+    let stake_account_metadata = get_stake_account_metadata_address(stake_account_positions);
+
+    let data = staking::instruction::MigratePositions {};
+
+    let accs = staking::accounts::MigratePositions {
+        owner: payer.pubkey(),
+        stake_account_positions,
+        stake_account_metadata,
+        system_program: system_program::ID,
+    };
+    let ix = Instruction::new_with_bytes(staking::ID, &data.data(), accs.to_account_metas(None));
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
 pub fn transfer_account(
     svm: &mut litesvm::LiteSVM,
     governance_authority: &Keypair,
@@ -545,6 +1097,196 @@ pub fn transfer_account(
     svm.send_transaction(tx)
 }
 
+pub fn close_stake_account(
+    svm: &mut litesvm::LiteSVM,
+    owner: &Keypair,
+    stake_account_positions: Pubkey,
+) -> TransactionResult {
+    let pool_config = get_pool_config_address();
+    let pool_data = fetch_account_data::<PoolConfig>(svm, &pool_config).pool_data;
+
+    let accs = staking::accounts::CloseStakeAccount {
+        owner: owner.pubkey(),
+        stake_account_positions,
+        stake_account_metadata: get_stake_account_metadata_address(stake_account_positions),
+        stake_account_custody: get_stake_account_custody_address(stake_account_positions),
+        custody_authority: get_stake_account_custody_authority_address(stake_account_positions),
+        voter_record: get_voter_record_address(stake_account_positions),
+        stake_account_split_request: get_split_request_address(stake_account_positions),
+        config: get_config_address(),
+        pool_config,
+        pool_data,
+        token_program: spl_token::ID,
+    };
+
+    let ix = Instruction::new_with_bytes(
+        staking::ID,
+        &staking::instruction::CloseStakeAccount {}.data(),
+        accs.to_account_metas(None),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
+pub fn check_delegation_records(
+    svm: &mut litesvm::LiteSVM,
+    payer: &Keypair,
+    stake_account_positions: Pubkey,
+    publishers: &[Pubkey],
+) -> TransactionResult {
+    let pool_config = get_pool_config_address();
+    let pool_data = fetch_account_data::<PoolConfig>(svm, &pool_config).pool_data;
+
+    let mut accounts = staking::accounts::CheckDelegationRecords {
+        payer: payer.pubkey(),
+        stake_account_positions,
+        stake_account_metadata: get_stake_account_metadata_address(stake_account_positions),
+        config: get_config_address(),
+        pool_config,
+        pool_data,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+
+    for publisher in publishers {
+        accounts.push(AccountMeta::new_readonly(
+            get_delegation_record_address(*publisher, stake_account_positions),
+            false,
+        ));
+    }
+
+    let ix = Instruction::new_with_bytes(
+        staking::ID,
+        &staking::instruction::CheckDelegationRecords {}.data(),
+        accounts,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
+pub fn request_split(
+    svm: &mut litesvm::LiteSVM,
+    owner: &Keypair,
+    stake_account_positions: Pubkey,
+    amount: u64,
+    recipient: Pubkey,
+) -> TransactionResult {
+    let accs = staking::accounts::RequestSplit {
+        owner: owner.pubkey(),
+        stake_account_positions,
+        stake_account_metadata: get_stake_account_metadata_address(stake_account_positions),
+        stake_account_split_request: get_split_request_address(stake_account_positions),
+        config: get_config_address(),
+        system_program: system_program::ID,
+    };
+
+    let ix = Instruction::new_with_bytes(
+        staking::ID,
+        &staking::instruction::RequestSplit { amount, recipient }.data(),
+        accs.to_account_metas(None),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
+pub fn cancel_split(
+    svm: &mut litesvm::LiteSVM,
+    owner: &Keypair,
+    stake_account_positions: Pubkey,
+) -> TransactionResult {
+    let accs = staking::accounts::CancelSplit {
+        owner: owner.pubkey(),
+        stake_account_positions,
+        stake_account_metadata: get_stake_account_metadata_address(stake_account_positions),
+        stake_account_split_request: get_split_request_address(stake_account_positions),
+    };
+
+    let ix = Instruction::new_with_bytes(
+        staking::ID,
+        &staking::instruction::CancelSplit {}.data(),
+        accs.to_account_metas(None),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&owner.pubkey()),
+        &[&owner],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
+pub fn accept_split(
+    svm: &mut litesvm::LiteSVM,
+    pda_authority: &Keypair,
+    pyth_token_mint: Pubkey,
+    source_stake_account_positions: Pubkey,
+    new_stake_account_positions: Pubkey,
+    amount: u64,
+    recipient: Pubkey,
+) -> TransactionResult {
+    let accs = staking::accounts::AcceptSplit {
+        pda_authority: pda_authority.pubkey(),
+        source_stake_account_positions,
+        source_stake_account_metadata: get_stake_account_metadata_address(
+            source_stake_account_positions,
+        ),
+        source_stake_account_split_request: get_split_request_address(
+            source_stake_account_positions,
+        ),
+        source_stake_account_custody: get_stake_account_custody_address(
+            source_stake_account_positions,
+        ),
+        source_custody_authority: get_stake_account_custody_authority_address(
+            source_stake_account_positions,
+        ),
+        new_stake_account_positions,
+        new_stake_account_metadata: get_stake_account_metadata_address(new_stake_account_positions),
+        new_stake_account_custody: get_stake_account_custody_address(new_stake_account_positions),
+        new_custody_authority: get_stake_account_custody_authority_address(
+            new_stake_account_positions,
+        ),
+        config: get_config_address(),
+        pyth_token_mint,
+        rent: solana_program::sysvar::rent::ID,
+        token_program: spl_token::ID,
+        system_program: system_program::ID,
+        pool_authority: None,
+    };
+
+    let ix = Instruction::new_with_bytes(
+        staking::ID,
+        &staking::instruction::AcceptSplit { amount, recipient }.data(),
+        accs.to_account_metas(None),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&pda_authority.pubkey()),
+        &[&pda_authority],
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
 pub fn create_voter_record(
     svm: &mut litesvm::LiteSVM,
     payer: &Keypair,
@@ -67,6 +67,17 @@ pub fn get_voter_record_address(stake_account_positions: Pubkey) -> Pubkey {
     .0
 }
 
+pub fn get_split_request_address(stake_account_positions: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            staking::context::SPLIT_REQUEST.as_bytes(),
+            stake_account_positions.as_ref(),
+        ],
+        &staking::ID,
+    )
+    .0
+}
+
 pub fn get_max_voter_record_address() -> Pubkey {
     Pubkey::find_program_address(
         &[staking::context::MAX_VOTER_RECORD_SEED.as_bytes()],
@@ -1,10 +1,13 @@
 use {
-    crate::staking::pda::get_config_address,
+    crate::{
+        solana::utils::fetch_account_data,
+        staking::pda::get_config_address,
+        utils::constants::EPOCH_DURATION,
+    },
     anchor_lang::{
         InstructionData,
         ToAccountMetas,
     },
-    integrity_pool::utils::clock::EPOCH_DURATION,
     litesvm::LiteSVM,
     solana_sdk::{
         clock::Clock,
@@ -13,6 +16,10 @@ use {
         signer::Signer,
         transaction::Transaction,
     },
+    staking::{
+        state::global_config::GlobalConfig,
+        utils::clock::time_to_epoch,
+    },
     std::convert::TryInto,
 };
 
@@ -29,8 +36,8 @@ pub fn advance_n_epochs(svm: &mut LiteSVM, payer: &Keypair, n: u64) {
 }
 
 pub fn get_current_epoch(svm: &mut LiteSVM) -> u64 {
-    let clock = svm.get_sysvar::<Clock>();
-    clock.unix_timestamp as u64 / EPOCH_DURATION
+    let config: GlobalConfig = fetch_account_data(svm, &get_config_address());
+    time_to_epoch(&config, config.mock_clock_time).unwrap()
 }
 
 pub fn advance_mock_clock(svm: &mut LiteSVM, payer: &Keypair, seconds: i64) {
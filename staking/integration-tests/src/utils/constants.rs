@@ -8,3 +8,8 @@ pub const STAKED_TOKENS: frac64 = 100 * FRAC_64_MULTIPLIER;
 
 // 1% yield per epoch
 pub const YIELD: frac64 = FRAC_64_MULTIPLIER / 100;
+
+// 10% maximum delegation fee
+pub const MAX_DELEGATION_FEE: frac64 = FRAC_64_MULTIPLIER / 10;
+
+pub const EPOCH_DURATION: u64 = 60 * 60 * 24 * 7; // 1 week
@@ -0,0 +1,26 @@
+use {
+    anchor_lang::Event,
+    base64::{
+        prelude::BASE64_STANDARD,
+        Engine,
+    },
+};
+
+/// `emit!` logs the events with `sol_log_data`, which writes them as base64 after this prefix
+pub const PROGRAM_DATA_LOG_PREFIX: &str = "Program data: ";
+
+/// Decodes a log line into an event of type `T`, returns `None` if the line is another log or
+/// another event
+pub fn parse_event<T: Event>(log: &str) -> Option<T> {
+    let data = BASE64_STANDARD
+        .decode(log.strip_prefix(PROGRAM_DATA_LOG_PREFIX)?)
+        .ok()?;
+    let event_data = data.strip_prefix(&T::DISCRIMINATOR[..])?;
+    T::try_from_slice(event_data).ok()
+}
+
+/// Decodes the events of type `T` from the logs of a transaction, including the ones emitted
+/// during CPIs, in the order they were emitted
+pub fn parse_events<T: Event>(logs: &[String]) -> Vec<T> {
+    logs.iter().filter_map(|log| parse_event(log)).collect()
+}
@@ -0,0 +1,147 @@
+use {
+    integration_tests::{
+        assert_anchor_program_error,
+        setup::{
+            setup,
+            SetupProps,
+            SetupResult,
+        },
+        solana::{
+            instructions::create_account,
+            utils::fetch_account_data,
+        },
+        staking::{
+            instructions::{
+                accelerate_vesting,
+                create_stake_account_with_lock,
+            },
+            pda::{
+                get_config_address,
+                get_stake_account_metadata_address,
+            },
+        },
+        utils::constants::EPOCH_DURATION,
+    },
+    integrity_pool::utils::types::FRAC_64_MULTIPLIER,
+    solana_sdk::{
+        native_token::LAMPORTS_PER_SOL,
+        signature::Keypair,
+        signer::Signer,
+    },
+    staking::{
+        error::ErrorCode,
+        state::{
+            global_config::GlobalConfig,
+            positions::PositionData,
+            stake_account::StakeAccountMetadataV2,
+            vesting::VestingSchedule,
+        },
+    },
+};
+
+#[test]
+fn test_accelerate_vesting() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair: _,
+        pool_data_pubkey: _,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    let config: GlobalConfig = fetch_account_data(&mut svm, &get_config_address());
+    let vesting_schedule = |num_periods| VestingSchedule::PeriodicVesting {
+        initial_balance: 60 * FRAC_64_MULTIPLIER,
+        start_date: config.mock_clock_time,
+        period_duration: EPOCH_DURATION,
+        num_periods,
+    };
+
+    let stake_account_positions = create_account(&mut svm, &payer, PositionData::LEN, staking::ID);
+    create_stake_account_with_lock(
+        &mut svm,
+        &payer,
+        &pyth_token_mint,
+        stake_account_positions,
+        vesting_schedule(4),
+    )
+    .unwrap();
+    let stake_account_metadata = get_stake_account_metadata_address(stake_account_positions);
+
+    // only the governance authority can accelerate vesting
+    let attacker = Keypair::new();
+    svm.airdrop(&attacker.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    assert_anchor_program_error!(
+        accelerate_vesting(
+            &mut svm,
+            &attacker,
+            stake_account_positions,
+            vesting_schedule(2)
+        ),
+        anchor_lang::error::ErrorCode::ConstraintHasOne,
+        0
+    );
+
+    // the new schedule can't vest slower
+    assert_anchor_program_error!(
+        accelerate_vesting(
+            &mut svm,
+            &payer,
+            stake_account_positions,
+            vesting_schedule(8)
+        ),
+        ErrorCode::InvalidVestingAcceleration,
+        0
+    );
+
+    let metadata = accelerate_vesting(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        vesting_schedule(2),
+    )
+    .unwrap();
+    assert!(metadata
+        .logs
+        .iter()
+        .any(|log| log.starts_with("Program data: ")));
+
+    let stake_account_metadata_data: StakeAccountMetadataV2 =
+        fetch_account_data(&mut svm, &stake_account_metadata);
+    assert_eq!(stake_account_metadata_data.lock, vesting_schedule(2));
+
+    // going back to the previous schedule would slow vesting down
+    assert_anchor_program_error!(
+        accelerate_vesting(
+            &mut svm,
+            &payer,
+            stake_account_positions,
+            vesting_schedule(4)
+        ),
+        ErrorCode::InvalidVestingAcceleration,
+        0
+    );
+
+    accelerate_vesting(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        VestingSchedule::FullyVested,
+    )
+    .unwrap();
+    let stake_account_metadata_data: StakeAccountMetadataV2 =
+        fetch_account_data(&mut svm, &stake_account_metadata);
+    assert_eq!(
+        stake_account_metadata_data.lock,
+        VestingSchedule::FullyVested
+    );
+}
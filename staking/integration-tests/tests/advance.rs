@@ -8,11 +8,17 @@ use {
                 advance,
                 advance_delegation_record,
                 delegate,
+                set_publisher_delegation_fee,
                 set_publisher_stake_account,
                 undelegate,
                 update_delegation_fee,
+                update_fee_notice_epochs,
+                update_max_delegation_fee,
+            },
+            pda::{
+                get_pool_config_address,
+                get_pool_reward_custody_address,
             },
-            pda::get_pool_reward_custody_address,
         },
         publisher_caps::{
             helper_functions::post_dummy_publisher_caps,
@@ -37,10 +43,12 @@ use {
         },
         utils::{
             clock::{
+                advance_mock_clock,
                 advance_n_epochs,
                 get_current_epoch,
             },
             constants::{
+                EPOCH_DURATION,
                 STAKED_TOKENS,
                 YIELD,
             },
@@ -50,20 +58,28 @@ use {
         error::IntegrityPoolError,
         state::{
             event::Event,
-            pool::PoolData,
+            pool::{
+                DelegationFeeChange,
+                PoolConfig,
+                PoolData,
+            },
         },
         utils::{
             constants::{
                 MAX_EVENTS,
+                MAX_FEE_NOTICE_EPOCHS,
                 MAX_PUBLISHERS,
             },
             types::FRAC_64_MULTIPLIER,
         },
     },
     solana_sdk::{
+        clock::Clock,
         pubkey::Pubkey,
+        signature::Keypair,
         signer::Signer,
     },
+    std::convert::TryInto,
 };
 
 #[test]
@@ -126,6 +142,51 @@ fn test_advance() {
     );
 }
 
+#[test]
+fn test_advance_follows_mock_clock() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint: _,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    // only the mock clock of the staking program moves, the integrity pool should follow it
+    advance_mock_clock(&mut svm, &payer, EPOCH_DURATION.try_into().unwrap());
+    assert_eq!(get_current_epoch(&mut svm), STARTING_EPOCH + 1);
+
+    // the caps are published at the time of the sysvar clock, which is still in the previous epoch
+    let publisher_caps =
+        post_dummy_publisher_caps(&mut svm, &payer, publisher_keypair.pubkey(), 50);
+    assert_anchor_program_error!(
+        advance(&mut svm, &payer, publisher_caps),
+        IntegrityPoolError::OutdatedPublisherCaps,
+        0
+    );
+
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += TryInto::<i64>::try_into(EPOCH_DURATION).unwrap();
+    svm.set_sysvar::<Clock>(&clock);
+    svm.expire_blockhash();
+
+    let publisher_caps =
+        post_dummy_publisher_caps(&mut svm, &payer, publisher_keypair.pubkey(), 50);
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+
+    let pool_data = fetch_account_data_bytemuck::<PoolData>(&mut svm, &pool_data_pubkey);
+    assert_eq!(pool_data.last_updated_epoch, STARTING_EPOCH + 1);
+}
+
 #[test]
 fn test_advance_reward_events() {
     let SetupResult {
@@ -257,6 +318,27 @@ fn test_reward_events_with_delegation_fee() {
     let publisher_stake_account_positions =
         initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
 
+    // the fee can't exceed the maximum delegation fee
+    assert_anchor_program_error!(
+        update_delegation_fee(
+            &mut svm,
+            &payer,
+            pool_data_pubkey,
+            &reward_program_authority,
+            FRAC_64_MULTIPLIER / 20,
+        ),
+        IntegrityPoolError::DelegationFeeTooHigh,
+        0
+    );
+
+    update_max_delegation_fee(
+        &mut svm,
+        &payer,
+        &reward_program_authority,
+        FRAC_64_MULTIPLIER / 20,
+    )
+    .unwrap();
+    update_fee_notice_epochs(&mut svm, &payer, &reward_program_authority, 1).unwrap();
     update_delegation_fee(
         &mut svm,
         &payer,
@@ -395,6 +477,491 @@ fn test_reward_events_with_delegation_fee() {
     );
 }
 
+#[test]
+fn test_set_publisher_delegation_fee() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority,
+        maybe_publisher_index,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+    let publisher_index = maybe_publisher_index.unwrap();
+
+    // only the reward program authority can set the maximum
+    assert_anchor_program_error!(
+        update_max_delegation_fee(&mut svm, &payer, &payer, FRAC_64_MULTIPLIER / 10),
+        IntegrityPoolError::InvalidRewardProgramAuthority,
+        0
+    );
+    assert_anchor_program_error!(
+        update_max_delegation_fee(
+            &mut svm,
+            &payer,
+            &reward_program_authority,
+            FRAC_64_MULTIPLIER + 1
+        ),
+        IntegrityPoolError::InvalidDelegationFee,
+        0
+    );
+    update_max_delegation_fee(
+        &mut svm,
+        &payer,
+        &reward_program_authority,
+        FRAC_64_MULTIPLIER / 10,
+    )
+    .unwrap();
+    update_fee_notice_epochs(&mut svm, &payer, &reward_program_authority, 1).unwrap();
+
+    assert_anchor_program_error!(
+        set_publisher_delegation_fee(
+            &mut svm,
+            &payer,
+            &Keypair::new(),
+            publisher_keypair.pubkey(),
+            None,
+            FRAC_64_MULTIPLIER / 20,
+        ),
+        IntegrityPoolError::PublisherOrPublisherStakeAccountOwnerNeedsToSign,
+        0
+    );
+    assert_anchor_program_error!(
+        set_publisher_delegation_fee(
+            &mut svm,
+            &payer,
+            &publisher_keypair,
+            publisher_keypair.pubkey(),
+            None,
+            FRAC_64_MULTIPLIER / 5,
+        ),
+        IntegrityPoolError::DelegationFeeTooHigh,
+        0
+    );
+
+    set_publisher_delegation_fee(
+        &mut svm,
+        &payer,
+        &publisher_keypair,
+        publisher_keypair.pubkey(),
+        None,
+        FRAC_64_MULTIPLIER / 20,
+    )
+    .unwrap();
+
+    // the new fee only takes effect at the next epoch
+    let pool_data = fetch_account_data_bytemuck::<PoolData>(&mut svm, &pool_data_pubkey);
+    assert_eq!(pool_data.delegation_fees[publisher_index], 0);
+    assert_eq!(
+        pool_data.pending_delegation_fees[publisher_index],
+        FRAC_64_MULTIPLIER / 20
+    );
+    assert_eq!(
+        pool_data.pending_fee_epochs[publisher_index],
+        STARTING_EPOCH + 1
+    );
+
+    advance_n_epochs(&mut svm, &payer, 1);
+    let publisher_caps =
+        post_dummy_publisher_caps(&mut svm, &payer, publisher_keypair.pubkey(), 50);
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+
+    let pool_data = fetch_account_data_bytemuck::<PoolData>(&mut svm, &pool_data_pubkey);
+    assert_eq!(pool_data.events[2].epoch, 2);
+    assert_eq!(
+        pool_data.events[2].event_data[publisher_index].delegation_fee,
+        0
+    );
+    assert_eq!(
+        pool_data.delegation_fees[publisher_index],
+        FRAC_64_MULTIPLIER / 20
+    );
+    assert_eq!(pool_data.pending_fee_epochs[publisher_index], 0);
+
+    // the owner of the publisher stake account can also set the fee
+    let publisher_stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+    let other_stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+
+    assert_anchor_program_error!(
+        set_publisher_delegation_fee(
+            &mut svm,
+            &payer,
+            &payer,
+            publisher_keypair.pubkey(),
+            Some(publisher_stake_account_positions),
+            FRAC_64_MULTIPLIER / 10,
+        ),
+        IntegrityPoolError::PublisherStakeAccountMismatch,
+        0
+    );
+
+    set_publisher_stake_account(
+        &mut svm,
+        &payer,
+        &publisher_keypair,
+        publisher_keypair.pubkey(),
+        None,
+        Some(publisher_stake_account_positions),
+    )
+    .unwrap();
+
+    assert_anchor_program_error!(
+        set_publisher_delegation_fee(
+            &mut svm,
+            &payer,
+            &payer,
+            publisher_keypair.pubkey(),
+            Some(other_stake_account_positions),
+            FRAC_64_MULTIPLIER / 10,
+        ),
+        IntegrityPoolError::PublisherStakeAccountMismatch,
+        0
+    );
+
+    set_publisher_delegation_fee(
+        &mut svm,
+        &payer,
+        &payer,
+        publisher_keypair.pubkey(),
+        Some(publisher_stake_account_positions),
+        FRAC_64_MULTIPLIER / 10,
+    )
+    .unwrap();
+
+    advance_n_epochs(&mut svm, &payer, 2);
+
+    // the pool needs to be up to date before the fee can be changed
+    assert_anchor_program_error!(
+        set_publisher_delegation_fee(
+            &mut svm,
+            &payer,
+            &publisher_keypair,
+            publisher_keypair.pubkey(),
+            None,
+            0,
+        ),
+        IntegrityPoolError::OutdatedPublisherAccounting,
+        0
+    );
+
+    let publisher_caps =
+        post_dummy_publisher_caps(&mut svm, &payer, publisher_keypair.pubkey(), 50);
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+
+    let pool_data = fetch_account_data_bytemuck::<PoolData>(&mut svm, &pool_data_pubkey);
+    assert_eq!(pool_data.events[3].epoch, 3);
+    assert_eq!(
+        pool_data.events[3].event_data[publisher_index].delegation_fee,
+        FRAC_64_MULTIPLIER / 20
+    );
+    assert_eq!(pool_data.events[4].epoch, 4);
+    assert_eq!(
+        pool_data.events[4].event_data[publisher_index].delegation_fee,
+        FRAC_64_MULTIPLIER / 10
+    );
+    assert_eq!(
+        pool_data.delegation_fees[publisher_index],
+        FRAC_64_MULTIPLIER / 10
+    );
+    assert_eq!(pool_data.pending_fee_epochs[publisher_index], 0);
+}
+
+#[test]
+fn test_delegation_fee_notice_period() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint: _,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority,
+        maybe_publisher_index,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+    let publisher_index = maybe_publisher_index.unwrap();
+
+    assert_anchor_program_error!(
+        update_fee_notice_epochs(&mut svm, &payer, &payer, 3),
+        IntegrityPoolError::InvalidRewardProgramAuthority,
+        0
+    );
+    assert_anchor_program_error!(
+        update_fee_notice_epochs(
+            &mut svm,
+            &payer,
+            &reward_program_authority,
+            MAX_FEE_NOTICE_EPOCHS + 1
+        ),
+        IntegrityPoolError::InvalidFeeNoticeEpochs,
+        0
+    );
+    update_fee_notice_epochs(&mut svm, &payer, &reward_program_authority, 3).unwrap();
+    update_max_delegation_fee(
+        &mut svm,
+        &payer,
+        &reward_program_authority,
+        FRAC_64_MULTIPLIER / 10,
+    )
+    .unwrap();
+
+    // a fee increase only takes effect after the notice period
+    set_publisher_delegation_fee(
+        &mut svm,
+        &payer,
+        &publisher_keypair,
+        publisher_keypair.pubkey(),
+        None,
+        FRAC_64_MULTIPLIER / 10,
+    )
+    .unwrap();
+
+    let pool_data = fetch_account_data_bytemuck::<PoolData>(&mut svm, &pool_data_pubkey);
+    assert_eq!(
+        pool_data.pending_fee_epochs[publisher_index],
+        STARTING_EPOCH + 3
+    );
+
+    advance_n_epochs(&mut svm, &payer, 1);
+    let publisher_caps =
+        post_dummy_publisher_caps(&mut svm, &payer, publisher_keypair.pubkey(), 50);
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+
+    let pool_data = fetch_account_data_bytemuck::<PoolData>(&mut svm, &pool_data_pubkey);
+    assert_eq!(pool_data.delegation_fees[publisher_index], 0);
+    assert_eq!(
+        pool_data.pending_fee_epochs[publisher_index],
+        STARTING_EPOCH + 3
+    );
+
+    advance_n_epochs(&mut svm, &payer, 2);
+    let publisher_caps =
+        post_dummy_publisher_caps(&mut svm, &payer, publisher_keypair.pubkey(), 50);
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+
+    let pool_data = fetch_account_data_bytemuck::<PoolData>(&mut svm, &pool_data_pubkey);
+    for epoch in 2..5 {
+        assert_eq!(pool_data.events[epoch].epoch, epoch as u64);
+        assert_eq!(
+            pool_data.events[epoch].event_data[publisher_index].delegation_fee,
+            0
+        );
+    }
+    assert_eq!(
+        pool_data.delegation_fees[publisher_index],
+        FRAC_64_MULTIPLIER / 10
+    );
+    assert_eq!(pool_data.pending_fee_epochs[publisher_index], 0);
+    assert_eq!(
+        pool_data.fee_history[publisher_index][0],
+        DelegationFeeChange {
+            epoch:          STARTING_EPOCH + 3,
+            delegation_fee: FRAC_64_MULTIPLIER / 10,
+        }
+    );
+
+    // a fee decrease takes effect at the next epoch, also when made by the reward program
+    // authority
+    update_delegation_fee(
+        &mut svm,
+        &payer,
+        pool_data_pubkey,
+        &reward_program_authority,
+        FRAC_64_MULTIPLIER / 20,
+    )
+    .unwrap();
+
+    let pool_data = fetch_account_data_bytemuck::<PoolData>(&mut svm, &pool_data_pubkey);
+    assert_eq!(
+        pool_data.pending_delegation_fees[publisher_index],
+        FRAC_64_MULTIPLIER / 20
+    );
+    assert_eq!(
+        pool_data.pending_fee_epochs[publisher_index],
+        STARTING_EPOCH + 4
+    );
+
+    advance_n_epochs(&mut svm, &payer, 1);
+    let publisher_caps =
+        post_dummy_publisher_caps(&mut svm, &payer, publisher_keypair.pubkey(), 50);
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+
+    let pool_data = fetch_account_data_bytemuck::<PoolData>(&mut svm, &pool_data_pubkey);
+    assert_eq!(
+        pool_data.events[5].event_data[publisher_index].delegation_fee,
+        FRAC_64_MULTIPLIER / 10
+    );
+    assert_eq!(
+        pool_data.delegation_fees[publisher_index],
+        FRAC_64_MULTIPLIER / 20
+    );
+    assert_eq!(
+        pool_data.fee_history[publisher_index][1],
+        DelegationFeeChange {
+            epoch:          STARTING_EPOCH + 3,
+            delegation_fee: FRAC_64_MULTIPLIER / 10,
+        }
+    );
+}
+
+#[test]
+fn test_update_max_delegation_fee() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint: _,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority,
+        maybe_publisher_index,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+    let publisher_index = maybe_publisher_index.unwrap();
+
+    set_publisher_delegation_fee(
+        &mut svm,
+        &payer,
+        &publisher_keypair,
+        publisher_keypair.pubkey(),
+        None,
+        FRAC_64_MULTIPLIER / 10,
+    )
+    .unwrap();
+
+    // the pending fee is lowered to the new maximum
+    update_max_delegation_fee(
+        &mut svm,
+        &payer,
+        &reward_program_authority,
+        FRAC_64_MULTIPLIER / 20,
+    )
+    .unwrap();
+
+    let pool_data = fetch_account_data_bytemuck::<PoolData>(&mut svm, &pool_data_pubkey);
+    assert_eq!(
+        pool_data.pending_delegation_fees[publisher_index],
+        FRAC_64_MULTIPLIER / 20
+    );
+
+    advance_n_epochs(&mut svm, &payer, 2);
+    let publisher_caps =
+        post_dummy_publisher_caps(&mut svm, &payer, publisher_keypair.pubkey(), 50);
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+
+    // the current fee is lowered at the next epoch
+    update_max_delegation_fee(
+        &mut svm,
+        &payer,
+        &reward_program_authority,
+        FRAC_64_MULTIPLIER / 50,
+    )
+    .unwrap();
+
+    let pool_data = fetch_account_data_bytemuck::<PoolData>(&mut svm, &pool_data_pubkey);
+    assert_eq!(
+        pool_data.delegation_fees[publisher_index],
+        FRAC_64_MULTIPLIER / 20
+    );
+    assert_eq!(
+        pool_data.pending_delegation_fees[publisher_index],
+        FRAC_64_MULTIPLIER / 50
+    );
+    assert_eq!(
+        pool_data.pending_fee_epochs[publisher_index],
+        get_current_epoch(&mut svm) + 1
+    );
+}
+
+#[test]
+fn test_max_delegation_fee_of_existing_pools() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint: _,
+        publisher_keypair,
+        pool_data_pubkey: _,
+        reward_program_authority,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    // pools created before the maximum existed have zeroes after the slash custody
+    let pool_config_pubkey = get_pool_config_address();
+    let mut pool_config_account = svm.get_account(&pool_config_pubkey).unwrap();
+    pool_config_account.data[8 + 32 * 3 + 8 + 32..].fill(0);
+    svm.set_account(pool_config_pubkey, pool_config_account)
+        .unwrap();
+    let pool_config: PoolConfig = fetch_account_data(&mut svm, &pool_config_pubkey);
+    assert_eq!(pool_config.max_delegation_fee, None);
+
+    // publishers can't raise their fee until the maximum is set
+    assert_anchor_program_error!(
+        set_publisher_delegation_fee(
+            &mut svm,
+            &payer,
+            &publisher_keypair,
+            publisher_keypair.pubkey(),
+            None,
+            FRAC_64_MULTIPLIER / 20,
+        ),
+        IntegrityPoolError::DelegationFeeTooHigh,
+        0
+    );
+    set_publisher_delegation_fee(
+        &mut svm,
+        &payer,
+        &publisher_keypair,
+        publisher_keypair.pubkey(),
+        None,
+        0,
+    )
+    .unwrap();
+
+    update_max_delegation_fee(
+        &mut svm,
+        &payer,
+        &reward_program_authority,
+        FRAC_64_MULTIPLIER / 10,
+    )
+    .unwrap();
+    set_publisher_delegation_fee(
+        &mut svm,
+        &payer,
+        &publisher_keypair,
+        publisher_keypair.pubkey(),
+        None,
+        FRAC_64_MULTIPLIER / 20,
+    )
+    .unwrap();
+}
+
 #[test]
 fn test_reward_after_undelegate() {
     let SetupResult {
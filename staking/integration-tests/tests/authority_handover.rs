@@ -0,0 +1,197 @@
+use {
+    anchor_lang::error::ErrorCode,
+    integration_tests::{
+        assert_anchor_program_error,
+        integrity_pool::{
+            instructions::accept_pool_authority as accept_pool_authority_through_pool,
+            pda::get_pool_config_address,
+        },
+        setup::{
+            setup,
+            SetupProps,
+            SetupResult,
+        },
+        solana::utils::fetch_account_data,
+        staking::{
+            instructions::{
+                accept_governance_authority,
+                accept_pda_authority,
+                accept_pool_authority,
+                cancel_governance_authority_transfer,
+                cancel_pda_authority_transfer,
+                cancel_pool_authority_transfer,
+                update_governance_authority,
+                update_pda_authority,
+                update_pool_authority,
+            },
+            pda::get_config_address,
+        },
+    },
+    integrity_pool::error::IntegrityPoolError,
+    solana_sdk::{
+        native_token::LAMPORTS_PER_SOL,
+        signature::Keypair,
+        signer::Signer,
+    },
+    staking::{
+        error::ErrorCode as StakingError,
+        state::global_config::GlobalConfig,
+    },
+};
+
+#[test]
+fn test_authority_handover() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint: _,
+        publisher_keypair: _,
+        pool_data_pubkey: _,
+        reward_program_authority,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    let new_governance_authority = Keypair::new();
+    svm.airdrop(&new_governance_authority.pubkey(), LAMPORTS_PER_SOL)
+        .unwrap();
+
+    update_governance_authority(&mut svm, &payer, new_governance_authority.pubkey()).unwrap();
+
+    // the authority only changes once the nominee accepts
+    let config: GlobalConfig = fetch_account_data(&mut svm, &get_config_address());
+    assert_eq!(config.governance_authority, payer.pubkey());
+    assert_eq!(
+        config.pending_governance_authority,
+        Some(new_governance_authority.pubkey())
+    );
+
+    assert_anchor_program_error!(
+        accept_governance_authority(&mut svm, &payer, &Keypair::new()),
+        StakingError::InvalidPendingAuthority,
+        0
+    );
+
+    // only the current authority can cancel the nomination
+    assert_anchor_program_error!(
+        cancel_governance_authority_transfer(&mut svm, &new_governance_authority),
+        ErrorCode::ConstraintHasOne,
+        0
+    );
+    cancel_governance_authority_transfer(&mut svm, &payer).unwrap();
+
+    let config: GlobalConfig = fetch_account_data(&mut svm, &get_config_address());
+    assert_eq!(config.pending_governance_authority, None);
+
+    assert_anchor_program_error!(
+        accept_governance_authority(&mut svm, &payer, &new_governance_authority),
+        StakingError::InvalidPendingAuthority,
+        0
+    );
+
+    svm.expire_blockhash();
+    update_governance_authority(&mut svm, &payer, new_governance_authority.pubkey()).unwrap();
+    accept_governance_authority(&mut svm, &payer, &new_governance_authority).unwrap();
+
+    let config: GlobalConfig = fetch_account_data(&mut svm, &get_config_address());
+    assert_eq!(
+        config.governance_authority,
+        new_governance_authority.pubkey()
+    );
+    assert_eq!(config.pending_governance_authority, None);
+
+    assert_anchor_program_error!(
+        update_governance_authority(&mut svm, &payer, payer.pubkey()),
+        ErrorCode::ConstraintHasOne,
+        0
+    );
+
+    // pda authority
+    let new_pda_authority = Keypair::new();
+
+    assert_anchor_program_error!(
+        update_pda_authority(
+            &mut svm,
+            &new_governance_authority,
+            new_pda_authority.pubkey()
+        ),
+        ErrorCode::ConstraintHasOne,
+        0
+    );
+    update_pda_authority(&mut svm, &payer, new_pda_authority.pubkey()).unwrap();
+    cancel_pda_authority_transfer(&mut svm, &payer).unwrap();
+    assert_anchor_program_error!(
+        accept_pda_authority(&mut svm, &payer, &new_pda_authority),
+        StakingError::InvalidPendingAuthority,
+        0
+    );
+
+    svm.expire_blockhash();
+    update_pda_authority(&mut svm, &payer, new_pda_authority.pubkey()).unwrap();
+    accept_pda_authority(&mut svm, &payer, &new_pda_authority).unwrap();
+
+    let config: GlobalConfig = fetch_account_data(&mut svm, &get_config_address());
+    assert_eq!(config.pda_authority, new_pda_authority.pubkey());
+    assert_eq!(config.pending_pda_authority, None);
+
+    // pool authority, nominated by the governance authority
+    let new_pool_authority = Keypair::new();
+
+    assert_anchor_program_error!(
+        update_pool_authority(&mut svm, &payer, new_pool_authority.pubkey()),
+        ErrorCode::ConstraintHasOne,
+        0
+    );
+    update_pool_authority(
+        &mut svm,
+        &new_governance_authority,
+        new_pool_authority.pubkey(),
+    )
+    .unwrap();
+    cancel_pool_authority_transfer(&mut svm, &new_governance_authority).unwrap();
+    assert_anchor_program_error!(
+        accept_pool_authority(&mut svm, &payer, &new_pool_authority),
+        StakingError::InvalidPendingAuthority,
+        0
+    );
+
+    let config: GlobalConfig = fetch_account_data(&mut svm, &get_config_address());
+    assert_eq!(config.pool_authority, get_pool_config_address());
+
+    svm.expire_blockhash();
+    update_pool_authority(
+        &mut svm,
+        &new_governance_authority,
+        new_pool_authority.pubkey(),
+    )
+    .unwrap();
+    accept_pool_authority(&mut svm, &payer, &new_pool_authority).unwrap();
+
+    let config: GlobalConfig = fetch_account_data(&mut svm, &get_config_address());
+    assert_eq!(config.pool_authority, new_pool_authority.pubkey());
+    assert_eq!(config.pending_pool_authority, None);
+
+    // the pool config accepts through the integrity pool program
+    update_pool_authority(
+        &mut svm,
+        &new_governance_authority,
+        get_pool_config_address(),
+    )
+    .unwrap();
+    assert_anchor_program_error!(
+        accept_pool_authority_through_pool(&mut svm, &payer, &payer),
+        IntegrityPoolError::InvalidRewardProgramAuthority,
+        0
+    );
+    accept_pool_authority_through_pool(&mut svm, &payer, &reward_program_authority).unwrap();
+
+    let config: GlobalConfig = fetch_account_data(&mut svm, &get_config_address());
+    assert_eq!(config.pool_authority, get_pool_config_address());
+    assert_eq!(config.pending_pool_authority, None);
+}
@@ -0,0 +1,330 @@
+use {
+    integration_tests::{
+        assert_anchor_program_error,
+        integrity_pool::instructions::{
+            advance,
+            cancel_undelegate,
+            delegate,
+            redelegate,
+            undelegate,
+        },
+        publisher_caps::{
+            helper_functions::post_dummy_publisher_caps,
+            utils::get_dummy_publisher,
+        },
+        setup::{
+            setup,
+            SetupProps,
+            SetupResult,
+        },
+        solana::utils::{
+            fetch_account_data,
+            fetch_account_data_bytemuck,
+            fetch_positions_account,
+        },
+        staking::{
+            helper_functions::initialize_new_stake_account,
+            instructions::{
+                cancel_unlock,
+                close_position,
+                create_position,
+            },
+            pda::get_target_address,
+        },
+        utils::{
+            clock::advance_n_epochs,
+            constants::STAKED_TOKENS,
+        },
+    },
+    integrity_pool::{
+        error::IntegrityPoolError,
+        state::pool::{
+            DelegationState,
+            PoolData,
+        },
+        utils::types::FRAC_64_MULTIPLIER,
+    },
+    solana_sdk::signer::Signer,
+    staking::state::{
+        positions::TargetWithParameters,
+        target::TargetMetadata,
+    },
+};
+
+#[test]
+fn test_cancel_unlock() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair: _,
+        pool_data_pubkey: _,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+
+    create_position(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        STAKED_TOKENS,
+    )
+    .unwrap();
+
+    advance_n_epochs(&mut svm, &payer, 1);
+
+    close_position(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        40 * FRAC_64_MULTIPLIER,
+        0,
+    )
+    .unwrap();
+
+    let target_account: TargetMetadata = fetch_account_data(&mut svm, &get_target_address());
+    let delta_locked = target_account.delta_locked;
+
+    cancel_unlock(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        1,
+    )
+    .unwrap();
+
+    let mut stake_positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+    let positions = stake_positions_account.to_dynamic_position_array();
+    let pos1 = positions.read_position(1).unwrap().unwrap();
+    assert_eq!(pos1.amount, 40 * FRAC_64_MULTIPLIER);
+    assert_eq!(pos1.target_with_parameters, TargetWithParameters::Voting);
+    assert_eq!(pos1.unlocking_start, None);
+
+    let target_account: TargetMetadata = fetch_account_data(&mut svm, &get_target_address());
+    assert_eq!(
+        target_account.delta_locked,
+        delta_locked + (40 * FRAC_64_MULTIPLIER) as i64
+    );
+
+    // the position is locked again
+    assert_anchor_program_error!(
+        cancel_unlock(
+            &mut svm,
+            &payer,
+            stake_account_positions,
+            TargetWithParameters::Voting,
+            None,
+            1,
+        ),
+        staking::error::ErrorCode::NotPreunlocking,
+        0
+    );
+
+    close_position(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        40 * FRAC_64_MULTIPLIER,
+        1,
+    )
+    .unwrap();
+
+    advance_n_epochs(&mut svm, &payer, 1);
+
+    // the unlock can only be cancelled during the epoch it was requested
+    assert_anchor_program_error!(
+        cancel_unlock(
+            &mut svm,
+            &payer,
+            stake_account_positions,
+            TargetWithParameters::Voting,
+            None,
+            1,
+        ),
+        staking::error::ErrorCode::NotPreunlocking,
+        0
+    );
+}
+
+#[test]
+fn test_cancel_undelegate() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+    let publisher_index = maybe_publisher_index.unwrap();
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+
+    delegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        STAKED_TOKENS,
+    )
+    .unwrap();
+
+    advance_n_epochs(&mut svm, &payer, 1);
+    let publisher_caps =
+        post_dummy_publisher_caps(&mut svm, &payer, publisher_keypair.pubkey(), 100);
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+
+    undelegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        0,
+        40 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+
+    assert_anchor_program_error!(
+        cancel_undelegate(
+            &mut svm,
+            &payer,
+            get_dummy_publisher(1),
+            pool_data_pubkey,
+            stake_account_positions,
+            1,
+        ),
+        IntegrityPoolError::InvalidPublisher,
+        0
+    );
+
+    cancel_undelegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        1,
+    )
+    .unwrap();
+
+    let mut stake_positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+    let positions = stake_positions_account.to_dynamic_position_array();
+    let pos1 = positions.read_position(1).unwrap().unwrap();
+    assert_eq!(pos1.amount, 40 * FRAC_64_MULTIPLIER);
+    assert_eq!(pos1.unlocking_start, None);
+
+    let pool_data: PoolData = fetch_account_data_bytemuck(&mut svm, &pool_data_pubkey);
+    assert_eq!(
+        pool_data.del_state[publisher_index],
+        DelegationState {
+            total_delegation: STAKED_TOKENS,
+            delta_delegation: 0,
+        }
+    );
+
+    assert_anchor_program_error!(
+        cancel_undelegate(
+            &mut svm,
+            &payer,
+            publisher_keypair.pubkey(),
+            pool_data_pubkey,
+            stake_account_positions,
+            0,
+        ),
+        staking::error::ErrorCode::NotPreunlocking,
+        0
+    );
+}
+
+#[test]
+fn test_cancel_undelegate_after_redelegate() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+
+    delegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        STAKED_TOKENS,
+    )
+    .unwrap();
+
+    advance_n_epochs(&mut svm, &payer, 1);
+    let publisher_caps =
+        post_dummy_publisher_caps(&mut svm, &payer, publisher_keypair.pubkey(), 100);
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+
+    redelegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        get_dummy_publisher(1),
+        pool_data_pubkey,
+        stake_account_positions,
+        0,
+        STAKED_TOKENS,
+    )
+    .unwrap();
+
+    // the tokens are already delegated to the new publisher
+    assert_anchor_program_error!(
+        cancel_undelegate(
+            &mut svm,
+            &payer,
+            publisher_keypair.pubkey(),
+            pool_data_pubkey,
+            stake_account_positions,
+            0,
+        ),
+        staking::error::ErrorCode::TooMuchExposureToIntegrityPool,
+        0
+    );
+}
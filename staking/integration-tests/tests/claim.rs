@@ -3,18 +3,41 @@
 use {
     anchor_lang::AccountDeserialize,
     integration_tests::{
-        integrity_pool::instructions::{
-            advance,
-            advance_delegation_record,
-            delegate,
+        assert_anchor_program_error,
+        integrity_pool::{
+            instructions::{
+                advance,
+                advance_delegation_record,
+                advance_delegation_records,
+                claim_rewards_to_destination,
+                delegate,
+                set_auto_compound,
+            },
+            pda::get_reward_index_history_address,
+        },
+        publisher_caps::{
+            helper_functions::{
+                post_dummy_publisher_caps,
+                write_and_verify_publisher_caps,
+            },
+            utils::{
+                create_publisher_caps_message,
+                get_dummy_publisher,
+            },
         },
-        publisher_caps::helper_functions::post_dummy_publisher_caps,
         setup::{
             setup,
             SetupProps,
             SetupResult,
         },
-        solana::utils::fetch_account_data_bytemuck,
+        solana::{
+            instructions::create_token_account,
+            utils::{
+                fetch_account_data,
+                fetch_account_data_bytemuck,
+                fetch_positions_account,
+            },
+        },
         staking::{
             helper_functions::initialize_new_stake_account,
             pda::get_stake_account_custody_address,
@@ -28,13 +51,22 @@ use {
         },
     },
     integrity_pool::{
-        state::pool::PoolData,
+        error::IntegrityPoolError,
+        state::{
+            pool::{
+                DelegationState,
+                PoolData,
+            },
+            reward_index::RewardIndexHistory,
+        },
         utils::types::FRAC_64_MULTIPLIER,
     },
     solana_sdk::{
+        native_token::LAMPORTS_PER_SOL,
         signature::Keypair,
         signer::Signer,
     },
+    staking::state::positions::TargetWithParameters,
 };
 
 #[test]
@@ -237,6 +269,126 @@ fn test_lost_reward() {
     )
     .unwrap();
 
+    let stake_account_positions_2 =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+
+    // advance 200 epochs
+    for i in 0..20 {
+        advance_n_epochs(&mut svm, &payer, 10);
+
+        let publisher_caps = post_dummy_publisher_caps(
+            &mut svm,
+            &payer,
+            publisher_keypair.pubkey(),
+            2 * FRAC_64_MULTIPLIER,
+        );
+        advance(&mut svm, &payer, publisher_caps).unwrap();
+
+        // another delegation to the same publisher moves the checkpoint of the first delegation
+        // to the reward index history
+        if i == 0 {
+            delegate(
+                &mut svm,
+                &payer,
+                publisher_keypair.pubkey(),
+                pool_data_pubkey,
+                stake_account_positions_2,
+                1 * FRAC_64_MULTIPLIER,
+            )
+            .unwrap();
+
+            let reward_index_history: RewardIndexHistory = fetch_account_data_bytemuck(
+                &mut svm,
+                &get_reward_index_history_address(publisher_keypair.pubkey()),
+            );
+            assert_eq!(reward_index_history.num_checkpoints, 1);
+            assert_eq!(reward_index_history.checkpoints[0].epoch, 3);
+        }
+    }
+
+    for stake_account_positions in [stake_account_positions, stake_account_positions_2] {
+        advance_delegation_record(
+            &mut svm,
+            &payer,
+            publisher_keypair.pubkey(),
+            stake_account_positions,
+            pyth_token_mint.pubkey(),
+            pool_data_pubkey,
+            None,
+        )
+        .unwrap();
+    }
+
+    // the positions activated after the delegation records were last settled and more than 52
+    // epochs ago, they still get rewarded since they activated
+    // the first position activated at epoch 3, reward = 199 epochs * YIELD * 1 PYTH
+    let custody_data: anchor_spl::token::TokenAccount = fetch_account_data(
+        &mut svm,
+        &get_stake_account_custody_address(stake_account_positions),
+    );
+    assert_eq!(custody_data.amount, STAKED_TOKENS + 199 * YIELD * 1);
+
+    // the second position activated at epoch 13, reward = 189 epochs * YIELD * 1 PYTH
+    let custody_data: anchor_spl::token::TokenAccount = fetch_account_data(
+        &mut svm,
+        &get_stake_account_custody_address(stake_account_positions_2),
+    );
+    assert_eq!(custody_data.amount, STAKED_TOKENS + 189 * YIELD * 1);
+}
+
+#[test]
+fn test_reward_after_many_epochs() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+
+    delegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        1 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+
+    advance_n_epochs(&mut svm, &payer, 1);
+    let publisher_caps = post_dummy_publisher_caps(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        1 * FRAC_64_MULTIPLIER,
+    );
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+
+    // settle the delegation record once the position is active
+    advance_delegation_record(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        stake_account_positions,
+        pyth_token_mint.pubkey(),
+        pool_data_pubkey,
+        None,
+    )
+    .unwrap();
+
     // advance 200 epochs
     for _ in 0..20 {
         advance_n_epochs(&mut svm, &payer, 10);
@@ -272,9 +424,9 @@ fn test_lost_reward() {
     )
     .unwrap();
 
-    // user only get rewarded for the last 52 epochs
-    // reward = 52 epochs * YIELD * 1 PYTH
-    assert_eq!(custody_data.amount, STAKED_TOKENS + 52 * YIELD * 1);
+    // no rewards are lost even though the record wasn't settled for more than 52 epochs
+    // reward = 200 epochs * YIELD * 1 PYTH
+    assert_eq!(custody_data.amount, STAKED_TOKENS + 200 * YIELD * 1);
 }
 
 
@@ -440,3 +592,464 @@ fn test_advance_delegation_record_permissionlessness() {
 
     assert_eq!(custody_data.amount, STAKED_TOKENS + 1 * YIELD);
 }
+
+#[test]
+fn test_auto_compound() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+    let publisher_index = maybe_publisher_index.unwrap();
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+
+    delegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        1 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+
+    set_auto_compound(
+        &mut svm,
+        &payer,
+        &payer,
+        publisher_keypair.pubkey(),
+        stake_account_positions,
+        true,
+    )
+    .unwrap();
+
+    assert_anchor_program_error!(
+        set_auto_compound(
+            &mut svm,
+            &payer,
+            &Keypair::new(),
+            publisher_keypair.pubkey(),
+            stake_account_positions,
+            false,
+        ),
+        IntegrityPoolError::StakeAccountOwnerNeedsToSign,
+        0
+    );
+
+    for _ in 0..2 {
+        advance_n_epochs(&mut svm, &payer, 1);
+        let publisher_caps = post_dummy_publisher_caps(
+            &mut svm,
+            &payer,
+            publisher_keypair.pubkey(),
+            2 * FRAC_64_MULTIPLIER,
+        );
+        advance(&mut svm, &payer, publisher_caps).unwrap();
+    }
+
+    // the position is rewarded for epoch 3
+    advance_delegation_record(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        stake_account_positions,
+        pyth_token_mint.pubkey(),
+        pool_data_pubkey,
+        None,
+    )
+    .unwrap();
+
+    let stake_account_custody = get_stake_account_custody_address(stake_account_positions);
+    let custody_data = anchor_spl::token::TokenAccount::try_deserialize(
+        &mut svm
+            .get_account(&stake_account_custody)
+            .unwrap()
+            .data
+            .as_slice(),
+    )
+    .unwrap();
+    assert_eq!(custody_data.amount, STAKED_TOKENS + YIELD * 1);
+
+    // the reward is delegated to the same publisher
+    let mut stake_positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+    let positions = stake_positions_account.to_dynamic_position_array();
+    let pos1 = positions.read_position(1).unwrap().unwrap();
+    assert_eq!(pos1.amount, YIELD * 1);
+    assert_eq!(
+        pos1.target_with_parameters,
+        TargetWithParameters::IntegrityPool {
+            publisher: publisher_keypair.pubkey(),
+        }
+    );
+    assert_eq!(pos1.activation_epoch, 5);
+    assert_eq!(pos1.unlocking_start, None);
+
+    let pool_data: PoolData = fetch_account_data_bytemuck(&mut svm, &pool_data_pubkey);
+    assert_eq!(
+        pool_data.del_state[publisher_index],
+        DelegationState {
+            total_delegation: 1 * FRAC_64_MULTIPLIER,
+            delta_delegation: (YIELD * 1) as i64,
+        }
+    );
+}
+
+#[test]
+fn test_auto_compound_merges_positions() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+
+    delegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        1 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+
+    set_auto_compound(
+        &mut svm,
+        &payer,
+        &payer,
+        publisher_keypair.pubkey(),
+        stake_account_positions,
+        true,
+    )
+    .unwrap();
+
+    for _ in 0..2 {
+        advance_n_epochs(&mut svm, &payer, 1);
+        let publisher_caps = post_dummy_publisher_caps(
+            &mut svm,
+            &payer,
+            publisher_keypair.pubkey(),
+            2 * FRAC_64_MULTIPLIER,
+        );
+        advance(&mut svm, &payer, publisher_caps).unwrap();
+    }
+
+    // a position activating next epoch already exists
+    delegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        1 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+
+    advance_delegation_record(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        stake_account_positions,
+        pyth_token_mint.pubkey(),
+        pool_data_pubkey,
+        None,
+    )
+    .unwrap();
+
+    // the reward is added to that position instead of taking a new slot
+    let mut stake_positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+    let positions = stake_positions_account.to_dynamic_position_array();
+    let pos1 = positions.read_position(1).unwrap().unwrap();
+    assert_eq!(pos1.amount, 1 * FRAC_64_MULTIPLIER + YIELD * 1);
+    assert_eq!(pos1.activation_epoch, 5);
+    assert_eq!(
+        (0..positions.get_position_capacity())
+            .filter(|i| positions.read_position(*i).unwrap().is_some())
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn test_claim_rewards_to_destination() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+    let destination = create_token_account(&mut svm, &payer, &pyth_token_mint.pubkey()).pubkey();
+
+    delegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        1 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+
+    // rewards sent to another account are not compounded
+    set_auto_compound(
+        &mut svm,
+        &payer,
+        &payer,
+        publisher_keypair.pubkey(),
+        stake_account_positions,
+        true,
+    )
+    .unwrap();
+
+    for _ in 0..2 {
+        advance_n_epochs(&mut svm, &payer, 1);
+        let publisher_caps = post_dummy_publisher_caps(
+            &mut svm,
+            &payer,
+            publisher_keypair.pubkey(),
+            2 * FRAC_64_MULTIPLIER,
+        );
+        advance(&mut svm, &payer, publisher_caps).unwrap();
+    }
+
+    let attacker = Keypair::new();
+    svm.airdrop(&attacker.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    assert_anchor_program_error!(
+        claim_rewards_to_destination(
+            &mut svm,
+            &attacker,
+            publisher_keypair.pubkey(),
+            stake_account_positions,
+            pyth_token_mint.pubkey(),
+            pool_data_pubkey,
+            None,
+            destination,
+        ),
+        IntegrityPoolError::StakeAccountOwnerNeedsToSign,
+        0
+    );
+
+    claim_rewards_to_destination(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        stake_account_positions,
+        pyth_token_mint.pubkey(),
+        pool_data_pubkey,
+        None,
+        destination,
+    )
+    .unwrap();
+
+    let destination_data = anchor_spl::token::TokenAccount::try_deserialize(
+        &mut svm.get_account(&destination).unwrap().data.as_slice(),
+    )
+    .unwrap();
+    assert_eq!(destination_data.amount, YIELD * 1);
+
+    let stake_account_custody = get_stake_account_custody_address(stake_account_positions);
+    let custody_data = anchor_spl::token::TokenAccount::try_deserialize(
+        &mut svm
+            .get_account(&stake_account_custody)
+            .unwrap()
+            .data
+            .as_slice(),
+    )
+    .unwrap();
+    assert_eq!(custody_data.amount, STAKED_TOKENS);
+
+    let mut stake_positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+    let positions = stake_positions_account.to_dynamic_position_array();
+    assert!(positions.read_position(1).unwrap().is_none());
+
+    // the rewards can't be claimed twice
+    advance_delegation_record(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        stake_account_positions,
+        pyth_token_mint.pubkey(),
+        pool_data_pubkey,
+        None,
+    )
+    .unwrap();
+    let custody_data = anchor_spl::token::TokenAccount::try_deserialize(
+        &mut svm
+            .get_account(&stake_account_custody)
+            .unwrap()
+            .data
+            .as_slice(),
+    )
+    .unwrap();
+    assert_eq!(custody_data.amount, STAKED_TOKENS);
+}
+
+#[test]
+fn test_advance_delegation_records() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+    let publishers = [publisher_keypair.pubkey(), get_dummy_publisher(1)];
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+
+    for publisher in publishers {
+        delegate(
+            &mut svm,
+            &payer,
+            publisher,
+            pool_data_pubkey,
+            stake_account_positions,
+            1 * FRAC_64_MULTIPLIER,
+        )
+        .unwrap();
+
+        // create the delegation record
+        advance_delegation_record(
+            &mut svm,
+            &payer,
+            publisher,
+            stake_account_positions,
+            pyth_token_mint.pubkey(),
+            pool_data_pubkey,
+            None,
+        )
+        .unwrap();
+    }
+
+    for _ in 0..2 {
+        advance_n_epochs(&mut svm, &payer, 1);
+        let publisher_caps_message = create_publisher_caps_message(
+            &mut svm,
+            publishers.to_vec(),
+            vec![2 * FRAC_64_MULTIPLIER; 2],
+            true,
+        );
+        let publisher_caps =
+            write_and_verify_publisher_caps(&mut svm, &payer, publisher_caps_message);
+        advance(&mut svm, &payer, publisher_caps).unwrap();
+    }
+
+    // the delegation records have to exist
+    assert_anchor_program_error!(
+        advance_delegation_records(
+            &mut svm,
+            &payer,
+            &[(publishers[0], None), (get_dummy_publisher(2), None)],
+            stake_account_positions,
+            pyth_token_mint.pubkey(),
+            pool_data_pubkey,
+        ),
+        anchor_lang::error::ErrorCode::AccountNotInitialized,
+        0
+    );
+
+    advance_delegation_records(
+        &mut svm,
+        &payer,
+        &[(publishers[0], None), (publishers[1], None)],
+        stake_account_positions,
+        pyth_token_mint.pubkey(),
+        pool_data_pubkey,
+    )
+    .unwrap();
+
+    // the position of each publisher is rewarded for epoch 3
+    let stake_account_custody = get_stake_account_custody_address(stake_account_positions);
+    let custody_data = anchor_spl::token::TokenAccount::try_deserialize(
+        &mut svm
+            .get_account(&stake_account_custody)
+            .unwrap()
+            .data
+            .as_slice(),
+    )
+    .unwrap();
+    assert_eq!(custody_data.amount, STAKED_TOKENS + 2 * YIELD);
+
+    let pool_data: PoolData = fetch_account_data_bytemuck(&mut svm, &pool_data_pubkey);
+    assert_eq!(pool_data.claimable_rewards, 0);
+
+    // passing a delegation record twice doesn't claim the rewards twice
+    advance_n_epochs(&mut svm, &payer, 1);
+    let publisher_caps_message = create_publisher_caps_message(
+        &mut svm,
+        publishers.to_vec(),
+        vec![2 * FRAC_64_MULTIPLIER; 2],
+        true,
+    );
+    let publisher_caps = write_and_verify_publisher_caps(&mut svm, &payer, publisher_caps_message);
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+
+    advance_delegation_records(
+        &mut svm,
+        &payer,
+        &[(publishers[0], None), (publishers[0], None)],
+        stake_account_positions,
+        pyth_token_mint.pubkey(),
+        pool_data_pubkey,
+    )
+    .unwrap();
+
+    let custody_data = anchor_spl::token::TokenAccount::try_deserialize(
+        &mut svm
+            .get_account(&stake_account_custody)
+            .unwrap()
+            .data
+            .as_slice(),
+    )
+    .unwrap();
+    assert_eq!(custody_data.amount, STAKED_TOKENS + 3 * YIELD);
+}
@@ -0,0 +1,181 @@
+use {
+    anchor_spl::token::TokenAccount,
+    integration_tests::{
+        assert_anchor_program_error,
+        integrity_pool::instructions::delegate,
+        setup::{
+            setup,
+            SetupProps,
+            SetupResult,
+        },
+        solana::{
+            instructions::{
+                airdrop_spl,
+                create_account,
+                create_token_account,
+            },
+            utils::{
+                fetch_account_data,
+                fetch_positions_account,
+            },
+        },
+        staking::{
+            instructions::{
+                clawback,
+                create_position,
+                create_stake_account_with_lock,
+                join_dao_llc,
+            },
+            pda::{
+                get_stake_account_custody_address,
+                get_stake_account_metadata_address,
+                get_target_address,
+            },
+        },
+        utils::{
+            clock::advance_n_epochs,
+            constants::STAKED_TOKENS,
+        },
+    },
+    integrity_pool::utils::types::FRAC_64_MULTIPLIER,
+    solana_sdk::{
+        native_token::LAMPORTS_PER_SOL,
+        signature::Keypair,
+        signer::Signer,
+    },
+    staking::state::{
+        positions::{
+            PositionData,
+            TargetWithParameters,
+        },
+        stake_account::StakeAccountMetadataV2,
+        target::TargetMetadata,
+        vesting::VestingSchedule,
+    },
+};
+
+#[test]
+fn test_clawback() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    // 60 of the 100 tokens in custody are unvested until the token is listed
+    let stake_account_positions = create_account(&mut svm, &payer, PositionData::LEN, staking::ID);
+    create_stake_account_with_lock(
+        &mut svm,
+        &payer,
+        &pyth_token_mint,
+        stake_account_positions,
+        VestingSchedule::PeriodicVestingAfterListing {
+            initial_balance: 60 * FRAC_64_MULTIPLIER,
+            period_duration: 1,
+            num_periods:     1,
+        },
+    )
+    .unwrap();
+    join_dao_llc(&mut svm, &payer, stake_account_positions).unwrap();
+    let stake_account_custody = get_stake_account_custody_address(stake_account_positions);
+    airdrop_spl(
+        &mut svm,
+        &payer,
+        stake_account_custody,
+        &pyth_token_mint,
+        STAKED_TOKENS,
+    );
+
+    create_position(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        90 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+
+    advance_n_epochs(&mut svm, &payer, 1);
+
+    delegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        30 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+    create_position(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        5 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+
+    let treasury = create_token_account(&mut svm, &payer, &pyth_token_mint.pubkey());
+
+    // only the governance authority can claw back tokens
+    let attacker = Keypair::new();
+    svm.airdrop(&attacker.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    assert_anchor_program_error!(
+        clawback(
+            &mut svm,
+            &attacker,
+            stake_account_positions,
+            treasury.pubkey()
+        ),
+        anchor_lang::error::ErrorCode::ConstraintHasOne,
+        0
+    );
+
+    clawback(&mut svm, &payer, stake_account_positions, treasury.pubkey()).unwrap();
+
+    let treasury_account: TokenAccount = fetch_account_data(&mut svm, &treasury.pubkey());
+    assert_eq!(treasury_account.amount, 60 * FRAC_64_MULTIPLIER);
+    let custody_account: TokenAccount = fetch_account_data(&mut svm, &stake_account_custody);
+    assert_eq!(custody_account.amount, 40 * FRAC_64_MULTIPLIER);
+
+    let metadata: StakeAccountMetadataV2 = fetch_account_data(
+        &mut svm,
+        &get_stake_account_metadata_address(stake_account_positions),
+    );
+    assert_eq!(metadata.lock, VestingSchedule::FullyVested);
+
+    // the voting positions are reduced to the 40 tokens left in custody, starting with the first
+    // one, the integrity pool position doesn't change
+    let mut positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+    let positions = positions_account.to_dynamic_position_array();
+    assert_eq!(positions.get_next_index(metadata.next_index).unwrap(), 3);
+    assert_eq!(
+        positions.read_position(0).unwrap().unwrap().amount,
+        35 * FRAC_64_MULTIPLIER
+    );
+    assert_eq!(
+        positions.read_position(1).unwrap().unwrap().amount,
+        30 * FRAC_64_MULTIPLIER
+    );
+    assert_eq!(
+        positions.read_position(2).unwrap().unwrap().amount,
+        5 * FRAC_64_MULTIPLIER
+    );
+
+    let target_account: TargetMetadata = fetch_account_data(&mut svm, &get_target_address());
+    assert_eq!(target_account.locked, 35 * FRAC_64_MULTIPLIER);
+    assert_eq!(target_account.delta_locked, 5 * FRAC_64_MULTIPLIER as i64);
+}
@@ -0,0 +1,468 @@
+use {
+    anchor_lang::AccountSerialize,
+    anchor_spl::token::TokenAccount,
+    integration_tests::{
+        assert_anchor_program_error,
+        integrity_pool::{
+            instructions::{
+                advance,
+                advance_delegation_record,
+                close_delegation_record,
+                delegate,
+                merge_delegation_positions,
+                set_publisher_stake_account,
+                undelegate,
+            },
+            pda::get_delegation_record_address,
+        },
+        publisher_caps::helper_functions::post_dummy_publisher_caps,
+        setup::{
+            setup,
+            SetupProps,
+            SetupResult,
+        },
+        solana::{
+            instructions::create_token_account,
+            utils::{
+                fetch_account_data,
+                fetch_account_data_bytemuck,
+            },
+        },
+        staking::{
+            helper_functions::initialize_new_stake_account,
+            instructions::{
+                check_delegation_records,
+                close_stake_account,
+                create_position,
+                create_voter_record,
+                withdraw_stake,
+            },
+            pda::{
+                get_stake_account_custody_address,
+                get_stake_account_metadata_address,
+                get_voter_record_address,
+            },
+        },
+        utils::{
+            clock::advance_n_epochs,
+            constants::STAKED_TOKENS,
+        },
+    },
+    integrity_pool::{
+        error::IntegrityPoolError,
+        state::pool::PoolData,
+    },
+    litesvm::LiteSVM,
+    solana_sdk::{
+        native_token::LAMPORTS_PER_SOL,
+        pubkey::Pubkey,
+        signature::Keypair,
+        signer::Signer,
+    },
+    staking::{
+        context::DELEGATION_RECORD_REGISTERED_OFFSET,
+        error::ErrorCode as StakingError,
+        state::{
+            positions::TargetWithParameters,
+            stake_account::StakeAccountMetadataV2,
+        },
+    },
+};
+
+fn get_lamports(svm: &mut LiteSVM, address: &Pubkey) -> u64 {
+    svm.get_account(address).unwrap_or_default().lamports
+}
+
+#[test]
+fn test_close_stake_account() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey: _,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    // the stake account of a publisher can't be closed
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &owner, &pyth_token_mint, true, false);
+    set_publisher_stake_account(
+        &mut svm,
+        &payer,
+        &publisher_keypair,
+        publisher_keypair.pubkey(),
+        None,
+        Some(stake_account_positions),
+    )
+    .unwrap();
+    assert_anchor_program_error!(
+        close_stake_account(&mut svm, &owner, stake_account_positions),
+        StakingError::ClosePublisherStakeAccount,
+        0
+    );
+
+    // accounts with positions can't be closed
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &owner, &pyth_token_mint, true, true);
+    create_position(
+        &mut svm,
+        &owner,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        STAKED_TOKENS,
+    )
+    .unwrap();
+    assert_anchor_program_error!(
+        close_stake_account(&mut svm, &owner, stake_account_positions),
+        StakingError::CloseWithStake,
+        0
+    );
+
+    // accounts with tokens in custody can't be closed
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &owner, &pyth_token_mint, true, true);
+    assert_anchor_program_error!(
+        close_stake_account(&mut svm, &owner, stake_account_positions),
+        StakingError::CloseWithBalance,
+        0
+    );
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &owner, &pyth_token_mint, true, false);
+    create_voter_record(&mut svm, &owner, stake_account_positions).unwrap();
+
+    // only the owner can close the account
+    assert_anchor_program_error!(
+        close_stake_account(&mut svm, &payer, stake_account_positions),
+        anchor_lang::error::ErrorCode::ConstraintHasOne,
+        0
+    );
+
+    let accounts = [
+        stake_account_positions,
+        get_stake_account_metadata_address(stake_account_positions),
+        get_stake_account_custody_address(stake_account_positions),
+        get_voter_record_address(stake_account_positions),
+    ];
+    let rent: u64 = accounts
+        .iter()
+        .map(|account| get_lamports(&mut svm, account))
+        .sum();
+    let owner_lamports = get_lamports(&mut svm, &owner.pubkey());
+
+    close_stake_account(&mut svm, &owner, stake_account_positions).unwrap();
+
+    for account in accounts.iter() {
+        assert_eq!(get_lamports(&mut svm, account), 0);
+    }
+    // the owner pays the transaction fee
+    assert_eq!(
+        get_lamports(&mut svm, &owner.pubkey()),
+        owner_lamports + rent - 5000
+    );
+}
+
+#[test]
+fn test_close_delegation_record() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &owner, &pyth_token_mint, true, true);
+    let delegation_record =
+        get_delegation_record_address(publisher_keypair.pubkey(), stake_account_positions);
+
+    delegate(
+        &mut svm,
+        &owner,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        STAKED_TOKENS,
+    )
+    .unwrap();
+
+    advance_n_epochs(&mut svm, &payer, 1);
+    let publisher_caps =
+        post_dummy_publisher_caps(&mut svm, &payer, publisher_keypair.pubkey(), STAKED_TOKENS);
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+    advance_delegation_record(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        stake_account_positions,
+        pyth_token_mint.pubkey(),
+        pool_data_pubkey,
+        None,
+    )
+    .unwrap();
+
+    assert_anchor_program_error!(
+        close_delegation_record(
+            &mut svm,
+            &payer,
+            &payer,
+            publisher_keypair.pubkey(),
+            stake_account_positions,
+        ),
+        IntegrityPoolError::StakeAccountOwnerNeedsToSign,
+        0
+    );
+
+    // the stake account still delegates to the publisher
+    assert_anchor_program_error!(
+        close_delegation_record(
+            &mut svm,
+            &payer,
+            &owner,
+            publisher_keypair.pubkey(),
+            stake_account_positions,
+        ),
+        IntegrityPoolError::DelegationRecordInUse,
+        0
+    );
+
+    undelegate(
+        &mut svm,
+        &owner,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        0,
+        STAKED_TOKENS,
+    )
+    .unwrap();
+
+    advance_n_epochs(&mut svm, &payer, 2);
+    let publisher_caps =
+        post_dummy_publisher_caps(&mut svm, &payer, publisher_keypair.pubkey(), STAKED_TOKENS);
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+
+    // the delegation record needs to be up to date
+    assert_anchor_program_error!(
+        close_delegation_record(
+            &mut svm,
+            &payer,
+            &owner,
+            publisher_keypair.pubkey(),
+            stake_account_positions,
+        ),
+        IntegrityPoolError::OutdatedDelegatorAccounting,
+        0
+    );
+
+    advance_delegation_record(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        stake_account_positions,
+        pyth_token_mint.pubkey(),
+        pool_data_pubkey,
+        None,
+    )
+    .unwrap();
+
+    // the unlocked position is removed
+    merge_delegation_positions(
+        &mut svm,
+        &owner,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+    )
+    .unwrap();
+
+    let custody: TokenAccount = fetch_account_data(
+        &mut svm,
+        &get_stake_account_custody_address(stake_account_positions),
+    );
+    let destination = create_token_account(&mut svm, &owner, &pyth_token_mint.pubkey());
+    withdraw_stake(
+        &mut svm,
+        &owner,
+        stake_account_positions,
+        destination.pubkey(),
+        custody.amount,
+    )
+    .unwrap();
+
+    // the delegation record needs to be closed before the stake account
+    assert_anchor_program_error!(
+        close_stake_account(&mut svm, &owner, stake_account_positions),
+        StakingError::CloseWithDelegationRecords,
+        0
+    );
+
+    let rent = get_lamports(&mut svm, &delegation_record);
+    let owner_lamports = get_lamports(&mut svm, &owner.pubkey());
+
+    close_delegation_record(
+        &mut svm,
+        &payer,
+        &owner,
+        publisher_keypair.pubkey(),
+        stake_account_positions,
+    )
+    .unwrap();
+
+    assert_eq!(get_lamports(&mut svm, &delegation_record), 0);
+    assert_eq!(
+        get_lamports(&mut svm, &owner.pubkey()),
+        owner_lamports + rent
+    );
+
+    close_stake_account(&mut svm, &owner, stake_account_positions).unwrap();
+    assert_eq!(get_lamports(&mut svm, &stake_account_positions), 0);
+}
+
+#[test]
+fn test_close_legacy_stake_account() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    let owner = Keypair::new();
+    svm.airdrop(&owner.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &owner, &pyth_token_mint, true, false);
+    advance_delegation_record(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        stake_account_positions,
+        pyth_token_mint.pubkey(),
+        pool_data_pubkey,
+        None,
+    )
+    .unwrap();
+
+    // the stake account and the delegation record were created before the count existed
+    let metadata_address = get_stake_account_metadata_address(stake_account_positions);
+    let mut metadata: StakeAccountMetadataV2 = fetch_account_data(&mut svm, &metadata_address);
+    assert_eq!(metadata.delegation_record_count, 1);
+    metadata.delegation_record_count = 0;
+    metadata.delegation_records_checked = 0;
+    let mut metadata_account = svm.get_account(&metadata_address).unwrap();
+    metadata
+        .try_serialize(&mut &mut metadata_account.data[..])
+        .unwrap();
+    svm.set_account(metadata_address, metadata_account).unwrap();
+
+    let delegation_record =
+        get_delegation_record_address(publisher_keypair.pubkey(), stake_account_positions);
+    let mut delegation_record_account = svm.get_account(&delegation_record).unwrap();
+    delegation_record_account.data[DELEGATION_RECORD_REGISTERED_OFFSET] = 0;
+    svm.set_account(delegation_record, delegation_record_account)
+        .unwrap();
+
+    assert_anchor_program_error!(
+        close_stake_account(&mut svm, &owner, stake_account_positions),
+        StakingError::UncheckedDelegationRecords,
+        0
+    );
+
+    let pool_data = fetch_account_data_bytemuck::<PoolData>(&mut svm, &pool_data_pubkey);
+    let publishers: Vec<Pubkey> = pool_data
+        .publishers
+        .iter()
+        .filter(|publisher| **publisher != Pubkey::default())
+        .copied()
+        .collect();
+    let publisher_index = publishers
+        .iter()
+        .position(|publisher| *publisher == publisher_keypair.pubkey())
+        .unwrap();
+
+    // the publishers before the publisher don't have delegation records
+    for chunk in publishers[..publisher_index].chunks(20) {
+        check_delegation_records(&mut svm, &payer, stake_account_positions, chunk).unwrap();
+    }
+    let metadata: StakeAccountMetadataV2 = fetch_account_data(&mut svm, &metadata_address);
+    assert_eq!(
+        usize::from(metadata.delegation_records_checked),
+        publisher_index
+    );
+
+    // the delegation records need to be the ones of the publishers of the pool
+    assert_anchor_program_error!(
+        check_delegation_records(
+            &mut svm,
+            &payer,
+            stake_account_positions,
+            &[Pubkey::new_unique()],
+        ),
+        StakingError::InvalidDelegationRecord,
+        0
+    );
+    assert_anchor_program_error!(
+        check_delegation_records(
+            &mut svm,
+            &payer,
+            stake_account_positions,
+            &publishers[publisher_index..publisher_index + 1],
+        ),
+        StakingError::UnregisteredDelegationRecord,
+        0
+    );
+
+    close_delegation_record(
+        &mut svm,
+        &payer,
+        &owner,
+        publisher_keypair.pubkey(),
+        stake_account_positions,
+    )
+    .unwrap();
+
+    svm.expire_blockhash();
+    for chunk in publishers[publisher_index..].chunks(20) {
+        check_delegation_records(&mut svm, &payer, stake_account_positions, chunk).unwrap();
+    }
+
+    close_stake_account(&mut svm, &owner, stake_account_positions).unwrap();
+    assert_eq!(get_lamports(&mut svm, &stake_account_positions), 0);
+}
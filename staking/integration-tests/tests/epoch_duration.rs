@@ -0,0 +1,285 @@
+use {
+    integration_tests::{
+        assert_anchor_program_error,
+        setup::{
+            setup,
+            SetupProps,
+            SetupResult,
+            STARTING_EPOCH,
+        },
+        solana::utils::{
+            fetch_account_data,
+            fetch_positions_account,
+        },
+        staking::{
+            helper_functions::initialize_new_stake_account,
+            instructions::{
+                close_position,
+                create_position,
+                update_epoch_duration,
+            },
+            pda::get_config_address,
+        },
+        utils::{
+            clock::{
+                advance_mock_clock,
+                advance_n_epochs,
+                get_current_epoch,
+            },
+            constants::EPOCH_DURATION,
+        },
+    },
+    litesvm::LiteSVM,
+    solana_sdk::{
+        pubkey::Pubkey,
+        signature::Keypair,
+        signer::Signer,
+    },
+    staking::{
+        error::ErrorCode,
+        state::{
+            global_config::GlobalConfig,
+            positions::{
+                PositionState,
+                TargetWithParameters,
+                UnlockingDurations,
+            },
+        },
+        utils::clock::{
+            epoch_to_time,
+            time_to_epoch,
+        },
+    },
+    std::convert::TryInto,
+};
+
+fn assert_position_states(
+    svm: &mut LiteSVM,
+    stake_account_positions: &Pubkey,
+    expected_states: &[PositionState],
+) {
+    let current_epoch = get_current_epoch(svm);
+    let mut positions_account = fetch_positions_account(svm, stake_account_positions);
+    let positions = positions_account.to_dynamic_position_array();
+
+    for (i, expected_state) in expected_states.iter().enumerate() {
+        let position = positions.read_position(i).unwrap().unwrap();
+        assert_eq!(
+            position
+                .get_current_position(current_epoch, UnlockingDurations::default())
+                .unwrap(),
+            *expected_state
+        );
+    }
+}
+
+fn advance_clock(svm: &mut LiteSVM, payer: &Keypair, seconds: u64) {
+    svm.expire_blockhash();
+    advance_mock_clock(svm, payer, seconds.try_into().unwrap());
+}
+
+#[test]
+fn test_update_epoch_duration() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair: _,
+        pool_data_pubkey: _,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    assert_anchor_program_error!(
+        update_epoch_duration(&mut svm, &payer, 0),
+        ErrorCode::ZeroEpochDuration,
+        0
+    );
+
+    let not_governance_authority = Keypair::new();
+    svm.airdrop(&not_governance_authority.pubkey(), 1_000_000_000)
+        .unwrap();
+    assert_anchor_program_error!(
+        update_epoch_duration(&mut svm, &not_governance_authority, 2 * EPOCH_DURATION),
+        anchor_lang::error::ErrorCode::ConstraintHasOne,
+        0
+    );
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+    // one position per epoch, otherwise they would be added to the same position
+    for amount in 1..=4 {
+        create_position(
+            &mut svm,
+            &payer,
+            stake_account_positions,
+            TargetWithParameters::Voting,
+            None,
+            amount,
+        )
+        .unwrap();
+        advance_n_epochs(&mut svm, &payer, 1);
+    }
+
+    // close one position per epoch so they end up in different states
+    for index in 0u16..3 {
+        advance_n_epochs(&mut svm, &payer, 1);
+        close_position(
+            &mut svm,
+            &payer,
+            stake_account_positions,
+            TargetWithParameters::Voting,
+            None,
+            (index + 1).into(),
+            index,
+        )
+        .unwrap();
+    }
+    create_position(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        5,
+    )
+    .unwrap();
+
+    let change_epoch = STARTING_EPOCH + 7;
+    assert_eq!(get_current_epoch(&mut svm), change_epoch);
+    let expected_states = [
+        PositionState::UNLOCKED,
+        PositionState::UNLOCKING,
+        PositionState::PREUNLOCKING,
+        PositionState::LOCKED,
+        PositionState::LOCKING,
+    ];
+    assert_position_states(&mut svm, &stake_account_positions, &expected_states);
+
+    // halfway through the epoch, the epochs get twice as long
+    advance_clock(&mut svm, &payer, EPOCH_DURATION / 2);
+    let config: GlobalConfig = fetch_account_data(&mut svm, &get_config_address());
+    let change_epoch_start = epoch_to_time(&config, change_epoch).unwrap();
+
+    update_epoch_duration(&mut svm, &payer, 2 * EPOCH_DURATION).unwrap();
+
+    let config: GlobalConfig = fetch_account_data(&mut svm, &get_config_address());
+    assert_eq!(config.epoch_duration, 2 * EPOCH_DURATION);
+    assert_eq!(config.epoch_offset, change_epoch);
+    assert_eq!(config.epoch_duration_change_time, change_epoch_start);
+    assert_eq!(get_current_epoch(&mut svm), change_epoch);
+    // times before the change still use the old duration
+    assert_eq!(
+        time_to_epoch(&config, change_epoch_start - 1).unwrap(),
+        change_epoch - 1
+    );
+    assert_eq!(
+        time_to_epoch(
+            &config,
+            change_epoch_start - TryInto::<i64>::try_into(EPOCH_DURATION).unwrap()
+        )
+        .unwrap(),
+        change_epoch - 1
+    );
+    assert_eq!(
+        epoch_to_time(&config, change_epoch - 1).unwrap(),
+        change_epoch_start - TryInto::<i64>::try_into(EPOCH_DURATION).unwrap()
+    );
+    assert_position_states(&mut svm, &stake_account_positions, &expected_states);
+
+    // the duration can't change again until a full epoch has passed since the change
+    assert_anchor_program_error!(
+        update_epoch_duration(&mut svm, &payer, EPOCH_DURATION),
+        ErrorCode::EpochDurationChangeTooSoon,
+        0
+    );
+
+    // with the old duration, this would be the next epoch
+    advance_clock(&mut svm, &payer, EPOCH_DURATION);
+    assert_eq!(get_current_epoch(&mut svm), change_epoch);
+    assert_position_states(&mut svm, &stake_account_positions, &expected_states);
+
+    assert_anchor_program_error!(
+        update_epoch_duration(&mut svm, &payer, EPOCH_DURATION),
+        ErrorCode::EpochDurationChangeTooSoon,
+        0
+    );
+
+    advance_clock(&mut svm, &payer, EPOCH_DURATION);
+    assert_eq!(get_current_epoch(&mut svm), change_epoch + 1);
+    assert_position_states(
+        &mut svm,
+        &stake_account_positions,
+        &[
+            PositionState::UNLOCKED,
+            PositionState::UNLOCKED,
+            PositionState::UNLOCKING,
+            PositionState::LOCKED,
+            PositionState::LOCKED,
+        ],
+    );
+
+    advance_clock(&mut svm, &payer, 2 * EPOCH_DURATION);
+    assert_eq!(get_current_epoch(&mut svm), change_epoch + 2);
+    assert_position_states(
+        &mut svm,
+        &stake_account_positions,
+        &[
+            PositionState::UNLOCKED,
+            PositionState::UNLOCKED,
+            PositionState::UNLOCKED,
+            PositionState::LOCKED,
+            PositionState::LOCKED,
+        ],
+    );
+
+    // the current epoch is already longer than the new duration, so it ends in one second
+    update_epoch_duration(&mut svm, &payer, EPOCH_DURATION / 4).unwrap();
+    let config: GlobalConfig = fetch_account_data(&mut svm, &get_config_address());
+    assert_eq!(config.epoch_offset, change_epoch + 2);
+    assert_eq!(
+        config.epoch_duration_change_time,
+        config.mock_clock_time - TryInto::<i64>::try_into(EPOCH_DURATION / 4).unwrap() + 1
+    );
+    assert_eq!(get_current_epoch(&mut svm), change_epoch + 2);
+    assert_eq!(
+        time_to_epoch(&config, config.epoch_duration_change_time - 1).unwrap(),
+        change_epoch + 2
+    );
+    assert_eq!(
+        time_to_epoch(&config, change_epoch_start).unwrap(),
+        change_epoch
+    );
+    // only the previous epoch duration is kept
+    assert_eq!(
+        time_to_epoch(&config, change_epoch_start - 1).unwrap_err(),
+        ErrorCode::TimeBeforeEpochDurationChange.into()
+    );
+    assert_anchor_program_error!(
+        update_epoch_duration(&mut svm, &payer, EPOCH_DURATION),
+        ErrorCode::EpochDurationChangeTooSoon,
+        0
+    );
+
+    advance_clock(&mut svm, &payer, 1);
+    assert_eq!(get_current_epoch(&mut svm), change_epoch + 3);
+    // a full epoch of the new duration has passed, so the duration can change again
+    update_epoch_duration(&mut svm, &payer, EPOCH_DURATION).unwrap();
+    let config: GlobalConfig = fetch_account_data(&mut svm, &get_config_address());
+    assert_eq!(config.epoch_offset, change_epoch + 3);
+    assert_eq!(config.previous_epoch_offset, change_epoch + 2);
+    assert_eq!(get_current_epoch(&mut svm), change_epoch + 3);
+
+    advance_clock(&mut svm, &payer, EPOCH_DURATION / 4);
+    assert_eq!(get_current_epoch(&mut svm), change_epoch + 3);
+
+    advance_clock(&mut svm, &payer, EPOCH_DURATION - EPOCH_DURATION / 4);
+    assert_eq!(get_current_epoch(&mut svm), change_epoch + 4);
+}
@@ -3,6 +3,8 @@ use {
         assert_anchor_program_error,
         integrity_pool::{
             instructions::{
+                accept_reward_program_authority,
+                cancel_reward_program_authority_transfer,
                 create_pool_data_account,
                 update_reward_program_authority,
                 update_y,
@@ -15,12 +17,18 @@ use {
             SetupResult,
         },
         solana::utils::fetch_account_data,
-        utils::constants::YIELD,
+        utils::constants::{
+            MAX_DELEGATION_FEE,
+            YIELD,
+        },
     },
     integrity_pool::{
         error::IntegrityPoolError,
         state::pool::PoolConfig,
-        utils::types::FRAC_64_MULTIPLIER,
+        utils::{
+            constants::DEFAULT_FEE_NOTICE_EPOCHS,
+            types::FRAC_64_MULTIPLIER,
+        },
     },
     solana_sdk::{
         program_error::ProgramError,
@@ -52,6 +60,8 @@ fn initialize_pool() {
     let pool_config: PoolConfig = fetch_account_data(&mut svm, &pool_config_pubkey);
 
     assert!(pool_config.pool_data == pool_data_pubkey);
+    assert!(pool_config.max_delegation_fee == Some(MAX_DELEGATION_FEE));
+    assert!(pool_config.fee_notice_epochs == DEFAULT_FEE_NOTICE_EPOCHS);
 
     // Trying to initialize the pool again should fail
     let pool_data2_keypair = Keypair::new();
@@ -130,18 +140,60 @@ fn test_update_reward_program_authority() {
     let pool_config: PoolConfig = fetch_account_data(&mut svm, &pool_config_pubkey);
 
     assert!(pool_config.reward_program_authority == reward_program_authority.pubkey());
+    assert!(pool_config.pending_reward_program_authority.is_none());
+
+    let new_reward_program_authority = Keypair::new();
+    update_reward_program_authority(
+        &mut svm,
+        &payer,
+        &reward_program_authority,
+        new_reward_program_authority.pubkey(),
+    )
+    .unwrap();
+
+    // the authority only changes once the nominee accepts
+    let pool_config: PoolConfig = fetch_account_data(&mut svm, &pool_config_pubkey);
+    assert!(pool_config.reward_program_authority == reward_program_authority.pubkey());
+    assert!(
+        pool_config.pending_reward_program_authority == Some(new_reward_program_authority.pubkey())
+    );
+
+    assert_anchor_program_error!(
+        accept_reward_program_authority(&mut svm, &payer, &Keypair::new()),
+        IntegrityPoolError::InvalidPendingRewardProgramAuthority,
+        0
+    );
+
+    // only the current authority can cancel the nomination
+    assert_anchor_program_error!(
+        cancel_reward_program_authority_transfer(&mut svm, &payer, &new_reward_program_authority),
+        IntegrityPoolError::InvalidRewardProgramAuthority,
+        0
+    );
+    cancel_reward_program_authority_transfer(&mut svm, &payer, &reward_program_authority).unwrap();
+
+    let pool_config: PoolConfig = fetch_account_data(&mut svm, &pool_config_pubkey);
+    assert!(pool_config.pending_reward_program_authority.is_none());
+
+    assert_anchor_program_error!(
+        accept_reward_program_authority(&mut svm, &payer, &new_reward_program_authority),
+        IntegrityPoolError::InvalidPendingRewardProgramAuthority,
+        0
+    );
 
-    let new_reward_program_authority = Pubkey::new_unique();
+    svm.expire_blockhash();
     update_reward_program_authority(
         &mut svm,
         &payer,
         &reward_program_authority,
-        new_reward_program_authority,
+        new_reward_program_authority.pubkey(),
     )
     .unwrap();
+    accept_reward_program_authority(&mut svm, &payer, &new_reward_program_authority).unwrap();
 
     let pool_config: PoolConfig = fetch_account_data(&mut svm, &pool_config_pubkey);
-    assert!(pool_config.reward_program_authority == new_reward_program_authority);
+    assert!(pool_config.reward_program_authority == new_reward_program_authority.pubkey());
+    assert!(pool_config.pending_reward_program_authority.is_none());
 
     // Trying to update the reward program authority without the correct authority should fail
     let new_reward_program_authority2 = Keypair::new();
@@ -0,0 +1,219 @@
+use {
+    integration_tests::{
+        integrity_pool::instructions::{
+            accept_reward_program_authority,
+            advance,
+            cancel_reward_program_authority_transfer,
+            delegate,
+            undelegate,
+            update_reward_program_authority,
+        },
+        publisher_caps::helper_functions::post_dummy_publisher_caps,
+        setup::{
+            setup,
+            SetupProps,
+            SetupResult,
+        },
+        staking::helper_functions::initialize_new_stake_account,
+        utils::{
+            clock::{
+                advance_n_epochs,
+                get_current_epoch,
+            },
+            events::parse_events,
+        },
+    },
+    integrity_pool::{
+        events::{
+            Delegated,
+            PoolAdvanced,
+            RewardProgramAuthorityAccepted,
+            RewardProgramAuthorityNominated,
+            RewardProgramAuthorityTransferCancelled,
+            Undelegated,
+        },
+        utils::types::FRAC_64_MULTIPLIER,
+    },
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+    staking::{
+        events::{
+            PositionClosed,
+            PositionCreated,
+        },
+        state::positions::TargetWithParameters,
+    },
+};
+
+#[test]
+fn test_integrity_pool_events() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    let publisher = publisher_keypair.pubkey();
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+
+    let logs = delegate(
+        &mut svm,
+        &payer,
+        publisher,
+        pool_data_pubkey,
+        stake_account_positions,
+        50 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap()
+    .logs;
+
+    let delegated = parse_events::<Delegated>(&logs);
+    assert_eq!(delegated.len(), 1);
+    assert_eq!(
+        delegated[0].stake_account_positions,
+        stake_account_positions
+    );
+    assert_eq!(delegated[0].publisher, publisher);
+    assert_eq!(delegated[0].amount, 50 * FRAC_64_MULTIPLIER);
+    assert_eq!(delegated[0].epoch, get_current_epoch(&mut svm));
+
+    // the events of the staking program are emitted during the CPI
+    let created = parse_events::<PositionCreated>(&logs);
+    assert_eq!(created.len(), 1);
+    assert_eq!(created[0].index, 0);
+    assert_eq!(
+        created[0].target_with_parameters,
+        TargetWithParameters::IntegrityPool { publisher }
+    );
+
+    advance_n_epochs(&mut svm, &payer, 1);
+    let publisher_caps = post_dummy_publisher_caps(&mut svm, &payer, publisher, 50);
+    let logs = advance(&mut svm, &payer, publisher_caps).unwrap().logs;
+
+    let advanced = parse_events::<PoolAdvanced>(&logs);
+    assert_eq!(advanced.len(), 1);
+    assert_eq!(advanced[0].epoch, get_current_epoch(&mut svm) - 1);
+    assert_eq!(advanced[0].current_epoch, get_current_epoch(&mut svm));
+
+    // one event is emitted for every epoch, the delegation is eligible from its activation
+    advance_n_epochs(&mut svm, &payer, 2);
+    let publisher_caps = post_dummy_publisher_caps(&mut svm, &payer, publisher, 50);
+    let logs = advance(&mut svm, &payer, publisher_caps).unwrap().logs;
+
+    let advanced = parse_events::<PoolAdvanced>(&logs);
+    assert_eq!(advanced.len(), 2);
+    for (i, event) in advanced.iter().enumerate() {
+        assert_eq!(event.epoch, get_current_epoch(&mut svm) - 2 + i as u64);
+        assert_eq!(event.current_epoch, get_current_epoch(&mut svm));
+        assert_eq!(event.total_eligible_delegation, 50);
+    }
+
+    let logs = undelegate(
+        &mut svm,
+        &payer,
+        publisher,
+        pool_data_pubkey,
+        stake_account_positions,
+        0,
+        20 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap()
+    .logs;
+
+    let undelegated = parse_events::<Undelegated>(&logs);
+    assert_eq!(undelegated.len(), 1);
+    assert_eq!(undelegated[0].position_index, 0);
+    assert_eq!(undelegated[0].amount, 20 * FRAC_64_MULTIPLIER);
+
+    // the rest of the locked position stays at index 0
+    let closed = parse_events::<PositionClosed>(&logs);
+    assert_eq!(closed.len(), 1);
+    assert_eq!(closed[0].index, 0);
+    assert_eq!(closed[0].unlocking_index, Some(1));
+
+    // events of other types are ignored
+    assert!(parse_events::<PoolAdvanced>(&logs).is_empty());
+}
+
+#[test]
+fn test_reward_program_authority_events() {
+    let SetupResult {
+        mut svm,
+        payer,
+        reward_program_authority,
+        ..
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    let new_reward_program_authority = Keypair::new();
+    let logs = update_reward_program_authority(
+        &mut svm,
+        &payer,
+        &reward_program_authority,
+        new_reward_program_authority.pubkey(),
+    )
+    .unwrap()
+    .logs;
+
+    let nominated = parse_events::<RewardProgramAuthorityNominated>(&logs);
+    assert_eq!(nominated.len(), 1);
+    assert_eq!(
+        nominated[0].reward_program_authority,
+        reward_program_authority.pubkey()
+    );
+    assert_eq!(
+        nominated[0].pending_reward_program_authority,
+        new_reward_program_authority.pubkey()
+    );
+
+    let logs =
+        cancel_reward_program_authority_transfer(&mut svm, &payer, &reward_program_authority)
+            .unwrap()
+            .logs;
+
+    let cancelled = parse_events::<RewardProgramAuthorityTransferCancelled>(&logs);
+    assert_eq!(cancelled.len(), 1);
+    assert_eq!(
+        cancelled[0].reward_program_authority,
+        reward_program_authority.pubkey()
+    );
+
+    svm.expire_blockhash();
+    update_reward_program_authority(
+        &mut svm,
+        &payer,
+        &reward_program_authority,
+        new_reward_program_authority.pubkey(),
+    )
+    .unwrap();
+    let logs = accept_reward_program_authority(&mut svm, &payer, &new_reward_program_authority)
+        .unwrap()
+        .logs;
+
+    let accepted = parse_events::<RewardProgramAuthorityAccepted>(&logs);
+    assert_eq!(accepted.len(), 1);
+    assert_eq!(
+        accepted[0].reward_program_authority,
+        new_reward_program_authority.pubkey()
+    );
+}
@@ -1,6 +1,5 @@
 use {
     integration_tests::{
-        assert_anchor_program_error,
         integrity_pool::instructions::{
             advance,
             advance_delegation_record,
@@ -12,12 +11,22 @@ use {
             SetupProps,
             SetupResult,
         },
-        staking::helper_functions::initialize_new_stake_account,
+        solana::utils::{
+            fetch_account_data,
+            fetch_positions_account,
+        },
+        staking::{
+            helper_functions::initialize_new_stake_account,
+            pda::get_stake_account_metadata_address,
+        },
         utils::clock::advance_n_epochs,
     },
     integrity_pool::utils::types::FRAC_64_MULTIPLIER,
     solana_sdk::signer::Signer,
-    staking::error::ErrorCode,
+    staking::state::{
+        positions::PositionsLayout,
+        stake_account::StakeAccountMetadataV2,
+    },
 };
 
 
@@ -43,8 +52,8 @@ fn test_max_positions() {
     let stake_account_positions =
         initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
 
+    // delegations in the same epoch are added to the same position, so we delegate once per epoch
     for _ in 0..u8::MAX {
-        svm.expire_blockhash();
         delegate(
             &mut svm,
             &payer,
@@ -54,21 +63,16 @@ fn test_max_positions() {
             100,
         )
         .unwrap();
-    }
 
-    svm.expire_blockhash();
-    assert_anchor_program_error!(
-        delegate(
+        advance_n_epochs(&mut svm, &payer, 1);
+        let publisher_caps = post_dummy_publisher_caps(
             &mut svm,
             &payer,
             publisher_keypair.pubkey(),
-            pool_data_pubkey,
-            stake_account_positions,
-            100,
-        ),
-        ErrorCode::TooManyPositions,
-        0
-    );
+            200 * FRAC_64_MULTIPLIER,
+        );
+        advance(&mut svm, &payer, publisher_caps).unwrap();
+    }
 
     for _ in 0..10 {
         advance_n_epochs(&mut svm, &payer, 10);
@@ -116,4 +120,33 @@ fn test_max_positions() {
         None,
     )
     .unwrap();
+
+    // the positions index isn't limited to a u8 anymore
+    svm.expire_blockhash();
+    delegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        100,
+    )
+    .unwrap();
+
+    let stake_account_metadata: StakeAccountMetadataV2 = fetch_account_data(
+        &mut svm,
+        &get_stake_account_metadata_address(stake_account_positions),
+    );
+    let mut positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+    let positions = positions_account.to_dynamic_position_array();
+    assert_eq!(
+        positions
+            .get_next_index(stake_account_metadata.next_index)
+            .unwrap(),
+        usize::from(u8::MAX) + 1
+    );
+    assert_eq!(
+        positions.acc_info.data_len(),
+        PositionsLayout::V2.data_len(usize::from(u8::MAX) + 1)
+    );
 }
@@ -23,6 +23,7 @@ use {
         utils::clock::advance_n_epochs,
     },
     integrity_pool::error::IntegrityPoolError,
+    litesvm::LiteSVM,
     solana_sdk::{
         native_token::LAMPORTS_PER_SOL,
         pubkey::Pubkey,
@@ -31,8 +32,8 @@ use {
         signer::Signer,
     },
     staking::state::positions::{
+        PositionsLayout,
         TargetWithParameters,
-        POSITION_BUFFER_SIZE,
     },
 };
 
@@ -85,6 +86,7 @@ fn test_merge_delegation_positions() {
     )
     .unwrap();
 
+    // delegating twice in the same epoch adds to the same position
     delegate(
         &mut svm,
         &payer,
@@ -110,29 +112,21 @@ fn test_merge_delegation_positions() {
 
     let pos0 = positions.read_position(0).unwrap().unwrap();
 
-    assert_eq!(pos0.amount, 1);
+    assert_eq!(pos0.amount, 3);
     assert_eq!(pos0.target_with_parameters, target_with_parameters);
     assert_eq!(pos0.activation_epoch, STARTING_EPOCH + 1);
     assert_eq!(pos0.unlocking_start, None);
 
     let pos1 = positions.read_position(1).unwrap().unwrap();
 
-    assert_eq!(pos1.amount, 2);
-    assert_eq!(pos1.target_with_parameters, target_with_parameters);
+    assert_eq!(pos1.amount, 3);
+    assert_eq!(pos1.target_with_parameters, TargetWithParameters::Voting);
     assert_eq!(pos1.activation_epoch, STARTING_EPOCH + 1);
     assert_eq!(pos1.unlocking_start, None);
 
 
-    let pos2 = positions.read_position(2).unwrap().unwrap();
-
-    assert_eq!(pos2.amount, 3);
-    assert_eq!(pos2.target_with_parameters, TargetWithParameters::Voting);
-    assert_eq!(pos2.activation_epoch, STARTING_EPOCH + 1);
-    assert_eq!(pos2.unlocking_start, None);
-
-
     assert_eq!(
-        positions.read_position(3).unwrap_err(),
+        positions.read_position(2).unwrap_err(),
         staking::error::ErrorCode::PositionOutOfBounds.into()
     );
 
@@ -201,49 +195,34 @@ fn test_merge_delegation_positions() {
 
     let pos0 = positions.read_position(0).unwrap().unwrap();
 
-    assert_eq!(pos0.amount, 1);
+    assert_eq!(pos0.amount, 3);
     assert_eq!(pos0.target_with_parameters, target_with_parameters);
     assert_eq!(pos0.activation_epoch, STARTING_EPOCH + 1);
     assert_eq!(pos0.unlocking_start, None);
 
     let pos1 = positions.read_position(1).unwrap().unwrap();
 
-    assert_eq!(pos1.amount, 2);
-    assert_eq!(pos1.target_with_parameters, target_with_parameters);
+    assert_eq!(pos1.amount, 3);
+    assert_eq!(pos1.target_with_parameters, TargetWithParameters::Voting);
     assert_eq!(pos1.activation_epoch, STARTING_EPOCH + 1);
     assert_eq!(pos1.unlocking_start, None);
 
-
     let pos2 = positions.read_position(2).unwrap().unwrap();
 
-    assert_eq!(pos2.amount, 3);
-    assert_eq!(pos2.target_with_parameters, TargetWithParameters::Voting);
-    assert_eq!(pos2.activation_epoch, STARTING_EPOCH + 1);
+    assert_eq!(pos2.amount, 10);
+    assert_eq!(pos2.target_with_parameters, target_with_parameters);
+    assert_eq!(pos2.activation_epoch, STARTING_EPOCH + 2);
     assert_eq!(pos2.unlocking_start, None);
 
     let pos3 = positions.read_position(3).unwrap().unwrap();
 
-    assert_eq!(pos3.amount, 4);
-    assert_eq!(pos3.target_with_parameters, target_with_parameters);
+    assert_eq!(pos3.amount, 5);
+    assert_eq!(pos3.target_with_parameters, TargetWithParameters::Voting);
     assert_eq!(pos3.activation_epoch, STARTING_EPOCH + 2);
     assert_eq!(pos3.unlocking_start, None);
 
-    let pos4 = positions.read_position(4).unwrap().unwrap();
-
-    assert_eq!(pos4.amount, 5);
-    assert_eq!(pos4.target_with_parameters, TargetWithParameters::Voting);
-    assert_eq!(pos4.activation_epoch, STARTING_EPOCH + 2);
-    assert_eq!(pos4.unlocking_start, None);
-
-    let pos5 = positions.read_position(5).unwrap().unwrap();
-
-    assert_eq!(pos5.amount, 6);
-    assert_eq!(pos5.target_with_parameters, target_with_parameters);
-    assert_eq!(pos5.activation_epoch, STARTING_EPOCH + 2);
-    assert_eq!(pos5.unlocking_start, None);
-
     assert_eq!(
-        positions.read_position(6).unwrap_err(),
+        positions.read_position(4).unwrap_err(),
         staking::error::ErrorCode::PositionOutOfBounds.into()
     );
 
@@ -301,7 +280,7 @@ fn test_merge_delegation_positions() {
         publisher_keypair.pubkey(),
         pool_data_pubkey,
         stake_account_positions,
-        3,
+        2,
         1,
     )
     .unwrap();
@@ -312,72 +291,55 @@ fn test_merge_delegation_positions() {
 
     let pos0 = positions.read_position(0).unwrap().unwrap();
 
-    assert_eq!(pos0.amount, 1);
+    assert_eq!(pos0.amount, 2);
     assert_eq!(pos0.target_with_parameters, target_with_parameters);
     assert_eq!(pos0.activation_epoch, STARTING_EPOCH + 1);
-    assert_eq!(pos0.unlocking_start, Some(STARTING_EPOCH + 3));
+    assert_eq!(pos0.unlocking_start, None);
 
     let pos1 = positions.read_position(1).unwrap().unwrap();
 
-    assert_eq!(pos1.amount, 2);
-    assert_eq!(pos1.target_with_parameters, target_with_parameters);
+    assert_eq!(pos1.amount, 3);
+    assert_eq!(pos1.target_with_parameters, TargetWithParameters::Voting);
     assert_eq!(pos1.activation_epoch, STARTING_EPOCH + 1);
     assert_eq!(pos1.unlocking_start, None);
 
-
     let pos2 = positions.read_position(2).unwrap().unwrap();
 
-    assert_eq!(pos2.amount, 3);
-    assert_eq!(pos2.target_with_parameters, TargetWithParameters::Voting);
-    assert_eq!(pos2.activation_epoch, STARTING_EPOCH + 1);
+    assert_eq!(pos2.amount, 9);
+    assert_eq!(pos2.target_with_parameters, target_with_parameters);
+    assert_eq!(pos2.activation_epoch, STARTING_EPOCH + 2);
     assert_eq!(pos2.unlocking_start, None);
 
     let pos3 = positions.read_position(3).unwrap().unwrap();
 
-    assert_eq!(pos3.amount, 3);
-    assert_eq!(pos3.target_with_parameters, target_with_parameters);
+    assert_eq!(pos3.amount, 5);
+    assert_eq!(pos3.target_with_parameters, TargetWithParameters::Voting);
     assert_eq!(pos3.activation_epoch, STARTING_EPOCH + 2);
     assert_eq!(pos3.unlocking_start, None);
 
     let pos4 = positions.read_position(4).unwrap().unwrap();
 
-    assert_eq!(pos4.amount, 5);
-    assert_eq!(pos4.target_with_parameters, TargetWithParameters::Voting);
-    assert_eq!(pos4.activation_epoch, STARTING_EPOCH + 2);
+    assert_eq!(pos4.amount, 15);
+    assert_eq!(pos4.target_with_parameters, target_with_parameters);
+    assert_eq!(pos4.activation_epoch, STARTING_EPOCH + 3);
     assert_eq!(pos4.unlocking_start, None);
 
     let pos5 = positions.read_position(5).unwrap().unwrap();
 
-    assert_eq!(pos5.amount, 6);
+    assert_eq!(pos5.amount, 1);
     assert_eq!(pos5.target_with_parameters, target_with_parameters);
-    assert_eq!(pos5.activation_epoch, STARTING_EPOCH + 2);
-    assert_eq!(pos5.unlocking_start, None);
-
+    assert_eq!(pos5.activation_epoch, STARTING_EPOCH + 1);
+    assert_eq!(pos5.unlocking_start, Some(STARTING_EPOCH + 3));
 
     let pos6 = positions.read_position(6).unwrap().unwrap();
 
-    assert_eq!(pos6.amount, 7);
+    assert_eq!(pos6.amount, 1);
     assert_eq!(pos6.target_with_parameters, target_with_parameters);
-    assert_eq!(pos6.activation_epoch, STARTING_EPOCH + 3);
-    assert_eq!(pos6.unlocking_start, None);
-
-
-    let pos7 = positions.read_position(7).unwrap().unwrap();
-
-    assert_eq!(pos7.amount, 8);
-    assert_eq!(pos7.target_with_parameters, target_with_parameters);
-    assert_eq!(pos7.activation_epoch, STARTING_EPOCH + 3);
-    assert_eq!(pos7.unlocking_start, None);
-
-    let pos8 = positions.read_position(8).unwrap().unwrap();
-
-    assert_eq!(pos8.amount, 1);
-    assert_eq!(pos8.target_with_parameters, target_with_parameters);
-    assert_eq!(pos8.activation_epoch, STARTING_EPOCH + 2);
-    assert_eq!(pos8.unlocking_start, Some(STARTING_EPOCH + 3));
+    assert_eq!(pos6.activation_epoch, STARTING_EPOCH + 2);
+    assert_eq!(pos6.unlocking_start, Some(STARTING_EPOCH + 3));
 
     assert_eq!(
-        positions.read_position(9).unwrap_err(),
+        positions.read_position(7).unwrap_err(),
         staking::error::ErrorCode::PositionOutOfBounds.into()
     );
 
@@ -409,67 +371,25 @@ fn test_merge_delegation_positions() {
     )
     .unwrap();
 
-    let mut stake_positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
-    let positions = stake_positions_account.to_dynamic_position_array();
-
-    let pos0 = positions.read_position(0).unwrap().unwrap();
-
-    assert_eq!(pos0.amount, 2);
-    assert_eq!(pos0.target_with_parameters, target_with_parameters);
-    assert_eq!(pos0.activation_epoch, STARTING_EPOCH + 1);
-    assert_eq!(pos0.unlocking_start, Some(STARTING_EPOCH + 3));
-
-    let pos1 = positions.read_position(1).unwrap().unwrap();
-
-    assert_eq!(pos1.amount, 11);
-    assert_eq!(pos1.target_with_parameters, target_with_parameters);
-    assert_eq!(pos1.activation_epoch, STARTING_EPOCH + 1);
-    assert_eq!(pos1.unlocking_start, None);
-
-
-    let pos2 = positions.read_position(2).unwrap().unwrap();
-
-    assert_eq!(pos2.amount, 3);
-    assert_eq!(pos2.target_with_parameters, TargetWithParameters::Voting);
-    assert_eq!(pos2.activation_epoch, STARTING_EPOCH + 1);
-    assert_eq!(pos2.unlocking_start, None);
-
-    let pos3 = positions.read_position(3).unwrap().unwrap();
-
-    assert_eq!(pos3.amount, 15);
-    assert_eq!(pos3.target_with_parameters, target_with_parameters);
-    assert_eq!(pos3.activation_epoch, STARTING_EPOCH + 3);
-    assert_eq!(pos3.unlocking_start, None);
-
-    let pos4 = positions.read_position(4).unwrap().unwrap();
-
-    assert_eq!(pos4.amount, 5);
-    assert_eq!(pos4.target_with_parameters, TargetWithParameters::Voting);
-    assert_eq!(pos4.activation_epoch, STARTING_EPOCH + 2);
-    assert_eq!(pos4.unlocking_start, None);
-
-
-    assert_eq!(
-        positions.read_position(5).unwrap_err(),
-        staking::error::ErrorCode::PositionOutOfBounds.into()
-    );
-
+    assert_merged_positions(&mut svm, &stake_account_positions, target_with_parameters);
 
     let payer_balance_after = svm.get_balance(&payer.pubkey()).unwrap();
     let balance = svm.get_balance(&stake_account_positions).unwrap();
     let stake_positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
 
     assert_eq!(
-        payer_balance_before + Rent::default().minimum_balance(40 + 9 * POSITION_BUFFER_SIZE),
-        payer_balance_after + Rent::default().minimum_balance(40 + 5 * POSITION_BUFFER_SIZE) + 5000
+        payer_balance_before + Rent::default().minimum_balance(PositionsLayout::V2.data_len(7)),
+        payer_balance_after
+            + Rent::default().minimum_balance(PositionsLayout::V2.data_len(5))
+            + 5000
     );
     assert_eq!(
         balance,
-        Rent::default().minimum_balance(40 + 5 * POSITION_BUFFER_SIZE)
+        Rent::default().minimum_balance(PositionsLayout::V2.data_len(5))
     );
     assert_eq!(
         stake_positions_account.data.len(),
-        40 + 5 * POSITION_BUFFER_SIZE
+        PositionsLayout::V2.data_len(5)
     );
 
     //anyone can call merge_delegation_positions
@@ -485,58 +405,72 @@ fn test_merge_delegation_positions() {
     .unwrap();
 
     // account is unchanged
-    let mut stake_positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+    assert_merged_positions(&mut svm, &stake_account_positions, target_with_parameters);
+
+    let balance = svm.get_balance(&stake_account_positions).unwrap();
+    let stake_positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+
+    assert_eq!(
+        balance,
+        Rent::default().minimum_balance(PositionsLayout::V2.data_len(5))
+    );
+    assert_eq!(
+        stake_positions_account.data.len(),
+        PositionsLayout::V2.data_len(5)
+    );
+    assert_eq!(
+        svm.get_balance(&payer.pubkey()).unwrap(),
+        payer_balance_after
+    );
+}
+
+/// Checks the positions of the account after merging: the locked positions and the unlocking
+/// positions are merged, the locking position and the voting positions don't change
+fn assert_merged_positions(
+    svm: &mut LiteSVM,
+    stake_account_positions: &Pubkey,
+    target_with_parameters: TargetWithParameters,
+) {
+    let mut stake_positions_account = fetch_positions_account(svm, stake_account_positions);
     let positions = stake_positions_account.to_dynamic_position_array();
 
     let pos0 = positions.read_position(0).unwrap().unwrap();
 
-    assert_eq!(pos0.amount, 2);
+    assert_eq!(pos0.amount, 11);
     assert_eq!(pos0.target_with_parameters, target_with_parameters);
     assert_eq!(pos0.activation_epoch, STARTING_EPOCH + 1);
-    assert_eq!(pos0.unlocking_start, Some(STARTING_EPOCH + 3));
+    assert_eq!(pos0.unlocking_start, None);
 
     let pos1 = positions.read_position(1).unwrap().unwrap();
 
-    assert_eq!(pos1.amount, 11);
-    assert_eq!(pos1.target_with_parameters, target_with_parameters);
+    assert_eq!(pos1.amount, 3);
+    assert_eq!(pos1.target_with_parameters, TargetWithParameters::Voting);
     assert_eq!(pos1.activation_epoch, STARTING_EPOCH + 1);
     assert_eq!(pos1.unlocking_start, None);
 
-
     let pos2 = positions.read_position(2).unwrap().unwrap();
 
-    assert_eq!(pos2.amount, 3);
-    assert_eq!(pos2.target_with_parameters, TargetWithParameters::Voting);
+    assert_eq!(pos2.amount, 2);
+    assert_eq!(pos2.target_with_parameters, target_with_parameters);
     assert_eq!(pos2.activation_epoch, STARTING_EPOCH + 1);
-    assert_eq!(pos2.unlocking_start, None);
+    assert_eq!(pos2.unlocking_start, Some(STARTING_EPOCH + 3));
 
     let pos3 = positions.read_position(3).unwrap().unwrap();
 
-    assert_eq!(pos3.amount, 15);
-    assert_eq!(pos3.target_with_parameters, target_with_parameters);
-    assert_eq!(pos3.activation_epoch, STARTING_EPOCH + 3);
+    assert_eq!(pos3.amount, 5);
+    assert_eq!(pos3.target_with_parameters, TargetWithParameters::Voting);
+    assert_eq!(pos3.activation_epoch, STARTING_EPOCH + 2);
     assert_eq!(pos3.unlocking_start, None);
 
     let pos4 = positions.read_position(4).unwrap().unwrap();
 
-    assert_eq!(pos4.amount, 5);
-    assert_eq!(pos4.target_with_parameters, TargetWithParameters::Voting);
-    assert_eq!(pos4.activation_epoch, STARTING_EPOCH + 2);
+    assert_eq!(pos4.amount, 15);
+    assert_eq!(pos4.target_with_parameters, target_with_parameters);
+    assert_eq!(pos4.activation_epoch, STARTING_EPOCH + 3);
     assert_eq!(pos4.unlocking_start, None);
 
-    let balance = svm.get_balance(&stake_account_positions).unwrap();
-    let stake_positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
-
-    assert_eq!(
-        balance,
-        Rent::default().minimum_balance(40 + 5 * POSITION_BUFFER_SIZE)
-    );
-    assert_eq!(
-        stake_positions_account.data.len(),
-        40 + 5 * POSITION_BUFFER_SIZE
-    );
     assert_eq!(
-        svm.get_balance(&payer.pubkey()).unwrap(),
-        payer_balance_after
+        positions.read_position(5).unwrap_err(),
+        staking::error::ErrorCode::PositionOutOfBounds.into()
     );
 }
@@ -0,0 +1,279 @@
+use {
+    anchor_lang::error::ErrorCode,
+    integration_tests::{
+        assert_anchor_program_error,
+        integrity_pool::instructions::{
+            delegate,
+            undelegate,
+        },
+        setup::{
+            setup,
+            SetupProps,
+            SetupResult,
+        },
+        solana::{
+            instructions::create_token_account,
+            utils::{
+                fetch_account_data,
+                fetch_positions_account,
+            },
+        },
+        staking::{
+            helper_functions::initialize_new_stake_account,
+            instructions::{
+                close_position,
+                create_position,
+                create_voter_record,
+                request_split,
+                set_operator,
+                transfer_account,
+                withdraw_stake,
+            },
+            pda::get_stake_account_metadata_address,
+        },
+    },
+    integrity_pool::utils::types::FRAC_64_MULTIPLIER,
+    solana_sdk::{
+        account::Account,
+        native_token::LAMPORTS_PER_SOL,
+        signature::Keypair,
+        signer::Signer,
+    },
+    staking::{
+        error::ErrorCode as StakingError,
+        state::{
+            positions::TargetWithParameters,
+            stake_account::StakeAccountMetadataV2,
+        },
+    },
+};
+
+#[test]
+fn test_operator() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    let operator = Keypair::new();
+    svm.airdrop(&operator.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+    let stake_account_metadata_address =
+        get_stake_account_metadata_address(stake_account_positions);
+
+    // shrink the metadata to the size of the accounts created before the operator was introduced
+    let metadata_account = svm.get_account(&stake_account_metadata_address).unwrap();
+    let legacy_metadata_account = Account {
+        lamports: svm.minimum_balance_for_rent_exemption(200),
+        data: metadata_account.data[..200].to_vec(),
+        ..metadata_account
+    };
+    svm.set_account(stake_account_metadata_address, legacy_metadata_account)
+        .unwrap();
+
+    assert_anchor_program_error!(
+        create_position(
+            &mut svm,
+            &operator,
+            stake_account_positions,
+            TargetWithParameters::Voting,
+            None,
+            10 * FRAC_64_MULTIPLIER,
+        ),
+        StakingError::OwnerOrOperatorNeedsToSign,
+        0
+    );
+
+    // only the owner can set the operator
+    assert_anchor_program_error!(
+        set_operator(
+            &mut svm,
+            &operator,
+            stake_account_positions,
+            Some(operator.pubkey())
+        ),
+        ErrorCode::ConstraintHasOne,
+        0
+    );
+
+    set_operator(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        Some(operator.pubkey()),
+    )
+    .unwrap();
+
+    let metadata_account = svm.get_account(&stake_account_metadata_address).unwrap();
+    assert_eq!(metadata_account.data.len(), StakeAccountMetadataV2::LEN);
+    assert_eq!(
+        metadata_account.lamports,
+        svm.minimum_balance_for_rent_exemption(StakeAccountMetadataV2::LEN)
+    );
+    let stake_account_metadata: StakeAccountMetadataV2 =
+        fetch_account_data(&mut svm, &stake_account_metadata_address);
+    assert_eq!(stake_account_metadata.owner, payer.pubkey());
+    assert_eq!(stake_account_metadata.operator, Some(operator.pubkey()));
+
+    // the operator manages the positions
+    svm.expire_blockhash();
+    create_position(
+        &mut svm,
+        &operator,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        10 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+    delegate(
+        &mut svm,
+        &operator,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        20 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+
+    let mut positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+    let positions = positions_account.to_dynamic_position_array();
+    assert_eq!(positions.owner().unwrap(), payer.pubkey());
+    assert_eq!(
+        positions.read_position(0).unwrap().unwrap().amount,
+        10 * FRAC_64_MULTIPLIER
+    );
+    assert_eq!(
+        positions.read_position(1).unwrap().unwrap().amount,
+        20 * FRAC_64_MULTIPLIER
+    );
+
+    // but can't move tokens out of the account
+    let destination = create_token_account(&mut svm, &operator, &pyth_token_mint.pubkey());
+    assert_anchor_program_error!(
+        withdraw_stake(
+            &mut svm,
+            &operator,
+            stake_account_positions,
+            destination.pubkey(),
+            FRAC_64_MULTIPLIER
+        ),
+        ErrorCode::ConstraintHasOne,
+        0
+    );
+    assert_anchor_program_error!(
+        request_split(
+            &mut svm,
+            &operator,
+            stake_account_positions,
+            FRAC_64_MULTIPLIER,
+            operator.pubkey()
+        ),
+        ErrorCode::ConstraintHasOne,
+        0
+    );
+    withdraw_stake(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        destination.pubkey(),
+        FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+
+    undelegate(
+        &mut svm,
+        &operator,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        1,
+        20 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+    close_position(
+        &mut svm,
+        &operator,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        10 * FRAC_64_MULTIPLIER,
+        0,
+    )
+    .unwrap();
+
+    let mut positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+    let positions = positions_account.to_dynamic_position_array();
+    assert!(positions.read_position(0).unwrap().is_none());
+    assert!(positions.read_position(1).unwrap().is_none());
+
+    // the owner revokes the operator
+    set_operator(&mut svm, &payer, stake_account_positions, None).unwrap();
+
+    let stake_account_metadata: StakeAccountMetadataV2 =
+        fetch_account_data(&mut svm, &stake_account_metadata_address);
+    assert_eq!(stake_account_metadata.operator, None);
+
+    assert_anchor_program_error!(
+        create_position(
+            &mut svm,
+            &operator,
+            stake_account_positions,
+            TargetWithParameters::Voting,
+            None,
+            5 * FRAC_64_MULTIPLIER,
+        ),
+        StakingError::OwnerOrOperatorNeedsToSign,
+        0
+    );
+
+    // the operator is revoked when the account is transferred
+    svm.expire_blockhash();
+    set_operator(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        Some(operator.pubkey()),
+    )
+    .unwrap();
+    create_voter_record(&mut svm, &payer, stake_account_positions).unwrap();
+    transfer_account(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        Keypair::new().pubkey(),
+    )
+    .unwrap();
+
+    let stake_account_metadata: StakeAccountMetadataV2 =
+        fetch_account_data(&mut svm, &stake_account_metadata_address);
+    assert_eq!(stake_account_metadata.operator, None);
+
+    svm.expire_blockhash();
+    assert_anchor_program_error!(
+        create_position(
+            &mut svm,
+            &operator,
+            stake_account_positions,
+            TargetWithParameters::Voting,
+            None,
+            5 * FRAC_64_MULTIPLIER,
+        ),
+        StakingError::OwnerOrOperatorNeedsToSign,
+        0
+    );
+}
@@ -14,6 +14,7 @@ use {
         staking::{
             helper_functions::initialize_new_stake_account,
             instructions::{
+                accept_pool_authority,
                 close_position,
                 create_position,
                 update_pool_authority,
@@ -61,7 +62,8 @@ fn test_pool_authority() {
 
     let pool_authority = Keypair::new();
 
-    update_pool_authority(&mut svm, &payer, pool_authority.pubkey());
+    update_pool_authority(&mut svm, &payer, pool_authority.pubkey()).unwrap();
+    accept_pool_authority(&mut svm, &payer, &pool_authority).unwrap();
 
     create_position(
         &mut svm,
@@ -0,0 +1,593 @@
+use {
+    anchor_spl::token::TokenAccount,
+    integration_tests::{
+        assert_anchor_program_error,
+        integrity_pool::{
+            helper_functions::get_default_slash_custody,
+            instructions::{
+                advance,
+                advance_delegation_record,
+                create_slash_event,
+                delegate,
+                redelegate,
+                slash,
+                undelegate,
+            },
+        },
+        publisher_caps::{
+            helper_functions::post_dummy_publisher_caps,
+            utils::get_dummy_publisher,
+        },
+        setup::{
+            setup,
+            SetupProps,
+            SetupResult,
+        },
+        solana::{
+            instructions::create_token_account,
+            utils::{
+                fetch_account_data,
+                fetch_account_data_bytemuck,
+                fetch_positions_account,
+            },
+        },
+        staking::{
+            helper_functions::initialize_new_stake_account,
+            instructions::{
+                close_position,
+                create_position,
+                withdraw_stake,
+            },
+        },
+        utils::{
+            clock::advance_n_epochs,
+            constants::STAKED_TOKENS,
+        },
+    },
+    integrity_pool::{
+        state::pool::{
+            DelegationState,
+            PoolData,
+        },
+        utils::types::FRAC_64_MULTIPLIER,
+    },
+    solana_sdk::signer::Signer,
+    staking::{
+        error::ErrorCode as StakingError,
+        state::positions::{
+            RedelegationMarker,
+            TargetWithParameters,
+        },
+    },
+};
+
+#[test]
+fn test_redelegate() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+    let publisher_index = maybe_publisher_index.unwrap();
+    let new_publisher = get_dummy_publisher(1);
+
+    let pool_data: PoolData = fetch_account_data_bytemuck(&mut svm, &pool_data_pubkey);
+    let new_publisher_index = pool_data
+        .publishers
+        .iter()
+        .position(|&x| x == new_publisher)
+        .unwrap();
+
+    let target_with_parameters = TargetWithParameters::IntegrityPool {
+        publisher: publisher_keypair.pubkey(),
+    };
+    let new_target_with_parameters = TargetWithParameters::IntegrityPool {
+        publisher: new_publisher,
+    };
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+
+    delegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        STAKED_TOKENS,
+    )
+    .unwrap();
+
+    assert_anchor_program_error!(
+        redelegate(
+            &mut svm,
+            &payer,
+            publisher_keypair.pubkey(),
+            publisher_keypair.pubkey(),
+            pool_data_pubkey,
+            stake_account_positions,
+            0,
+            40 * FRAC_64_MULTIPLIER,
+        ),
+        StakingError::InvalidRedelegationTarget,
+        0
+    );
+
+    // a locking position is moved right away
+    redelegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        new_publisher,
+        pool_data_pubkey,
+        stake_account_positions,
+        0,
+        40 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+
+    let mut stake_positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+    let positions = stake_positions_account.to_dynamic_position_array();
+
+    let pos0 = positions.read_position(0).unwrap().unwrap();
+    assert_eq!(pos0.amount, 60 * FRAC_64_MULTIPLIER);
+    assert_eq!(pos0.target_with_parameters, target_with_parameters);
+    assert_eq!(pos0.activation_epoch, 3);
+    assert_eq!(pos0.unlocking_start, None);
+
+    let pos1 = positions.read_position(1).unwrap().unwrap();
+    assert_eq!(pos1.amount, 40 * FRAC_64_MULTIPLIER);
+    assert_eq!(pos1.target_with_parameters, new_target_with_parameters);
+    assert_eq!(pos1.activation_epoch, 3);
+    assert_eq!(pos1.unlocking_start, None);
+    // the old position doesn't stop being delegated, so the new one doesn't take its place
+    assert_eq!(
+        positions.read_redelegation_marker(1).unwrap(),
+        RedelegationMarker::default()
+    );
+
+    let pool_data: PoolData = fetch_account_data_bytemuck(&mut svm, &pool_data_pubkey);
+    assert_eq!(
+        pool_data.del_state[publisher_index],
+        DelegationState {
+            total_delegation: 0,
+            delta_delegation: 60 * FRAC_64_MULTIPLIER as i64,
+        }
+    );
+    assert_eq!(
+        pool_data.del_state[new_publisher_index],
+        DelegationState {
+            total_delegation: 0,
+            delta_delegation: 40 * FRAC_64_MULTIPLIER as i64,
+        }
+    );
+
+    advance_n_epochs(&mut svm, &payer, 1);
+    let publisher_caps =
+        post_dummy_publisher_caps(&mut svm, &payer, publisher_keypair.pubkey(), 100);
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+
+    // a locked position is moved without waiting for the unlocking period, even if all the
+    // tokens of the stake account are delegated
+    redelegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        new_publisher,
+        pool_data_pubkey,
+        stake_account_positions,
+        0,
+        60 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+
+    let mut stake_positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+    let positions = stake_positions_account.to_dynamic_position_array();
+
+    // the old position stays slashable until it's unlocked
+    let pos0 = positions.read_position(0).unwrap().unwrap();
+    assert_eq!(pos0.amount, 60 * FRAC_64_MULTIPLIER);
+    assert_eq!(pos0.target_with_parameters, target_with_parameters);
+    assert_eq!(pos0.activation_epoch, 3);
+    assert_eq!(pos0.unlocking_start, Some(4));
+
+    let pos2 = positions.read_position(2).unwrap().unwrap();
+    assert_eq!(pos2.amount, 60 * FRAC_64_MULTIPLIER);
+    assert_eq!(pos2.target_with_parameters, new_target_with_parameters);
+    assert_eq!(pos2.activation_epoch, 4);
+    assert_eq!(pos2.unlocking_start, None);
+
+    assert_eq!(
+        positions.read_redelegation_marker(0).unwrap(),
+        RedelegationMarker {
+            unlocking:  true,
+            activating: false,
+        }
+    );
+    assert_eq!(
+        positions.read_redelegation_marker(2).unwrap(),
+        RedelegationMarker {
+            unlocking:  false,
+            activating: true,
+        }
+    );
+
+    let pool_data: PoolData = fetch_account_data_bytemuck(&mut svm, &pool_data_pubkey);
+    assert_eq!(
+        pool_data.del_state[publisher_index],
+        DelegationState {
+            total_delegation: 60 * FRAC_64_MULTIPLIER,
+            delta_delegation: -60 * FRAC_64_MULTIPLIER as i64,
+        }
+    );
+    assert_eq!(
+        pool_data.del_state[new_publisher_index],
+        DelegationState {
+            total_delegation: 40 * FRAC_64_MULTIPLIER,
+            delta_delegation: 60 * FRAC_64_MULTIPLIER as i64,
+        }
+    );
+
+    advance_n_epochs(&mut svm, &payer, 1);
+    let publisher_caps =
+        post_dummy_publisher_caps(&mut svm, &payer, publisher_keypair.pubkey(), 100);
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+
+    let pool_data: PoolData = fetch_account_data_bytemuck(&mut svm, &pool_data_pubkey);
+    assert_eq!(
+        pool_data.del_state[publisher_index],
+        DelegationState {
+            total_delegation: 0,
+            delta_delegation: 0,
+        }
+    );
+    assert_eq!(
+        pool_data.del_state[new_publisher_index],
+        DelegationState {
+            total_delegation: STAKED_TOKENS,
+            delta_delegation: 0,
+        }
+    );
+}
+
+#[test]
+fn test_close_and_withdraw_after_redelegate() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+    let new_publisher = get_dummy_publisher(1);
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+    let destination = create_token_account(&mut svm, &payer, &pyth_token_mint.pubkey());
+
+    delegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        STAKED_TOKENS,
+    )
+    .unwrap();
+
+    advance_n_epochs(&mut svm, &payer, 1);
+    let publisher_caps =
+        post_dummy_publisher_caps(&mut svm, &payer, publisher_keypair.pubkey(), 100);
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+
+    redelegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        new_publisher,
+        pool_data_pubkey,
+        stake_account_positions,
+        0,
+        60 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+
+    // the redelegated tokens only count once, so the account can still be managed, but all of
+    // its tokens are delegated
+    assert_anchor_program_error!(
+        withdraw_stake(
+            &mut svm,
+            &payer,
+            stake_account_positions,
+            destination.pubkey(),
+            1
+        ),
+        StakingError::TooMuchExposureToIntegrityPool,
+        0
+    );
+
+    create_position(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        10 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+    close_position(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        10 * FRAC_64_MULTIPLIER,
+        3,
+    )
+    .unwrap();
+
+    undelegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        0,
+        40 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+
+    // once the old positions are unlocked, only the new position is exposed
+    advance_n_epochs(&mut svm, &payer, 2);
+
+    withdraw_stake(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        destination.pubkey(),
+        40 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+    svm.expire_blockhash();
+    assert_anchor_program_error!(
+        withdraw_stake(
+            &mut svm,
+            &payer,
+            stake_account_positions,
+            destination.pubkey(),
+            1
+        ),
+        StakingError::TooMuchExposureToIntegrityPool,
+        0
+    );
+}
+
+#[test]
+fn test_slash_after_redelegate() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority,
+        maybe_publisher_index,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+    let publisher_index = maybe_publisher_index.unwrap();
+    let new_publisher = get_dummy_publisher(1);
+
+    let pool_data: PoolData = fetch_account_data_bytemuck(&mut svm, &pool_data_pubkey);
+    let new_publisher_index = pool_data
+        .publishers
+        .iter()
+        .position(|&x| x == new_publisher)
+        .unwrap();
+
+    let target_with_parameters = TargetWithParameters::IntegrityPool {
+        publisher: publisher_keypair.pubkey(),
+    };
+    let new_target_with_parameters = TargetWithParameters::IntegrityPool {
+        publisher: new_publisher,
+    };
+
+    let slash_custody = get_default_slash_custody(
+        &reward_program_authority.pubkey(),
+        &pyth_token_mint.pubkey(),
+    );
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+
+    delegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        STAKED_TOKENS,
+    )
+    .unwrap();
+
+    advance_n_epochs(&mut svm, &payer, 1);
+    let publisher_caps =
+        post_dummy_publisher_caps(&mut svm, &payer, publisher_keypair.pubkey(), 100);
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+
+    redelegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        new_publisher,
+        pool_data_pubkey,
+        stake_account_positions,
+        0,
+        60 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+
+    advance_n_epochs(&mut svm, &payer, 1);
+    let publisher_caps =
+        post_dummy_publisher_caps(&mut svm, &payer, publisher_keypair.pubkey(), 100);
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+
+    // the old publisher is slashed 50% for the epoch in which the tokens were redelegated
+    create_slash_event(
+        &mut svm,
+        &payer,
+        &reward_program_authority,
+        0,
+        FRAC_64_MULTIPLIER / 2,
+        slash_custody,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+    )
+    .unwrap();
+    advance_delegation_record(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        stake_account_positions,
+        pyth_token_mint.pubkey(),
+        pool_data_pubkey,
+        None,
+    )
+    .unwrap();
+    slash(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        0,
+        slash_custody,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+    )
+    .unwrap();
+
+    let slash_custody_account: TokenAccount = fetch_account_data(&mut svm, &slash_custody);
+    assert_eq!(slash_custody_account.amount, 50 * FRAC_64_MULTIPLIER);
+
+    let mut stake_positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+    let positions = stake_positions_account.to_dynamic_position_array();
+
+    let pos0 = positions.read_position(0).unwrap().unwrap();
+    assert_eq!(pos0.amount, 20 * FRAC_64_MULTIPLIER);
+    assert_eq!(pos0.target_with_parameters, target_with_parameters);
+
+    let pos1 = positions.read_position(1).unwrap().unwrap();
+    assert_eq!(pos1.amount, 30 * FRAC_64_MULTIPLIER);
+    assert_eq!(pos1.unlocking_start, Some(4));
+
+    // half of the tokens that backed the new position were slashed
+    let pos2 = positions.read_position(2).unwrap().unwrap();
+    assert_eq!(pos2.amount, 30 * FRAC_64_MULTIPLIER);
+    assert_eq!(pos2.target_with_parameters, new_target_with_parameters);
+    assert_eq!(pos2.activation_epoch, 4);
+
+    let pool_data: PoolData = fetch_account_data_bytemuck(&mut svm, &pool_data_pubkey);
+    assert_eq!(
+        pool_data.del_state[publisher_index],
+        DelegationState {
+            total_delegation: 20 * FRAC_64_MULTIPLIER,
+            delta_delegation: 0,
+        }
+    );
+    assert_eq!(
+        pool_data.del_state[new_publisher_index],
+        DelegationState {
+            total_delegation: 30 * FRAC_64_MULTIPLIER,
+            delta_delegation: 0,
+        }
+    );
+}
+
+#[test]
+fn test_undelegate_and_delegate_is_not_redelegate() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+    let new_publisher = get_dummy_publisher(1);
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+
+    delegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        STAKED_TOKENS,
+    )
+    .unwrap();
+
+    advance_n_epochs(&mut svm, &payer, 1);
+    let publisher_caps =
+        post_dummy_publisher_caps(&mut svm, &payer, publisher_keypair.pubkey(), 100);
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+
+    undelegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        0,
+        60 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+
+    // the undelegated tokens stay slashable until they're unlocked, they can't back a new
+    // delegation starting at the same epoch
+    assert_anchor_program_error!(
+        delegate(
+            &mut svm,
+            &payer,
+            new_publisher,
+            pool_data_pubkey,
+            stake_account_positions,
+            60 * FRAC_64_MULTIPLIER,
+        ),
+        StakingError::TooMuchExposureToIntegrityPool,
+        0
+    );
+}
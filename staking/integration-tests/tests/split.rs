@@ -0,0 +1,698 @@
+use {
+    anchor_spl::token::TokenAccount,
+    integration_tests::{
+        assert_anchor_program_error,
+        integrity_pool::{
+            instructions::{
+                advance,
+                advance_delegation_record,
+                delegate,
+                set_publisher_stake_account,
+            },
+            pda::get_delegation_record_address,
+        },
+        publisher_caps::helper_functions::post_dummy_publisher_caps,
+        setup::{
+            setup,
+            SetupProps,
+            SetupResult,
+        },
+        solana::{
+            instructions::create_account,
+            utils::{
+                fetch_account_data,
+                fetch_account_data_bytemuck,
+                fetch_positions_account,
+            },
+        },
+        staking::{
+            helper_functions::initialize_new_stake_account,
+            instructions::{
+                accept_split,
+                cancel_split,
+                close_position,
+                create_position,
+                create_voter_record,
+                join_dao_llc,
+                request_split,
+                update_voter_weight,
+            },
+            pda::{
+                get_split_request_address,
+                get_stake_account_custody_address,
+                get_stake_account_metadata_address,
+                get_target_address,
+            },
+        },
+        utils::{
+            clock::{
+                advance_n_epochs,
+                get_current_epoch,
+            },
+            constants::STAKED_TOKENS,
+        },
+    },
+    integrity_pool::{
+        error::IntegrityPoolError,
+        state::{
+            delegation_record::DelegationRecord,
+            pool::PoolData,
+        },
+        utils::types::FRAC_64_MULTIPLIER,
+    },
+    litesvm::LiteSVM,
+    solana_sdk::{
+        native_token::LAMPORTS_PER_SOL,
+        pubkey::Pubkey,
+        signature::Keypair,
+        signer::Signer,
+    },
+    staking::{
+        error::ErrorCode,
+        state::{
+            positions::{
+                PositionData,
+                PositionState,
+                TargetWithParameters,
+                UnlockingDurations,
+            },
+            split_request::{
+                SplitRequest,
+                SPLIT_REQUEST_DURATION,
+            },
+            stake_account::StakeAccountMetadataV2,
+            target::TargetMetadata,
+        },
+    },
+    std::{
+        collections::HashMap,
+        convert::TryFrom,
+    },
+};
+
+/// Sums the amounts of the positions of a stake account by state
+fn get_amounts_by_state(
+    svm: &mut LiteSVM,
+    stake_account_positions: &Pubkey,
+) -> HashMap<PositionState, u64> {
+    let current_epoch = get_current_epoch(svm);
+    let mut positions_account = fetch_positions_account(svm, stake_account_positions);
+    let positions = positions_account.to_dynamic_position_array();
+
+    let mut amounts = HashMap::new();
+    for i in 0..positions.get_position_capacity() {
+        if let Some(position) = positions.read_position(i).unwrap() {
+            let state = position
+                .get_current_position(current_epoch, UnlockingDurations::default())
+                .unwrap();
+            *amounts.entry(state).or_insert(0) += position.amount;
+        }
+    }
+    amounts
+}
+
+#[test]
+fn test_split_with_positions() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    let recipient = Keypair::new();
+    svm.airdrop(&recipient.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+
+    create_position(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        50 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+
+    advance_n_epochs(&mut svm, &payer, 1);
+
+    create_position(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        33 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+    close_position(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        20 * FRAC_64_MULTIPLIER,
+        0,
+    )
+    .unwrap();
+
+    let pre_amounts = get_amounts_by_state(&mut svm, &stake_account_positions);
+    assert_eq!(pre_amounts.len(), 3);
+    let pre_target_account: TargetMetadata = fetch_account_data(&mut svm, &get_target_address());
+
+    let amount = 40 * FRAC_64_MULTIPLIER;
+    request_split(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        amount,
+        recipient.pubkey(),
+    )
+    .unwrap();
+
+    let new_stake_account_positions =
+        create_account(&mut svm, &payer, PositionData::LEN, staking::ID);
+    accept_split(
+        &mut svm,
+        &payer,
+        pyth_token_mint.pubkey(),
+        stake_account_positions,
+        new_stake_account_positions,
+        amount,
+        recipient.pubkey(),
+    )
+    .unwrap();
+
+    // the governance target doesn't change
+    let target_account: TargetMetadata = fetch_account_data(&mut svm, &get_target_address());
+    assert!(target_account == pre_target_account);
+
+    // the amounts in each state are split between both accounts
+    let source_amounts = get_amounts_by_state(&mut svm, &stake_account_positions);
+    let new_amounts = get_amounts_by_state(&mut svm, &new_stake_account_positions);
+    for (state, pre_amount) in pre_amounts.iter() {
+        assert_eq!(
+            source_amounts.get(state).unwrap_or(&0) + new_amounts.get(state).unwrap_or(&0),
+            *pre_amount
+        );
+    }
+
+    // the new account gets 40% of the 83 staked tokens
+    assert_eq!(
+        new_amounts.values().sum::<u64>(),
+        83 * FRAC_64_MULTIPLIER * 40 / 100
+    );
+
+    let source_custody: TokenAccount = fetch_account_data(
+        &mut svm,
+        &get_stake_account_custody_address(stake_account_positions),
+    );
+    let new_custody: TokenAccount = fetch_account_data(
+        &mut svm,
+        &get_stake_account_custody_address(new_stake_account_positions),
+    );
+    assert_eq!(source_custody.amount, 60 * FRAC_64_MULTIPLIER);
+    assert_eq!(new_custody.amount, amount);
+    assert!(source_amounts.values().sum::<u64>() <= source_custody.amount);
+    assert!(new_amounts.values().sum::<u64>() <= new_custody.amount);
+
+    let source_metadata: StakeAccountMetadataV2 = fetch_account_data(
+        &mut svm,
+        &get_stake_account_metadata_address(stake_account_positions),
+    );
+    let new_metadata: StakeAccountMetadataV2 = fetch_account_data(
+        &mut svm,
+        &get_stake_account_metadata_address(new_stake_account_positions),
+    );
+    let mut source_positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+    let mut new_positions_account = fetch_positions_account(&mut svm, &new_stake_account_positions);
+    assert_eq!(
+        source_positions_account
+            .to_dynamic_position_array()
+            .get_next_index(source_metadata.next_index)
+            .unwrap(),
+        3
+    );
+    assert_eq!(
+        new_positions_account
+            .to_dynamic_position_array()
+            .get_next_index(new_metadata.next_index)
+            .unwrap(),
+        3
+    );
+    assert_eq!(new_metadata.owner, recipient.pubkey());
+    assert_eq!(
+        new_metadata.transfer_epoch,
+        Some(get_current_epoch(&mut svm))
+    );
+
+    // the recipient can't vote with the positions during the epoch of the split
+    join_dao_llc(&mut svm, &recipient, new_stake_account_positions).unwrap();
+    create_voter_record(&mut svm, &recipient, new_stake_account_positions).unwrap();
+    assert_anchor_program_error!(
+        update_voter_weight(&mut svm, &recipient, new_stake_account_positions),
+        ErrorCode::VoteDuringTransferEpoch,
+        0
+    );
+
+    advance_n_epochs(&mut svm, &payer, 1);
+    update_voter_weight(&mut svm, &recipient, new_stake_account_positions).unwrap();
+
+    // accounts with integrity pool positions can't be split
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+    delegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        50 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+    request_split(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        amount,
+        recipient.pubkey(),
+    )
+    .unwrap();
+
+    let new_stake_account_positions =
+        create_account(&mut svm, &payer, PositionData::LEN, staking::ID);
+    assert_anchor_program_error!(
+        accept_split(
+            &mut svm,
+            &payer,
+            pyth_token_mint.pubkey(),
+            stake_account_positions,
+            new_stake_account_positions,
+            amount,
+            recipient.pubkey(),
+        ),
+        ErrorCode::SplitWithDelegation,
+        0
+    );
+}
+
+#[test]
+fn test_split_with_delegation() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+    let publisher_index = maybe_publisher_index.unwrap();
+    let publisher = publisher_keypair.pubkey();
+
+    let recipient = Keypair::new();
+    svm.airdrop(&recipient.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+    delegate(
+        &mut svm,
+        &payer,
+        publisher,
+        pool_data_pubkey,
+        stake_account_positions,
+        50 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+
+    advance_n_epochs(&mut svm, &payer, 1);
+    let publisher_caps = post_dummy_publisher_caps(&mut svm, &payer, publisher, STAKED_TOKENS);
+    advance(&mut svm, &payer, publisher_caps).unwrap();
+    advance_delegation_record(
+        &mut svm,
+        &payer,
+        publisher,
+        stake_account_positions,
+        pyth_token_mint.pubkey(),
+        pool_data_pubkey,
+        None,
+    )
+    .unwrap();
+
+    let pre_pool_data: PoolData = fetch_account_data_bytemuck(&mut svm, &pool_data_pubkey);
+    let pre_custody: TokenAccount = fetch_account_data(
+        &mut svm,
+        &get_stake_account_custody_address(stake_account_positions),
+    );
+    let source_delegation_record: DelegationRecord = fetch_account_data(
+        &mut svm,
+        &get_delegation_record_address(publisher, stake_account_positions),
+    );
+
+    let amount = 40 * FRAC_64_MULTIPLIER;
+    request_split(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        amount,
+        recipient.pubkey(),
+    )
+    .unwrap();
+    let new_stake_account_positions =
+        create_account(&mut svm, &payer, PositionData::LEN, staking::ID);
+
+    // the delegation record of the publisher is required
+    assert_anchor_program_error!(
+        integration_tests::integrity_pool::instructions::accept_split(
+            &mut svm,
+            &payer,
+            pyth_token_mint.pubkey(),
+            stake_account_positions,
+            new_stake_account_positions,
+            amount,
+            recipient.pubkey(),
+            &[],
+        ),
+        IntegrityPoolError::SplitDelegationRecordRequired,
+        0
+    );
+
+    integration_tests::integrity_pool::instructions::accept_split(
+        &mut svm,
+        &payer,
+        pyth_token_mint.pubkey(),
+        stake_account_positions,
+        new_stake_account_positions,
+        amount,
+        recipient.pubkey(),
+        &[publisher],
+    )
+    .unwrap();
+
+    // the new account gets the same share of the delegated tokens as of the balance, the balance
+    // includes the claimed rewards
+    let source_amounts = get_amounts_by_state(&mut svm, &stake_account_positions);
+    let new_amounts = get_amounts_by_state(&mut svm, &new_stake_account_positions);
+    let new_amount = u64::try_from(
+        u128::from(50 * FRAC_64_MULTIPLIER) * u128::from(amount) / u128::from(pre_custody.amount),
+    )
+    .unwrap();
+    assert_eq!(new_amounts.get(&PositionState::LOCKED), Some(&new_amount));
+    assert_eq!(
+        source_amounts.get(&PositionState::LOCKED),
+        Some(&(50 * FRAC_64_MULTIPLIER - new_amount))
+    );
+
+    // the delegation of the publisher doesn't change
+    let pool_data: PoolData = fetch_account_data_bytemuck(&mut svm, &pool_data_pubkey);
+    assert_eq!(
+        pool_data.del_state[publisher_index],
+        pre_pool_data.del_state[publisher_index]
+    );
+
+    // the delegation record of the new account starts where the source record is
+    let new_delegation_record: DelegationRecord = fetch_account_data(
+        &mut svm,
+        &get_delegation_record_address(publisher, new_stake_account_positions),
+    );
+    assert_eq!(
+        new_delegation_record.last_epoch,
+        source_delegation_record.last_epoch
+    );
+    assert_eq!(
+        new_delegation_record.reward_index,
+        source_delegation_record.reward_index
+    );
+    assert_eq!(
+        new_delegation_record.next_slash_event_index,
+        source_delegation_record.next_slash_event_index
+    );
+    assert!(!new_delegation_record.auto_compound);
+    assert!(new_delegation_record.registered);
+
+    let new_metadata: StakeAccountMetadataV2 = fetch_account_data(
+        &mut svm,
+        &get_stake_account_metadata_address(new_stake_account_positions),
+    );
+    assert_eq!(new_metadata.owner, recipient.pubkey());
+    assert_eq!(new_metadata.delegation_record_count, 1);
+
+    // the stake account of a publisher can't be split
+    set_publisher_stake_account(
+        &mut svm,
+        &payer,
+        &publisher_keypair,
+        publisher,
+        None,
+        Some(stake_account_positions),
+    )
+    .unwrap();
+    svm.expire_blockhash();
+    request_split(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        amount,
+        recipient.pubkey(),
+    )
+    .unwrap();
+    let new_stake_account_positions =
+        create_account(&mut svm, &payer, PositionData::LEN, staking::ID);
+    assert_anchor_program_error!(
+        integration_tests::integrity_pool::instructions::accept_split(
+            &mut svm,
+            &payer,
+            pyth_token_mint.pubkey(),
+            stake_account_positions,
+            new_stake_account_positions,
+            amount,
+            recipient.pubkey(),
+            &[publisher],
+        ),
+        IntegrityPoolError::SplitPublisherStakeAccount,
+        0
+    );
+}
+
+#[test]
+fn test_cancel_and_expire_split() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair: _,
+        pool_data_pubkey: _,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    let recipient = Keypair::new();
+    svm.airdrop(&recipient.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+    let split_request_address = get_split_request_address(stake_account_positions);
+    let new_stake_account_positions =
+        create_account(&mut svm, &payer, PositionData::LEN, staking::ID);
+    let amount = 40 * FRAC_64_MULTIPLIER;
+
+    request_split(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        amount,
+        recipient.pubkey(),
+    )
+    .unwrap();
+    let split_request: SplitRequest = fetch_account_data(&mut svm, &split_request_address);
+    assert_eq!(split_request.amount, amount);
+    assert_eq!(
+        split_request.expiry_epoch,
+        get_current_epoch(&mut svm) + SPLIT_REQUEST_DURATION - 1
+    );
+
+    // only the owner can cancel the request
+    assert_anchor_program_error!(
+        cancel_split(&mut svm, &recipient, stake_account_positions),
+        anchor_lang::error::ErrorCode::ConstraintHasOne,
+        0
+    );
+
+    cancel_split(&mut svm, &payer, stake_account_positions).unwrap();
+    assert!(svm.get_account(&split_request_address).is_none());
+    assert_anchor_program_error!(
+        accept_split(
+            &mut svm,
+            &payer,
+            pyth_token_mint.pubkey(),
+            stake_account_positions,
+            new_stake_account_positions,
+            amount,
+            recipient.pubkey(),
+        ),
+        anchor_lang::error::ErrorCode::ConstraintOwner,
+        0
+    );
+
+    // the request can't be accepted after it expires
+    svm.expire_blockhash();
+    request_split(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        amount,
+        recipient.pubkey(),
+    )
+    .unwrap();
+    advance_n_epochs(&mut svm, &payer, SPLIT_REQUEST_DURATION);
+    assert_anchor_program_error!(
+        accept_split(
+            &mut svm,
+            &payer,
+            pyth_token_mint.pubkey(),
+            stake_account_positions,
+            new_stake_account_positions,
+            amount,
+            recipient.pubkey(),
+        ),
+        ErrorCode::SplitRequestExpired,
+        0
+    );
+
+    // a new request resets the expiry
+    request_split(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        amount,
+        recipient.pubkey(),
+    )
+    .unwrap();
+    advance_n_epochs(&mut svm, &payer, SPLIT_REQUEST_DURATION - 1);
+    accept_split(
+        &mut svm,
+        &payer,
+        pyth_token_mint.pubkey(),
+        stake_account_positions,
+        new_stake_account_positions,
+        amount,
+        recipient.pubkey(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_legacy_split_request() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair: _,
+        pool_data_pubkey: _,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    let recipient = Keypair::new();
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+    let split_request_address = get_split_request_address(stake_account_positions);
+    let amount = 40 * FRAC_64_MULTIPLIER;
+
+    // requests created before the expiry epoch was introduced are shorter
+    request_split(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        amount,
+        recipient.pubkey(),
+    )
+    .unwrap();
+    let mut split_request_account = svm.get_account(&split_request_address).unwrap();
+    split_request_account
+        .data
+        .truncate(SplitRequest::LEGACY_LEN);
+    svm.set_account(split_request_address, split_request_account)
+        .unwrap();
+
+    // they're expired, even in the epoch they were created in
+    let new_stake_account_positions =
+        create_account(&mut svm, &payer, PositionData::LEN, staking::ID);
+    assert_anchor_program_error!(
+        accept_split(
+            &mut svm,
+            &payer,
+            pyth_token_mint.pubkey(),
+            stake_account_positions,
+            new_stake_account_positions,
+            amount,
+            recipient.pubkey(),
+        ),
+        ErrorCode::SplitRequestExpired,
+        0
+    );
+
+    // a new request on a short account is resized
+    svm.expire_blockhash();
+    request_split(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        amount,
+        recipient.pubkey(),
+    )
+    .unwrap();
+    let split_request_account = svm.get_account(&split_request_address).unwrap();
+    assert_eq!(split_request_account.data.len(), SplitRequest::LEN);
+    let split_request: SplitRequest = fetch_account_data(&mut svm, &split_request_address);
+    assert_eq!(split_request.amount, amount);
+    assert_eq!(
+        split_request.expiry_epoch,
+        get_current_epoch(&mut svm) + SPLIT_REQUEST_DURATION - 1
+    );
+
+    svm.expire_blockhash();
+    accept_split(
+        &mut svm,
+        &payer,
+        pyth_token_mint.pubkey(),
+        stake_account_positions,
+        new_stake_account_positions,
+        amount,
+        recipient.pubkey(),
+    )
+    .unwrap();
+}
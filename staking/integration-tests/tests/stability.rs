@@ -67,6 +67,7 @@ use {
             PositionState,
             Target,
             TargetWithParameters,
+            UnlockingDurations,
         },
     },
     std::{
@@ -333,7 +334,7 @@ fn sanity_check_publisher(
         for i in 0..positions.get_position_capacity() {
             if let Some(position) = positions.read_position(i).unwrap() {
                 let position_state = position
-                    .get_current_position(get_current_epoch(svm))
+                    .get_current_position(get_current_epoch(svm), UnlockingDurations::default())
                     .unwrap();
                 if matches!(position, Position {
                         target_with_parameters: TargetWithParameters::IntegrityPool { publisher: p, .. },
@@ -513,12 +514,15 @@ fn test_stability(props: StabilityTestProps) {
                     fetch_positions_account(&mut svm, &stake_account_positions[*delegator]);
                 let positions = stake_positions_account.to_dynamic_position_array();
 
-                let mut index: u8 = 0;
+                let mut index: u16 = 0;
                 let mut position_value = 0;
                 for i in 0..positions.get_position_capacity() {
                     if let Some(position) = positions.read_position(i).unwrap() {
                         let position_state = position
-                            .get_current_position(get_current_epoch(&mut svm))
+                            .get_current_position(
+                                get_current_epoch(&mut svm),
+                                UnlockingDurations::default(),
+                            )
                             .unwrap();
                         if matches!(position, Position {
                                 target_with_parameters: TargetWithParameters::IntegrityPool { publisher: p, .. },
@@ -678,12 +682,15 @@ fn test_stability(props: StabilityTestProps) {
                     fetch_positions_account(&mut svm, &stake_account_positions[*delegator]);
                 let positions = stake_positions_account.to_dynamic_position_array();
 
-                let mut index: u8 = 0;
+                let mut index: u16 = 0;
                 let mut position_value = 0;
                 for i in 0..positions.get_position_capacity() {
                     if let Some(position) = positions.read_position(i).unwrap() {
                         let position_state = position
-                            .get_current_position(get_current_epoch(&mut svm))
+                            .get_current_position(
+                                get_current_epoch(&mut svm),
+                                UnlockingDurations::default(),
+                            )
                             .unwrap();
                         if matches!(
                             position,
@@ -0,0 +1,123 @@
+use {
+    integration_tests::{
+        setup::{
+            setup,
+            SetupProps,
+            SetupResult,
+        },
+        staking::{
+            helper_functions::initialize_new_stake_account,
+            instructions::{
+                close_position,
+                create_position,
+                update_unlocking_durations,
+            },
+        },
+        utils::{
+            clock::{
+                advance_n_epochs,
+                get_current_epoch,
+            },
+            events::parse_events,
+        },
+    },
+    staking::{
+        events::{
+            ConfigUpdated,
+            PositionClosed,
+            PositionCreated,
+        },
+        state::positions::TargetWithParameters,
+    },
+};
+
+#[test]
+fn test_staking_events() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair: _,
+        pool_data_pubkey: _,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+
+    let logs = create_position(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        100,
+    )
+    .unwrap()
+    .logs;
+    let created = parse_events::<PositionCreated>(&logs);
+    assert_eq!(created.len(), 1);
+    assert_eq!(created[0].stake_account_positions, stake_account_positions);
+    assert_eq!(created[0].index, 0);
+    assert_eq!(
+        created[0].target_with_parameters,
+        TargetWithParameters::Voting
+    );
+    assert_eq!(created[0].amount, 100);
+    assert!(!created[0].merged);
+    assert_eq!(created[0].epoch, get_current_epoch(&mut svm));
+
+    // a position created in the same epoch is added to the existing one
+    let logs = create_position(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        20,
+    )
+    .unwrap()
+    .logs;
+    let created = parse_events::<PositionCreated>(&logs);
+    assert_eq!(created.len(), 1);
+    assert_eq!(created[0].index, 0);
+    assert_eq!(created[0].amount, 20);
+    assert!(created[0].merged);
+
+    advance_n_epochs(&mut svm, &payer, 1);
+
+    // a locked position is split in two when it's partially closed
+    let logs = close_position(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        50,
+        0,
+    )
+    .unwrap()
+    .logs;
+    let closed = parse_events::<PositionClosed>(&logs);
+    assert_eq!(closed.len(), 1);
+    assert_eq!(closed[0].index, 0);
+    assert_eq!(closed[0].unlocking_index, Some(1));
+    assert_eq!(closed[0].amount, 50);
+    assert_eq!(closed[0].epoch, get_current_epoch(&mut svm));
+
+    let logs = update_unlocking_durations(&mut svm, &payer, Some(2), None)
+        .unwrap()
+        .logs;
+    let updated = parse_events::<ConfigUpdated>(&logs);
+    assert_eq!(updated.len(), 1);
+    assert_eq!(updated[0].config.voting_unlocking_duration, Some(2));
+    assert_eq!(updated[0].config.integrity_pool_unlocking_duration, None);
+}
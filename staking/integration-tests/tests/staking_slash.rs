@@ -17,6 +17,7 @@ use {
         staking::{
             helper_functions::initialize_new_stake_account,
             instructions::{
+                accept_pool_authority,
                 create_position,
                 slash_staking,
                 update_pool_authority,
@@ -70,7 +71,8 @@ fn test_staking_slash() {
 
     let slash_token_account = create_token_account(&mut svm, &payer, &pyth_token_mint.pubkey());
 
-    update_pool_authority(&mut svm, &payer, pool_authority.pubkey());
+    update_pool_authority(&mut svm, &payer, pool_authority.pubkey()).unwrap();
+    accept_pool_authority(&mut svm, &payer, &pool_authority).unwrap();
 
     create_position(
         &mut svm,
@@ -107,7 +109,15 @@ fn test_staking_slash() {
     let stake_account_metadata = get_stake_account_metadata_address(stake_account_positions);
     let metadata_account: StakeAccountMetadataV2 =
         fetch_account_data(&mut svm, &stake_account_metadata);
-    assert_eq!(metadata_account.next_index, 3);
+    // both voting positions are in the same position
+    let mut stake_positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+    assert_eq!(
+        stake_positions_account
+            .to_dynamic_position_array()
+            .get_next_index(metadata_account.next_index)
+            .unwrap(),
+        2
+    );
 
     // initiate delegate at epoch N
     // position will become LOCKED at epoch N+1
@@ -163,7 +173,12 @@ fn test_staking_slash() {
     let metadata_account: StakeAccountMetadataV2 =
         fetch_account_data(&mut svm, &stake_account_metadata);
 
-    assert_eq!(metadata_account.next_index, 2);
+    assert_eq!(
+        positions
+            .get_next_index(metadata_account.next_index)
+            .unwrap(),
+        2
+    );
     assert!(positions.read_position(2).unwrap().is_none());
 
     let target_account: TargetMetadata = fetch_account_data(&mut svm, &get_target_address());
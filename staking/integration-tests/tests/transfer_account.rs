@@ -17,12 +17,17 @@ use {
                 create_position,
                 create_voter_record,
                 transfer_account,
+                update_voter_weight,
             },
             pda::{
                 get_stake_account_metadata_address,
                 get_voter_record_address,
             },
         },
+        utils::clock::{
+            advance_n_epochs,
+            get_current_epoch,
+        },
     },
     solana_sdk::{
         native_token::LAMPORTS_PER_SOL,
@@ -113,16 +118,51 @@ fn test_transfer_account() {
     )
     .unwrap();
 
-    svm.expire_blockhash();
-    // now the account can't be recovered
+    advance_n_epochs(&mut svm, &governance_authority, 1);
+    update_voter_weight(&mut svm, &new_owner, stake_account_positions).unwrap();
+
+    // accounts with positions can be transferred too
+    let final_owner = Keypair::new();
+    svm.airdrop(&final_owner.pubkey(), LAMPORTS_PER_SOL)
+        .unwrap();
+    transfer_account(
+        &mut svm,
+        &governance_authority,
+        stake_account_positions,
+        final_owner.pubkey(),
+    )
+    .unwrap();
+
+    let stake_account_metadata: StakeAccountMetadataV2 = fetch_account_data(
+        &mut svm,
+        &get_stake_account_metadata_address(stake_account_positions),
+    );
+    assert_eq!(stake_account_metadata.owner, final_owner.pubkey());
+    let mut positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+    assert_eq!(
+        positions_account
+            .to_dynamic_position_array()
+            .get_next_index(stake_account_metadata.next_index)
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        stake_account_metadata.transfer_epoch,
+        Some(get_current_epoch(&mut svm))
+    );
+
+    // the account can't vote with the snapshot of the transfer epoch
     assert_anchor_program_error!(
-        transfer_account(
-            &mut svm,
-            &governance_authority,
-            stake_account_positions,
-            new_owner.pubkey()
-        ),
-        StakingError::RecoverWithStake,
+        update_voter_weight(&mut svm, &final_owner, stake_account_positions),
+        StakingError::VoteDuringTransferEpoch,
         0
     );
+
+    advance_n_epochs(&mut svm, &governance_authority, 1);
+    update_voter_weight(&mut svm, &final_owner, stake_account_positions).unwrap();
+
+    let voter_record: VoterWeightRecord =
+        fetch_account_data(&mut svm, &get_voter_record_address(stake_account_positions));
+    assert_eq!(voter_record.governing_token_owner, final_owner.pubkey());
+    assert!(voter_record.voter_weight > 0);
 }
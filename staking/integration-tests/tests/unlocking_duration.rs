@@ -0,0 +1,217 @@
+use {
+    integration_tests::{
+        assert_anchor_program_error,
+        integrity_pool::instructions::{
+            delegate,
+            undelegate,
+        },
+        setup::{
+            setup,
+            SetupProps,
+            SetupResult,
+        },
+        solana::utils::{
+            fetch_account_data,
+            fetch_positions_account,
+        },
+        staking::{
+            helper_functions::initialize_new_stake_account,
+            instructions::{
+                close_position,
+                create_position,
+                update_unlocking_durations,
+            },
+            pda::get_config_address,
+        },
+        utils::{
+            clock::{
+                advance_n_epochs,
+                get_current_epoch,
+            },
+            constants::STAKED_TOKENS,
+        },
+    },
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+    staking::state::{
+        global_config::GlobalConfig,
+        positions::{
+            PositionState,
+            TargetWithParameters,
+            UnlockingDurations,
+        },
+    },
+};
+
+#[test]
+fn test_unlocking_duration() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    let config: GlobalConfig = fetch_account_data(&mut svm, &get_config_address());
+    assert_eq!(
+        config.get_unlocking_durations(),
+        UnlockingDurations::default()
+    );
+
+    assert_anchor_program_error!(
+        update_unlocking_durations(&mut svm, &payer, Some(0), None),
+        staking::error::ErrorCode::InvalidUnlockingDuration,
+        0
+    );
+    assert_anchor_program_error!(
+        update_unlocking_durations(&mut svm, &payer, None, Some(0)),
+        staking::error::ErrorCode::InvalidUnlockingDuration,
+        0
+    );
+    assert_anchor_program_error!(
+        update_unlocking_durations(&mut svm, &payer, Some(u64::from(u32::MAX) + 1), None),
+        staking::error::ErrorCode::InvalidUnlockingDuration,
+        0
+    );
+
+    let not_governance_authority = Keypair::new();
+    svm.airdrop(&not_governance_authority.pubkey(), 1_000_000_000)
+        .unwrap();
+    assert_anchor_program_error!(
+        update_unlocking_durations(&mut svm, &not_governance_authority, None, Some(3)),
+        anchor_lang::error::ErrorCode::ConstraintHasOne,
+        0
+    );
+
+    update_unlocking_durations(&mut svm, &payer, None, Some(3)).unwrap();
+
+    let config: GlobalConfig = fetch_account_data(&mut svm, &get_config_address());
+    let unlocking_durations = config.get_unlocking_durations();
+    assert_eq!(
+        unlocking_durations,
+        UnlockingDurations {
+            voting:         1,
+            integrity_pool: 3,
+        }
+    );
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+
+    create_position(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        STAKED_TOKENS / 2,
+    )
+    .unwrap();
+    delegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        STAKED_TOKENS / 2,
+    )
+    .unwrap();
+
+    advance_n_epochs(&mut svm, &payer, 1);
+
+    close_position(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        STAKED_TOKENS / 2,
+        0,
+    )
+    .unwrap();
+    undelegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        1,
+        STAKED_TOKENS / 2,
+    )
+    .unwrap();
+
+    let expected_states = [
+        (PositionState::PREUNLOCKING, PositionState::PREUNLOCKING),
+        (PositionState::UNLOCKING, PositionState::UNLOCKING),
+        (PositionState::UNLOCKED, PositionState::UNLOCKING),
+        (PositionState::UNLOCKED, PositionState::UNLOCKING),
+        (PositionState::UNLOCKED, PositionState::UNLOCKED),
+    ];
+
+    let mut positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+    let positions = positions_account.to_dynamic_position_array();
+    assert_eq!(positions.read_unlocking_duration(0).unwrap(), Some(1));
+    assert_eq!(positions.read_unlocking_duration(1).unwrap(), Some(3));
+
+    for (epoch, &(voting_state, integrity_pool_state)) in expected_states.iter().enumerate() {
+        // positions that are already unlocking keep their unlocking duration
+        if epoch == 2 {
+            update_unlocking_durations(&mut svm, &payer, Some(10), Some(10)).unwrap();
+        }
+
+        let current_epoch = get_current_epoch(&mut svm);
+        let mut positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+        let positions = positions_account.to_dynamic_position_array();
+        let config: GlobalConfig = fetch_account_data(&mut svm, &get_config_address());
+
+        let voting_position = positions.read_position(0).unwrap().unwrap();
+        assert_eq!(
+            voting_position.target_with_parameters,
+            TargetWithParameters::Voting
+        );
+        assert_eq!(
+            voting_position
+                .get_current_position(
+                    current_epoch,
+                    positions
+                        .get_unlocking_durations(0, config.get_unlocking_durations())
+                        .unwrap()
+                )
+                .unwrap(),
+            voting_state
+        );
+
+        let integrity_pool_position = positions.read_position(1).unwrap().unwrap();
+        assert_eq!(
+            integrity_pool_position.target_with_parameters,
+            TargetWithParameters::IntegrityPool {
+                publisher: publisher_keypair.pubkey(),
+            }
+        );
+        assert_eq!(
+            integrity_pool_position
+                .get_current_position(
+                    current_epoch,
+                    positions
+                        .get_unlocking_durations(1, config.get_unlocking_durations())
+                        .unwrap()
+                )
+                .unwrap(),
+            integrity_pool_state
+        );
+
+        advance_n_epochs(&mut svm, &payer, 1);
+    }
+}
@@ -0,0 +1,228 @@
+use {
+    anchor_lang::AnchorDeserialize,
+    integration_tests::{
+        assert_anchor_program_error,
+        integrity_pool::instructions::delegate,
+        setup::{
+            setup,
+            SetupProps,
+            SetupResult,
+        },
+        solana::utils::fetch_account_data,
+        staking::{
+            helper_functions::initialize_new_stake_account,
+            instructions::{
+                close_position,
+                create_position,
+                get_position_summary,
+                get_voter_weight_at_epoch,
+                get_withdrawable_balance,
+                update_voter_weight,
+            },
+            pda::get_voter_record_address,
+        },
+        utils::{
+            clock::{
+                advance_n_epochs,
+                get_current_epoch,
+            },
+            constants::STAKED_TOKENS,
+        },
+    },
+    integrity_pool::utils::types::FRAC_64_MULTIPLIER,
+    litesvm::LiteSVM,
+    solana_sdk::{
+        pubkey::Pubkey,
+        signature::Keypair,
+        signer::Signer,
+    },
+    staking::{
+        error::ErrorCode,
+        state::{
+            max_voter_weight_record::MAX_VOTER_WEIGHT,
+            positions::{
+                LockedBalanceSummary,
+                TargetWithParameters,
+            },
+            voter_weight_record::VoterWeightRecord,
+        },
+    },
+};
+
+fn fetch_withdrawable_balance(
+    svm: &mut LiteSVM,
+    payer: &Keypair,
+    stake_account_positions: Pubkey,
+) -> u64 {
+    svm.expire_blockhash();
+    let return_data = get_withdrawable_balance(svm, payer, stake_account_positions)
+        .unwrap()
+        .return_data;
+    assert_eq!(return_data.program_id, staking::ID);
+    u64::try_from_slice(&return_data.data).unwrap()
+}
+
+fn fetch_voter_weight_at_epoch(
+    svm: &mut LiteSVM,
+    payer: &Keypair,
+    stake_account_positions: Pubkey,
+    epoch: u64,
+) -> u64 {
+    svm.expire_blockhash();
+    let return_data = get_voter_weight_at_epoch(svm, payer, stake_account_positions, epoch)
+        .unwrap()
+        .return_data;
+    assert_eq!(return_data.program_id, staking::ID);
+    u64::try_from_slice(&return_data.data).unwrap()
+}
+
+fn fetch_position_summary(
+    svm: &mut LiteSVM,
+    payer: &Keypair,
+    stake_account_positions: Pubkey,
+) -> LockedBalanceSummary {
+    svm.expire_blockhash();
+    let return_data = get_position_summary(svm, payer, stake_account_positions)
+        .unwrap()
+        .return_data;
+    assert_eq!(return_data.program_id, staking::ID);
+    LockedBalanceSummary::try_from_slice(&return_data.data).unwrap()
+}
+
+#[test]
+fn test_view_instructions() {
+    let SetupResult {
+        mut svm,
+        payer,
+        pyth_token_mint,
+        publisher_keypair,
+        pool_data_pubkey,
+        reward_program_authority: _,
+        maybe_publisher_index: _,
+    } = setup(SetupProps {
+        init_config:            true,
+        init_target:            true,
+        init_mint:              true,
+        init_pool_data:         true,
+        init_publishers:        true,
+        reward_amount_override: None,
+    });
+
+    let stake_account_positions =
+        initialize_new_stake_account(&mut svm, &payer, &pyth_token_mint, true, true);
+
+    assert_eq!(
+        fetch_withdrawable_balance(&mut svm, &payer, stake_account_positions),
+        STAKED_TOKENS
+    );
+    assert_eq!(
+        fetch_position_summary(&mut svm, &payer, stake_account_positions),
+        LockedBalanceSummary::default()
+    );
+
+    create_position(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        30 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+    delegate(
+        &mut svm,
+        &payer,
+        publisher_keypair.pubkey(),
+        pool_data_pubkey,
+        stake_account_positions,
+        20 * FRAC_64_MULTIPLIER,
+    )
+    .unwrap();
+
+    // the voting position is the biggest exposure
+    assert_eq!(
+        fetch_withdrawable_balance(&mut svm, &payer, stake_account_positions),
+        70 * FRAC_64_MULTIPLIER
+    );
+    assert_eq!(
+        fetch_position_summary(&mut svm, &payer, stake_account_positions),
+        LockedBalanceSummary {
+            locking:      50 * FRAC_64_MULTIPLIER,
+            locked:       0,
+            unlocking:    0,
+            preunlocking: 0,
+        }
+    );
+
+    advance_n_epochs(&mut svm, &payer, 1);
+    let current_epoch = get_current_epoch(&mut svm);
+
+    close_position(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        10 * FRAC_64_MULTIPLIER,
+        0,
+    )
+    .unwrap();
+
+    assert_eq!(
+        fetch_withdrawable_balance(&mut svm, &payer, stake_account_positions),
+        70 * FRAC_64_MULTIPLIER
+    );
+    assert_eq!(
+        fetch_position_summary(&mut svm, &payer, stake_account_positions),
+        LockedBalanceSummary {
+            locking:      0,
+            locked:       40 * FRAC_64_MULTIPLIER,
+            unlocking:    0,
+            preunlocking: 10 * FRAC_64_MULTIPLIER,
+        }
+    );
+
+    // this is the only account with voting positions, so it has all the voting power
+    assert_eq!(
+        fetch_voter_weight_at_epoch(&mut svm, &payer, stake_account_positions, current_epoch),
+        MAX_VOTER_WEIGHT
+    );
+    // the positions were locking in the previous epoch
+    assert_eq!(
+        fetch_voter_weight_at_epoch(&mut svm, &payer, stake_account_positions, current_epoch - 1),
+        0
+    );
+
+    update_voter_weight(&mut svm, &payer, stake_account_positions).unwrap();
+    let voter_record: VoterWeightRecord =
+        fetch_account_data(&mut svm, &get_voter_record_address(stake_account_positions));
+    assert_eq!(voter_record.voter_weight, MAX_VOTER_WEIGHT);
+
+    assert_anchor_program_error!(
+        get_voter_weight_at_epoch(&mut svm, &payer, stake_account_positions, current_epoch - 2),
+        ErrorCode::InvalidVotingEpoch,
+        0
+    );
+    assert_anchor_program_error!(
+        get_voter_weight_at_epoch(&mut svm, &payer, stake_account_positions, current_epoch + 1),
+        ErrorCode::InvalidVotingEpoch,
+        0
+    );
+
+    // unlocking positions still count towards the exposure
+    advance_n_epochs(&mut svm, &payer, 1);
+
+    assert_eq!(
+        fetch_withdrawable_balance(&mut svm, &payer, stake_account_positions),
+        70 * FRAC_64_MULTIPLIER
+    );
+    assert_eq!(
+        fetch_position_summary(&mut svm, &payer, stake_account_positions),
+        LockedBalanceSummary {
+            locking:      0,
+            locked:       40 * FRAC_64_MULTIPLIER,
+            unlocking:    10 * FRAC_64_MULTIPLIER,
+            preunlocking: 0,
+        }
+    );
+}
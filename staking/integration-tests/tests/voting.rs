@@ -1,5 +1,6 @@
 use {
     integration_tests::{
+        assert_anchor_program_error,
         governance::{
             addresses::MAINNET_GOVERNANCE_PROGRAM_ID,
             helper_functions::create_proposal_and_vote,
@@ -20,10 +21,12 @@ use {
                 create_position,
                 join_dao_llc,
                 merge_target_positions,
+                migrate_positions,
                 update_token_list_time,
                 update_voter_weight,
             },
             pda::{
+                get_stake_account_metadata_address,
                 get_target_address,
                 get_voter_record_address,
             },
@@ -38,16 +41,22 @@ use {
             WritableAccount,
         },
         pubkey::Pubkey,
+        rent::Rent,
         signer::Signer,
     },
-    staking::state::{
-        max_voter_weight_record::MAX_VOTER_WEIGHT,
-        positions::{
-            TargetWithParameters,
-            POSITION_BUFFER_SIZE,
+    staking::{
+        error::ErrorCode,
+        state::{
+            max_voter_weight_record::MAX_VOTER_WEIGHT,
+            positions::{
+                PositionsLayout,
+                TargetWithParameters,
+                POSITION_BUFFER_SIZE,
+            },
+            stake_account::StakeAccountMetadataV2,
+            target::TargetMetadata,
+            voter_weight_record::VoterWeightRecord,
         },
-        target::TargetMetadata,
-        voter_weight_record::VoterWeightRecord,
     },
     std::{
         fs::File,
@@ -64,7 +73,8 @@ const MAINNET_ELAPSED_EPOCHS: u64 = 2850;
 /// 1) to test the voting functionality against the deployed governance program and configuration
 /// 2) to test that the new staking account is compatible with stake account positions with the old
 ///    fixed sized position array and such accounts can be turned into the new version by calling
-///    merge_target_positions and nothing breaks
+///    merge_target_positions and nothing breaks, and that they can be migrated to the V2 layout
+///    with migrate_positions
 fn test_voting() {
     let SetupResult {
         mut svm,
@@ -218,6 +228,80 @@ fn test_voting() {
         proposal_account.options[0].vote_weight,
         expected_voter_weight
     );
+
+    // Migrate the positions to the V2 layout
+    assert_eq!(positions.get_layout().unwrap(), PositionsLayout::V1);
+    migrate_positions(&mut svm, &payer, stake_account_positions).unwrap();
+
+    let mut positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+    let positions = positions_account.to_dynamic_position_array();
+    let stake_account_metadata: StakeAccountMetadataV2 = fetch_account_data(
+        &mut svm,
+        &get_stake_account_metadata_address(stake_account_positions),
+    );
+
+    assert_eq!(positions.get_layout().unwrap(), PositionsLayout::V2);
+    assert_eq!(stake_account_metadata.next_index, 0);
+    assert_eq!(
+        positions
+            .get_next_index(stake_account_metadata.next_index)
+            .unwrap(),
+        2
+    );
+    assert!(positions.get_position_capacity() == 2);
+    assert_eq!(
+        positions.acc_info.data_len(),
+        PositionsLayout::V2.data_len(2)
+    );
+    assert_eq!(
+        svm.get_balance(&stake_account_positions).unwrap(),
+        Rent::default().minimum_balance(PositionsLayout::V2.data_len(2))
+    );
+    assert_eq!(
+        positions.read_position(0).unwrap().unwrap(),
+        post_merge_pos1
+    );
+    assert_eq!(
+        positions.read_position(1).unwrap().unwrap(),
+        post_merge_pos2
+    );
+
+    svm.expire_blockhash();
+    assert_anchor_program_error!(
+        migrate_positions(&mut svm, &payer, stake_account_positions),
+        ErrorCode::PositionsAlreadyMigrated,
+        0
+    );
+
+    // Voter weight should be the same after migrating
+    update_voter_weight(&mut svm, &payer, stake_account_positions).unwrap();
+    let voter_record: VoterWeightRecord =
+        fetch_account_data(&mut svm, &get_voter_record_address(stake_account_positions));
+    assert_eq!(voter_record.voter_weight, expected_voter_weight);
+
+    // New positions use the V2 layout
+    create_position(
+        &mut svm,
+        &payer,
+        stake_account_positions,
+        TargetWithParameters::Voting,
+        None,
+        100,
+    )
+    .unwrap();
+
+    let mut positions_account = fetch_positions_account(&mut svm, &stake_account_positions);
+    let positions = positions_account.to_dynamic_position_array();
+    assert!(positions.get_position_capacity() == 3);
+    assert_eq!(
+        positions.acc_info.data_len(),
+        PositionsLayout::V2.data_len(3)
+    );
+    assert_eq!(
+        positions.read_position(0).unwrap().unwrap(),
+        post_merge_pos1
+    );
+    assert_eq!(positions.read_position(2).unwrap().unwrap().amount, 100);
 }
 
 // These accounts were snapshotted on 16th August 2024
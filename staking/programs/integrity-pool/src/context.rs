@@ -12,6 +12,7 @@ use {
         utils::constants::{
             DELEGATION_RECORD,
             POOL_CONFIG,
+            REWARD_INDEX_HISTORY,
             SLASH_EVENT,
         },
     },
@@ -80,6 +81,55 @@ pub struct UpdateRewardProgramAuthority<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct AcceptRewardProgramAuthority<'info> {
+    pub new_reward_program_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POOL_CONFIG.as_bytes()],
+        bump,
+        constraint = pool_config.pending_reward_program_authority == Some(new_reward_program_authority.key()) @ IntegrityPoolError::InvalidPendingRewardProgramAuthority,
+    )]
+    pub pool_config: Account<'info, PoolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRewardProgramAuthorityTransfer<'info> {
+    pub reward_program_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POOL_CONFIG.as_bytes()],
+        bump,
+        has_one = reward_program_authority @ IntegrityPoolError::InvalidRewardProgramAuthority,
+    )]
+    pub pool_config: Account<'info, PoolConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptPoolAuthority<'info> {
+    pub reward_program_authority: Signer<'info>,
+
+    #[account(
+        seeds = [POOL_CONFIG.as_bytes()],
+        bump,
+        has_one = reward_program_authority @ IntegrityPoolError::InvalidRewardProgramAuthority,
+    )]
+    pub pool_config: Account<'info, PoolConfig>,
+
+    /// CHECK : This AccountInfo is safe because it's a checked PDA
+    #[account(
+        mut,
+        seeds = [staking::context::CONFIG_SEED.as_bytes()],
+        bump,
+        seeds::program = staking::ID,
+    )]
+    pub config_account: AccountInfo<'info>,
+
+    pub staking_program: Program<'info, Staking>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateDelegationFee<'info> {
     pub reward_program_authority: Signer<'info>,
@@ -97,6 +147,79 @@ pub struct UpdateDelegationFee<'info> {
     pub pool_config: Account<'info, PoolConfig>,
 
     pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [staking::context::CONFIG_SEED.as_bytes()],
+        bump,
+        seeds::program = staking::ID,
+    )]
+    pub config_account: Account<'info, staking::state::global_config::GlobalConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMaxDelegationFee<'info> {
+    pub reward_program_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_data: AccountLoader<'info, PoolData>,
+
+    #[account(
+        mut,
+        seeds = [POOL_CONFIG.as_bytes()],
+        bump,
+        has_one = reward_program_authority @ IntegrityPoolError::InvalidRewardProgramAuthority,
+        has_one = pool_data @ IntegrityPoolError::InvalidPoolDataAccount,
+    )]
+    pub pool_config: Account<'info, PoolConfig>,
+
+    #[account(
+        seeds = [staking::context::CONFIG_SEED.as_bytes()],
+        bump,
+        seeds::program = staking::ID,
+    )]
+    pub config_account: Account<'info, staking::state::global_config::GlobalConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFeeNoticeEpochs<'info> {
+    pub reward_program_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POOL_CONFIG.as_bytes()],
+        bump,
+        has_one = reward_program_authority @ IntegrityPoolError::InvalidRewardProgramAuthority,
+    )]
+    pub pool_config: Account<'info, PoolConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPublisherDelegationFee<'info> {
+    pub signer: Signer<'info>,
+
+    /// CHECK : The publisher will be checked against data in the pool_data
+    pub publisher: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub pool_data: AccountLoader<'info, PoolData>,
+
+    #[account(seeds = [POOL_CONFIG.as_bytes()], bump, has_one = pool_data)]
+    pub pool_config: Account<'info, PoolConfig>,
+
+    // only required when the signer is the owner of the publisher stake account
+    pub publisher_stake_account_positions:
+        Option<AccountLoader<'info, staking::state::positions::PositionData>>,
+
+    #[account(
+        seeds = [staking::context::CONFIG_SEED.as_bytes()],
+        bump,
+        seeds::program = staking::ID,
+    )]
+    pub config_account: Account<'info, staking::state::global_config::GlobalConfig>,
 }
 
 #[derive(Accounts)]
@@ -114,13 +237,12 @@ pub struct Delegate<'info> {
     /// CHECK : The publisher will be checked against data in the pool_data
     pub publisher: AccountInfo<'info>,
 
-    /// CHECK : This AccountInfo is safe because it's a checked PDA
     #[account(
         seeds = [staking::context::CONFIG_SEED.as_bytes()],
         bump,
         seeds::program = staking_program.key(),
     )]
-    pub config_account: AccountInfo<'info>,
+    pub config_account: Account<'info, staking::state::global_config::GlobalConfig>,
 
     /// CHECK : This AccountInfo is safe because it will checked in staking program
     #[account(mut)]
@@ -146,6 +268,11 @@ pub struct Delegate<'info> {
     pub staking_program: Program<'info, Staking>,
 
     pub system_program: Program<'info, System>,
+
+    /// CHECK : This AccountInfo is safe because it's a checked PDA, it's created once the first
+    /// reward index checkpoint of the publisher moves to the history
+    #[account(mut, seeds = [REWARD_INDEX_HISTORY.as_bytes(), publisher.key().as_ref()], bump)]
+    pub reward_index_history: AccountInfo<'info>,
 }
 
 #[derive(Accounts)]
@@ -173,13 +300,12 @@ pub struct MergeDelegationPositions<'info> {
     )]
     pub delegation_record: Account<'info, DelegationRecord>,
 
-    /// CHECK : This AccountInfo is safe because it's a checked PDA
     #[account(
         seeds = [staking::context::CONFIG_SEED.as_bytes()],
         bump,
         seeds::program = staking_program.key(),
     )]
-    pub config_account: AccountInfo<'info>,
+    pub config_account: Account<'info, staking::state::global_config::GlobalConfig>,
 
     /// CHECK : This AccountInfo is safe because it will checked in staking program
     #[account(mut)]
@@ -199,7 +325,7 @@ pub struct MergeDelegationPositions<'info> {
 
 
 #[derive(Accounts)]
-#[instruction(position_index: u8, amount: u64)]
+#[instruction(position_index: u16, amount: u64)]
 pub struct Undelegate<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
@@ -213,13 +339,109 @@ pub struct Undelegate<'info> {
     /// CHECK : The publisher will be checked against data in the pool_data
     pub publisher: AccountInfo<'info>,
 
+    #[account(
+        seeds = [staking::context::CONFIG_SEED.as_bytes()],
+        bump,
+        seeds::program = staking_program.key(),
+    )]
+    pub config_account: Account<'info, staking::state::global_config::GlobalConfig>,
+
+    #[account(mut)]
+    pub stake_account_positions: AccountLoader<'info, staking::state::positions::PositionData>,
+
+    /// CHECK : This AccountInfo is safe because it's a checked PDA
+    #[account(
+        mut,
+        seeds = [staking::context::STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump,
+        seeds::program = staking_program.key(),
+    )]
+    pub stake_account_metadata: AccountInfo<'info>,
+
+    /// CHECK : This AccountInfo is safe because it's a checked PDA
+    #[account(
+        seeds = [staking::context::CUSTODY_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump,
+        seeds::program = staking_program.key(),
+    )]
+    pub stake_account_custody: AccountInfo<'info>,
+
+    pub staking_program: Program<'info, Staking>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK : This AccountInfo is safe because it's a checked PDA, it's created once the first
+    /// reward index checkpoint of the publisher moves to the history
+    #[account(mut, seeds = [REWARD_INDEX_HISTORY.as_bytes(), publisher.key().as_ref()], bump)]
+    pub reward_index_history: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelUndelegate<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_data: AccountLoader<'info, PoolData>,
+
+    #[account(seeds = [POOL_CONFIG.as_bytes()], bump, has_one = pool_data)]
+    pub pool_config: Account<'info, PoolConfig>,
+
+    /// CHECK : The publisher will be checked against data in the pool_data
+    pub publisher: AccountInfo<'info>,
+
+    #[account(
+        seeds = [staking::context::CONFIG_SEED.as_bytes()],
+        bump,
+        seeds::program = staking_program.key(),
+    )]
+    pub config_account: Account<'info, staking::state::global_config::GlobalConfig>,
+
+    #[account(mut)]
+    pub stake_account_positions: AccountLoader<'info, staking::state::positions::PositionData>,
+
     /// CHECK : This AccountInfo is safe because it's a checked PDA
+    #[account(
+        seeds = [staking::context::STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump,
+        seeds::program = staking_program.key(),
+    )]
+    pub stake_account_metadata: AccountInfo<'info>,
+
+    /// CHECK : This AccountInfo is safe because it's a checked PDA
+    #[account(
+        seeds = [staking::context::CUSTODY_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump,
+        seeds::program = staking_program.key(),
+    )]
+    pub stake_account_custody: AccountInfo<'info>,
+
+    pub staking_program: Program<'info, Staking>,
+}
+
+#[derive(Accounts)]
+#[instruction(position_index: u16, amount: u64)]
+pub struct Redelegate<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_data: AccountLoader<'info, PoolData>,
+
+    #[account(seeds = [POOL_CONFIG.as_bytes()], bump, has_one = pool_data)]
+    pub pool_config: Account<'info, PoolConfig>,
+
+    /// CHECK : The publisher will be checked against data in the pool_data
+    pub publisher: AccountInfo<'info>,
+
+    /// CHECK : The publisher will be checked against data in the pool_data
+    pub new_publisher: AccountInfo<'info>,
+
     #[account(
         seeds = [staking::context::CONFIG_SEED.as_bytes()],
         bump,
         seeds::program = staking_program.key(),
     )]
-    pub config_account: AccountInfo<'info>,
+    pub config_account: Account<'info, staking::state::global_config::GlobalConfig>,
 
     #[account(mut)]
     pub stake_account_positions: AccountLoader<'info, staking::state::positions::PositionData>,
@@ -244,6 +466,15 @@ pub struct Undelegate<'info> {
     pub staking_program: Program<'info, Staking>,
 
     pub system_program: Program<'info, System>,
+
+    /// CHECK : This AccountInfo is safe because it's a checked PDA, it's created once the first
+    /// reward index checkpoint of the publisher moves to the history
+    #[account(mut, seeds = [REWARD_INDEX_HISTORY.as_bytes(), publisher.key().as_ref()], bump)]
+    pub reward_index_history: AccountInfo<'info>,
+
+    /// CHECK : Same as reward_index_history, for the new publisher
+    #[account(mut, seeds = [REWARD_INDEX_HISTORY.as_bytes(), new_publisher.key().as_ref()], bump)]
+    pub new_reward_index_history: AccountInfo<'info>,
 }
 
 #[derive(Accounts)]
@@ -279,6 +510,13 @@ pub struct Advance<'info> {
         associated_token::authority = pool_config.key(),
     )]
     pub pool_reward_custody: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [staking::context::CONFIG_SEED.as_bytes()],
+        bump,
+        seeds::program = staking::ID,
+    )]
+    pub config_account: Account<'info, staking::state::global_config::GlobalConfig>,
 }
 
 #[derive(Accounts)]
@@ -286,6 +524,7 @@ pub struct AdvanceDelegationRecord<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
+    #[account(mut)]
     pub stake_account_positions: AccountLoader<'info, staking::state::positions::PositionData>,
 
     #[account(mut)]
@@ -340,6 +579,247 @@ pub struct AdvanceDelegationRecord<'info> {
 
     pub token_program:  Program<'info, Token>,
     pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [staking::context::CONFIG_SEED.as_bytes()],
+        bump,
+        seeds::program = staking::id(),
+    )]
+    pub config_account: Box<Account<'info, staking::state::global_config::GlobalConfig>>,
+
+    /// CHECK : This AccountInfo is safe because it's a checked PDA, it's created once the first
+    /// reward index checkpoint of the publisher moves to the history
+    #[account(mut, seeds = [REWARD_INDEX_HISTORY.as_bytes(), publisher.key().as_ref()], bump)]
+    pub reward_index_history: AccountInfo<'info>,
+
+    /// CHECK : This AccountInfo is safe because it's a checked PDA
+    #[account(
+        mut,
+        seeds = [staking::context::STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump,
+        seeds::program = staking::id(),
+    )]
+    pub stake_account_metadata: AccountInfo<'info>,
+
+    pub staking_program: Program<'info, Staking>,
+}
+
+#[derive(Accounts)]
+pub struct AdvanceDelegationRecords<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub stake_account_positions: AccountLoader<'info, staking::state::positions::PositionData>,
+
+    #[account(mut)]
+    pub pool_data: AccountLoader<'info, PoolData>,
+
+    #[account(seeds = [POOL_CONFIG.as_bytes()], bump, has_one = pool_data)]
+    pub pool_config: Account<'info, PoolConfig>,
+
+    #[account(
+        mut,
+        associated_token::mint = pool_config.pyth_token_mint,
+        associated_token::authority = pool_config.key(),
+    )]
+    pub pool_reward_custody: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [staking::context::CUSTODY_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump,
+        seeds::program = staking::id(),
+    )]
+    pub stake_account_custody: Account<'info, TokenAccount>,
+
+    pub token_program:  Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [staking::context::CONFIG_SEED.as_bytes()],
+        bump,
+        seeds::program = staking::id(),
+    )]
+    pub config_account: Box<Account<'info, staking::state::global_config::GlobalConfig>>,
+
+    /// CHECK : This AccountInfo is safe because it's a checked PDA
+    #[account(
+        mut,
+        seeds = [staking::context::STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump,
+        seeds::program = staking::id(),
+    )]
+    pub stake_account_metadata: AccountInfo<'info>,
+
+    pub staking_program: Program<'info, Staking>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewardsToDestination<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = advance_delegation_record.pool_config.pyth_token_mint,
+    )]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub advance_delegation_record: AdvanceDelegationRecord<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAutoCompound<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK : The publisher is only used in the seeds of the delegation record
+    pub publisher: AccountInfo<'info>,
+
+    pub stake_account_positions: AccountLoader<'info, staking::state::positions::PositionData>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = DelegationRecord::LEN,
+        seeds = [
+            DELEGATION_RECORD.as_bytes(),
+            publisher.key().as_ref(),
+            stake_account_positions.key().as_ref()
+        ],
+        bump,
+    )]
+    pub delegation_record: Account<'info, DelegationRecord>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds = [POOL_CONFIG.as_bytes()], bump)]
+    pub pool_config: Account<'info, PoolConfig>,
+
+    /// CHECK : This AccountInfo is safe because it's a checked PDA
+    #[account(
+        mut,
+        seeds = [staking::context::STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump,
+        seeds::program = staking::id(),
+    )]
+    pub stake_account_metadata: AccountInfo<'info>,
+
+    #[account(
+        seeds = [staking::context::CONFIG_SEED.as_bytes()],
+        bump,
+        seeds::program = staking::id(),
+    )]
+    pub config_account: Account<'info, staking::state::global_config::GlobalConfig>,
+
+    pub staking_program: Program<'info, Staking>,
+}
+
+#[derive(Accounts)]
+pub struct CloseDelegationRecord<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK : The publisher is only used in the seeds of the delegation record
+    pub publisher: AccountInfo<'info>,
+
+    pub stake_account_positions: AccountLoader<'info, staking::state::positions::PositionData>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [
+            DELEGATION_RECORD.as_bytes(),
+            publisher.key().as_ref(),
+            stake_account_positions.key().as_ref()
+        ],
+        bump,
+    )]
+    pub delegation_record: Account<'info, DelegationRecord>,
+
+    #[account(
+        seeds = [staking::context::CONFIG_SEED.as_bytes()],
+        bump,
+        seeds::program = staking::ID,
+    )]
+    pub config_account: Account<'info, staking::state::global_config::GlobalConfig>,
+
+    #[account(seeds = [POOL_CONFIG.as_bytes()], bump)]
+    pub pool_config: Account<'info, PoolConfig>,
+
+    /// CHECK : This AccountInfo is safe because it's a checked PDA
+    #[account(
+        mut,
+        seeds = [staking::context::STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump,
+        seeds::program = staking::ID,
+    )]
+    pub stake_account_metadata: AccountInfo<'info>,
+
+    pub staking_program: Program<'info, Staking>,
+    pub system_program:  Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptSplit<'info> {
+    // The accounts below are passed to the staking program, which checks them
+    #[account(mut)]
+    pub pda_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub source_stake_account_positions:
+        AccountLoader<'info, staking::state::positions::PositionData>,
+
+    /// CHECK : This AccountInfo is safe because it will checked in staking program
+    #[account(mut)]
+    pub source_stake_account_metadata: AccountInfo<'info>,
+
+    /// CHECK : This AccountInfo is safe because it will checked in staking program
+    #[account(mut)]
+    pub source_stake_account_split_request: AccountInfo<'info>,
+
+    /// CHECK : This AccountInfo is safe because it will checked in staking program
+    #[account(mut)]
+    pub source_stake_account_custody: AccountInfo<'info>,
+
+    /// CHECK : This AccountInfo is safe because it will checked in staking program
+    pub source_custody_authority: AccountInfo<'info>,
+
+    /// CHECK : This AccountInfo is safe because it will checked in staking program
+    #[account(mut)]
+    pub new_stake_account_positions: AccountInfo<'info>,
+
+    /// CHECK : This AccountInfo is safe because it will checked in staking program
+    #[account(mut)]
+    pub new_stake_account_metadata: AccountInfo<'info>,
+
+    /// CHECK : This AccountInfo is safe because it will checked in staking program
+    #[account(mut)]
+    pub new_stake_account_custody: AccountInfo<'info>,
+
+    /// CHECK : This AccountInfo is safe because it will checked in staking program
+    pub new_custody_authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [staking::context::CONFIG_SEED.as_bytes()],
+        bump,
+        seeds::program = staking::ID,
+    )]
+    pub config_account: Box<Account<'info, staking::state::global_config::GlobalConfig>>,
+
+    /// CHECK : This AccountInfo is safe because it will checked in staking program
+    pub pyth_token_mint: AccountInfo<'info>,
+
+    pub rent:           Sysvar<'info, Rent>,
+    pub token_program:  Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    pub pool_data: AccountLoader<'info, PoolData>,
+
+    #[account(seeds = [POOL_CONFIG.as_bytes()], bump, has_one = pool_data)]
+    pub pool_config: Box<Account<'info, PoolConfig>>,
+
+    pub staking_program: Program<'info, Staking>,
 }
 
 #[derive(Accounts)]
@@ -380,6 +860,13 @@ pub struct CreateSlashEvent<'info> {
     /// CHECK : The publisher will be checked against data in the pool_data
     pub publisher: AccountInfo<'info>,
 
+    #[account(
+        seeds = [staking::context::CONFIG_SEED.as_bytes()],
+        bump,
+        seeds::program = staking::ID,
+    )]
+    pub config_account: Account<'info, staking::state::global_config::GlobalConfig>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -436,13 +923,12 @@ pub struct Slash<'info> {
     )]
     pub stake_account_custody: Account<'info, TokenAccount>,
 
-    /// CHECK : This AccountInfo is safe because it's a checked PDA
     #[account(
         seeds = [staking::context::CONFIG_SEED.as_bytes()],
         bump,
         seeds::program = staking::id(),
     )]
-    pub config_account: AccountInfo<'info>,
+    pub config_account: Box<Account<'info, staking::state::global_config::GlobalConfig>>,
 
     #[account(
         mut,
@@ -33,4 +33,23 @@ pub enum IntegrityPoolError {
     #[msg("Y should not be greater than 1%")]
     InvalidY,
     InvalidSlashCustodyAccount,
+    #[msg("Delegation fee must not be greater than the maximum delegation fee of the pool")]
+    DelegationFeeTooHigh,
+    #[msg("Publisher or the owner of the publisher stake account needs to sign")]
+    PublisherOrPublisherStakeAccountOwnerNeedsToSign,
+    #[msg("Remaining accounts must be publisher, delegation record, custody, history quadruples")]
+    InvalidRemainingAccounts,
+    InvalidDelegationRecord,
+    #[msg("The stake account still has positions delegated to this publisher")]
+    DelegationRecordInUse,
+    #[msg("The signer is not the nominated reward program authority")]
+    InvalidPendingRewardProgramAuthority,
+    InvalidRewardIndexHistory,
+    #[msg("The stake account of a publisher can't be split")]
+    SplitPublisherStakeAccount,
+    #[msg("The delegation records of every publisher of the new stake account are required")]
+    SplitDelegationRecordRequired,
+    #[msg("The fee notice period must not be longer than 52 epochs")]
+    InvalidFeeNoticeEpochs,
+    GenericOverflow,
 }
@@ -0,0 +1,107 @@
+use {
+    crate::utils::types::frac64,
+    anchor_lang::prelude::*,
+};
+
+#[event]
+pub struct Delegated {
+    pub stake_account_positions: Pubkey,
+    pub publisher:               Pubkey,
+    pub amount:                  u64,
+    pub epoch:                   u64,
+}
+
+#[event]
+pub struct Undelegated {
+    pub stake_account_positions: Pubkey,
+    pub publisher:               Pubkey,
+    pub position_index:          u16,
+    pub amount:                  u64,
+    pub epoch:                   u64,
+}
+
+#[event]
+pub struct UndelegationCancelled {
+    pub stake_account_positions: Pubkey,
+    pub publisher:               Pubkey,
+    pub position_index:          u16,
+    pub amount:                  u64,
+    pub epoch:                   u64,
+}
+
+#[event]
+pub struct Redelegated {
+    pub stake_account_positions: Pubkey,
+    pub publisher:               Pubkey,
+    pub new_publisher:           Pubkey,
+    pub position_index:          u16,
+    pub amount:                  u64,
+    pub epoch:                   u64,
+}
+
+#[event]
+pub struct DelegationPositionsMerged {
+    pub stake_account_positions: Pubkey,
+    pub publisher:               Pubkey,
+    pub epoch:                   u64,
+}
+
+/// Emitted for each epoch the rewards are created for when the pool advances to `current_epoch`,
+/// they all use the same `y`.
+#[event]
+pub struct PoolAdvanced {
+    pub epoch:                     u64,
+    pub current_epoch:             u64,
+    pub y:                         frac64,
+    pub total_eligible_delegation: u64,
+}
+
+#[event]
+pub struct DelegationRecordAdvanced {
+    pub stake_account_positions: Pubkey,
+    pub publisher:               Pubkey,
+    pub delegator_reward:        frac64,
+    pub publisher_reward:        frac64,
+    pub epoch:                   u64,
+}
+
+#[event]
+pub struct SlashEventCreated {
+    pub publisher:     Pubkey,
+    pub index:         u64,
+    pub slash_ratio:   frac64,
+    pub slash_custody: Pubkey,
+    pub epoch:         u64,
+}
+
+#[event]
+pub struct DelegationSlashed {
+    pub stake_account_positions: Pubkey,
+    pub publisher:               Pubkey,
+    pub index:                   u64,
+    pub locked_slashed:          u64,
+    pub preunlocking_slashed:    u64,
+    pub epoch:                   u64,
+}
+
+#[event]
+pub struct PublisherStakeAccountSet {
+    pub publisher:               Pubkey,
+    pub stake_account_positions: Option<Pubkey>,
+}
+
+#[event]
+pub struct RewardProgramAuthorityNominated {
+    pub reward_program_authority:         Pubkey,
+    pub pending_reward_program_authority: Pubkey,
+}
+
+#[event]
+pub struct RewardProgramAuthorityAccepted {
+    pub reward_program_authority: Pubkey,
+}
+
+#[event]
+pub struct RewardProgramAuthorityTransferCancelled {
+    pub reward_program_authority: Pubkey,
+}
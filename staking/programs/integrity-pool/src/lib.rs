@@ -1,16 +1,37 @@
 use {
-    anchor_lang::prelude::*,
+    anchor_lang::{
+        prelude::*,
+        Discriminator,
+    },
     context::*,
     error::IntegrityPoolError,
-    staking::state::positions::{
-        DynamicPositionArray,
-        TargetWithParameters,
+    events::*,
+    staking::{
+        state::positions::{
+            DynamicPositionArray,
+            PositionState,
+            SlashedAmounts,
+            TargetWithParameters,
+        },
+        utils::account::create_or_grow_account,
+    },
+    state::{
+        delegation_record::DelegationRecord,
+        reward_index::{
+            RewardIndexCheckpoint,
+            RewardIndexHistory,
+        },
     },
+    std::cell::Ref,
     utils::{
         clock::get_current_epoch,
         constants::{
+            DEFAULT_FEE_NOTICE_EPOCHS,
+            DELEGATION_RECORD,
+            MAX_FEE_NOTICE_EPOCHS,
             MAX_PUBLISHERS,
             POOL_CONFIG,
+            REWARD_INDEX_HISTORY,
         },
         types::{
             frac64,
@@ -21,6 +42,7 @@ use {
 
 mod context;
 pub mod error;
+pub mod events;
 pub mod state;
 pub mod utils;
 
@@ -34,20 +56,28 @@ pub mod integrity_pool {
         ctx: Context<InitializePool>,
         reward_program_authority: Pubkey,
         y: frac64,
+        max_delegation_fee: frac64,
     ) -> Result<()> {
         let global_config = &ctx.accounts.config_account;
         let pool_config = &mut ctx.accounts.pool_config;
 
         require_gte!(FRAC_64_MULTIPLIER / 100, y, IntegrityPoolError::InvalidY);
+        require_gte!(
+            FRAC_64_MULTIPLIER,
+            max_delegation_fee,
+            IntegrityPoolError::InvalidDelegationFee
+        );
 
         pool_config.pool_data = ctx.accounts.pool_data.key();
         pool_config.reward_program_authority = reward_program_authority;
         pool_config.pyth_token_mint = global_config.pyth_token_mint;
         pool_config.y = y;
         pool_config.slash_custody = ctx.accounts.slash_custody.key();
+        pool_config.max_delegation_fee = Some(max_delegation_fee);
+        pool_config.fee_notice_epochs = DEFAULT_FEE_NOTICE_EPOCHS;
 
         let mut pool_data = ctx.accounts.pool_data.load_init()?;
-        pool_data.last_updated_epoch = get_current_epoch()? - 1;
+        pool_data.last_updated_epoch = get_current_epoch(&ctx.accounts.config_account)? - 1;
 
         Ok(())
     }
@@ -59,30 +89,199 @@ pub mod integrity_pool {
         Ok(())
     }
 
+    /// Nominates `reward_program_authority` as the reward program authority, it becomes the
+    /// authority once it calls `accept_reward_program_authority`
     pub fn update_reward_program_authority(
         ctx: Context<UpdateRewardProgramAuthority>,
         reward_program_authority: Pubkey,
     ) -> Result<()> {
-        ctx.accounts.pool_config.reward_program_authority = reward_program_authority;
+        let pool_config = &mut ctx.accounts.pool_config;
+        pool_config.pending_reward_program_authority = Some(reward_program_authority);
+
+        emit!(RewardProgramAuthorityNominated {
+            reward_program_authority:         pool_config.reward_program_authority,
+            pending_reward_program_authority: reward_program_authority,
+        });
+
+        Ok(())
+    }
+
+    pub fn accept_reward_program_authority(
+        ctx: Context<AcceptRewardProgramAuthority>,
+    ) -> Result<()> {
+        let pool_config = &mut ctx.accounts.pool_config;
+        pool_config.reward_program_authority = ctx.accounts.new_reward_program_authority.key();
+        pool_config.pending_reward_program_authority = None;
+
+        emit!(RewardProgramAuthorityAccepted {
+            reward_program_authority: pool_config.reward_program_authority,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel_reward_program_authority_transfer(
+        ctx: Context<CancelRewardProgramAuthorityTransfer>,
+    ) -> Result<()> {
+        let pool_config = &mut ctx.accounts.pool_config;
+        pool_config.pending_reward_program_authority = None;
+
+        emit!(RewardProgramAuthorityTransferCancelled {
+            reward_program_authority: pool_config.reward_program_authority,
+        });
+
         Ok(())
     }
 
+    /// The pool config can't sign a transaction, so once the governance authority nominated it as
+    /// the pool authority of the staking program, it accepts the nomination through this CPI
+    pub fn accept_pool_authority(ctx: Context<AcceptPoolAuthority>) -> Result<()> {
+        let cpi_accounts = staking::cpi::accounts::AcceptPoolAuthority {
+            new_pool_authority: ctx.accounts.pool_config.to_account_info(),
+            config:             ctx.accounts.config_account.to_account_info(),
+        };
+
+        let signer_seeds: &[&[&[u8]]] = &[&[POOL_CONFIG.as_bytes(), &[ctx.bumps.pool_config]]];
+        let cpi_ctx = CpiContext::new(ctx.accounts.staking_program.to_account_info(), cpi_accounts)
+            .with_signer(signer_seeds);
+        staking::cpi::accept_pool_authority(cpi_ctx)
+    }
+
+    /// Sets the delegation fee of all the publishers, it can't exceed `max_delegation_fee`. Like
+    /// the fees set by the publishers, the fee of a publisher that has delegators only changes
+    /// once it's been announced for long enough.
     pub fn update_delegation_fee(
         ctx: Context<UpdateDelegationFee>,
         delegation_fee: frac64,
     ) -> Result<()> {
         let pool_data = &mut ctx.accounts.pool_data.load_mut()?;
+        let pool_config = &ctx.accounts.pool_config;
 
         require_gte!(
             FRAC_64_MULTIPLIER,
             delegation_fee,
             IntegrityPoolError::InvalidDelegationFee
         );
+        require_gte!(
+            pool_config.max_delegation_fee.unwrap_or(FRAC_64_MULTIPLIER),
+            delegation_fee,
+            IntegrityPoolError::DelegationFeeTooHigh
+        );
+
+        let current_epoch = get_current_epoch(&ctx.accounts.config_account)?;
+        pool_data.assert_up_to_date(current_epoch)?;
 
         for publisher_index in 0..MAX_PUBLISHERS {
-            pool_data.delegation_fees[publisher_index] = delegation_fee;
+            if pool_data.publishers[publisher_index] == Pubkey::default() {
+                // no delegators to notify yet
+                pool_data.delegation_fees[publisher_index] = delegation_fee;
+            } else {
+                pool_data.schedule_delegation_fee(
+                    publisher_index,
+                    delegation_fee,
+                    current_epoch,
+                    pool_config.fee_notice_epochs,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn update_fee_notice_epochs(
+        ctx: Context<UpdateFeeNoticeEpochs>,
+        fee_notice_epochs: u64,
+    ) -> Result<()> {
+        require_gte!(
+            MAX_FEE_NOTICE_EPOCHS,
+            fee_notice_epochs,
+            IntegrityPoolError::InvalidFeeNoticeEpochs
+        );
+
+        ctx.accounts.pool_config.fee_notice_epochs = fee_notice_epochs;
+        Ok(())
+    }
+
+    /// Sets the maximum delegation fee of the pool. Fees above the new maximum are lowered to it
+    /// at the next epoch.
+    pub fn update_max_delegation_fee(
+        ctx: Context<UpdateMaxDelegationFee>,
+        max_delegation_fee: frac64,
+    ) -> Result<()> {
+        let pool_data = &mut ctx.accounts.pool_data.load_mut()?;
+        let pool_config = &mut ctx.accounts.pool_config;
+
+        require_gte!(
+            FRAC_64_MULTIPLIER,
+            max_delegation_fee,
+            IntegrityPoolError::InvalidDelegationFee
+        );
+
+        let current_epoch = get_current_epoch(&ctx.accounts.config_account)?;
+        pool_data.assert_up_to_date(current_epoch)?;
+        pool_data.cap_delegation_fees(
+            max_delegation_fee,
+            current_epoch,
+            pool_config.fee_notice_epochs,
+        )?;
+
+        pool_config.max_delegation_fee = Some(max_delegation_fee);
+        Ok(())
+    }
+
+    /// Schedules a new delegation fee for a publisher. Fee decreases take effect at the next
+    /// epoch, fee increases after the notice period of the pool.
+    /// The signer needs to be either the publisher or the owner of the publisher stake account.
+    pub fn set_publisher_delegation_fee(
+        ctx: Context<SetPublisherDelegationFee>,
+        delegation_fee: frac64,
+    ) -> Result<()> {
+        let signer = &ctx.accounts.signer;
+        let publisher = &ctx.accounts.publisher;
+        let pool_data = &mut ctx.accounts.pool_data.load_mut()?;
+        let pool_config = &ctx.accounts.pool_config;
+
+        let current_epoch = get_current_epoch(&ctx.accounts.config_account)?;
+        // a pending fee might still need to be recorded in the reward events
+        pool_data.assert_up_to_date(current_epoch)?;
+
+        let publisher_index = pool_data.get_publisher_index(publisher.key)?;
+
+        if signer.key() != publisher.key() {
+            let publisher_stake_account_positions = ctx
+                .accounts
+                .publisher_stake_account_positions
+                .as_ref()
+                .ok_or(IntegrityPoolError::PublisherOrPublisherStakeAccountOwnerNeedsToSign)?;
+            require_eq!(
+                publisher_stake_account_positions.key(),
+                pool_data.publisher_stake_accounts[publisher_index],
+                IntegrityPoolError::PublisherStakeAccountMismatch
+            );
+            require_eq!(
+                signer.key(),
+                DynamicPositionArray::load(publisher_stake_account_positions)?.owner()?,
+                IntegrityPoolError::PublisherOrPublisherStakeAccountOwnerNeedsToSign
+            );
         }
 
+        // pools created before the maximum existed only let publishers lower their fee until the
+        // reward program authority sets it
+        require_gte!(
+            pool_config
+                .max_delegation_fee
+                .unwrap_or(pool_data.delegation_fees[publisher_index]),
+            delegation_fee,
+            IntegrityPoolError::DelegationFeeTooHigh
+        );
+
+        pool_data.schedule_delegation_fee(
+            publisher_index,
+            delegation_fee,
+            current_epoch,
+            pool_config.fee_notice_epochs,
+        )?;
+
         Ok(())
     }
 
@@ -92,7 +291,7 @@ pub mod integrity_pool {
         let publisher = &ctx.accounts.publisher;
         let pool_data = &mut ctx.accounts.pool_data.load_mut()?;
 
-        let config_account = ctx.accounts.config_account.clone();
+        let config_account = ctx.accounts.config_account.to_account_info();
         let staking_program = &ctx.accounts.staking_program;
         let stake_account_positions = ctx.accounts.stake_account_positions.clone();
         let stake_account_metadata = ctx.accounts.stake_account_metadata.clone();
@@ -122,12 +321,27 @@ pub mod integrity_pool {
         staking::cpi::create_position(cpi_ctx, target_with_parameters, amount)?;
 
         // update publisher accounting
-        pool_data.add_delegation(
+        let checkpoint = pool_data.add_delegation(
             publisher.key,
             &ctx.accounts.stake_account_positions.key(),
             amount,
-            get_current_epoch()?,
+            get_current_epoch(&ctx.accounts.config_account)?,
         )?;
+        save_reward_index_checkpoint(
+            &ctx.accounts.reward_index_history,
+            ctx.bumps.reward_index_history,
+            publisher.key,
+            checkpoint,
+            &owner.to_account_info(),
+            &ctx.accounts.system_program,
+        )?;
+
+        emit!(Delegated {
+            stake_account_positions: ctx.accounts.stake_account_positions.key(),
+            publisher: publisher.key(),
+            amount,
+            epoch: get_current_epoch(&ctx.accounts.config_account)?,
+        });
 
         Ok(())
     }
@@ -140,12 +354,12 @@ pub mod integrity_pool {
         let owner = ctx.accounts.owner.to_account_info();
         let pool_config = ctx.accounts.pool_config.to_account_info();
         let staking_program = ctx.accounts.staking_program.to_account_info();
-        let config_account = ctx.accounts.config_account.clone();
+        let config_account = ctx.accounts.config_account.to_account_info();
         let stake_account_metadata = ctx.accounts.stake_account_metadata.clone();
         let stake_account_positions = ctx.accounts.stake_account_positions.clone();
 
         // assert delegator record is up to date
-        delegation_record.assert_up_to_date(get_current_epoch()?)?;
+        delegation_record.assert_up_to_date(get_current_epoch(&ctx.accounts.config_account)?)?;
 
         // check that publisher exists
         pool_data.get_publisher_index(&publisher)?;
@@ -166,16 +380,22 @@ pub mod integrity_pool {
         let cpi_ctx = CpiContext::new(staking_program, cpi_accounts).with_signer(signer_seeds);
         staking::cpi::merge_target_positions(cpi_ctx, target_with_parameters)?;
 
+        emit!(DelegationPositionsMerged {
+            stake_account_positions: ctx.accounts.stake_account_positions.key(),
+            publisher,
+            epoch: get_current_epoch(&ctx.accounts.config_account)?,
+        });
+
         Ok(())
     }
 
-    pub fn undelegate(ctx: Context<Undelegate>, position_index: u8, amount: u64) -> Result<()> {
+    pub fn undelegate(ctx: Context<Undelegate>, position_index: u16, amount: u64) -> Result<()> {
         let owner = ctx.accounts.owner.clone();
         let pool_config = &ctx.accounts.pool_config;
         let publisher = &ctx.accounts.publisher;
         let pool_data = &mut ctx.accounts.pool_data.load_mut()?;
 
-        let config_account = ctx.accounts.config_account.clone();
+        let config_account = ctx.accounts.config_account.to_account_info();
         let staking_program = &ctx.accounts.staking_program;
         let stake_account_metadata = ctx.accounts.stake_account_metadata.clone();
         let stake_account_custody = ctx.accounts.stake_account_custody.clone();
@@ -188,13 +408,27 @@ pub mod integrity_pool {
             .read_position(position_index.into())?
             .ok_or(IntegrityPoolError::ThisCodeShouldBeUnreachable)?;
 
-        let position_state = position.get_current_position(get_current_epoch()?)?;
-        pool_data.remove_delegation(
+        let position_state = position.get_current_position(
+            get_current_epoch(&ctx.accounts.config_account)?,
+            stake_account_positions.get_unlocking_durations(
+                position_index.into(),
+                ctx.accounts.config_account.get_unlocking_durations(),
+            )?,
+        )?;
+        let checkpoint = pool_data.remove_delegation(
             publisher.key,
             &ctx.accounts.stake_account_positions.key(),
             amount,
             position_state,
-            get_current_epoch()?,
+            get_current_epoch(&ctx.accounts.config_account)?,
+        )?;
+        save_reward_index_checkpoint(
+            &ctx.accounts.reward_index_history,
+            ctx.bumps.reward_index_history,
+            publisher.key,
+            checkpoint,
+            &owner.to_account_info(),
+            &ctx.accounts.system_program,
         )?;
 
         //cpi
@@ -220,6 +454,181 @@ pub mod integrity_pool {
 
         staking::cpi::close_position(cpi_ctx, position_index, amount, target_with_parameters)?;
 
+        emit!(Undelegated {
+            stake_account_positions: ctx.accounts.stake_account_positions.key(),
+            publisher: publisher.key(),
+            position_index,
+            amount,
+            epoch: get_current_epoch(&ctx.accounts.config_account)?,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel_undelegate(ctx: Context<CancelUndelegate>, position_index: u16) -> Result<()> {
+        let owner = ctx.accounts.owner.clone();
+        let pool_config = &ctx.accounts.pool_config;
+        let publisher = &ctx.accounts.publisher;
+        let pool_data = &mut ctx.accounts.pool_data.load_mut()?;
+
+        let config_account = ctx.accounts.config_account.to_account_info();
+        let staking_program = &ctx.accounts.staking_program;
+        let stake_account_metadata = ctx.accounts.stake_account_metadata.clone();
+        let stake_account_custody = ctx.accounts.stake_account_custody.clone();
+        let stake_account_positions =
+            &DynamicPositionArray::load(&ctx.accounts.stake_account_positions)?;
+
+        let position = stake_account_positions
+            .read_position(position_index.into())?
+            .ok_or(IntegrityPoolError::ThisCodeShouldBeUnreachable)?;
+
+        require!(
+            position.target_with_parameters
+                == TargetWithParameters::IntegrityPool {
+                    publisher: publisher.key(),
+                },
+            IntegrityPoolError::InvalidPublisher
+        );
+
+        // update publisher accounting, the staking program checks that the position was
+        // undelegated during the current epoch, so the publisher is already checkpointed
+        let checkpoint = pool_data.add_delegation(
+            publisher.key,
+            &ctx.accounts.stake_account_positions.key(),
+            position.amount,
+            get_current_epoch(&ctx.accounts.config_account)?,
+        )?;
+        require!(
+            checkpoint.is_none(),
+            IntegrityPoolError::ThisCodeShouldBeUnreachable
+        );
+
+        //cpi
+        let cpi_accounts = staking::cpi::accounts::CancelUnlock {
+            owner:                   owner.to_account_info(),
+            config:                  config_account.clone(),
+            stake_account_positions: ctx.accounts.stake_account_positions.to_account_info(),
+            stake_account_metadata:  stake_account_metadata.clone(),
+            stake_account_custody:   stake_account_custody.clone(),
+            target_account:          None,
+            pool_authority:          Some(pool_config.to_account_info()),
+        };
+
+        let signer_seeds: &[&[&[u8]]] = &[&[POOL_CONFIG.as_bytes(), &[ctx.bumps.pool_config]]];
+        let cpi_ctx = CpiContext::new(staking_program.to_account_info(), cpi_accounts)
+            .with_signer(signer_seeds);
+
+        staking::cpi::cancel_unlock(cpi_ctx, position_index)?;
+
+        emit!(UndelegationCancelled {
+            stake_account_positions: ctx.accounts.stake_account_positions.key(),
+            publisher: publisher.key(),
+            position_index,
+            amount: position.amount,
+            epoch: get_current_epoch(&ctx.accounts.config_account)?,
+        });
+
+        Ok(())
+    }
+
+    pub fn redelegate(ctx: Context<Redelegate>, position_index: u16, amount: u64) -> Result<()> {
+        let owner = ctx.accounts.owner.clone();
+        let pool_config = &ctx.accounts.pool_config;
+        let publisher = &ctx.accounts.publisher;
+        let new_publisher = &ctx.accounts.new_publisher;
+        let pool_data = &mut ctx.accounts.pool_data.load_mut()?;
+
+        let config_account = ctx.accounts.config_account.to_account_info();
+        let staking_program = &ctx.accounts.staking_program;
+        let stake_account_metadata = ctx.accounts.stake_account_metadata.clone();
+        let stake_account_custody = ctx.accounts.stake_account_custody.clone();
+        let stake_account_positions =
+            &DynamicPositionArray::load(&ctx.accounts.stake_account_positions)?;
+        let system_program = ctx.accounts.system_program.to_account_info();
+
+        // update publisher accounting
+        let position = stake_account_positions
+            .read_position(position_index.into())?
+            .ok_or(IntegrityPoolError::ThisCodeShouldBeUnreachable)?;
+
+        let position_state = position.get_current_position(
+            get_current_epoch(&ctx.accounts.config_account)?,
+            stake_account_positions.get_unlocking_durations(
+                position_index.into(),
+                ctx.accounts.config_account.get_unlocking_durations(),
+            )?,
+        )?;
+        let checkpoint = pool_data.remove_delegation(
+            publisher.key,
+            &ctx.accounts.stake_account_positions.key(),
+            amount,
+            position_state,
+            get_current_epoch(&ctx.accounts.config_account)?,
+        )?;
+        save_reward_index_checkpoint(
+            &ctx.accounts.reward_index_history,
+            ctx.bumps.reward_index_history,
+            publisher.key,
+            checkpoint,
+            &owner.to_account_info(),
+            &ctx.accounts.system_program,
+        )?;
+        let new_checkpoint = pool_data.add_delegation(
+            new_publisher.key,
+            &ctx.accounts.stake_account_positions.key(),
+            amount,
+            get_current_epoch(&ctx.accounts.config_account)?,
+        )?;
+        save_reward_index_checkpoint(
+            &ctx.accounts.new_reward_index_history,
+            ctx.bumps.new_reward_index_history,
+            new_publisher.key,
+            new_checkpoint,
+            &owner.to_account_info(),
+            &ctx.accounts.system_program,
+        )?;
+
+        //cpi
+        let target_with_parameters =
+            staking::state::positions::TargetWithParameters::IntegrityPool {
+                publisher: publisher.key(),
+            };
+        let new_target_with_parameters =
+            staking::state::positions::TargetWithParameters::IntegrityPool {
+                publisher: new_publisher.key(),
+            };
+
+        let cpi_accounts = staking::cpi::accounts::RedelegatePosition {
+            owner: owner.to_account_info(),
+            config: config_account,
+            stake_account_positions: ctx.accounts.stake_account_positions.to_account_info(),
+            stake_account_metadata,
+            stake_account_custody,
+            pool_authority: pool_config.to_account_info(),
+            system_program,
+        };
+
+        let signer_seeds: &[&[&[u8]]] = &[&[POOL_CONFIG.as_bytes(), &[ctx.bumps.pool_config]]];
+        let cpi_ctx = CpiContext::new(staking_program.to_account_info(), cpi_accounts)
+            .with_signer(signer_seeds);
+
+        staking::cpi::redelegate_position(
+            cpi_ctx,
+            position_index,
+            amount,
+            target_with_parameters,
+            new_target_with_parameters,
+        )?;
+
+        emit!(Redelegated {
+            stake_account_positions: ctx.accounts.stake_account_positions.key(),
+            publisher: publisher.key(),
+            new_publisher: new_publisher.key(),
+            position_index,
+            amount,
+            epoch: get_current_epoch(&ctx.accounts.config_account)?,
+        });
+
         Ok(())
     }
 
@@ -282,6 +691,14 @@ pub mod integrity_pool {
             pool_data.publisher_stake_accounts[publisher_index] = Pubkey::default();
         }
 
+        emit!(PublisherStakeAccountSet {
+            publisher:               publisher.key(),
+            stake_account_positions: ctx
+                .accounts
+                .new_stake_account_positions_option
+                .as_ref()
+                .map(|x| x.key()),
+        });
 
         Ok(())
     }
@@ -291,18 +708,62 @@ pub mod integrity_pool {
         let publisher_caps = &ctx.accounts.publisher_caps.load()?;
         let pool_config = &ctx.accounts.pool_config;
 
-        pool_data.advance(
+        let last_updated_epoch = pool_data.last_updated_epoch;
+        let current_epoch = get_current_epoch(&ctx.accounts.config_account)?;
+        let (y, eligible_delegations) = pool_data.advance(
+            &ctx.accounts.config_account,
             publisher_caps,
             pool_config.y,
-            get_current_epoch()?,
+            current_epoch,
             ctx.accounts.pool_reward_custody.amount,
         )?;
 
+        for (epoch, total_eligible_delegation) in
+            (last_updated_epoch..current_epoch).zip(eligible_delegations)
+        {
+            emit!(PoolAdvanced {
+                epoch,
+                current_epoch,
+                y,
+                total_eligible_delegation,
+            });
+        }
+
         Ok(())
     }
 
     pub fn advance_delegation_record(ctx: Context<AdvanceDelegationRecord>) -> Result<frac64> {
-        let delegation_record = &mut ctx.accounts.delegation_record;
+        advance_delegation_record_to(ctx.accounts, &ctx.bumps, None)
+    }
+
+    pub fn claim_rewards_to_destination(ctx: Context<ClaimRewardsToDestination>) -> Result<frac64> {
+        // only the owner can choose where the rewards go
+        require_eq!(
+            ctx.accounts.owner.key(),
+            DynamicPositionArray::load(
+                &ctx.accounts
+                    .advance_delegation_record
+                    .stake_account_positions
+            )?
+            .owner()?,
+            IntegrityPoolError::StakeAccountOwnerNeedsToSign
+        );
+
+        let destination = ctx.accounts.destination.to_account_info();
+        advance_delegation_record_to(
+            &mut ctx.accounts.advance_delegation_record,
+            &ctx.bumps.advance_delegation_record,
+            Some(destination),
+        )
+    }
+
+    /// Claims the rewards of a stake account for several publishers at once.
+    /// The remaining accounts are quadruples of publisher, delegation record, publisher stake
+    /// account custody and reward index history. The program id can be passed as the custody of
+    /// a publisher without a stake account.
+    pub fn advance_delegation_records<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AdvanceDelegationRecords<'info>>,
+    ) -> Result<frac64> {
         let pool_data = &mut ctx.accounts.pool_data.load_mut()?;
         let pool_config = &ctx.accounts.pool_config;
         let stake_account_positions =
@@ -310,71 +771,470 @@ pub mod integrity_pool {
         let pool_reward_custody = &ctx.accounts.pool_reward_custody;
         let stake_account_custody = &ctx.accounts.stake_account_custody;
         let token_program = &ctx.accounts.token_program;
-        let publisher = &ctx.accounts.publisher;
-        let publisher_stake_account_positions = &ctx.accounts.publisher_stake_account_positions;
-        let publisher_stake_account_custody = &ctx.accounts.publisher_stake_account_custody;
 
-        let publisher_index = pool_data.get_publisher_index(&publisher.key())?;
+        let current_epoch = get_current_epoch(&ctx.accounts.config_account)?;
+        let signer_seeds: &[&[&[u8]]] = &[&[POOL_CONFIG.as_bytes(), &[ctx.bumps.pool_config]]];
 
-        // reward amount in PYTH with decimals
-        let (delegator_reward, publisher_reward) = pool_data.calculate_reward(
-            delegation_record.last_epoch,
-            &ctx.accounts.stake_account_positions.key(),
-            stake_account_positions,
-            &publisher.key(),
-            get_current_epoch()?,
-        )?;
-        pool_data.claimable_rewards -= delegator_reward + publisher_reward;
+        let mut total_delegator_reward: frac64 = 0;
+        let mut compounded_rewards: Vec<(
+            Pubkey,
+            frac64,
+            &AccountInfo<'info>,
+            &AccountInfo<'info>,
+            u8,
+        )> = vec![];
+
+        for accounts in ctx.remaining_accounts.chunks(4) {
+            let [publisher, delegation_record, publisher_stake_account_custody, reward_index_history] =
+                accounts
+            else {
+                return err!(IntegrityPoolError::InvalidRemainingAccounts);
+            };
 
-        // transfer delegator reward from pool_reward_custody to stake_account_custody
-        let cpi_accounts = anchor_spl::token::Transfer {
-            from:      pool_reward_custody.to_account_info(),
-            to:        stake_account_custody.to_account_info(),
-            authority: pool_config.to_account_info(),
-        };
-        let signer_seeds: &[&[&[u8]]] = &[&[POOL_CONFIG.as_bytes(), &[ctx.bumps.pool_config]]];
+            let publisher_index = pool_data.get_publisher_index(publisher.key)?;
 
-        let transfer_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts)
-            .with_signer(signer_seeds);
-        anchor_spl::token::transfer(transfer_ctx, delegator_reward)?;
+            let (delegation_record_address, _) = Pubkey::find_program_address(
+                &[
+                    DELEGATION_RECORD.as_bytes(),
+                    publisher.key.as_ref(),
+                    ctx.accounts.stake_account_positions.key().as_ref(),
+                ],
+                &crate::ID,
+            );
+            require_eq!(
+                delegation_record.key(),
+                delegation_record_address,
+                IntegrityPoolError::InvalidDelegationRecord
+            );
+            let delegation_record_info = delegation_record;
+            let mut delegation_record = Account::<DelegationRecord>::try_from(delegation_record)?;
+            register_delegation_record(
+                &mut delegation_record,
+                ctx.accounts.staking_program.to_account_info(),
+                staking::cpi::accounts::UpdateDelegationRecordCount {
+                    payer:                   ctx.accounts.payer.to_account_info(),
+                    stake_account_positions: ctx.accounts.stake_account_positions.to_account_info(),
+                    stake_account_metadata:  ctx.accounts.stake_account_metadata.clone(),
+                    config:                  ctx.accounts.config_account.to_account_info(),
+                    pool_authority:          pool_config.to_account_info(),
+                    system_program:          ctx.accounts.system_program.to_account_info(),
+                },
+                signer_seeds,
+            )?;
+
+            let (reward_index_history_address, reward_index_history_bump) =
+                Pubkey::find_program_address(
+                    &[REWARD_INDEX_HISTORY.as_bytes(), publisher.key.as_ref()],
+                    &crate::ID,
+                );
+            require_eq!(
+                reward_index_history.key(),
+                reward_index_history_address,
+                IntegrityPoolError::InvalidRewardIndexHistory
+            );
 
-        match publisher_stake_account_positions {
-            Some(publisher_stake_account_positions) => {
+            // reward amount in PYTH with decimals
+            let (delegator_reward, publisher_reward) = pool_data.calculate_reward(
+                &delegation_record,
+                &ctx.accounts.stake_account_positions.key(),
+                stake_account_positions,
+                publisher.key,
+                load_reward_index_history(reward_index_history)?.as_deref(),
+                current_epoch,
+                ctx.accounts.config_account.get_unlocking_durations(),
+            )?;
+            pool_data.claimable_rewards -= delegator_reward + publisher_reward;
+
+            total_delegator_reward += delegator_reward;
+            if delegation_record.auto_compound && delegator_reward > 0 {
+                compounded_rewards.push((
+                    publisher.key(),
+                    delegator_reward,
+                    delegation_record_info,
+                    reward_index_history,
+                    reward_index_history_bump,
+                ));
+            }
+
+            let publisher_stake_account_positions =
+                pool_data.publisher_stake_accounts[publisher_index];
+            if publisher_stake_account_positions != Pubkey::default() {
+                let (publisher_stake_account_custody_address, _) = Pubkey::find_program_address(
+                    &[
+                        staking::context::CUSTODY_SEED.as_bytes(),
+                        publisher_stake_account_positions.as_ref(),
+                    ],
+                    &staking::ID,
+                );
                 require_eq!(
-                    pool_data.publisher_stake_accounts[publisher_index],
-                    publisher_stake_account_positions.key(),
+                    publisher_stake_account_custody.key(),
+                    publisher_stake_account_custody_address,
                     IntegrityPoolError::PublisherStakeAccountMismatch
                 );
 
-                let destination = publisher_stake_account_custody
-                    .as_ref()
-                    .ok_or(IntegrityPoolError::PublisherCustodyAccountRequired)?;
-
                 // transfer publisher reward from pool_reward_custody to
                 // publisher_stake_account_custody
                 let cpi_accounts = anchor_spl::token::Transfer {
                     from:      pool_reward_custody.to_account_info(),
-                    to:        destination.to_account_info(),
+                    to:        publisher_stake_account_custody.clone(),
                     authority: pool_config.to_account_info(),
                 };
-                let signer_seeds: &[&[&[u8]]] =
-                    &[&[POOL_CONFIG.as_bytes(), &[ctx.bumps.pool_config]]];
-
                 let transfer_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts)
                     .with_signer(signer_seeds);
                 anchor_spl::token::transfer(transfer_ctx, publisher_reward)?;
             }
-            None => {
-                require_eq!(
-                    pool_data.publisher_stake_accounts[publisher_index],
-                    Pubkey::default(),
-                    IntegrityPoolError::PublisherStakeAccountMismatch
-                );
+
+            delegation_record.advance(current_epoch, pool_data.reward_indices[publisher_index])?;
+            // persist the record right away in case it's passed more than once
+            delegation_record.exit(&crate::ID)?;
+
+            emit!(DelegationRecordAdvanced {
+                stake_account_positions: ctx.accounts.stake_account_positions.key(),
+                publisher: publisher.key(),
+                delegator_reward,
+                publisher_reward,
+                epoch: current_epoch,
+            });
+        }
+
+        // transfer the combined delegator reward from pool_reward_custody to stake_account_custody
+        let cpi_accounts = anchor_spl::token::Transfer {
+            from:      pool_reward_custody.to_account_info(),
+            to:        stake_account_custody.to_account_info(),
+            authority: pool_config.to_account_info(),
+        };
+        let transfer_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts)
+            .with_signer(signer_seeds);
+        anchor_spl::token::transfer(transfer_ctx, total_delegator_reward)?;
+
+        if !compounded_rewards.is_empty() {
+            let stake_account_metadata = &ctx.accounts.stake_account_metadata;
+            let staking_program = &ctx.accounts.staking_program;
+
+            for (
+                publisher,
+                delegator_reward,
+                delegation_record,
+                reward_index_history,
+                reward_index_history_bump,
+            ) in compounded_rewards
+            {
+                // delegate the reward to the same publisher
+                let cpi_accounts = staking::cpi::accounts::CreatePoolPosition {
+                    payer:                   ctx.accounts.payer.to_account_info(),
+                    stake_account_positions: ctx.accounts.stake_account_positions.to_account_info(),
+                    stake_account_metadata:  stake_account_metadata.clone(),
+                    stake_account_custody:   stake_account_custody.to_account_info(),
+                    config:                  ctx.accounts.config_account.to_account_info(),
+                    pool_authority:          pool_config.to_account_info(),
+                    system_program:          ctx.accounts.system_program.to_account_info(),
+                    delegation_record:       delegation_record.clone(),
+                };
+                let cpi_ctx = CpiContext::new(staking_program.to_account_info(), cpi_accounts)
+                    .with_signer(signer_seeds);
+                staking::cpi::create_pool_position(
+                    cpi_ctx,
+                    TargetWithParameters::IntegrityPool { publisher },
+                    delegator_reward,
+                )?;
+
+                let checkpoint = pool_data.add_delegation(
+                    &publisher,
+                    &ctx.accounts.stake_account_positions.key(),
+                    delegator_reward,
+                    current_epoch,
+                )?;
+                save_reward_index_checkpoint(
+                    reward_index_history,
+                    reward_index_history_bump,
+                    &publisher,
+                    checkpoint,
+                    &ctx.accounts.payer.to_account_info(),
+                    &ctx.accounts.system_program,
+                )?;
             }
         }
 
-        delegation_record.advance(get_current_epoch()?)?;
-        Ok(delegator_reward)
+        Ok(total_delegator_reward)
+    }
+
+    pub fn set_auto_compound(ctx: Context<SetAutoCompound>, auto_compound: bool) -> Result<()> {
+        let stake_account_positions =
+            &DynamicPositionArray::load(&ctx.accounts.stake_account_positions)?;
+
+        require_eq!(
+            ctx.accounts.owner.key(),
+            stake_account_positions.owner()?,
+            IntegrityPoolError::StakeAccountOwnerNeedsToSign
+        );
+
+        ctx.accounts.delegation_record.auto_compound = auto_compound;
+        register_delegation_record(
+            &mut ctx.accounts.delegation_record,
+            ctx.accounts.staking_program.to_account_info(),
+            staking::cpi::accounts::UpdateDelegationRecordCount {
+                payer:                   ctx.accounts.owner.to_account_info(),
+                stake_account_positions: ctx.accounts.stake_account_positions.to_account_info(),
+                stake_account_metadata:  ctx.accounts.stake_account_metadata.clone(),
+                config:                  ctx.accounts.config_account.to_account_info(),
+                pool_authority:          ctx.accounts.pool_config.to_account_info(),
+                system_program:          ctx.accounts.system_program.to_account_info(),
+            },
+            &[&[POOL_CONFIG.as_bytes(), &[ctx.bumps.pool_config]]],
+        )?;
+
+        Ok(())
+    }
+
+    /// Closes the delegation record of a publisher the stake account doesn't delegate to anymore
+    /// and sends its rent to the owner of the stake account. The delegation record needs to be
+    /// advanced to the current epoch first so no rewards are lost.
+    pub fn close_delegation_record(ctx: Context<CloseDelegationRecord>) -> Result<()> {
+        let stake_account_positions =
+            &DynamicPositionArray::load(&ctx.accounts.stake_account_positions)?;
+        let publisher = &ctx.accounts.publisher.key();
+
+        require_eq!(
+            ctx.accounts.owner.key(),
+            stake_account_positions.owner()?,
+            IntegrityPoolError::StakeAccountOwnerNeedsToSign
+        );
+
+        ctx.accounts
+            .delegation_record
+            .assert_up_to_date(get_current_epoch(&ctx.accounts.config_account)?)?;
+
+        for i in 0..stake_account_positions.get_position_capacity() {
+            if let Some(position) = stake_account_positions.read_position(i)? {
+                if position.target_with_parameters
+                    == (TargetWithParameters::IntegrityPool {
+                        publisher: *publisher,
+                    })
+                {
+                    return err!(IntegrityPoolError::DelegationRecordInUse);
+                }
+            }
+        }
+
+        if ctx.accounts.delegation_record.registered {
+            let cpi_accounts = staking::cpi::accounts::UpdateDelegationRecordCount {
+                payer:                   ctx.accounts.owner.to_account_info(),
+                stake_account_positions: ctx.accounts.stake_account_positions.to_account_info(),
+                stake_account_metadata:  ctx.accounts.stake_account_metadata.clone(),
+                config:                  ctx.accounts.config_account.to_account_info(),
+                pool_authority:          ctx.accounts.pool_config.to_account_info(),
+                system_program:          ctx.accounts.system_program.to_account_info(),
+            };
+            let signer_seeds: &[&[&[u8]]] = &[&[POOL_CONFIG.as_bytes(), &[ctx.bumps.pool_config]]];
+            staking::cpi::update_delegation_record_count(
+                CpiContext::new(ctx.accounts.staking_program.to_account_info(), cpi_accounts)
+                    .with_signer(signer_seeds),
+                false,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Accepts the split request of a stake account with integrity pool positions. The
+    /// delegation records of the new stake account start where the up to date records of the
+    /// source stake account are, so the rewards of the split positions aren't claimed twice and
+    /// the delegation of the publishers doesn't change. Remaining accounts are publisher, source
+    /// delegation record, new delegation record triplets for every publisher of the positions
+    /// that are split.
+    pub fn accept_split<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AcceptSplit<'info>>,
+        amount: u64,
+        recipient: Pubkey,
+    ) -> Result<()> {
+        let source_stake_account_positions = ctx.accounts.source_stake_account_positions.key();
+        let new_stake_account_positions = ctx.accounts.new_stake_account_positions.key();
+        let current_epoch = get_current_epoch(&ctx.accounts.config_account)?;
+
+        // the positions of a publisher stake account are self-delegations, they would become
+        // regular delegations in the new account
+        require!(
+            !ctx.accounts
+                .pool_data
+                .load()?
+                .publisher_stake_accounts
+                .contains(&source_stake_account_positions),
+            IntegrityPoolError::SplitPublisherStakeAccount
+        );
+
+        // publishers of the positions that are split, unlocked positions stay in the source account
+        let mut publishers: Vec<Pubkey> = vec![];
+        let positions = &DynamicPositionArray::load(&ctx.accounts.source_stake_account_positions)?;
+        for i in 0..positions.get_position_capacity() {
+            if let Some(position) = positions.read_position(i)? {
+                let unlocking_durations = positions.get_unlocking_durations(
+                    i,
+                    ctx.accounts.config_account.get_unlocking_durations(),
+                )?;
+                if let TargetWithParameters::IntegrityPool { publisher } =
+                    position.target_with_parameters
+                {
+                    if position.get_current_position(current_epoch, unlocking_durations)?
+                        != PositionState::UNLOCKED
+                        && !publishers.contains(&publisher)
+                    {
+                        publishers.push(publisher);
+                    }
+                }
+            }
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[POOL_CONFIG.as_bytes(), &[ctx.bumps.pool_config]]];
+        let cpi_accounts = staking::cpi::accounts::AcceptSplit {
+            pda_authority:                      ctx.accounts.pda_authority.to_account_info(),
+            source_stake_account_positions:     ctx
+                .accounts
+                .source_stake_account_positions
+                .to_account_info(),
+            source_stake_account_metadata:      ctx
+                .accounts
+                .source_stake_account_metadata
+                .to_account_info(),
+            source_stake_account_split_request: ctx
+                .accounts
+                .source_stake_account_split_request
+                .to_account_info(),
+            source_stake_account_custody:       ctx
+                .accounts
+                .source_stake_account_custody
+                .to_account_info(),
+            source_custody_authority:           ctx
+                .accounts
+                .source_custody_authority
+                .to_account_info(),
+            new_stake_account_positions:        ctx
+                .accounts
+                .new_stake_account_positions
+                .to_account_info(),
+            new_stake_account_metadata:         ctx
+                .accounts
+                .new_stake_account_metadata
+                .to_account_info(),
+            new_stake_account_custody:          ctx
+                .accounts
+                .new_stake_account_custody
+                .to_account_info(),
+            new_custody_authority:              ctx
+                .accounts
+                .new_custody_authority
+                .to_account_info(),
+            config:                             ctx.accounts.config_account.to_account_info(),
+            pyth_token_mint:                    ctx.accounts.pyth_token_mint.to_account_info(),
+            rent:                               ctx.accounts.rent.to_account_info(),
+            token_program:                      ctx.accounts.token_program.to_account_info(),
+            system_program:                     ctx.accounts.system_program.to_account_info(),
+            pool_authority:                     Some(ctx.accounts.pool_config.to_account_info()),
+        };
+        staking::cpi::accept_split(
+            CpiContext::new(ctx.accounts.staking_program.to_account_info(), cpi_accounts)
+                .with_signer(signer_seeds),
+            amount,
+            recipient,
+        )?;
+
+        for accounts in ctx.remaining_accounts.chunks(3) {
+            let [publisher, source_delegation_record, new_delegation_record] = accounts else {
+                return err!(IntegrityPoolError::InvalidRemainingAccounts);
+            };
+
+            let publisher_index = publishers
+                .iter()
+                .position(|key| key == publisher.key)
+                .ok_or(IntegrityPoolError::InvalidPublisher)?;
+            publishers.swap_remove(publisher_index);
+
+            let (source_delegation_record_address, _) = Pubkey::find_program_address(
+                &[
+                    DELEGATION_RECORD.as_bytes(),
+                    publisher.key.as_ref(),
+                    source_stake_account_positions.as_ref(),
+                ],
+                &crate::ID,
+            );
+            require_eq!(
+                source_delegation_record.key(),
+                source_delegation_record_address,
+                IntegrityPoolError::InvalidDelegationRecord
+            );
+            let source_delegation_record =
+                Account::<DelegationRecord>::try_from(source_delegation_record)?;
+            source_delegation_record.assert_up_to_date(current_epoch)?;
+
+            let (new_delegation_record_address, new_delegation_record_bump) =
+                Pubkey::find_program_address(
+                    &[
+                        DELEGATION_RECORD.as_bytes(),
+                        publisher.key.as_ref(),
+                        new_stake_account_positions.as_ref(),
+                    ],
+                    &crate::ID,
+                );
+            require_eq!(
+                new_delegation_record.key(),
+                new_delegation_record_address,
+                IntegrityPoolError::InvalidDelegationRecord
+            );
+            require!(
+                new_delegation_record.data_is_empty(),
+                IntegrityPoolError::InvalidDelegationRecord
+            );
+
+            create_or_grow_account(
+                new_delegation_record,
+                &ctx.accounts.pda_authority.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                DelegationRecord::LEN,
+                &crate::ID,
+                &[&[
+                    DELEGATION_RECORD.as_bytes(),
+                    publisher.key.as_ref(),
+                    new_stake_account_positions.as_ref(),
+                    &[new_delegation_record_bump],
+                ]],
+            )?;
+
+            // the recipient opts in to auto-compounding separately
+            let mut delegation_record = DelegationRecord {
+                last_epoch:             source_delegation_record.last_epoch,
+                next_slash_event_index: source_delegation_record.next_slash_event_index,
+                reward_index:           source_delegation_record.reward_index,
+                auto_compound:          false,
+                registered:             false,
+            };
+            register_delegation_record(
+                &mut delegation_record,
+                ctx.accounts.staking_program.to_account_info(),
+                staking::cpi::accounts::UpdateDelegationRecordCount {
+                    payer:                   ctx.accounts.pda_authority.to_account_info(),
+                    stake_account_positions: ctx
+                        .accounts
+                        .new_stake_account_positions
+                        .to_account_info(),
+                    stake_account_metadata:  ctx
+                        .accounts
+                        .new_stake_account_metadata
+                        .to_account_info(),
+                    config:                  ctx.accounts.config_account.to_account_info(),
+                    pool_authority:          ctx.accounts.pool_config.to_account_info(),
+                    system_program:          ctx.accounts.system_program.to_account_info(),
+                },
+                signer_seeds,
+            )?;
+            delegation_record
+                .try_serialize(&mut &mut new_delegation_record.try_borrow_mut_data()?[..])?;
+        }
+
+        require!(
+            publishers.is_empty(),
+            IntegrityPoolError::SplitDelegationRecordRequired
+        );
+
+        Ok(())
     }
 
     pub fn create_slash_event(
@@ -397,10 +1257,18 @@ pub mod integrity_pool {
 
         pool_data.num_slash_events[publisher_index] += 1;
 
-        slash_event.epoch = get_current_epoch()?;
+        slash_event.epoch = get_current_epoch(&ctx.accounts.config_account)?;
         slash_event.slash_ratio = slash_ratio;
         slash_event.slash_custody = slash_custody.key();
 
+        emit!(SlashEventCreated {
+            publisher: *publisher,
+            index,
+            slash_ratio,
+            slash_custody: slash_custody.key(),
+            epoch: slash_event.epoch,
+        });
+
         Ok(())
     }
 
@@ -411,7 +1279,7 @@ pub mod integrity_pool {
         let delegation_record = &mut ctx.accounts.delegation_record;
         let stake_account_positions = &ctx.accounts.stake_account_positions.key();
 
-        let current_epoch = get_current_epoch()?;
+        let current_epoch = get_current_epoch(&ctx.accounts.config_account)?;
 
         // assert delegator record is up to date
         delegation_record.assert_up_to_date(current_epoch)?;
@@ -441,7 +1309,12 @@ pub mod integrity_pool {
         );
         delegation_record.next_slash_event_index += 1;
 
-        let (locked_slashed, preunlocking_slashed) = staking::cpi::slash_account(
+        let SlashedAmounts {
+            locked_slashed,
+            preunlocking_slashed,
+            redelegation_caps,
+            ..
+        } = staking::cpi::slash_account_v2(
             CpiContext::from(&*ctx.accounts)
                 .with_signer(&[&[POOL_CONFIG.as_bytes(), &[ctx.bumps.pool_config]]]),
             slash_event.slash_ratio,
@@ -455,7 +1328,264 @@ pub mod integrity_pool {
             preunlocking_slashed,
             current_epoch,
         )?;
+        for redelegation_cap in redelegation_caps.iter() {
+            pool_data.apply_redelegation_cap(redelegation_cap, stake_account_positions)?;
+        }
+
+        emit!(DelegationSlashed {
+            stake_account_positions: *stake_account_positions,
+            publisher: publisher.key(),
+            index,
+            locked_slashed,
+            preunlocking_slashed,
+            epoch: current_epoch,
+        });
 
         Ok(())
     }
 }
+
+// Transfers the rewards of a delegation record and advances it to the current epoch. The delegator
+// reward goes to the stake account custody unless another destination is provided.
+fn advance_delegation_record_to<'info>(
+    accounts: &mut AdvanceDelegationRecord<'info>,
+    bumps: &AdvanceDelegationRecordBumps,
+    destination: Option<AccountInfo<'info>>,
+) -> Result<frac64> {
+    let pool_config_bump = bumps.pool_config;
+    let delegation_record = &mut accounts.delegation_record;
+    let pool_data = &mut accounts.pool_data.load_mut()?;
+    let pool_config = &accounts.pool_config;
+    let stake_account_positions = &DynamicPositionArray::load(&accounts.stake_account_positions)?;
+    let pool_reward_custody = &accounts.pool_reward_custody;
+    let stake_account_custody = &accounts.stake_account_custody;
+    let token_program = &accounts.token_program;
+    let publisher = &accounts.publisher;
+    let publisher_stake_account_positions = &accounts.publisher_stake_account_positions;
+    let publisher_stake_account_custody = &accounts.publisher_stake_account_custody;
+
+    let publisher_index = pool_data.get_publisher_index(&publisher.key())?;
+
+    register_delegation_record(
+        delegation_record,
+        accounts.staking_program.to_account_info(),
+        staking::cpi::accounts::UpdateDelegationRecordCount {
+            payer:                   accounts.payer.to_account_info(),
+            stake_account_positions: accounts.stake_account_positions.to_account_info(),
+            stake_account_metadata:  accounts.stake_account_metadata.clone(),
+            config:                  accounts.config_account.to_account_info(),
+            pool_authority:          pool_config.to_account_info(),
+            system_program:          accounts.system_program.to_account_info(),
+        },
+        &[&[POOL_CONFIG.as_bytes(), &[pool_config_bump]]],
+    )?;
+
+    // reward amount in PYTH with decimals
+    let (delegator_reward, publisher_reward) = pool_data.calculate_reward(
+        delegation_record,
+        &accounts.stake_account_positions.key(),
+        stake_account_positions,
+        &publisher.key(),
+        load_reward_index_history(&accounts.reward_index_history)?.as_deref(),
+        get_current_epoch(&accounts.config_account)?,
+        accounts.config_account.get_unlocking_durations(),
+    )?;
+    pool_data.claimable_rewards -= delegator_reward + publisher_reward;
+
+    // rewards are compounded only if they're sent to the stake account custody
+    let auto_compound = delegation_record.auto_compound && destination.is_none();
+    let destination = destination.unwrap_or_else(|| stake_account_custody.to_account_info());
+
+    // transfer delegator reward from pool_reward_custody to destination
+    let cpi_accounts = anchor_spl::token::Transfer {
+        from:      pool_reward_custody.to_account_info(),
+        to:        destination,
+        authority: pool_config.to_account_info(),
+    };
+    let signer_seeds: &[&[&[u8]]] = &[&[POOL_CONFIG.as_bytes(), &[pool_config_bump]]];
+
+    let transfer_ctx =
+        CpiContext::new(token_program.to_account_info(), cpi_accounts).with_signer(signer_seeds);
+    anchor_spl::token::transfer(transfer_ctx, delegator_reward)?;
+
+    if auto_compound && delegator_reward > 0 {
+        let stake_account_metadata = &accounts.stake_account_metadata;
+        let staking_program = &accounts.staking_program;
+
+        // delegate the reward to the same publisher
+        let cpi_accounts = staking::cpi::accounts::CreatePoolPosition {
+            payer:                   accounts.payer.to_account_info(),
+            stake_account_positions: accounts.stake_account_positions.to_account_info(),
+            stake_account_metadata:  stake_account_metadata.clone(),
+            stake_account_custody:   stake_account_custody.to_account_info(),
+            config:                  accounts.config_account.to_account_info(),
+            pool_authority:          pool_config.to_account_info(),
+            system_program:          accounts.system_program.to_account_info(),
+            delegation_record:       delegation_record.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(staking_program.to_account_info(), cpi_accounts)
+            .with_signer(signer_seeds);
+        staking::cpi::create_pool_position(
+            cpi_ctx,
+            TargetWithParameters::IntegrityPool {
+                publisher: publisher.key(),
+            },
+            delegator_reward,
+        )?;
+
+        let checkpoint = pool_data.add_delegation(
+            publisher.key,
+            &accounts.stake_account_positions.key(),
+            delegator_reward,
+            get_current_epoch(&accounts.config_account)?,
+        )?;
+        save_reward_index_checkpoint(
+            &accounts.reward_index_history,
+            bumps.reward_index_history,
+            publisher.key,
+            checkpoint,
+            &accounts.payer.to_account_info(),
+            &accounts.system_program,
+        )?;
+    }
+
+    match publisher_stake_account_positions {
+        Some(publisher_stake_account_positions) => {
+            require_eq!(
+                pool_data.publisher_stake_accounts[publisher_index],
+                publisher_stake_account_positions.key(),
+                IntegrityPoolError::PublisherStakeAccountMismatch
+            );
+
+            let destination = publisher_stake_account_custody
+                .as_ref()
+                .ok_or(IntegrityPoolError::PublisherCustodyAccountRequired)?;
+
+            // transfer publisher reward from pool_reward_custody to
+            // publisher_stake_account_custody
+            let cpi_accounts = anchor_spl::token::Transfer {
+                from:      pool_reward_custody.to_account_info(),
+                to:        destination.to_account_info(),
+                authority: pool_config.to_account_info(),
+            };
+            let signer_seeds: &[&[&[u8]]] = &[&[POOL_CONFIG.as_bytes(), &[pool_config_bump]]];
+
+            let transfer_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts)
+                .with_signer(signer_seeds);
+            anchor_spl::token::transfer(transfer_ctx, publisher_reward)?;
+        }
+        None => {
+            require_eq!(
+                pool_data.publisher_stake_accounts[publisher_index],
+                Pubkey::default(),
+                IntegrityPoolError::PublisherStakeAccountMismatch
+            );
+        }
+    }
+
+    delegation_record.advance(
+        get_current_epoch(&accounts.config_account)?,
+        pool_data.reward_indices[publisher_index],
+    )?;
+
+    emit!(DelegationRecordAdvanced {
+        stake_account_positions: accounts.stake_account_positions.key(),
+        publisher: publisher.key(),
+        delegator_reward,
+        publisher_reward,
+        epoch: get_current_epoch(&accounts.config_account)?,
+    });
+
+    Ok(delegator_reward)
+}
+
+// Counts the delegation record in the metadata of the stake account if it isn't yet, so that the
+// stake account can't be closed before the record
+fn register_delegation_record<'info>(
+    delegation_record: &mut DelegationRecord,
+    staking_program: AccountInfo<'info>,
+    cpi_accounts: staking::cpi::accounts::UpdateDelegationRecordCount<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if delegation_record.registered {
+        return Ok(());
+    }
+
+    staking::cpi::update_delegation_record_count(
+        CpiContext::new(staking_program, cpi_accounts).with_signer(signer_seeds),
+        true,
+    )?;
+    delegation_record.registered = true;
+    Ok(())
+}
+
+// Checks that the reward index history is a history account of this program
+fn check_reward_index_history(reward_index_history: &AccountInfo) -> Result<()> {
+    require_keys_eq!(
+        *reward_index_history.owner,
+        crate::ID,
+        ErrorCode::AccountOwnedByWrongProgram
+    );
+    let data = reward_index_history.try_borrow_data()?;
+    require!(
+        data.len() >= RewardIndexHistory::LEN,
+        ErrorCode::AccountDidNotDeserialize
+    );
+    require!(
+        data[..8] == RewardIndexHistory::discriminator(),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    Ok(())
+}
+
+// Reads the reward index history of a publisher, None if it hasn't been created yet
+fn load_reward_index_history<'a>(
+    reward_index_history: &'a AccountInfo,
+) -> Result<Option<Ref<'a, RewardIndexHistory>>> {
+    if reward_index_history.data_is_empty() {
+        return Ok(None);
+    }
+    check_reward_index_history(reward_index_history)?;
+    Ok(Some(Ref::map(
+        reward_index_history.try_borrow_data()?,
+        |data| bytemuck::from_bytes(&data[8..RewardIndexHistory::LEN]),
+    )))
+}
+
+// Moves a checkpoint replaced in the pool data to the reward index history of the publisher,
+// creating the history account at the expense of the payer
+fn save_reward_index_checkpoint<'info>(
+    reward_index_history: &AccountInfo<'info>,
+    reward_index_history_bump: u8,
+    publisher: &Pubkey,
+    checkpoint: Option<RewardIndexCheckpoint>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let Some(checkpoint) = checkpoint else {
+        return Ok(());
+    };
+
+    if reward_index_history.data_is_empty() {
+        create_or_grow_account(
+            reward_index_history,
+            payer,
+            system_program,
+            RewardIndexHistory::LEN,
+            &crate::ID,
+            &[&[
+                REWARD_INDEX_HISTORY.as_bytes(),
+                publisher.as_ref(),
+                &[reward_index_history_bump],
+            ]],
+        )?;
+        reward_index_history.try_borrow_mut_data()?[..8]
+            .copy_from_slice(&RewardIndexHistory::discriminator());
+    }
+    check_reward_index_history(reward_index_history)?;
+
+    let mut data = reward_index_history.try_borrow_mut_data()?;
+    let history: &mut RewardIndexHistory =
+        bytemuck::from_bytes_mut(&mut data[8..RewardIndexHistory::LEN]);
+    history.push(checkpoint)
+}
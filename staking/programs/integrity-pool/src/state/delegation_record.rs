@@ -1,4 +1,5 @@
 use {
+    super::reward_index::RewardIndex,
     crate::error::IntegrityPoolError,
     anchor_lang::prelude::*,
     borsh::BorshSchema,
@@ -9,6 +10,13 @@ use {
 pub struct DelegationRecord {
     pub last_epoch:             u64,
     pub next_slash_event_index: u64,
+    // reward index of the publisher at last_epoch
+    pub reward_index:           RewardIndex,
+    // whether claimed rewards are delegated to the same publisher
+    pub auto_compound:          bool,
+    // whether the record is counted in the metadata of the stake account, records created before
+    // the count existed are counted the next time they're advanced
+    pub registered:             bool,
 }
 
 impl DelegationRecord {
@@ -25,8 +33,9 @@ impl DelegationRecord {
         Ok(())
     }
 
-    pub fn advance(&mut self, current_epoch: u64) -> Result<()> {
+    pub fn advance(&mut self, current_epoch: u64, reward_index: RewardIndex) -> Result<()> {
         self.last_epoch = current_epoch;
+        self.reward_index = reward_index;
         Ok(())
     }
 }
@@ -48,14 +57,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_staking_offsets() {
+        let record = DelegationRecord {
+            last_epoch:             0,
+            next_slash_event_index: 0,
+            reward_index:           RewardIndex::default(),
+            auto_compound:          true,
+            registered:             true,
+        };
+        let mut data = vec![];
+        record.try_serialize(&mut data).unwrap();
+        // the staking program reads these bytes when compounding rewards and closing stake
+        // accounts
+        assert_eq!(
+            data[staking::context::DELEGATION_RECORD_AUTO_COMPOUND_OFFSET],
+            1
+        );
+        assert_eq!(
+            data[staking::context::DELEGATION_RECORD_REGISTERED_OFFSET],
+            1
+        );
+        assert_eq!(
+            staking::context::DELEGATION_RECORD_SEED,
+            crate::utils::constants::DELEGATION_RECORD
+        );
+    }
+
     #[test]
     fn test_advance() {
         let mut record = DelegationRecord {
             last_epoch:             0,
             next_slash_event_index: 0,
+            reward_index:           RewardIndex::default(),
+            auto_compound:          false,
+            registered:             false,
+        };
+        let reward_index = RewardIndex {
+            self_reward:    1,
+            other_reward:   2,
+            delegation_fee: 0,
         };
-        record.advance(1).unwrap();
+        record.advance(1, reward_index).unwrap();
         assert_eq!(record.last_epoch, 1);
+        assert_eq!(record.reward_index, reward_index);
     }
 
     #[test]
@@ -63,6 +108,9 @@ mod tests {
         let record = DelegationRecord {
             last_epoch:             100,
             next_slash_event_index: 0,
+            reward_index:           RewardIndex::default(),
+            auto_compound:          false,
+            registered:             false,
         };
         assert!(record.assert_up_to_date(100).is_ok());
         assert!(record.assert_up_to_date(101).is_err());
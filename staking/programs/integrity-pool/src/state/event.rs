@@ -1,4 +1,5 @@
 use {
+    super::reward_index::RewardIndex,
     crate::utils::{
         constants::MAX_PUBLISHERS,
         types::{
@@ -65,6 +66,24 @@ impl Event {
 
         Ok((delegator_reward.try_into()?, publisher_reward.try_into()?))
     }
+
+    // reward per PYTH delegated to the publisher during this event
+    pub fn get_reward_rates(&self, publisher_index: usize) -> Result<RewardIndex> {
+        let event_data = &self.event_data[publisher_index];
+
+        let self_reward =
+            u128::from(self.y) * u128::from(event_data.self_reward_ratio) / FRAC_64_MULTIPLIER_U128;
+        let other_reward = u128::from(self.y) * u128::from(event_data.other_reward_ratio)
+            / FRAC_64_MULTIPLIER_U128;
+        let delegation_fee =
+            other_reward * u128::from(event_data.delegation_fee) / FRAC_64_MULTIPLIER_U128;
+
+        Ok(RewardIndex {
+            self_reward:    self_reward.try_into()?,
+            other_reward:   other_reward.try_into()?,
+            delegation_fee: delegation_fee.try_into()?,
+        })
+    }
 }
 
 #[derive(
@@ -175,4 +194,31 @@ mod tests {
         let reward = event.calculate_reward(u64::MAX, 0, true).unwrap();
         assert_eq!(reward, (u64::MAX, 0));
     }
+
+    #[test]
+    fn test_get_reward_rates() {
+        let mut event = Event {
+            // 10%
+            y: FRAC_64_MULTIPLIER / 10,
+            ..Default::default()
+        };
+
+        event.event_data[0] = PublisherEventData {
+            // ratio = 50%
+            self_reward_ratio:  FRAC_64_MULTIPLIER / 2,
+            // ratio = 100%
+            other_reward_ratio: FRAC_64_MULTIPLIER,
+            delegation_fee:     FRAC_64_MULTIPLIER / 10,
+        };
+
+        assert_eq!(
+            event.get_reward_rates(0).unwrap(),
+            RewardIndex {
+                self_reward:    FRAC_64_MULTIPLIER / 20,
+                other_reward:   FRAC_64_MULTIPLIER / 10,
+                delegation_fee: FRAC_64_MULTIPLIER / 100,
+            }
+        );
+        assert_eq!(event.get_reward_rates(1).unwrap(), RewardIndex::default());
+    }
 }
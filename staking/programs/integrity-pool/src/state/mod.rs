@@ -1,4 +1,5 @@
 pub mod delegation_record;
 pub mod event;
 pub mod pool;
+pub mod reward_index;
 pub mod slash;
@@ -1,7 +1,15 @@
 use {
-    super::event::{
-        Event,
-        PublisherEventData,
+    super::{
+        delegation_record::DelegationRecord,
+        event::{
+            Event,
+            PublisherEventData,
+        },
+        reward_index::{
+            RewardIndex,
+            RewardIndexCheckpoint,
+            RewardIndexHistory,
+        },
     },
     crate::{
         error::IntegrityPoolError,
@@ -9,6 +17,7 @@ use {
             clock::time_to_epoch,
             constants::{
                 MAX_EVENTS,
+                MAX_FEE_HISTORY,
                 MAX_PUBLISHERS,
             },
             types::{
@@ -28,12 +37,20 @@ use {
         PublisherCaps,
         MAX_CAPS,
     },
-    staking::state::positions::{
-        PositionState,
-        TargetWithParameters,
+    staking::state::{
+        global_config::GlobalConfig,
+        positions::{
+            PositionState,
+            RedelegationCap,
+            TargetWithParameters,
+            UnlockingDurations,
+        },
     },
     std::{
-        cmp::min,
+        cmp::{
+            max,
+            min,
+        },
         convert::{
             TryFrom,
             TryInto,
@@ -61,6 +78,16 @@ pub struct DelegationState {
     pub delta_delegation: i64,
 }
 
+// a delegation fee that took effect at the given epoch
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Zeroable, Pod, Eq, Debug, PartialEq, Default,
+)]
+#[repr(C)]
+pub struct DelegationFeeChange {
+    pub epoch:          u64,
+    pub delegation_fee: frac64,
+}
+
 pub struct EligibleDelegationData {
     pub self_delegation:           u64,
     pub other_delegation:          u64,
@@ -127,6 +154,19 @@ pub struct PoolData {
     pub num_events:               u64,
     pub num_slash_events:         [u64; MAX_PUBLISHERS],
     pub delegation_fees:          [frac64; MAX_PUBLISHERS],
+    pub pending_delegation_fees:  [frac64; MAX_PUBLISHERS],
+    // epoch at which the pending delegation fee takes effect, 0 if there is no pending fee
+    pub pending_fee_epochs:       [u64; MAX_PUBLISHERS],
+    // most recent fee changes first
+    pub fee_history:              [[DelegationFeeChange; MAX_FEE_HISTORY]; MAX_PUBLISHERS],
+    // cumulative reward per PYTH of every publisher since reward_index_start_epoch
+    pub reward_indices:           [RewardIndex; MAX_PUBLISHERS],
+    // first epoch accounted for in reward_indices, 0 if the indices haven't started yet
+    // rewards of earlier epochs are calculated from the events
+    pub reward_index_start_epoch: u64,
+    // reward index of every publisher at the last epoch in which its delegation changed, the
+    // previous checkpoint moves to the reward index history of the publisher
+    pub reward_index_checkpoints: [RewardIndexCheckpoint; MAX_PUBLISHERS],
 }
 
 impl PoolData {
@@ -147,15 +187,20 @@ impl PoolData {
     // returns (delegator_reward, publisher_reward)
     pub fn calculate_reward(
         &self,
-        from_epoch: u64,
+        delegation_record: &DelegationRecord,
         stake_account_positions_key: &Pubkey,
         positions: &staking::state::positions::DynamicPositionArray,
         publisher: &Pubkey,
+        reward_index_history: Option<&RewardIndexHistory>,
         current_epoch: u64,
+        unlocking_durations: UnlockingDurations,
     ) -> Result<(frac64, frac64)> {
         self.assert_up_to_date(current_epoch)?;
 
         let publisher_index = self.get_publisher_index(publisher)?;
+        let is_publisher =
+            &self.publisher_stake_accounts[publisher_index] == stake_account_positions_key;
+        let from_epoch = delegation_record.last_epoch;
 
         let mut delegator_reward: frac64 = 0;
         let mut publisher_reward: frac64 = 0;
@@ -174,6 +219,7 @@ impl PoolData {
                 } if position_publisher == publisher => {}
                 _ => continue,
             }
+            let unlocking_durations = positions.get_unlocking_durations(i, unlocking_durations)?;
 
             let mut last_event_index: usize = self.num_events.try_into()?;
             loop {
@@ -193,7 +239,16 @@ impl PoolData {
                     break;
                 }
 
-                let position_state = position.get_current_position(event.epoch)?;
+                // rewards of these events are calculated from the reward indices
+                if self.reward_index_start_epoch != 0
+                    && event.epoch >= self.reward_index_start_epoch
+                {
+                    continue;
+                }
+
+                // only locked positions earn rewards
+                let position_state =
+                    position.get_current_position(event.epoch, unlocking_durations)?;
 
                 match position_state {
                     PositionState::LOCKED | PositionState::PREUNLOCKING => {}
@@ -203,15 +258,73 @@ impl PoolData {
                 }
                 event_amounts[last_event_index % MAX_EVENTS] += position.amount;
             }
+
+            if self.reward_index_start_epoch == 0 {
+                continue;
+            }
+
+            // the position earns rewards from activation_epoch until unlocking_start
+            let earn_from = max(
+                max(position.activation_epoch, from_epoch),
+                self.reward_index_start_epoch,
+            );
+            let earn_to = min(
+                position.unlocking_start.unwrap_or(current_epoch),
+                current_epoch,
+            );
+            if earn_to <= earn_from {
+                continue;
+            }
+
+            // if the position started earning in an epoch that can't be reconstructed, it only
+            // gets rewarded from the oldest event
+            let (earn_from, index_from) = match self.get_reward_index(
+                publisher_index,
+                earn_from,
+                delegation_record,
+                reward_index_history,
+            )? {
+                Some(index_from) => (earn_from, index_from),
+                None => {
+                    let Some(oldest_epoch) = current_epoch.checked_sub(u64::try_from(MAX_EVENTS)?)
+                    else {
+                        continue;
+                    };
+                    match self.get_reward_index(
+                        publisher_index,
+                        oldest_epoch,
+                        delegation_record,
+                        reward_index_history,
+                    )? {
+                        Some(index_from) => (oldest_epoch, index_from),
+                        None => continue,
+                    }
+                }
+            };
+            if earn_to <= earn_from {
+                continue;
+            }
+            let mut reward_index = match self.get_reward_index(
+                publisher_index,
+                earn_to,
+                delegation_record,
+                reward_index_history,
+            )? {
+                Some(index_to) => index_to,
+                None => continue,
+            };
+            reward_index.sub(&index_from);
+
+            let (delegator_reward_for_position, publisher_reward_for_position) =
+                reward_index.calculate_reward(position.amount, is_publisher)?;
+            delegator_reward += delegator_reward_for_position;
+            publisher_reward += publisher_reward_for_position;
         }
 
         for (i, amount) in event_amounts.iter().enumerate() {
             let event = self.get_event(i);
-            let (delegator_reward_for_event, publisher_reward_for_event) = event.calculate_reward(
-                *amount,
-                publisher_index,
-                &self.publisher_stake_accounts[publisher_index] == stake_account_positions_key,
-            )?;
+            let (delegator_reward_for_event, publisher_reward_for_event) =
+                event.calculate_reward(*amount, publisher_index, is_publisher)?;
 
             delegator_reward += delegator_reward_for_event;
             publisher_reward += publisher_reward_for_event;
@@ -220,14 +333,54 @@ impl PoolData {
         Ok((delegator_reward, publisher_reward))
     }
 
+    // reward index of the publisher at the beginning of the given epoch
+    // returns None if the epoch is too old to be reconstructed from the events and isn't
+    // checkpointed
+    pub fn get_reward_index(
+        &self,
+        publisher_index: usize,
+        epoch: u64,
+        delegation_record: &DelegationRecord,
+        reward_index_history: Option<&RewardIndexHistory>,
+    ) -> Result<Option<RewardIndex>> {
+        if epoch <= self.reward_index_start_epoch {
+            return Ok(Some(RewardIndex::default()));
+        }
+        if epoch == delegation_record.last_epoch {
+            return Ok(Some(delegation_record.reward_index));
+        }
+
+        let checkpoint = &self.reward_index_checkpoints[publisher_index];
+        if checkpoint.epoch == epoch && epoch <= self.last_updated_epoch {
+            return Ok(Some(checkpoint.reward_index));
+        }
+
+        let epochs_ago = self.last_updated_epoch - epoch;
+        if epochs_ago > u64::try_from(MAX_EVENTS)? {
+            return Ok(reward_index_history.and_then(|history| history.get_reward_index(epoch)));
+        }
+
+        let mut reward_index = self.reward_indices[publisher_index];
+        for i in 1..=epochs_ago {
+            reward_index.sub(
+                &self
+                    .get_event((self.num_events - i).try_into()?)
+                    .get_reward_rates(publisher_index)?,
+            );
+        }
+        Ok(Some(reward_index))
+    }
 
+    /// Creates the reward events up to the current epoch. Returns the `y` used for the new
+    /// events and the total eligible delegation of each of their epochs.
     pub fn advance(
         &mut self,
+        global_config: &GlobalConfig,
         publisher_caps: &PublisherCaps,
         y: frac64,
         current_epoch: u64,
         pool_reward_custody_balance: u64,
-    ) -> Result<()> {
+    ) -> Result<(frac64, Vec<u64>)> {
         let mut existing_publishers = BoolArray::new(MAX_CAPS);
 
         require_eq!(
@@ -243,14 +396,21 @@ impl PoolData {
         );
         require_eq!(
             current_epoch,
-            time_to_epoch(publisher_caps.publish_time())?,
+            time_to_epoch(global_config, publisher_caps.publish_time())?,
             IntegrityPoolError::OutdatedPublisherCaps
         );
 
+        // existing pools start using the reward indices from the first advance after the upgrade
+        if self.reward_index_start_epoch == 0 {
+            self.reward_index_start_epoch = self.last_updated_epoch;
+        }
+
         let epochs_passed = current_epoch - self.last_updated_epoch;
         let mut i = 0;
 
-        let mut total_eligible_delegation: u64 = 0;
+        // the eligible delegation of last_updated_epoch and of each of the epochs after it
+        let mut first_eligible_delegation: u64 = 0;
+        let mut next_eligible_delegation: u64 = 0;
         while i < MAX_PUBLISHERS && self.publishers[i] != Pubkey::default() {
             let cap_index = publisher_caps
                 .caps()
@@ -278,7 +438,7 @@ impl PoolData {
                 i,
                 eligible_delegation_data.get_reward_ratios()?,
             )?;
-            total_eligible_delegation += eligible_delegation_data.get_total_eligible_delegation();
+            first_eligible_delegation += eligible_delegation_data.get_total_eligible_delegation();
 
             self.del_state[i] = DelegationState {
                 total_delegation: (TryInto::<i64>::try_into(self.del_state[i].total_delegation)?
@@ -308,14 +468,17 @@ impl PoolData {
                 i,
                 eligible_delegation_data.get_reward_ratios()?,
             )?;
-            total_eligible_delegation +=
-                eligible_delegation_data.get_total_eligible_delegation() * (epochs_passed - 1);
+            next_eligible_delegation += eligible_delegation_data.get_total_eligible_delegation();
+
+            self.apply_pending_delegation_fee(i, current_epoch);
             i += 1;
         }
 
+        let mut eligible_delegations = vec![first_eligible_delegation];
+        eligible_delegations.resize(epochs_passed.try_into()?, next_eligible_delegation);
         let (adjusted_y, adjusted_rewards_to_be_distributed) = self.adjust_rewards_if_needed(
             y,
-            total_eligible_delegation,
+            eligible_delegations.iter().sum(),
             pool_reward_custody_balance,
         )?;
         for epoch in self.last_updated_epoch..current_epoch {
@@ -326,6 +489,24 @@ impl PoolData {
         }
         self.claimable_rewards += adjusted_rewards_to_be_distributed;
 
+        if self.reward_index_start_epoch != 0 {
+            for epoch in self.last_updated_epoch..current_epoch {
+                let event_index: usize =
+                    (self.num_events + epoch - self.last_updated_epoch).try_into()?;
+                for publisher_index in 0..i {
+                    let reward_rates = self
+                        .get_event(event_index)
+                        .get_reward_rates(publisher_index)?;
+                    self.reward_indices[publisher_index].add(&reward_rates);
+
+                    let checkpoint = &mut self.reward_index_checkpoints[publisher_index];
+                    if checkpoint.epoch == epoch + 1 {
+                        checkpoint.reward_index = self.reward_indices[publisher_index];
+                    }
+                }
+            }
+        }
+
 
         for j in 0..publisher_caps.num_publishers() as usize {
             // Silently ignore if there are more publishers than MAX_PUBLISHERS
@@ -338,7 +519,7 @@ impl PoolData {
         self.num_events += epochs_passed;
         self.last_updated_epoch = current_epoch;
 
-        Ok(())
+        Ok((adjusted_y, eligible_delegations))
     }
 
     pub fn create_reward_events_for_publisher(
@@ -353,15 +534,97 @@ impl PoolData {
                 .event_data[publisher_index] = PublisherEventData {
                 self_reward_ratio:  reward_ratios.self_reward_ratio,
                 other_reward_ratio: reward_ratios.other_reward_ratio,
-                delegation_fee:     self.delegation_fees[publisher_index],
+                delegation_fee:     self.get_delegation_fee(publisher_index, epoch),
             };
         }
         Ok(())
     }
 
+    // the delegation fee that applies to the rewards of the given epoch
+    pub fn get_delegation_fee(&self, publisher_index: usize, epoch: u64) -> frac64 {
+        let pending_epoch = self.pending_fee_epochs[publisher_index];
+        if pending_epoch != 0 && epoch >= pending_epoch {
+            self.pending_delegation_fees[publisher_index]
+        } else {
+            self.delegation_fees[publisher_index]
+        }
+    }
+
+    // fee increases take effect after the notice period so delegators can undelegate before,
+    // fee decreases take effect at the next epoch
+    pub fn schedule_delegation_fee(
+        &mut self,
+        publisher_index: usize,
+        delegation_fee: frac64,
+        current_epoch: u64,
+        fee_notice_epochs: u64,
+    ) -> Result<()> {
+        let notice_epochs = if delegation_fee > self.delegation_fees[publisher_index] {
+            max(fee_notice_epochs, 1)
+        } else {
+            1
+        };
+        self.pending_delegation_fees[publisher_index] = delegation_fee;
+        self.pending_fee_epochs[publisher_index] = current_epoch
+            .checked_add(notice_epochs)
+            .ok_or_else(|| error!(IntegrityPoolError::GenericOverflow))?;
+        Ok(())
+    }
+
+    // lowers the fees above the maximum delegation fee at the next epoch, including the fees of
+    // the slots new publishers will take
+    pub fn cap_delegation_fees(
+        &mut self,
+        max_delegation_fee: frac64,
+        current_epoch: u64,
+        fee_notice_epochs: u64,
+    ) -> Result<()> {
+        for publisher_index in 0..MAX_PUBLISHERS {
+            if self.publishers[publisher_index] == Pubkey::default() {
+                self.delegation_fees[publisher_index] =
+                    min(self.delegation_fees[publisher_index], max_delegation_fee);
+                continue;
+            }
+
+            let scheduled_fee = match self.pending_fee_epochs[publisher_index] {
+                0 => self.delegation_fees[publisher_index],
+                _ => self.pending_delegation_fees[publisher_index],
+            };
+            if scheduled_fee > max_delegation_fee {
+                self.schedule_delegation_fee(
+                    publisher_index,
+                    max_delegation_fee,
+                    current_epoch,
+                    fee_notice_epochs,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn apply_pending_delegation_fee(&mut self, publisher_index: usize, current_epoch: u64) {
+        let pending_epoch = self.pending_fee_epochs[publisher_index];
+        if pending_epoch != 0 && current_epoch >= pending_epoch {
+            self.delegation_fees[publisher_index] = self.pending_delegation_fees[publisher_index];
+
+            let history = &mut self.fee_history[publisher_index];
+            history.copy_within(0..MAX_FEE_HISTORY - 1, 1);
+            history[0] = DelegationFeeChange {
+                epoch:          pending_epoch,
+                delegation_fee: self.delegation_fees[publisher_index],
+            };
+
+            self.pending_delegation_fees[publisher_index] = 0;
+            self.pending_fee_epochs[publisher_index] = 0;
+        }
+    }
+
     /**
      * Adjusts the rewards to be distributed if the pool reward custody balance is not enough to
      * cover the rewards
+     *
+     * The rewards are rounded up, the rewards calculated from the reward indices are rounded
+     * down once per claim and a claim can cover the epochs of several advances
      */
     pub fn adjust_rewards_if_needed(
         &self,
@@ -369,18 +632,19 @@ impl PoolData {
         total_eligible_delegation: u64,
         pool_reward_custody_balance: u64,
     ) -> Result<(u64, u64)> {
-        let rewards_to_be_distributed: u64 =
-            ((u128::from(total_eligible_delegation) * u128::from(y)) / FRAC_64_MULTIPLIER_U128)
-                .try_into()?;
+        let rewards_to_be_distributed: u64 = (u128::from(total_eligible_delegation)
+            * u128::from(y))
+        .div_ceil(FRAC_64_MULTIPLIER_U128)
+        .try_into()?;
         if rewards_to_be_distributed + self.claimable_rewards > pool_reward_custody_balance {
             let adjusted_y = ((u128::from(y)
                 * u128::from(pool_reward_custody_balance - self.claimable_rewards))
                 / u128::from(rewards_to_be_distributed))
             .try_into()?;
-            let adjusted_reward_to_be_distributed = ((u128::from(total_eligible_delegation)
+            let adjusted_reward_to_be_distributed = (u128::from(total_eligible_delegation)
                 * u128::from(adjusted_y))
-                / FRAC_64_MULTIPLIER_U128)
-                .try_into()?;
+            .div_ceil(FRAC_64_MULTIPLIER_U128)
+            .try_into()?;
             Ok((adjusted_y, adjusted_reward_to_be_distributed))
         } else {
             Ok((y, rewards_to_be_distributed))
@@ -406,7 +670,7 @@ impl PoolData {
         stake_account_positions_key: &Pubkey,
         amount: u64,
         current_epoch: u64,
-    ) -> Result<()> {
+    ) -> Result<Option<RewardIndexCheckpoint>> {
         let index = self.get_publisher_index(publisher)?;
         self.assert_up_to_date(current_epoch)?;
 
@@ -417,7 +681,7 @@ impl PoolData {
         } else {
             self.del_state[index].delta_delegation += amount_i64;
         }
-        Ok(())
+        Ok(self.checkpoint_delegation_change(index, current_epoch))
     }
 
     pub fn remove_delegation(
@@ -427,30 +691,46 @@ impl PoolData {
         amount: u64,
         position_state: PositionState,
         current_epoch: u64,
-    ) -> Result<()> {
+    ) -> Result<Option<RewardIndexCheckpoint>> {
         let index = self.get_publisher_index(publisher)?;
         self.assert_up_to_date(current_epoch)?;
 
         let amount_i64: i64 = amount.try_into()?;
 
-        if stake_account_positions_key == &self.publisher_stake_accounts[index] {
-            match position_state {
-                PositionState::LOCKED | PositionState::LOCKING => {
-                    self.self_del_state[index].delta_delegation -= amount_i64;
-                }
-                PositionState::UNLOCKED => {}
-                _ => return err!(IntegrityPoolError::UnexpectedPositionState),
-            }
+        let del_state = if stake_account_positions_key == &self.publisher_stake_accounts[index] {
+            &mut self.self_del_state[index]
         } else {
-            match position_state {
-                PositionState::LOCKED | PositionState::LOCKING => {
-                    self.del_state[index].delta_delegation -= amount_i64;
-                }
-                PositionState::UNLOCKED => {}
-                _ => return err!(IntegrityPoolError::UnexpectedPositionState),
+            &mut self.del_state[index]
+        };
+        match position_state {
+            PositionState::LOCKED | PositionState::LOCKING => {
+                del_state.delta_delegation -= amount_i64;
             }
+            PositionState::UNLOCKED => return Ok(None),
+            _ => return err!(IntegrityPoolError::UnexpectedPositionState),
         }
-        Ok(())
+        Ok(self.checkpoint_delegation_change(index, current_epoch))
+    }
+
+    // positions of the publisher activate or start unlocking at the next epoch, so rewards will
+    // need its reward index at that epoch. Returns the previous checkpoint of the publisher, it
+    // needs to be saved in the reward index history of the publisher
+    fn checkpoint_delegation_change(
+        &mut self,
+        publisher_index: usize,
+        current_epoch: u64,
+    ) -> Option<RewardIndexCheckpoint> {
+        let checkpoint = &mut self.reward_index_checkpoints[publisher_index];
+        if checkpoint.epoch == current_epoch + 1 {
+            return None;
+        }
+
+        let previous_checkpoint = *checkpoint;
+        *checkpoint = RewardIndexCheckpoint {
+            epoch:        current_epoch + 1,
+            reward_index: RewardIndex::default(),
+        };
+        (previous_checkpoint.epoch != 0).then_some(previous_checkpoint)
     }
 
     pub fn apply_slash(
@@ -478,6 +758,29 @@ impl PoolData {
         Ok(())
     }
 
+    // the staking program reduced the positions that took the place of slashed positions in a
+    // redelegation
+    pub fn apply_redelegation_cap(
+        &mut self,
+        redelegation_cap: &RedelegationCap,
+        stake_account_positions_key: &Pubkey,
+    ) -> Result<()> {
+        let publisher_index = self.get_publisher_index(&redelegation_cap.publisher)?;
+
+        let del_state =
+            if self.publisher_stake_accounts[publisher_index] == *stake_account_positions_key {
+                &mut self.self_del_state[publisher_index]
+            } else {
+                &mut self.del_state[publisher_index]
+            };
+
+        del_state.total_delegation -= redelegation_cap.locked + redelegation_cap.preunlocking;
+        del_state.delta_delegation += TryInto::<i64>::try_into(redelegation_cap.preunlocking)?
+            - TryInto::<i64>::try_into(redelegation_cap.locking)?;
+
+        Ok(())
+    }
+
     pub fn assert_up_to_date(&self, current_epoch: u64) -> Result<()> {
         require_eq!(
             self.last_updated_epoch,
@@ -496,6 +799,14 @@ pub struct PoolConfig {
     pub pyth_token_mint:          Pubkey,
     pub y:                        frac64,
     pub slash_custody:            Pubkey,
+    // None for pools created before the maximum existed
+    pub max_delegation_fee:       Option<frac64>,
+    // minimum number of epochs between scheduling a fee increase and it taking effect
+    pub fee_notice_epochs:        u64,
+
+    // key nominated to replace the reward program authority, it becomes the authority once it
+    // accepts the nomination
+    pub pending_reward_program_authority: Option<Pubkey>,
 }
 
 impl PoolConfig {
@@ -508,7 +819,10 @@ mod tests {
         super::*,
         crate::{
             state::event::PublisherEventData,
-            utils::types::FRAC_64_MULTIPLIER,
+            utils::{
+                constants::MAX_REWARD_INDEX_CHECKPOINTS,
+                types::FRAC_64_MULTIPLIER,
+            },
         },
         anchor_lang::Discriminator,
         publisher_caps::{
@@ -518,6 +832,39 @@ mod tests {
         staking::state::positions::DynamicPositionArrayAccount,
     };
 
+    impl PoolData {
+        fn default_for_tests() -> Self {
+            PoolData {
+                last_updated_epoch:       0,
+                claimable_rewards:        0,
+                publishers:               [Pubkey::default(); MAX_PUBLISHERS],
+                del_state:                [DelegationState::default(); MAX_PUBLISHERS],
+                self_del_state:           [DelegationState::default(); MAX_PUBLISHERS],
+                publisher_stake_accounts: [Pubkey::default(); MAX_PUBLISHERS],
+                events:                   [Event::default(); MAX_EVENTS],
+                num_events:               0,
+                num_slash_events:         [0; MAX_PUBLISHERS],
+                delegation_fees:          [0; MAX_PUBLISHERS],
+                pending_delegation_fees:  [0; MAX_PUBLISHERS],
+                pending_fee_epochs:       [0; MAX_PUBLISHERS],
+                fee_history:              [Default::default(); MAX_PUBLISHERS],
+                reward_indices:           [RewardIndex::default(); MAX_PUBLISHERS],
+                reward_index_start_epoch: 0,
+                reward_index_checkpoints: [RewardIndexCheckpoint::default(); MAX_PUBLISHERS],
+            }
+        }
+    }
+
+    fn delegation_record(last_epoch: u64) -> DelegationRecord {
+        DelegationRecord {
+            last_epoch,
+            next_slash_event_index: 0,
+            reward_index: RewardIndex::default(),
+            auto_compound: false,
+            registered: false,
+        }
+    }
+
     #[test]
     #[allow(deprecated)]
     fn test_size() {
@@ -529,21 +876,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_staking_offsets() {
+        // the staking program reads these bytes to find the publishers and their stake accounts
+        let pool_data_key = Pubkey::new_unique();
+        let mut pool_config_data = vec![];
+        PoolConfig {
+            pool_data:                        pool_data_key,
+            reward_program_authority:         Pubkey::default(),
+            pyth_token_mint:                  Pubkey::default(),
+            y:                                0,
+            slash_custody:                    Pubkey::default(),
+            max_delegation_fee:               None,
+            fee_notice_epochs:                0,
+            pending_reward_program_authority: None,
+        }
+        .try_serialize(&mut pool_config_data)
+        .unwrap();
+        let offset = staking::context::POOL_CONFIG_POOL_DATA_OFFSET;
+        assert_eq!(
+            pool_config_data[offset..offset + 32],
+            pool_data_key.to_bytes()
+        );
+
+        let publisher = Pubkey::new_unique();
+        let publisher_stake_account = Pubkey::new_unique();
+        let mut pool_data_words = vec![0u64; std::mem::size_of::<PoolData>() / 8];
+        let pool_data_bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut pool_data_words);
+        let pool_data: &mut PoolData = bytemuck::from_bytes_mut(pool_data_bytes);
+        pool_data.publishers[1] = publisher;
+        pool_data.publisher_stake_accounts[1] = publisher_stake_account;
+        let offset =
+            staking::context::POOL_DATA_PUBLISHERS_OFFSET - PoolData::discriminator().len() + 32;
+        assert_eq!(
+            bytemuck::cast_slice::<u64, u8>(&pool_data_words)[offset..offset + 32],
+            publisher.to_bytes()
+        );
+        let offset = staking::context::POOL_DATA_PUBLISHER_STAKE_ACCOUNTS_OFFSET
+            - PoolData::discriminator().len()
+            + 32;
+        assert_eq!(
+            bytemuck::cast_slice::<u64, u8>(&pool_data_words)[offset..offset + 32],
+            publisher_stake_account.to_bytes()
+        );
+        assert_eq!(staking::context::POOL_DATA_MAX_PUBLISHERS, MAX_PUBLISHERS);
+    }
+
     #[test]
     #[allow(deprecated)]
     fn test_circular_events() {
-        let mut pool_data = PoolData {
-            last_updated_epoch:       0,
-            claimable_rewards:        0,
-            publishers:               [Pubkey::default(); MAX_PUBLISHERS],
-            del_state:                [DelegationState::default(); MAX_PUBLISHERS],
-            self_del_state:           [DelegationState::default(); MAX_PUBLISHERS],
-            publisher_stake_accounts: [Pubkey::default(); MAX_PUBLISHERS],
-            events:                   [Event::default(); MAX_EVENTS],
-            num_events:               0,
-            num_slash_events:         [0; MAX_PUBLISHERS],
-            delegation_fees:          [0; MAX_PUBLISHERS],
-        };
+        let mut pool_data = PoolData::default_for_tests();
 
         pool_data.get_event_mut(1).epoch = 123;
         assert_eq!(pool_data.get_event(1 + MAX_EVENTS).epoch, 123);
@@ -554,16 +936,8 @@ mod tests {
     #[test]
     fn test_calculate_reward() {
         let mut pool_data = PoolData {
-            last_updated_epoch:       2,
-            claimable_rewards:        0,
-            publishers:               [Pubkey::default(); MAX_PUBLISHERS],
-            del_state:                [DelegationState::default(); MAX_PUBLISHERS],
-            self_del_state:           [DelegationState::default(); MAX_PUBLISHERS],
-            publisher_stake_accounts: [Pubkey::default(); MAX_PUBLISHERS],
-            events:                   [Event::default(); MAX_EVENTS],
-            num_events:               0,
-            num_slash_events:         [0; MAX_PUBLISHERS],
-            delegation_fees:          [0; MAX_PUBLISHERS],
+            last_updated_epoch: 2,
+            ..PoolData::default_for_tests()
         };
 
         let publisher_key = Pubkey::new_unique();
@@ -650,7 +1024,15 @@ mod tests {
         pool_data.last_updated_epoch = 2;
         pool_data.num_events = 1;
         let (delegator_reward, _) = pool_data
-            .calculate_reward(1, &publisher_key, &positions, &publisher_key, 2)
+            .calculate_reward(
+                &delegation_record(1),
+                &publisher_key,
+                &positions,
+                &publisher_key,
+                None,
+                2,
+                UnlockingDurations::default(),
+            )
             .unwrap();
 
         // 40 PYTH (amount) * 1 (self_reward_ratio) * 10% (y) = 4 PYTH
@@ -659,7 +1041,15 @@ mod tests {
         pool_data.num_events = 2;
         pool_data.last_updated_epoch = 3;
         let (delegator_reward, _) = pool_data
-            .calculate_reward(2, &publisher_key, &positions, &publisher_key, 3)
+            .calculate_reward(
+                &delegation_record(2),
+                &publisher_key,
+                &positions,
+                &publisher_key,
+                None,
+                3,
+                UnlockingDurations::default(),
+            )
             .unwrap();
 
         // 40 + 60 PYTH (amount) * 1 (self_reward_ratio) * 10% (y) = 10 PYTH
@@ -668,7 +1058,15 @@ mod tests {
         pool_data.num_events = 10;
         pool_data.last_updated_epoch = 11;
         let (delegator_reward, _) = pool_data
-            .calculate_reward(1, &publisher_key, &positions, &publisher_key, 11)
+            .calculate_reward(
+                &delegation_record(1),
+                &publisher_key,
+                &positions,
+                &publisher_key,
+                None,
+                11,
+                UnlockingDurations::default(),
+            )
             .unwrap();
 
         assert_eq!(delegator_reward, 94 * FRAC_64_MULTIPLIER);
@@ -685,7 +1083,15 @@ mod tests {
         }
 
         let (delegator_reward, _) = pool_data
-            .calculate_reward(1, &publisher_key, &positions, &publisher_key, 101)
+            .calculate_reward(
+                &delegation_record(1),
+                &publisher_key,
+                &positions,
+                &publisher_key,
+                None,
+                101,
+                UnlockingDurations::default(),
+            )
             .unwrap();
 
         assert_eq!(
@@ -694,20 +1100,272 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_calculate_reward_with_reward_indices() {
+        let mut pool_data = PoolData {
+            last_updated_epoch: 11,
+            num_events: 10,
+            reward_index_start_epoch: 1,
+            ..PoolData::default_for_tests()
+        };
+
+        let publisher_key = Pubkey::new_unique();
+        let publisher_index = 123;
+        pool_data.publisher_stake_accounts[publisher_index] = publisher_key;
+        pool_data.publishers[publisher_index] = publisher_key;
+
+        let mut event = Event {
+            epoch:       1,
+            y:           FRAC_64_MULTIPLIER / 10, // 10%
+            extra_space: [0; 7],
+            event_data:  [PublisherEventData::default(); MAX_PUBLISHERS],
+        };
+        event.event_data[publisher_index] = PublisherEventData {
+            self_reward_ratio:  FRAC_64_MULTIPLIER,     // 1
+            other_reward_ratio: FRAC_64_MULTIPLIER / 2, // 1/2
+            delegation_fee:     0,
+        };
+        let reward_rates = event.get_reward_rates(publisher_index).unwrap();
+
+        for i in 0..MAX_EVENTS {
+            pool_data.events[i] = event;
+        }
+        for i in 0..10 {
+            pool_data.get_event_mut(i).epoch = (i + 1) as u64;
+            pool_data.reward_indices[publisher_index].add(&reward_rates);
+        }
+
+        let mut stake_positions_account = DynamicPositionArrayAccount::default();
+        let mut positions = stake_positions_account.to_dynamic_position_array();
+        positions
+            .write_position(
+                0,
+                &staking::state::positions::Position {
+                    activation_epoch:       1,
+                    amount:                 40 * FRAC_64_MULTIPLIER,
+                    target_with_parameters: TargetWithParameters::IntegrityPool {
+                        publisher: publisher_key,
+                    },
+                    unlocking_start:        None,
+                },
+            )
+            .unwrap();
+        positions
+            .write_position(
+                1,
+                &staking::state::positions::Position {
+                    activation_epoch:       2,
+                    amount:                 60 * FRAC_64_MULTIPLIER,
+                    target_with_parameters: TargetWithParameters::IntegrityPool {
+                        publisher: publisher_key,
+                    },
+                    unlocking_start:        None,
+                },
+            )
+            .unwrap();
+
+        // same result as calculating the reward from the events
+        let (delegator_reward, _) = pool_data
+            .calculate_reward(
+                &delegation_record(1),
+                &publisher_key,
+                &positions,
+                &publisher_key,
+                None,
+                11,
+                UnlockingDurations::default(),
+            )
+            .unwrap();
+        assert_eq!(delegator_reward, 94 * FRAC_64_MULTIPLIER);
+
+        // the record settled at epoch 5 with the rewards of epochs 1 to 4
+        let mut record = delegation_record(5);
+        for _ in 1..5 {
+            record.reward_index.add(&reward_rates);
+        }
+        let (delegator_reward, _) = pool_data
+            .calculate_reward(
+                &record,
+                &publisher_key,
+                &positions,
+                &publisher_key,
+                None,
+                11,
+                UnlockingDurations::default(),
+            )
+            .unwrap();
+        // 40 + 60 PYTH (amount) * 6 epochs * 1 (self_reward_ratio) * 10% (y) = 60 PYTH
+        assert_eq!(delegator_reward, 60 * FRAC_64_MULTIPLIER);
+
+        // many more epochs than MAX_EVENTS
+        pool_data.num_events = 100;
+        pool_data.last_updated_epoch = 101;
+        for i in 10..100 {
+            pool_data.get_event_mut(i).epoch = (i + 1) as u64;
+            pool_data.reward_indices[publisher_index].add(&reward_rates);
+        }
+
+        let (delegator_reward, _) = pool_data
+            .calculate_reward(
+                &delegation_record(1),
+                &publisher_key,
+                &positions,
+                &publisher_key,
+                None,
+                101,
+                UnlockingDurations::default(),
+            )
+            .unwrap();
+        // the first position is rewarded for all 100 epochs since the record was last settled,
+        // the second position activated after the record was settled and more than MAX_EVENTS
+        // epochs ago, so it is rewarded for the last MAX_EVENTS epochs
+        assert_eq!(
+            delegator_reward,
+            (40 * 100 + 60 * MAX_EVENTS as u64) * FRAC_64_MULTIPLIER / 10
+        );
+
+        // with the checkpoint of the epoch in which the second position activated, it is rewarded
+        // for all 99 epochs, whether the checkpoint is still in the pool data or in the history
+        let checkpoint = RewardIndexCheckpoint {
+            epoch:        2,
+            reward_index: reward_rates,
+        };
+        pool_data.reward_index_checkpoints[publisher_index] = checkpoint;
+        let (delegator_reward, _) = pool_data
+            .calculate_reward(
+                &delegation_record(1),
+                &publisher_key,
+                &positions,
+                &publisher_key,
+                None,
+                101,
+                UnlockingDurations::default(),
+            )
+            .unwrap();
+        assert_eq!(
+            delegator_reward,
+            (40 * 100 + 60 * 99) * FRAC_64_MULTIPLIER / 10
+        );
+
+        pool_data.reward_index_checkpoints[publisher_index] = RewardIndexCheckpoint::default();
+        let mut history = RewardIndexHistory {
+            num_checkpoints: 0,
+            checkpoints:     [RewardIndexCheckpoint::default(); MAX_REWARD_INDEX_CHECKPOINTS],
+        };
+        history.push(checkpoint).unwrap();
+        let (delegator_reward, _) = pool_data
+            .calculate_reward(
+                &delegation_record(1),
+                &publisher_key,
+                &positions,
+                &publisher_key,
+                Some(&history),
+                101,
+                UnlockingDurations::default(),
+            )
+            .unwrap();
+        assert_eq!(
+            delegator_reward,
+            (40 * 100 + 60 * 99) * FRAC_64_MULTIPLIER / 10
+        );
+    }
+
+    #[test]
+    fn test_calculate_reward_over_reward_index_start() {
+        let mut pool_data = PoolData {
+            last_updated_epoch: 1,
+            reward_index_start_epoch: 6,
+            ..PoolData::default_for_tests()
+        };
+
+        let publisher_key = Pubkey::new_unique();
+        let publisher_index = 0;
+        pool_data.publishers[publisher_index] = publisher_key;
+
+        // the only stake account delegated to the publisher earns 1.5 units of reward per epoch
+        let amount = 150;
+        let y = FRAC_64_MULTIPLIER / 100;
+        let mut stake_positions_account = DynamicPositionArrayAccount::default();
+        let mut positions = stake_positions_account.to_dynamic_position_array();
+        positions
+            .write_position(
+                0,
+                &staking::state::positions::Position {
+                    activation_epoch: 1,
+                    amount,
+                    target_with_parameters: TargetWithParameters::IntegrityPool {
+                        publisher: publisher_key,
+                    },
+                    unlocking_start: None,
+                },
+            )
+            .unwrap();
+
+        // one advance per epoch, the events before epoch 6 are paid per event and the events
+        // from epoch 6 from the reward indices
+        for epoch in 1..11 {
+            let (_, rewards_to_be_distributed) = pool_data
+                .adjust_rewards_if_needed(y, amount, u64::MAX)
+                .unwrap();
+            pool_data.claimable_rewards += rewards_to_be_distributed;
+
+            let event = pool_data.get_event_mut(pool_data.num_events.try_into().unwrap());
+            event.epoch = epoch;
+            event.y = y;
+            event.event_data[publisher_index] = PublisherEventData {
+                self_reward_ratio:  0,
+                other_reward_ratio: FRAC_64_MULTIPLIER,
+                delegation_fee:     0,
+            };
+            let reward_rates = event.get_reward_rates(publisher_index).unwrap();
+            if epoch >= pool_data.reward_index_start_epoch {
+                pool_data.reward_indices[publisher_index].add(&reward_rates);
+            }
+            pool_data.num_events += 1;
+            pool_data.last_updated_epoch = epoch + 1;
+        }
+
+        let mut events_only_pool_data = pool_data;
+        events_only_pool_data.reward_index_start_epoch = 0;
+        let (event_reward, _) = events_only_pool_data
+            .calculate_reward(
+                &delegation_record(1),
+                &Pubkey::new_unique(),
+                &positions,
+                &publisher_key,
+                None,
+                11,
+                UnlockingDurations::default(),
+            )
+            .unwrap();
+        // rounded down once per event
+        assert_eq!(event_reward, 10);
+
+        let (index_reward, _) = pool_data
+            .calculate_reward(
+                &delegation_record(1),
+                &Pubkey::new_unique(),
+                &positions,
+                &publisher_key,
+                None,
+                11,
+                UnlockingDurations::default(),
+            )
+            .unwrap();
+        // 5 epochs rounded down once per event and 5 epochs rounded down once
+        assert_eq!(index_reward, 5 + 7);
+
+        // rounding the rewards of each advance up keeps both payouts within the claimable rewards
+        assert_eq!(pool_data.claimable_rewards, 20);
+        assert!(index_reward <= pool_data.claimable_rewards);
+    }
+
     #[test]
     fn test_reward_events() {
         let publisher_1 = Pubkey::new_unique();
         let mut pool_data = PoolData {
-            last_updated_epoch:       1,
-            claimable_rewards:        0,
-            publishers:               [Pubkey::default(); MAX_PUBLISHERS],
-            del_state:                [DelegationState::default(); MAX_PUBLISHERS],
-            self_del_state:           [DelegationState::default(); MAX_PUBLISHERS],
-            publisher_stake_accounts: [Pubkey::default(); MAX_PUBLISHERS],
-            events:                   [Event::default(); MAX_EVENTS],
-            num_events:               0,
-            num_slash_events:         [0; MAX_PUBLISHERS],
-            delegation_fees:          [0; MAX_PUBLISHERS],
+            last_updated_epoch: 1,
+            ..PoolData::default_for_tests()
         };
 
         let mut caps = [PublisherCap {
@@ -752,16 +1410,8 @@ mod tests {
     fn test_reward_events_overflow() {
         let publisher_1 = Pubkey::new_unique();
         let mut pool_data = PoolData {
-            last_updated_epoch:       1,
-            claimable_rewards:        0,
-            publishers:               [Pubkey::default(); MAX_PUBLISHERS],
-            del_state:                [DelegationState::default(); MAX_PUBLISHERS],
-            self_del_state:           [DelegationState::default(); MAX_PUBLISHERS],
-            publisher_stake_accounts: [Pubkey::default(); MAX_PUBLISHERS],
-            events:                   [Event::default(); MAX_EVENTS],
-            num_events:               0,
-            num_slash_events:         [0; MAX_PUBLISHERS],
-            delegation_fees:          [0; MAX_PUBLISHERS],
+            last_updated_epoch: 1,
+            ..PoolData::default_for_tests()
         };
 
         let mut caps = [PublisherCap {
@@ -801,21 +1451,94 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pending_delegation_fee() {
+        let mut pool_data = PoolData {
+            last_updated_epoch: 1,
+            ..PoolData::default_for_tests()
+        };
+
+        pool_data.delegation_fees[0] = FRAC_64_MULTIPLIER / 10;
+        // fee increase, takes effect after the notice period
+        pool_data
+            .schedule_delegation_fee(0, FRAC_64_MULTIPLIER / 5, 1, 2)
+            .unwrap();
+        assert_eq!(pool_data.pending_fee_epochs[0], 3);
+
+        let reward_ratios = || RewardRatios {
+            self_reward_ratio:  FRAC_64_MULTIPLIER,
+            other_reward_ratio: FRAC_64_MULTIPLIER,
+        };
+        pool_data
+            .create_reward_events_for_publisher(1, 5, 0, reward_ratios())
+            .unwrap();
+
+        // the old fee applies until the effective epoch
+        assert_eq!(
+            pool_data.events[0].event_data[0].delegation_fee,
+            FRAC_64_MULTIPLIER / 10
+        );
+        assert_eq!(
+            pool_data.events[1].event_data[0].delegation_fee,
+            FRAC_64_MULTIPLIER / 10
+        );
+        assert_eq!(
+            pool_data.events[2].event_data[0].delegation_fee,
+            FRAC_64_MULTIPLIER / 5
+        );
+        assert_eq!(
+            pool_data.events[3].event_data[0].delegation_fee,
+            FRAC_64_MULTIPLIER / 5
+        );
+
+        pool_data.apply_pending_delegation_fee(0, 2);
+        assert_eq!(pool_data.delegation_fees[0], FRAC_64_MULTIPLIER / 10);
+        assert_eq!(pool_data.pending_fee_epochs[0], 3);
+
+        pool_data.apply_pending_delegation_fee(0, 3);
+        assert_eq!(pool_data.delegation_fees[0], FRAC_64_MULTIPLIER / 5);
+        assert_eq!(pool_data.pending_delegation_fees[0], 0);
+        assert_eq!(pool_data.pending_fee_epochs[0], 0);
+        assert_eq!(
+            pool_data.fee_history[0][0],
+            DelegationFeeChange {
+                epoch:          3,
+                delegation_fee: FRAC_64_MULTIPLIER / 5,
+            }
+        );
+
+        // fee decrease, takes effect at the next epoch
+        pool_data
+            .schedule_delegation_fee(0, FRAC_64_MULTIPLIER / 20, 3, 2)
+            .unwrap();
+        assert_eq!(pool_data.pending_fee_epochs[0], 4);
+
+        pool_data.apply_pending_delegation_fee(0, 4);
+        assert_eq!(pool_data.delegation_fees[0], FRAC_64_MULTIPLIER / 20);
+        assert_eq!(
+            pool_data.fee_history[0][0],
+            DelegationFeeChange {
+                epoch:          4,
+                delegation_fee: FRAC_64_MULTIPLIER / 20,
+            }
+        );
+        assert_eq!(
+            pool_data.fee_history[0][1],
+            DelegationFeeChange {
+                epoch:          3,
+                delegation_fee: FRAC_64_MULTIPLIER / 5,
+            }
+        );
+        assert_eq!(pool_data.fee_history[0][2], DelegationFeeChange::default());
+    }
+
     #[test]
     fn test_delegation() {
         let publisher_1 = Pubkey::new_unique();
         let publisher_stake_account = Pubkey::new_unique();
         let mut pool_data = PoolData {
-            last_updated_epoch:       2,
-            claimable_rewards:        0,
-            publishers:               [Pubkey::default(); MAX_PUBLISHERS],
-            del_state:                [DelegationState::default(); MAX_PUBLISHERS],
-            self_del_state:           [DelegationState::default(); MAX_PUBLISHERS],
-            publisher_stake_accounts: [Pubkey::default(); MAX_PUBLISHERS],
-            events:                   [Event::default(); MAX_EVENTS],
-            num_events:               0,
-            num_slash_events:         [0; MAX_PUBLISHERS],
-            delegation_fees:          [0; MAX_PUBLISHERS],
+            last_updated_epoch: 2,
+            ..PoolData::default_for_tests()
         };
 
         pool_data.publishers[0] = publisher_1;
@@ -923,5 +1646,49 @@ mod tests {
             res.unwrap_err(),
             IntegrityPoolError::UnexpectedPositionState.into()
         );
+
+        // all the changes above activate at epoch 3
+        assert_eq!(pool_data.reward_index_checkpoints[0].epoch, 3);
+
+        // a change in a later epoch replaces the checkpoint
+        pool_data.last_updated_epoch = 3;
+        pool_data.reward_index_checkpoints[0]
+            .reward_index
+            .self_reward = 1;
+        let checkpoint = pool_data
+            .add_delegation(&publisher_1, &Pubkey::new_unique(), 1, 3)
+            .unwrap();
+        assert_eq!(
+            checkpoint,
+            Some(RewardIndexCheckpoint {
+                epoch:        3,
+                reward_index: RewardIndex {
+                    self_reward:    1,
+                    other_reward:   0,
+                    delegation_fee: 0,
+                },
+            })
+        );
+        assert_eq!(
+            pool_data.reward_index_checkpoints[0],
+            RewardIndexCheckpoint {
+                epoch:        4,
+                reward_index: RewardIndex::default(),
+            }
+        );
+
+        // removing unlocked positions doesn't change the delegation
+        pool_data.last_updated_epoch = 4;
+        let checkpoint = pool_data
+            .remove_delegation(
+                &publisher_1,
+                &publisher_stake_account,
+                1,
+                PositionState::UNLOCKED,
+                4,
+            )
+            .unwrap();
+        assert_eq!(checkpoint, None);
+        assert_eq!(pool_data.reward_index_checkpoints[0].epoch, 4);
     }
 }
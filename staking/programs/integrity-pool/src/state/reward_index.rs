@@ -0,0 +1,297 @@
+use {
+    crate::{
+        error::IntegrityPoolError,
+        utils::{
+            constants::MAX_REWARD_INDEX_CHECKPOINTS,
+            types::{
+                frac64,
+                FRAC_64_MULTIPLIER_U128,
+            },
+        },
+    },
+    anchor_lang::prelude::*,
+    borsh::BorshSchema,
+    bytemuck::{
+        Pod,
+        Zeroable,
+    },
+    std::{
+        cmp::Ordering,
+        convert::{
+            TryFrom,
+            TryInto,
+        },
+    },
+};
+
+// cumulative reward per PYTH delegated to a publisher
+#[derive(
+    AnchorSerialize,
+    AnchorDeserialize,
+    BorshSchema,
+    Clone,
+    Copy,
+    Zeroable,
+    Pod,
+    Eq,
+    Debug,
+    PartialEq,
+    Default,
+)]
+#[repr(C)]
+pub struct RewardIndex {
+    // reward of the publisher stake account
+    pub self_reward:    frac64,
+    // reward of the other stake accounts, including the delegation fee
+    pub other_reward:   frac64,
+    // part of other_reward that goes to the publisher as delegation fee
+    pub delegation_fee: frac64,
+}
+
+impl RewardIndex {
+    pub fn add(&mut self, other: &RewardIndex) {
+        self.self_reward += other.self_reward;
+        self.other_reward += other.other_reward;
+        self.delegation_fee += other.delegation_fee;
+    }
+
+    pub fn sub(&mut self, other: &RewardIndex) {
+        self.self_reward -= other.self_reward;
+        self.other_reward -= other.other_reward;
+        self.delegation_fee -= other.delegation_fee;
+    }
+
+    // calculate the reward in pyth with decimals for an amount that has been delegated during
+    // the epochs covered by this index
+    // returns (delegator_reward, publisher_reward)
+    pub fn calculate_reward(
+        &self,
+        amount: frac64, // in pyth with decimals
+        is_publisher: bool,
+    ) -> Result<(frac64, frac64)> {
+        if is_publisher {
+            let total_reward =
+                u128::from(amount) * u128::from(self.self_reward) / FRAC_64_MULTIPLIER_U128;
+            return Ok((total_reward.try_into()?, 0));
+        }
+
+        let total_reward =
+            u128::from(amount) * u128::from(self.other_reward) / FRAC_64_MULTIPLIER_U128;
+        let publisher_reward =
+            u128::from(amount) * u128::from(self.delegation_fee) / FRAC_64_MULTIPLIER_U128;
+        let delegator_reward = total_reward - publisher_reward;
+
+        Ok((delegator_reward.try_into()?, publisher_reward.try_into()?))
+    }
+}
+
+// reward index of a publisher at the beginning of an epoch in which its delegation changed
+#[derive(
+    AnchorSerialize,
+    AnchorDeserialize,
+    BorshSchema,
+    Clone,
+    Copy,
+    Zeroable,
+    Pod,
+    Eq,
+    Debug,
+    PartialEq,
+    Default,
+)]
+#[repr(C)]
+pub struct RewardIndexCheckpoint {
+    pub epoch:        u64,
+    pub reward_index: RewardIndex,
+}
+
+// checkpoints of a publisher that have been replaced by a newer checkpoint in the pool data,
+// positions that activated or started unlocking in these epochs still need them once the
+// events don't go back that far
+#[account(zero_copy)]
+#[repr(C)]
+pub struct RewardIndexHistory {
+    // number of checkpoints pushed so far, only the last MAX_REWARD_INDEX_CHECKPOINTS are kept
+    pub num_checkpoints: u64,
+    // circular buffer sorted by epoch
+    pub checkpoints:     [RewardIndexCheckpoint; MAX_REWARD_INDEX_CHECKPOINTS],
+}
+
+impl RewardIndexHistory {
+    pub const LEN: usize = 8 + std::mem::size_of::<RewardIndexHistory>();
+
+    fn get_checkpoint(&self, index: usize) -> &RewardIndexCheckpoint {
+        &self.checkpoints[index % MAX_REWARD_INDEX_CHECKPOINTS]
+    }
+
+    pub fn get_reward_index(&self, epoch: u64) -> Option<RewardIndex> {
+        let mut high = usize::try_from(self.num_checkpoints).ok()?;
+        let mut low = high.saturating_sub(MAX_REWARD_INDEX_CHECKPOINTS);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let checkpoint = self.get_checkpoint(mid);
+            match checkpoint.epoch.cmp(&epoch) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Some(checkpoint.reward_index),
+            }
+        }
+        None
+    }
+
+    pub fn push(&mut self, checkpoint: RewardIndexCheckpoint) -> Result<()> {
+        let num_checkpoints = usize::try_from(self.num_checkpoints)?;
+        if num_checkpoints > 0 {
+            require_gt!(
+                checkpoint.epoch,
+                self.get_checkpoint(num_checkpoints - 1).epoch,
+                IntegrityPoolError::ThisCodeShouldBeUnreachable
+            );
+        }
+        self.checkpoints[num_checkpoints % MAX_REWARD_INDEX_CHECKPOINTS] = checkpoint;
+        self.num_checkpoints += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::utils::types::FRAC_64_MULTIPLIER,
+    };
+
+    #[test]
+    fn test_add_sub() {
+        let mut index = RewardIndex {
+            self_reward:    10,
+            other_reward:   20,
+            delegation_fee: 2,
+        };
+        let delta = RewardIndex {
+            self_reward:    1,
+            other_reward:   2,
+            delegation_fee: 1,
+        };
+
+        index.add(&delta);
+        assert_eq!(
+            index,
+            RewardIndex {
+                self_reward:    11,
+                other_reward:   22,
+                delegation_fee: 3,
+            }
+        );
+
+        index.sub(&delta);
+        index.sub(&delta);
+        assert_eq!(
+            index,
+            RewardIndex {
+                self_reward:    9,
+                other_reward:   18,
+                delegation_fee: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_calculate_reward() {
+        let index = RewardIndex {
+            // 20%
+            self_reward:    FRAC_64_MULTIPLIER / 5,
+            // 10%
+            other_reward:   FRAC_64_MULTIPLIER / 10,
+            // 1%
+            delegation_fee: FRAC_64_MULTIPLIER / 100,
+        };
+
+        assert_eq!(
+            index
+                .calculate_reward(100 * FRAC_64_MULTIPLIER, true)
+                .unwrap(),
+            (20 * FRAC_64_MULTIPLIER, 0)
+        );
+        assert_eq!(
+            index
+                .calculate_reward(100 * FRAC_64_MULTIPLIER, false)
+                .unwrap(),
+            (9 * FRAC_64_MULTIPLIER, FRAC_64_MULTIPLIER)
+        );
+    }
+
+    #[test]
+    fn test_reward_index_history() {
+        let mut history = RewardIndexHistory {
+            num_checkpoints: 0,
+            checkpoints:     [RewardIndexCheckpoint::default(); MAX_REWARD_INDEX_CHECKPOINTS],
+        };
+        assert_eq!(history.get_reward_index(3), None);
+
+        let reward_index = RewardIndex {
+            self_reward:    1,
+            other_reward:   2,
+            delegation_fee: 0,
+        };
+        history
+            .push(RewardIndexCheckpoint {
+                epoch: 3,
+                reward_index,
+            })
+            .unwrap();
+        history
+            .push(RewardIndexCheckpoint {
+                epoch:        5,
+                reward_index: RewardIndex::default(),
+            })
+            .unwrap();
+        assert!(history
+            .push(RewardIndexCheckpoint {
+                epoch:        5,
+                reward_index: RewardIndex::default(),
+            })
+            .is_err());
+
+        assert_eq!(history.get_reward_index(3), Some(reward_index));
+        assert_eq!(history.get_reward_index(4), None);
+        assert_eq!(history.get_reward_index(5), Some(RewardIndex::default()));
+    }
+
+    #[test]
+    fn test_reward_index_history_wraps_around() {
+        let mut history = RewardIndexHistory {
+            num_checkpoints: 0,
+            checkpoints:     [RewardIndexCheckpoint::default(); MAX_REWARD_INDEX_CHECKPOINTS],
+        };
+        let checkpoint = |epoch: u64| RewardIndexCheckpoint {
+            epoch,
+            reward_index: RewardIndex {
+                self_reward:    epoch,
+                other_reward:   epoch,
+                delegation_fee: 0,
+            },
+        };
+
+        // one checkpoint every other epoch, the oldest ones get overwritten
+        let num_checkpoints = MAX_REWARD_INDEX_CHECKPOINTS + 10;
+        for i in 1..=num_checkpoints {
+            history.push(checkpoint(2 * i as u64)).unwrap();
+        }
+        assert_eq!(history.num_checkpoints, num_checkpoints as u64);
+
+        for i in 1..=10 {
+            assert_eq!(history.get_reward_index(2 * i), None);
+        }
+        for i in 11..=num_checkpoints {
+            assert_eq!(
+                history.get_reward_index(2 * i as u64),
+                Some(checkpoint(2 * i as u64).reward_index)
+            );
+            assert_eq!(history.get_reward_index(2 * i as u64 + 1), None);
+        }
+
+        // checkpoints still need to be pushed in order
+        assert!(history.push(checkpoint(2)).is_err());
+    }
+}
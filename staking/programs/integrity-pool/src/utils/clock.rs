@@ -1,25 +1,10 @@
-use {
-    anchor_lang::{
-        prelude::*,
-        solana_program::clock::UnixTimestamp,
-    },
-    std::convert::TryInto,
+//! The integrity pool uses the clock of the staking program, so both programs agree on the
+//! current epoch even if governance changes `GlobalConfig.epoch_duration`, and the mock clock of
+//! the staking program also applies to the integrity pool. The instructions that need the
+//! current epoch take the staking `GlobalConfig` as their `config_account`, this includes
+//! `advance`, `set_publisher_delegation_fee` and `create_slash_event`.
+pub use staking::utils::clock::{
+    get_current_epoch,
+    get_current_time,
+    time_to_epoch,
 };
-
-pub const EPOCH_DURATION: u64 = 60 * 60 * 24 * 7; // 1 week
-
-/// Computes Pyth clock.
-/// Right now it's just the current Unix timestamp divided by the epoch duration.
-pub fn get_current_epoch() -> Result<u64> {
-    let now_ts = get_current_time();
-    time_to_epoch(now_ts)
-}
-
-pub fn time_to_epoch(now_ts: UnixTimestamp) -> Result<u64> {
-    // divide now_ts by EPOCH_DURATION
-    Ok(TryInto::<u64>::try_into(now_ts)? / EPOCH_DURATION)
-}
-
-pub fn get_current_time() -> UnixTimestamp {
-    Clock::get().unwrap().unix_timestamp
-}
@@ -1,7 +1,14 @@
 pub const MAX_PUBLISHERS: usize = 1024;
 pub const MAX_EVENTS: usize = 52;
+pub const MAX_FEE_HISTORY: usize = 4;
+pub const DEFAULT_FEE_NOTICE_EPOCHS: u64 = 2;
+pub const MAX_FEE_NOTICE_EPOCHS: u64 = 52;
+// the reward index history is allocated at once, so it has to stay under the 10 KiB an account
+// can be allocated with in a CPI
+pub const MAX_REWARD_INDEX_CHECKPOINTS: usize = 128;
 
 
 pub const POOL_CONFIG: &str = "pool_config";
 pub const DELEGATION_RECORD: &str = "delegation_record";
 pub const SLASH_EVENT: &str = "slash_event";
+pub const REWARD_INDEX_HISTORY: &str = "reward_index_history";
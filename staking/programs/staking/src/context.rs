@@ -5,6 +5,7 @@ use {
     },
     anchor_lang::prelude::*,
     anchor_spl::token::{
+        CloseAccount,
         Mint,
         Token,
         TokenAccount,
@@ -20,6 +21,17 @@ pub const VOTER_RECORD_SEED: &str = "voter_weight";
 pub const TARGET_SEED: &str = "target";
 pub const MAX_VOTER_RECORD_SEED: &str = "max_voter";
 pub const VOTING_TARGET_SEED: &str = "voting";
+// Seed and `auto_compound` and `registered` offsets of the delegation records of the pool
+// authority's program
+pub const DELEGATION_RECORD_SEED: &str = "delegation_record";
+pub const DELEGATION_RECORD_AUTO_COMPOUND_OFFSET: usize = 48;
+pub const DELEGATION_RECORD_REGISTERED_OFFSET: usize = 49;
+// Offsets of `PoolConfig.pool_data`, `PoolData.publishers` and `PoolData.publisher_stake_accounts`
+// in the accounts of the pool authority's program
+pub const POOL_CONFIG_POOL_DATA_OFFSET: usize = 8;
+pub const POOL_DATA_PUBLISHERS_OFFSET: usize = 24;
+pub const POOL_DATA_PUBLISHER_STAKE_ACCOUNTS_OFFSET: usize = 65560;
+pub const POOL_DATA_MAX_PUBLISHERS: usize = 1024;
 pub const SPLIT_REQUEST: &str = "split_request";
 
 #[derive(Accounts)]
@@ -49,6 +61,20 @@ pub struct UpdateGovernanceAuthority<'info> {
     pub config:               Account<'info, global_config::GlobalConfig>,
 }
 
+#[derive(Accounts)]
+pub struct AcceptGovernanceAuthority<'info> {
+    pub new_governance_authority: Signer<'info>,
+    #[account(mut, seeds = [CONFIG_SEED.as_bytes()], bump = config.bump, constraint = config.pending_governance_authority == Some(new_governance_authority.key()) @ ErrorCode::InvalidPendingAuthority)]
+    pub config:                   Account<'info, global_config::GlobalConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CancelGovernanceAuthorityTransfer<'info> {
+    pub governance_authority: Signer<'info>,
+    #[account(mut, seeds = [CONFIG_SEED.as_bytes()], bump = config.bump, has_one = governance_authority)]
+    pub config:               Account<'info, global_config::GlobalConfig>,
+}
+
 #[derive(Accounts)]
 #[instruction(new_authority: Pubkey)]
 pub struct UpdatePdaAuthority<'info> {
@@ -57,6 +83,20 @@ pub struct UpdatePdaAuthority<'info> {
     pub config:        Account<'info, global_config::GlobalConfig>,
 }
 
+#[derive(Accounts)]
+pub struct AcceptPdaAuthority<'info> {
+    pub new_pda_authority: Signer<'info>,
+    #[account(mut, seeds = [CONFIG_SEED.as_bytes()], bump = config.bump, constraint = config.pending_pda_authority == Some(new_pda_authority.key()) @ ErrorCode::InvalidPendingAuthority)]
+    pub config:            Account<'info, global_config::GlobalConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CancelPdaAuthorityTransfer<'info> {
+    pub pda_authority: Signer<'info>,
+    #[account(mut, seeds = [CONFIG_SEED.as_bytes()], bump = config.bump, has_one = pda_authority)]
+    pub config:        Account<'info, global_config::GlobalConfig>,
+}
+
 #[derive(Accounts)]
 #[instruction(token_list_time: Option<i64>)]
 pub struct UpdateTokenListTime<'info> {
@@ -65,6 +105,20 @@ pub struct UpdateTokenListTime<'info> {
     pub config:               Account<'info, global_config::GlobalConfig>,
 }
 
+#[derive(Accounts)]
+pub struct UpdateUnlockingDurations<'info> {
+    pub governance_authority: Signer<'info>,
+    #[account(mut, seeds = [CONFIG_SEED.as_bytes()], bump = config.bump, has_one = governance_authority)]
+    pub config:               Account<'info, global_config::GlobalConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateEpochDuration<'info> {
+    pub governance_authority: Signer<'info>,
+    #[account(mut, seeds = [CONFIG_SEED.as_bytes()], bump = config.bump, has_one = governance_authority)]
+    pub config:               Account<'info, global_config::GlobalConfig>,
+}
+
 #[derive(Accounts)]
 #[instruction(agreement_hash: [u8; 32])]
 pub struct UpdateAgreementHash<'info> {
@@ -81,6 +135,20 @@ pub struct UpdatePoolAuthority<'info> {
     pub config:               Account<'info, global_config::GlobalConfig>,
 }
 
+#[derive(Accounts)]
+pub struct AcceptPoolAuthority<'info> {
+    pub new_pool_authority: Signer<'info>,
+    #[account(mut, seeds = [CONFIG_SEED.as_bytes()], bump = config.bump, constraint = config.pending_pool_authority == Some(new_pool_authority.key()) @ ErrorCode::InvalidPendingAuthority)]
+    pub config:             Account<'info, global_config::GlobalConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CancelPoolAuthorityTransfer<'info> {
+    pub governance_authority: Signer<'info>,
+    #[account(mut, seeds = [CONFIG_SEED.as_bytes()], bump = config.bump, has_one = governance_authority)]
+    pub config:               Account<'info, global_config::GlobalConfig>,
+}
+
 #[derive(Accounts)]
 #[instruction(owner : Pubkey, lock : vesting::VestingSchedule)]
 pub struct CreateStakeAccount<'info> {
@@ -176,16 +244,96 @@ impl<'a, 'b, 'c, 'info> From<&WithdrawStake<'info>>
     }
 }
 
+#[derive(Accounts)]
+pub struct CloseStakeAccount<'info> {
+    // Native payer, receives the rent of the closed accounts:
+    #[account(mut)]
+    pub owner:                       Signer<'info>,
+    // Stake program accounts:
+    #[account(mut, close = owner)]
+    pub stake_account_positions:     AccountLoader<'info, positions::PositionData>,
+    #[account(mut, close = owner, seeds = [STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()], bump = stake_account_metadata.metadata_bump, has_one = owner)]
+    pub stake_account_metadata:      Account<'info, stake_account::StakeAccountMetadataV2>,
+    #[account(
+        mut,
+        seeds = [CUSTODY_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump = stake_account_metadata.custody_bump,
+    )]
+    pub stake_account_custody:       Account<'info, TokenAccount>,
+    /// CHECK : This AccountInfo is safe because it's a checked PDA
+    #[account(seeds = [AUTHORITY_SEED.as_bytes(), stake_account_positions.key().as_ref()], bump = stake_account_metadata.authority_bump)]
+    pub custody_authority:           AccountInfo<'info>,
+    /// CHECK : This AccountInfo is safe because it's a checked PDA, it's closed if it exists
+    #[account(mut, seeds = [VOTER_RECORD_SEED.as_bytes(), stake_account_positions.key().as_ref()], bump)]
+    pub voter_record:                AccountInfo<'info>,
+    /// CHECK : This AccountInfo is safe because it's a checked PDA, it's closed if it exists
+    #[account(mut, seeds = [SPLIT_REQUEST.as_bytes(), stake_account_positions.key().as_ref()], bump)]
+    pub stake_account_split_request: AccountInfo<'info>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config:                      Account<'info, global_config::GlobalConfig>,
+    /// CHECK : The pool config of the pool authority's program, it stores the address of the pool
+    /// data
+    #[account(address = config.pool_authority @ ErrorCode::InvalidPoolAuthority)]
+    pub pool_config:                 AccountInfo<'info>,
+    /// CHECK : The pool data of the pool authority's program, it's checked in the instruction
+    pub pool_data:                   AccountInfo<'info>,
+    // Primitive accounts :
+    pub token_program:               Program<'info, Token>,
+}
+
+impl<'a, 'b, 'c, 'info> From<&CloseStakeAccount<'info>>
+    for CpiContext<'a, 'b, 'c, 'info, CloseAccount<'info>>
+{
+    fn from(
+        accounts: &CloseStakeAccount<'info>,
+    ) -> CpiContext<'a, 'b, 'c, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account:     accounts.stake_account_custody.to_account_info(),
+            destination: accounts.owner.to_account_info(),
+            authority:   accounts.custody_authority.to_account_info(),
+        };
+        let cpi_program = accounts.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
+
+#[derive(Accounts)]
+pub struct CheckDelegationRecords<'info> {
+    // Native payer, pays for the reallocation of accounts created before the count existed:
+    #[account(mut)]
+    pub payer:                   Signer<'info>,
+    // Stake program accounts:
+    pub stake_account_positions: AccountLoader<'info, positions::PositionData>,
+    #[account(
+        mut,
+        seeds = [STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump = stake_account_metadata.metadata_bump,
+        realloc = stake_account::StakeAccountMetadataV2::LEN,
+        realloc::payer = payer,
+        realloc::zero = false,
+    )]
+    pub stake_account_metadata:  Account<'info, stake_account::StakeAccountMetadataV2>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config:                  Account<'info, global_config::GlobalConfig>,
+    /// CHECK : The pool config of the pool authority's program, it stores the address of the pool
+    /// data
+    #[account(address = config.pool_authority @ ErrorCode::InvalidPoolAuthority)]
+    pub pool_config:             AccountInfo<'info>,
+    /// CHECK : The pool data of the pool authority's program, it's checked in the instruction
+    pub pool_data:               AccountInfo<'info>,
+    pub system_program:          Program<'info, System>,
+}
+
 #[derive(Accounts)]
 #[instruction(target_with_parameters:   positions::TargetWithParameters, amount : u64)]
 pub struct CreatePosition<'info> {
-    // Native payer:
+    // Native payer, the owner or the operator of the stake account:
     #[account(mut)]
     pub owner:                   Signer<'info>,
     // Stake program accounts:
     #[account(mut)]
     pub stake_account_positions: AccountLoader<'info, positions::PositionData>,
-    #[account(mut, seeds = [STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()], bump = stake_account_metadata.metadata_bump, has_one = owner)]
+    #[account(mut, seeds = [STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()], bump = stake_account_metadata.metadata_bump, constraint = stake_account_metadata.is_owner_or_operator(owner.key) @ ErrorCode::OwnerOrOperatorNeedsToSign)]
     pub stake_account_metadata:  Account<'info, stake_account::StakeAccountMetadataV2>,
     #[account(
         seeds = [CUSTODY_SEED.as_bytes(), stake_account_positions.key().as_ref()],
@@ -204,6 +352,53 @@ pub struct CreatePosition<'info> {
     pub system_program:          Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct CreatePoolPosition<'info> {
+    // Pays for the extra rent, the owner doesn't need to sign
+    #[account(mut)]
+    pub payer:                   Signer<'info>,
+    // Stake program accounts:
+    #[account(mut)]
+    pub stake_account_positions: AccountLoader<'info, positions::PositionData>,
+    #[account(mut, seeds = [STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()], bump = stake_account_metadata.metadata_bump)]
+    pub stake_account_metadata:  Account<'info, stake_account::StakeAccountMetadataV2>,
+    #[account(
+        seeds = [CUSTODY_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump = stake_account_metadata.custody_bump,
+    )]
+    pub stake_account_custody:   Account<'info, TokenAccount>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config:                  Account<'info, global_config::GlobalConfig>,
+    pub pool_authority:          Signer<'info>,
+    pub system_program:          Program<'info, System>,
+    /// CHECK : The delegation record of the stake account with the publisher, it's checked in
+    /// the instruction
+    pub delegation_record:       AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateDelegationRecordCount<'info> {
+    // Native payer, pays for the reallocation of accounts created before the count existed:
+    #[account(mut)]
+    pub payer:                   Signer<'info>,
+    // Stake program accounts:
+    pub stake_account_positions: AccountLoader<'info, positions::PositionData>,
+    #[account(
+        mut,
+        seeds = [STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump = stake_account_metadata.metadata_bump,
+        realloc = stake_account::StakeAccountMetadataV2::LEN,
+        realloc::payer = payer,
+        realloc::zero = false,
+    )]
+    pub stake_account_metadata:  Account<'info, stake_account::StakeAccountMetadataV2>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config:                  Account<'info, global_config::GlobalConfig>,
+    #[account(address = config.pool_authority @ ErrorCode::InvalidPoolAuthority)]
+    pub pool_authority:          Signer<'info>,
+    pub system_program:          Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct MergeTargetPositions<'info> {
     /// CHECK : This AccountInfo is safe because it's checked against stake_account_metadata
@@ -220,10 +415,7 @@ pub struct MergeTargetPositions<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(index : u8, amount : u64, target_with_parameters: positions::TargetWithParameters)] // target_with_parameters is in the instruction arguments because it's needed in the anchor PDA
-                                                                                                  // checks
-pub struct ClosePosition<'info> {
-    // Native payer:
+pub struct MigratePositions<'info> {
     #[account(mut)]
     pub owner:                   Signer<'info>,
     // Stake program accounts:
@@ -231,6 +423,47 @@ pub struct ClosePosition<'info> {
     pub stake_account_positions: AccountLoader<'info, positions::PositionData>,
     #[account(mut, seeds = [STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()], bump = stake_account_metadata.metadata_bump, has_one = owner)]
     pub stake_account_metadata:  Account<'info, stake_account::StakeAccountMetadataV2>,
+    pub system_program:          Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(index : u16, amount : u64, target_with_parameters: positions::TargetWithParameters)] // target_with_parameters is in the instruction arguments because it's needed in the anchor PDA
+                                                                                                   // checks
+pub struct ClosePosition<'info> {
+    // Native payer, the owner or the operator of the stake account:
+    #[account(mut)]
+    pub owner:                   Signer<'info>,
+    // Stake program accounts:
+    #[account(mut)]
+    pub stake_account_positions: AccountLoader<'info, positions::PositionData>,
+    #[account(mut, seeds = [STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()], bump = stake_account_metadata.metadata_bump, constraint = stake_account_metadata.is_owner_or_operator(owner.key) @ ErrorCode::OwnerOrOperatorNeedsToSign)]
+    pub stake_account_metadata:  Account<'info, stake_account::StakeAccountMetadataV2>,
+    #[account(
+        seeds = [CUSTODY_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump = stake_account_metadata.custody_bump,
+    )]
+    pub stake_account_custody:   Account<'info, TokenAccount>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config:                  Account<'info, global_config::GlobalConfig>,
+    // Target account :
+    #[account(
+        mut,
+        seeds = [TARGET_SEED.as_bytes(), VOTING_TARGET_SEED.as_bytes()],
+        bump = target_account.bump)]
+    pub target_account:          Option<Account<'info, target::TargetMetadata>>,
+    pub pool_authority:          Option<Signer<'info>>,
+    pub system_program:          Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelUnlock<'info> {
+    // The owner or the operator of the stake account:
+    pub owner:                   Signer<'info>,
+    // Stake program accounts:
+    #[account(mut)]
+    pub stake_account_positions: AccountLoader<'info, positions::PositionData>,
+    #[account(seeds = [STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()], bump = stake_account_metadata.metadata_bump, constraint = stake_account_metadata.is_owner_or_operator(owner.key) @ ErrorCode::OwnerOrOperatorNeedsToSign)]
+    pub stake_account_metadata:  Account<'info, stake_account::StakeAccountMetadataV2>,
     #[account(
         seeds = [CUSTODY_SEED.as_bytes(), stake_account_positions.key().as_ref()],
         bump = stake_account_metadata.custody_bump,
@@ -245,6 +478,26 @@ pub struct ClosePosition<'info> {
         bump = target_account.bump)]
     pub target_account:          Option<Account<'info, target::TargetMetadata>>,
     pub pool_authority:          Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct RedelegatePosition<'info> {
+    // Native payer, the owner or the operator of the stake account:
+    #[account(mut)]
+    pub owner:                   Signer<'info>,
+    // Stake program accounts:
+    #[account(mut)]
+    pub stake_account_positions: AccountLoader<'info, positions::PositionData>,
+    #[account(mut, seeds = [STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()], bump = stake_account_metadata.metadata_bump, constraint = stake_account_metadata.is_owner_or_operator(owner.key) @ ErrorCode::OwnerOrOperatorNeedsToSign)]
+    pub stake_account_metadata:  Account<'info, stake_account::StakeAccountMetadataV2>,
+    #[account(
+        seeds = [CUSTODY_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump = stake_account_metadata.custody_bump,
+    )]
+    pub stake_account_custody:   Account<'info, TokenAccount>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config:                  Account<'info, global_config::GlobalConfig>,
+    pub pool_authority:          Signer<'info>,
     pub system_program:          Program<'info, System>,
 }
 
@@ -276,6 +529,42 @@ pub struct UpdateVoterWeight<'info> {
         bump = governance_target.bump)]
     pub governance_target:       Account<'info, target::TargetMetadata>,
 }
+
+#[derive(Accounts)]
+pub struct GetWithdrawableBalance<'info> {
+    pub stake_account_positions: AccountLoader<'info, positions::PositionData>,
+    #[account(seeds = [STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()], bump = stake_account_metadata.metadata_bump)]
+    pub stake_account_metadata:  Account<'info, stake_account::StakeAccountMetadataV2>,
+    #[account(
+        seeds = [CUSTODY_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump = stake_account_metadata.custody_bump,
+    )]
+    pub stake_account_custody:   Account<'info, TokenAccount>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config:                  Account<'info, global_config::GlobalConfig>,
+}
+
+#[derive(Accounts)]
+pub struct GetVoterWeightAtEpoch<'info> {
+    pub stake_account_positions: AccountLoader<'info, positions::PositionData>,
+    #[account(seeds = [STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()], bump = stake_account_metadata.metadata_bump)]
+    pub stake_account_metadata:  Account<'info, stake_account::StakeAccountMetadataV2>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config:                  Account<'info, global_config::GlobalConfig>,
+    // Governance target account:
+    #[account(
+        seeds = [TARGET_SEED.as_bytes(), VOTING_TARGET_SEED.as_bytes()],
+        bump = governance_target.bump)]
+    pub governance_target:       Account<'info, target::TargetMetadata>,
+}
+
+#[derive(Accounts)]
+pub struct GetPositionSummary<'info> {
+    pub stake_account_positions: AccountLoader<'info, positions::PositionData>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config:                  Account<'info, global_config::GlobalConfig>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateMaxVoterWeight<'info> {
     // Native payer:
@@ -316,14 +605,31 @@ pub struct RequestSplit<'info> {
     pub stake_account_positions:     AccountLoader<'info, positions::PositionData>,
     #[account(seeds = [STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()], bump = stake_account_metadata.metadata_bump, has_one = owner)]
     pub stake_account_metadata:      Account<'info, stake_account::StakeAccountMetadataV2>,
-    #[account(init_if_needed, payer = owner, space=split_request::SplitRequest::LEN ,  seeds = [SPLIT_REQUEST.as_bytes(), stake_account_positions.key().as_ref()], bump)]
-    pub stake_account_split_request: Account<'info, split_request::SplitRequest>,
+    /// CHECK : This AccountInfo is safe because it's a checked PDA. It's created if it doesn't
+    /// exist and requests created before the expiry epoch was introduced are resized.
+    #[account(mut, seeds = [SPLIT_REQUEST.as_bytes(), stake_account_positions.key().as_ref()], bump)]
+    pub stake_account_split_request: AccountInfo<'info>,
     #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
     pub config:                      Account<'info, global_config::GlobalConfig>,
     // Primitive accounts :
     pub system_program:              Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct CancelSplit<'info> {
+    // Native payer, receives the rent of the split request:
+    #[account(mut)]
+    pub owner:                       Signer<'info>,
+    // Stake program accounts:
+    pub stake_account_positions:     AccountLoader<'info, positions::PositionData>,
+    #[account(seeds = [STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()], bump = stake_account_metadata.metadata_bump, has_one = owner)]
+    pub stake_account_metadata:      Account<'info, stake_account::StakeAccountMetadataV2>,
+    /// CHECK : This AccountInfo is safe because it's a checked PDA. It isn't deserialized so that
+    /// requests created before the expiry epoch was introduced can be closed too.
+    #[account(mut, owner = crate::ID, seeds = [SPLIT_REQUEST.as_bytes(), stake_account_positions.key().as_ref()], bump)]
+    pub stake_account_split_request: AccountInfo<'info>,
+}
+
 #[derive(Accounts)]
 #[instruction(amount: u64, recipient: Pubkey)]
 pub struct AcceptSplit<'info> {
@@ -335,8 +641,11 @@ pub struct AcceptSplit<'info> {
     pub source_stake_account_positions:     AccountLoader<'info, positions::PositionData>,
     #[account(mut, seeds = [STAKE_ACCOUNT_METADATA_SEED.as_bytes(), source_stake_account_positions.key().as_ref()], bump = source_stake_account_metadata.metadata_bump)]
     pub source_stake_account_metadata: Box<Account<'info, stake_account::StakeAccountMetadataV2>>,
-    #[account(mut, seeds = [SPLIT_REQUEST.as_bytes(), source_stake_account_positions.key().as_ref()], bump)]
-    pub source_stake_account_split_request: Box<Account<'info, split_request::SplitRequest>>,
+    /// CHECK : This AccountInfo is safe because it's a checked PDA. It's deserialized in the
+    /// instruction so that requests created before the expiry epoch was introduced can be
+    /// accepted.
+    #[account(mut, owner = crate::ID, seeds = [SPLIT_REQUEST.as_bytes(), source_stake_account_positions.key().as_ref()], bump)]
+    pub source_stake_account_split_request: AccountInfo<'info>,
     #[account(
         mut,
         seeds = [CUSTODY_SEED.as_bytes(), source_stake_account_positions.key().as_ref()],
@@ -373,6 +682,11 @@ pub struct AcceptSplit<'info> {
     pub rent:            Sysvar<'info, Rent>,
     pub token_program:   Program<'info, Token>,
     pub system_program:  Program<'info, System>,
+
+    // Only required if the account has integrity pool positions, the pool authority splits their
+    // delegation records
+    #[account(address = config.pool_authority @ ErrorCode::InvalidPoolAuthority)]
+    pub pool_authority: Option<Signer<'info>>,
 }
 
 impl<'a, 'b, 'c, 'info> From<&AcceptSplit<'info>>
@@ -402,6 +716,29 @@ pub struct JoinDaoLlc<'info> {
     pub config:                  Account<'info, global_config::GlobalConfig>,
 }
 
+#[derive(Accounts)]
+#[instruction(operator : Option<Pubkey>)]
+pub struct SetOperator<'info> {
+    // Native payer, pays for the reallocation of accounts created before the operator existed:
+    #[account(mut)]
+    pub owner:                   Signer<'info>,
+    // Stake program accounts:
+    pub stake_account_positions: AccountLoader<'info, positions::PositionData>,
+    #[account(
+        mut,
+        seeds = [STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump = stake_account_metadata.metadata_bump,
+        has_one = owner,
+        realloc = stake_account::StakeAccountMetadataV2::LEN,
+        realloc::payer = owner,
+        realloc::zero = false,
+    )]
+    pub stake_account_metadata:  Account<'info, stake_account::StakeAccountMetadataV2>,
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump)]
+    pub config:                  Account<'info, global_config::GlobalConfig>,
+    pub system_program:          Program<'info, System>,
+}
+
 // Anchor's parser doesn't understand cfg(feature), so the IDL gets messed
 // up if we try to use it here. We can just keep the definition the same.
 #[derive(Accounts)]
@@ -476,6 +813,80 @@ pub struct TransferAccount<'info> {
     pub config: Account<'info, global_config::GlobalConfig>,
 }
 
+#[derive(Accounts)]
+pub struct AccelerateVesting<'info> {
+    pub governance_authority: Signer<'info>,
+
+    // Stake program accounts:
+    pub stake_account_positions: AccountLoader<'info, positions::PositionData>,
+
+    #[account(
+        mut,
+        seeds = [STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump = stake_account_metadata.metadata_bump,
+    )]
+    pub stake_account_metadata: Account<'info, stake_account::StakeAccountMetadataV2>,
+
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump, has_one = governance_authority)]
+    pub config: Account<'info, global_config::GlobalConfig>,
+}
+
+#[derive(Accounts)]
+pub struct Clawback<'info> {
+    pub governance_authority: Signer<'info>,
+
+    // Stake program accounts:
+    #[account(mut)]
+    pub stake_account_positions: AccountLoader<'info, positions::PositionData>,
+
+    #[account(
+        mut,
+        seeds = [STAKE_ACCOUNT_METADATA_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump = stake_account_metadata.metadata_bump,
+    )]
+    pub stake_account_metadata: Account<'info, stake_account::StakeAccountMetadataV2>,
+
+    #[account(
+        mut,
+        seeds = [CUSTODY_SEED.as_bytes(), stake_account_positions.key().as_ref()],
+        bump = stake_account_metadata.custody_bump,
+    )]
+    pub stake_account_custody: Account<'info, TokenAccount>,
+
+    /// CHECK : This AccountInfo is safe because it's a checked PDA
+    #[account(seeds = [AUTHORITY_SEED.as_bytes(), stake_account_positions.key().as_ref()], bump = stake_account_metadata.authority_bump)]
+    pub custody_authority: AccountInfo<'info>,
+
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump = config.bump, has_one = governance_authority)]
+    pub config: Account<'info, global_config::GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [TARGET_SEED.as_bytes(), VOTING_TARGET_SEED.as_bytes()],
+        bump = governance_target_account.bump
+    )]
+    pub governance_target_account: Account<'info, target::TargetMetadata>,
+
+    // transfer the unvested tokens to this account
+    #[account(mut)]
+    pub treasury: Account<'info, TokenAccount>,
+
+    // Primitive accounts :
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'a, 'b, 'c, 'info> From<&Clawback<'info>> for CpiContext<'a, 'b, 'c, 'info, Transfer<'info>> {
+    fn from(accounts: &Clawback<'info>) -> CpiContext<'a, 'b, 'c, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from:      accounts.stake_account_custody.to_account_info(),
+            to:        accounts.treasury.to_account_info(),
+            authority: accounts.custody_authority.to_account_info(),
+        };
+        let cpi_program = accounts.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
+
 #[derive(Accounts)]
 #[instruction(slash_ratio: u64)]
 pub struct SlashAccount<'info> {
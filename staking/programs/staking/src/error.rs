@@ -86,4 +86,45 @@ pub enum ErrorCode {
     UnexpectedTargetAccount,
     #[msg("Other")] //6040
     Other,
+    #[msg("Positions can only be redelegated between different integrity pool publishers")] // 6041
+    InvalidRedelegationTarget,
+    #[msg("Only positions that started unlocking this epoch can be re-locked")] // 6042
+    NotPreunlocking,
+    #[msg("The unlocking duration should be at least 1 epoch and fit in a u32")] // 6043
+    InvalidUnlockingDuration,
+    #[msg("Can't close a stake account with staking positions. Unstake first.")] // 6044
+    CloseWithStake,
+    #[msg("Can't close a stake account with tokens in custody. Withdraw first.")] // 6045
+    CloseWithBalance,
+    #[msg("Integrity pool positions can only be split through the pool authority")] // 6046
+    SplitWithDelegation,
+    #[msg("The split request has expired")] // 6047
+    SplitRequestExpired,
+    #[msg("The new vesting schedule must not vest slower than the current one")] // 6048
+    InvalidVestingAcceleration,
+    #[msg("The positions account already has the latest layout")] // 6049
+    PositionsAlreadyMigrated,
+    #[msg("The owner or the operator of the stake account needs to sign")] // 6050
+    OwnerOrOperatorNeedsToSign,
+    #[msg("The signer is not the nominated authority")] // 6051
+    InvalidPendingAuthority,
+    #[msg("The stake account hasn't opted into auto-compounding for this publisher")] // 6052
+    AutoCompoundNotEnabled,
+    #[msg("Can't close a stake account with delegation records. Close them first.")] // 6053
+    CloseWithDelegationRecords,
+    #[msg("Can't close the stake account of a publisher")] // 6054
+    ClosePublisherStakeAccount,
+    #[msg("The pool data doesn't match the pool config")] // 6055
+    InvalidPoolData,
+    #[msg("The time is before the previous change of the epoch duration")] // 6056
+    TimeBeforeEpochDurationChange,
+    #[msg("The epoch duration was changed less than an epoch ago")] // 6057
+    EpochDurationChangeTooSoon,
+    #[msg("The delegation records of the stake account need to be checked before closing it")]
+    // 6058
+    UncheckedDelegationRecords,
+    #[msg("The account isn't the delegation record of the next publisher")] // 6059
+    InvalidDelegationRecord,
+    #[msg("The delegation record isn't counted yet, advance it first")] // 6060
+    UnregisteredDelegationRecord,
 }
@@ -0,0 +1,148 @@
+use {
+    crate::state::{
+        global_config::GlobalConfig,
+        positions::TargetWithParameters,
+        vesting::VestingSchedule,
+    },
+    anchor_lang::prelude::*,
+};
+
+#[event]
+pub struct ConfigUpdated {
+    pub config: GlobalConfig,
+    pub epoch:  u64,
+}
+
+#[event]
+pub struct PositionsMigrated {
+    pub stake_account_positions: Pubkey,
+    pub next_index:              u16,
+}
+
+/// `merged` is true when `index` is a position that already existed, in that case `amount` was
+/// added to it and the position holds more than `amount`.
+#[event]
+pub struct PositionCreated {
+    pub stake_account_positions: Pubkey,
+    pub index:                   u16,
+    pub target_with_parameters:  TargetWithParameters,
+    pub amount:                  u64,
+    pub merged:                  bool,
+    pub epoch:                   u64,
+}
+
+/// `unlocking_index` is the index of the position that holds the closed amount while it unlocks.
+/// It's `None` when the position was still locking and the amount was removed right away, if all
+/// of it was removed, the last position of the account moves to `index`.
+#[event]
+pub struct PositionClosed {
+    pub stake_account_positions: Pubkey,
+    pub index:                   u16,
+    pub unlocking_index:         Option<u16>,
+    pub target_with_parameters:  TargetWithParameters,
+    pub amount:                  u64,
+    pub epoch:                   u64,
+}
+
+#[event]
+pub struct UnlockCancelled {
+    pub stake_account_positions: Pubkey,
+    pub index:                   u16,
+    pub target_with_parameters:  TargetWithParameters,
+    pub amount:                  u64,
+    pub epoch:                   u64,
+}
+
+#[event]
+pub struct PositionRedelegated {
+    pub stake_account_positions:    Pubkey,
+    pub index:                      u16,
+    pub new_index:                  u16,
+    pub target_with_parameters:     TargetWithParameters,
+    pub new_target_with_parameters: TargetWithParameters,
+    pub amount:                     u64,
+    pub epoch:                      u64,
+}
+
+/// The positions of the target are rewritten, `next_index` is the number of positions of the
+/// account after the merge.
+#[event]
+pub struct TargetPositionsMerged {
+    pub stake_account_positions: Pubkey,
+    pub target_with_parameters:  TargetWithParameters,
+    pub next_index:              u16,
+    pub epoch:                   u64,
+}
+
+#[event]
+pub struct StakeWithdrawn {
+    pub stake_account_positions: Pubkey,
+    pub amount:                  u64,
+    pub epoch:                   u64,
+}
+
+#[event]
+pub struct SplitAccepted {
+    pub source_stake_account_positions: Pubkey,
+    pub new_stake_account_positions:    Pubkey,
+    pub recipient:                      Pubkey,
+    pub amount:                         u64,
+    pub epoch:                          u64,
+}
+
+#[event]
+pub struct AccountSlashed {
+    pub stake_account_positions: Pubkey,
+    pub publisher:               Pubkey,
+    pub slash_ratio:             u64,
+    pub total_slashed:           u64,
+    pub locked_slashed:          u64,
+    pub preunlocking_slashed:    u64,
+    pub epoch:                   u64,
+}
+
+#[event]
+pub struct AccountTransferred {
+    pub stake_account_positions: Pubkey,
+    pub previous_owner:          Pubkey,
+    pub new_owner:               Pubkey,
+    pub epoch:                   u64,
+}
+
+#[event]
+pub struct AccountRecovered {
+    pub stake_account_positions: Pubkey,
+    pub previous_owner:          Pubkey,
+    pub new_owner:               Pubkey,
+    pub epoch:                   u64,
+}
+
+#[event]
+pub struct JoinedDaoLlc {
+    pub stake_account_positions: Pubkey,
+    pub agreement_hash:          [u8; 32],
+    pub epoch:                   u64,
+}
+
+#[event]
+pub struct OperatorSet {
+    pub stake_account_positions: Pubkey,
+    pub operator:                Option<Pubkey>,
+    pub epoch:                   u64,
+}
+
+#[event]
+pub struct VestingAccelerated {
+    pub stake_account_positions: Pubkey,
+    pub previous_lock:           VestingSchedule,
+    pub new_lock:                VestingSchedule,
+    pub epoch:                   u64,
+}
+
+#[event]
+pub struct UnvestedTokensClawedBack {
+    pub stake_account_positions: Pubkey,
+    pub treasury:                Pubkey,
+    pub amount:                  u64,
+    pub epoch:                   u64,
+}
@@ -6,8 +6,12 @@
 use {
     crate::error::ErrorCode,
     anchor_lang::prelude::*,
-    anchor_spl::token::transfer,
+    anchor_spl::token::{
+        close_account,
+        transfer,
+    },
     context::*,
+    events::*,
     spl_governance::state::{
         governance::get_governance_data_for_realm,
         proposal::{
@@ -20,16 +24,25 @@ use {
         max_voter_weight_record::MAX_VOTER_WEIGHT,
         positions::{
             DynamicPositionArray,
+            LockedBalanceSummary,
             Position,
             PositionState,
+            RedelegationMarker,
             SlashedAmounts,
             TargetWithParameters,
         },
+        split_request::{
+            SplitRequest,
+            SPLIT_REQUEST_DURATION,
+        },
         vesting::VestingSchedule,
         voter_weight_record::VoterWeightAction,
     },
+    std::convert::TryInto,
     utils::{
+        account::check_pool_data,
         clock::{
+            epoch_to_time,
             get_current_epoch,
             time_to_epoch,
         },
@@ -37,9 +50,9 @@ use {
     },
 };
 
-
 pub mod context;
 pub mod error;
+pub mod events;
 pub mod state;
 pub mod utils;
 #[cfg(feature = "wasm")]
@@ -77,21 +90,89 @@ pub mod staking {
         Ok(())
     }
 
+    /// Nominates `new_authority` as the governance authority, it becomes the governance authority
+    /// once it calls `accept_governance_authority`
     pub fn update_governance_authority(
         ctx: Context<UpdateGovernanceAuthority>,
         new_authority: Pubkey,
     ) -> Result<()> {
         let config = &mut ctx.accounts.config;
-        config.governance_authority = new_authority;
+        config.pending_governance_authority = Some(new_authority);
+
+        emit!(ConfigUpdated {
+            config: (**config).clone(),
+            epoch:  get_current_epoch(config)?,
+        });
+
+        Ok(())
+    }
+
+    pub fn accept_governance_authority(ctx: Context<AcceptGovernanceAuthority>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.governance_authority = ctx.accounts.new_governance_authority.key();
+        config.pending_governance_authority = None;
+
+        emit!(ConfigUpdated {
+            config: (**config).clone(),
+            epoch:  get_current_epoch(config)?,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel_governance_authority_transfer(
+        ctx: Context<CancelGovernanceAuthorityTransfer>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.pending_governance_authority = None;
+
+        emit!(ConfigUpdated {
+            config: (**config).clone(),
+            epoch:  get_current_epoch(config)?,
+        });
+
         Ok(())
     }
 
+    /// Nominates `new_authority` as the PDA authority, it becomes the PDA authority once it calls
+    /// `accept_pda_authority`
     pub fn update_pda_authority(
         ctx: Context<UpdatePdaAuthority>,
         new_authority: Pubkey,
     ) -> Result<()> {
         let config = &mut ctx.accounts.config;
-        config.pda_authority = new_authority;
+        config.pending_pda_authority = Some(new_authority);
+
+        emit!(ConfigUpdated {
+            config: (**config).clone(),
+            epoch:  get_current_epoch(config)?,
+        });
+
+        Ok(())
+    }
+
+    pub fn accept_pda_authority(ctx: Context<AcceptPdaAuthority>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.pda_authority = ctx.accounts.new_pda_authority.key();
+        config.pending_pda_authority = None;
+
+        emit!(ConfigUpdated {
+            config: (**config).clone(),
+            epoch:  get_current_epoch(config)?,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel_pda_authority_transfer(ctx: Context<CancelPdaAuthorityTransfer>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.pending_pda_authority = None;
+
+        emit!(ConfigUpdated {
+            config: (**config).clone(),
+            epoch:  get_current_epoch(config)?,
+        });
+
         Ok(())
     }
 
@@ -101,6 +182,94 @@ pub mod staking {
     ) -> Result<()> {
         let config = &mut ctx.accounts.config;
         config.pyth_token_list_time = token_list_time;
+
+        emit!(ConfigUpdated {
+            config: (**config).clone(),
+            epoch:  get_current_epoch(config)?,
+        });
+
+        Ok(())
+    }
+
+    /// Sets the number of epochs positions of each target stay in the UNLOCKING state, `None`
+    /// restores the default of `UNLOCKING_DURATION`. Positions that are already unlocking keep the
+    /// unlocking duration they were closed with.
+    pub fn update_unlocking_durations(
+        ctx: Context<UpdateUnlockingDurations>,
+        voting_unlocking_duration: Option<u64>,
+        integrity_pool_unlocking_duration: Option<u64>,
+    ) -> Result<()> {
+        // the unlocking duration of a position is stored in a u32
+        let is_valid = |unlocking_duration: Option<u64>| {
+            unlocking_duration.map_or(true, |x| (1..=u64::from(u32::MAX)).contains(&x))
+        };
+        require!(
+            is_valid(voting_unlocking_duration) && is_valid(integrity_pool_unlocking_duration),
+            ErrorCode::InvalidUnlockingDuration
+        );
+
+        let config = &mut ctx.accounts.config;
+        config.voting_unlocking_duration = voting_unlocking_duration;
+        config.integrity_pool_unlocking_duration = integrity_pool_unlocking_duration;
+
+        emit!(ConfigUpdated {
+            config: (**config).clone(),
+            epoch:  get_current_epoch(config)?,
+        });
+
+        Ok(())
+    }
+
+    /// The new duration applies from the start of the current epoch, so the current epoch keeps
+    /// its number. If the current epoch would already be over with the new duration, it ends one
+    /// second from now instead.
+    /// Only one previous epoch duration is kept, so the duration can't change again until a full
+    /// epoch has passed since the last change. By then, no proposal that started before the last
+    /// change can still be voted on, since voting can't take longer than an epoch.
+    pub fn update_epoch_duration(
+        ctx: Context<UpdateEpochDuration>,
+        epoch_duration: u64,
+    ) -> Result<()> {
+        require_neq!(epoch_duration, 0, ErrorCode::ZeroEpochDuration);
+
+        let config = &mut ctx.accounts.config;
+        let current_time = utils::clock::get_current_time(config);
+        let current_epoch = get_current_epoch(config)?;
+
+        let next_change_time = TryInto::<i64>::try_into(config.epoch_duration)
+            .ok()
+            .and_then(|epoch_duration| {
+                config
+                    .epoch_duration_change_time
+                    .checked_add(epoch_duration)
+            })
+            .ok_or_else(|| error!(ErrorCode::GenericOverflow))?;
+        require_gte!(
+            current_time,
+            next_change_time,
+            ErrorCode::EpochDurationChangeTooSoon
+        );
+
+        let latest_change_time = TryInto::<i64>::try_into(epoch_duration)
+            .ok()
+            .and_then(|epoch_duration| current_time.checked_sub(epoch_duration - 1))
+            .ok_or_else(|| error!(ErrorCode::GenericOverflow))?;
+        let epoch_duration_change_time =
+            epoch_to_time(config, current_epoch)?.max(latest_change_time);
+
+        config.previous_epoch_duration = config.epoch_duration;
+        config.previous_epoch_offset = config.epoch_offset;
+        config.previous_epoch_duration_change_time = config.epoch_duration_change_time;
+
+        config.epoch_duration = epoch_duration;
+        config.epoch_offset = current_epoch;
+        config.epoch_duration_change_time = epoch_duration_change_time;
+
+        emit!(ConfigUpdated {
+            config: (**config).clone(),
+            epoch:  get_current_epoch(config)?,
+        });
+
         Ok(())
     }
 
@@ -110,15 +279,55 @@ pub mod staking {
     ) -> Result<()> {
         let config = &mut ctx.accounts.config;
         config.agreement_hash = agreement_hash;
+
+        emit!(ConfigUpdated {
+            config: (**config).clone(),
+            epoch:  get_current_epoch(config)?,
+        });
+
         Ok(())
     }
 
+    /// Nominates `pool_authority` as the pool authority, it becomes the pool authority once it
+    /// calls `accept_pool_authority`. The integrity pool config accepts through the
+    /// `accept_pool_authority` instruction of the integrity pool program, which signs for it.
     pub fn update_pool_authority(
         ctx: Context<UpdatePoolAuthority>,
         pool_authority: Pubkey,
     ) -> Result<()> {
         let config = &mut ctx.accounts.config;
-        config.pool_authority = pool_authority;
+        config.pending_pool_authority = Some(pool_authority);
+
+        emit!(ConfigUpdated {
+            config: (**config).clone(),
+            epoch:  get_current_epoch(config)?,
+        });
+
+        Ok(())
+    }
+
+    pub fn accept_pool_authority(ctx: Context<AcceptPoolAuthority>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.pool_authority = ctx.accounts.new_pool_authority.key();
+        config.pending_pool_authority = None;
+
+        emit!(ConfigUpdated {
+            config: (**config).clone(),
+            epoch:  get_current_epoch(config)?,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel_pool_authority_transfer(ctx: Context<CancelPoolAuthorityTransfer>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.pending_pool_authority = None;
+
+        emit!(ConfigUpdated {
+            config: (**config).clone(),
+            epoch:  get_current_epoch(config)?,
+        });
+
         Ok(())
     }
 
@@ -143,6 +352,7 @@ pub mod staking {
         let stake_account_positions =
             DynamicPositionArray::load_init(&ctx.accounts.stake_account_positions)?;
         stake_account_positions.set_owner(&owner)?;
+        stake_account_positions.adjust_rent_if_needed(&ctx.accounts.payer)?;
 
         Ok(())
     }
@@ -159,7 +369,9 @@ pub mod staking {
     }
 
     /// Creates a position
-    /// Looks for the first available place in the array, fails if array is full
+    /// If the account already has a position with the same target that was created in the current
+    /// epoch and isn't unlocking, the amount is added to it. Otherwise looks for the first
+    /// available place in the array, fails if array is full
     /// Computes risk and fails if new positions exceed risk limit
     pub fn create_position(
         ctx: Context<CreatePosition>,
@@ -211,9 +423,12 @@ pub mod staking {
             unlocking_start: None,
         };
 
-        let i = stake_account_positions
-            .reserve_new_index(&mut ctx.accounts.stake_account_metadata.next_index)?;
-        stake_account_positions.write_position(i, &new_position)?;
+        let position_count = stake_account_positions
+            .get_next_index(ctx.accounts.stake_account_metadata.next_index)?;
+        let i = stake_account_positions.add_position(
+            &mut ctx.accounts.stake_account_metadata.next_index,
+            &new_position,
+        )?;
 
         let unvested_balance = ctx
             .accounts
@@ -229,6 +444,7 @@ pub mod staking {
             stake_account_custody.amount,
             unvested_balance,
             current_epoch,
+            config.get_unlocking_durations(),
         )?;
 
         if let Some(target_account) = maybe_target_account {
@@ -237,6 +453,134 @@ pub mod staking {
 
         stake_account_positions.adjust_rent_if_needed(&ctx.accounts.owner)?;
 
+        emit!(PositionCreated {
+            stake_account_positions: ctx.accounts.stake_account_positions.key(),
+            index: i.try_into()?,
+            target_with_parameters,
+            amount,
+            merged: i < position_count,
+            epoch: current_epoch,
+        });
+
+        Ok(())
+    }
+
+    /// Creates an integrity pool position without the signature of the owner. This allows the
+    /// integrity pool to delegate the rewards of owners who opted into auto-compounding.
+    pub fn create_pool_position(
+        ctx: Context<CreatePoolPosition>,
+        target_with_parameters: TargetWithParameters,
+        amount: u64,
+    ) -> Result<()> {
+        if amount == 0 {
+            return Err(error!(ErrorCode::CreatePositionWithZero));
+        }
+
+        let stake_account_positions =
+            &mut DynamicPositionArray::load_mut(&ctx.accounts.stake_account_positions)?;
+        let stake_account_custody = &ctx.accounts.stake_account_custody;
+        let config = &ctx.accounts.config;
+        let current_epoch = get_current_epoch(config)?;
+
+        let publisher = match target_with_parameters {
+            TargetWithParameters::IntegrityPool { publisher }
+                if ctx.accounts.pool_authority.key() == config.pool_authority =>
+            {
+                publisher
+            }
+            _ => return err!(ErrorCode::InvalidPoolAuthority),
+        };
+
+        // the owner doesn't sign, the delegation record of the pool authority's program needs to
+        // have opted into auto-compounding
+        let pool_program = ctx.accounts.pool_authority.owner;
+        let (delegation_record_address, _) = Pubkey::find_program_address(
+            &[
+                DELEGATION_RECORD_SEED.as_bytes(),
+                publisher.as_ref(),
+                ctx.accounts.stake_account_positions.key().as_ref(),
+            ],
+            pool_program,
+        );
+        let delegation_record = &ctx.accounts.delegation_record;
+        require!(
+            delegation_record.key() == delegation_record_address
+                && delegation_record.owner == pool_program
+                && delegation_record
+                    .try_borrow_data()?
+                    .get(DELEGATION_RECORD_AUTO_COMPOUND_OFFSET)
+                    == Some(&1),
+            ErrorCode::AutoCompoundNotEnabled
+        );
+
+        ctx.accounts
+            .stake_account_metadata
+            .check_is_llc_member(&config.agreement_hash)?;
+
+        let new_position = Position {
+            amount,
+            target_with_parameters,
+            activation_epoch: current_epoch + 1,
+            unlocking_start: None,
+        };
+
+        // the reward is merged into a position of the same publisher activating next epoch if any
+        let position_count = stake_account_positions
+            .get_next_index(ctx.accounts.stake_account_metadata.next_index)?;
+        let i = stake_account_positions.add_position(
+            &mut ctx.accounts.stake_account_metadata.next_index,
+            &new_position,
+        )?;
+
+        let unvested_balance = ctx
+            .accounts
+            .stake_account_metadata
+            .lock
+            .get_unvested_balance(
+                utils::clock::get_current_time(config),
+                config.pyth_token_list_time,
+            )?;
+
+        utils::risk::validate(
+            stake_account_positions,
+            stake_account_custody.amount,
+            unvested_balance,
+            current_epoch,
+            config.get_unlocking_durations(),
+        )?;
+
+        stake_account_positions.adjust_rent_if_needed(&ctx.accounts.payer)?;
+
+        emit!(PositionCreated {
+            stake_account_positions: ctx.accounts.stake_account_positions.key(),
+            index: i.try_into()?,
+            target_with_parameters,
+            amount,
+            merged: i < position_count,
+            epoch: current_epoch,
+        });
+
+        Ok(())
+    }
+
+    /// Keeps count of the delegation records the pool authority creates and closes for a stake
+    /// account, the stake account can't be closed while it has some
+    pub fn update_delegation_record_count(
+        ctx: Context<UpdateDelegationRecordCount>,
+        created: bool,
+    ) -> Result<()> {
+        let stake_account_metadata = &mut ctx.accounts.stake_account_metadata;
+        stake_account_metadata.delegation_record_count = if created {
+            stake_account_metadata
+                .delegation_record_count
+                .checked_add(1)
+                .ok_or_else(|| error!(ErrorCode::GenericOverflow))?
+        } else {
+            stake_account_metadata
+                .delegation_record_count
+                .checked_sub(1)
+                .ok_or_else(|| error!(ErrorCode::GenericOverflow))?
+        };
         Ok(())
     }
 
@@ -265,17 +609,46 @@ pub mod staking {
             current_epoch,
             &mut stake_account_metadata.next_index,
             target_with_parameters,
+            config.get_unlocking_durations(),
         )?;
 
         stake_account_positions.realloc(&stake_account_metadata.next_index)?;
         stake_account_positions.adjust_rent_if_needed(&ctx.accounts.owner)?;
 
+        emit!(TargetPositionsMerged {
+            stake_account_positions: ctx.accounts.stake_account_positions.key(),
+            target_with_parameters,
+            next_index: stake_account_positions
+                .get_next_index(stake_account_metadata.next_index)?
+                .try_into()?,
+            epoch: current_epoch,
+        });
+
+        Ok(())
+    }
+
+    /// Moves the positions of an account with the legacy layout to the compact layout, which
+    /// allows more than 255 positions. Every position keeps its index.
+    pub fn migrate_positions(ctx: Context<MigratePositions>) -> Result<()> {
+        let stake_account_positions =
+            &mut DynamicPositionArray::load_mut(&ctx.accounts.stake_account_positions)?;
+        let stake_account_metadata = &mut ctx.accounts.stake_account_metadata;
+
+        let next_index = stake_account_metadata.next_index;
+        stake_account_positions.migrate_to_v2(&mut stake_account_metadata.next_index)?;
+        stake_account_positions.adjust_rent_if_needed(&ctx.accounts.owner)?;
+
+        emit!(PositionsMigrated {
+            stake_account_positions: ctx.accounts.stake_account_positions.key(),
+            next_index:              next_index.into(),
+        });
+
         Ok(())
     }
 
     pub fn close_position(
         ctx: Context<ClosePosition>,
-        index: u8,
+        index: u16,
         amount: u64,
         target_with_parameters: TargetWithParameters,
     ) -> Result<()> {
@@ -326,12 +699,22 @@ pub mod staking {
             .checked_sub(amount)
             .ok_or_else(|| error!(ErrorCode::AmountBiggerThanPosition))?;
 
-        match current_position.get_current_position(current_epoch)? {
+        // the unlocking position keeps the unlocking duration of the time it was closed
+        let unlocking_duration = config
+            .get_unlocking_durations()
+            .get(target_with_parameters.get_target());
+
+        let unlocking_index = match current_position.get_current_position(
+            current_epoch,
+            stake_account_positions.get_unlocking_durations(i, config.get_unlocking_durations())?,
+        )? {
             PositionState::LOCKED => {
                 // If remaining amount is 0 keep only 1 position
-                if remaining_amount == 0 {
+                let unlocking_index = if remaining_amount == 0 {
                     current_position.unlocking_start = Some(current_epoch + 1);
                     stake_account_positions.write_position(i, &current_position)?;
+                    stake_account_positions
+                        .write_unlocking_duration(i, Some(unlocking_duration))?;
                     // Otherwise leave remaining amount in the current position and
                     // create another position with the rest. The newly created position
                     // will unlock after unlocking_duration epochs.
@@ -343,6 +726,7 @@ pub mod staking {
                             .ok_or_else(|| error!(ErrorCode::PositionNotInUse))?
                             .amount
                     );
+                    index
                 } else {
                     current_position.amount = remaining_amount;
                     stake_account_positions.write_position(i, &current_position)?;
@@ -358,6 +742,19 @@ pub mod staking {
                             unlocking_start: Some(current_epoch + 1),
                         },
                     )?;
+                    stake_account_positions
+                        .write_unlocking_duration(j, Some(unlocking_duration))?;
+                    // the closed amount still took the place of another position if the position
+                    // was created by a redelegation
+                    stake_account_positions.write_redelegation_marker(
+                        j,
+                        RedelegationMarker {
+                            unlocking:  false,
+                            activating: stake_account_positions
+                                .read_redelegation_marker(i)?
+                                .activating,
+                        },
+                    )?;
 
                     assert_ne!(i, j);
                     assert_eq!(
@@ -374,11 +771,13 @@ pub mod staking {
                             )
                             .ok_or_else(|| error!(ErrorCode::GenericOverflow))?
                     );
-                }
+                    j.try_into()?
+                };
 
                 if let Some(target_account) = maybe_target_account {
                     target_account.add_unlocking(amount, current_epoch)?;
                 }
+                Some(unlocking_index)
             }
             PositionState::LOCKING => {
                 if remaining_amount == 0 {
@@ -391,14 +790,283 @@ pub mod staking {
                 if let Some(target_account) = maybe_target_account {
                     target_account.add_unlocking(amount, current_epoch)?;
                 }
+                None
             }
             PositionState::UNLOCKING | PositionState::PREUNLOCKING | PositionState::UNLOCKED => {
                 return Err(error!(ErrorCode::AlreadyUnlocking));
             }
+        };
+
+        stake_account_positions.adjust_rent_if_needed(&ctx.accounts.owner)?;
+
+        emit!(PositionClosed {
+            stake_account_positions: ctx.accounts.stake_account_positions.key(),
+            index,
+            unlocking_index,
+            target_with_parameters,
+            amount,
+            epoch: current_epoch,
+        });
+
+        Ok(())
+    }
+
+    /// Re-locks a position that was closed during the current epoch
+    /// Computes risk and fails if the stake account can't cover the position anymore
+    pub fn cancel_unlock(ctx: Context<CancelUnlock>, index: u16) -> Result<()> {
+        let i: usize = index.into();
+        let stake_account_positions =
+            &mut DynamicPositionArray::load_mut(&ctx.accounts.stake_account_positions)?;
+        let stake_account_custody = &ctx.accounts.stake_account_custody;
+        let config = &ctx.accounts.config;
+        let current_epoch = get_current_epoch(config)?;
+        let maybe_target_account = &mut ctx.accounts.target_account;
+
+        let mut current_position: Position = stake_account_positions
+            .read_position(i)?
+            .ok_or_else(|| error!(ErrorCode::PositionNotInUse))?;
+
+        match current_position.target_with_parameters {
+            TargetWithParameters::Voting => {
+                require!(
+                    maybe_target_account.is_some(),
+                    ErrorCode::MissingTargetAccount,
+                )
+            }
+            TargetWithParameters::IntegrityPool { .. } => {
+                require!(
+                    maybe_target_account.is_none(),
+                    ErrorCode::UnexpectedTargetAccount
+                );
+                require!(
+                    ctx.accounts
+                        .pool_authority
+                        .as_ref()
+                        .map_or(false, |x| x.key() == config.pool_authority),
+                    ErrorCode::InvalidPoolAuthority
+                )
+            }
         }
 
+        if current_position.get_current_position(
+            current_epoch,
+            stake_account_positions.get_unlocking_durations(i, config.get_unlocking_durations())?,
+        )? != PositionState::PREUNLOCKING
+        {
+            return Err(error!(ErrorCode::NotPreunlocking));
+        }
+
+        current_position.unlocking_start = None;
+        stake_account_positions.write_position(i, &current_position)?;
+        stake_account_positions.write_unlocking_duration(i, None)?;
+        // the position doesn't stop being delegated anymore, so it's not redelegated either
+        stake_account_positions.write_redelegation_marker(
+            i,
+            RedelegationMarker {
+                unlocking: false,
+                ..stake_account_positions.read_redelegation_marker(i)?
+            },
+        )?;
+
+        let unvested_balance = ctx
+            .accounts
+            .stake_account_metadata
+            .lock
+            .get_unvested_balance(
+                utils::clock::get_current_time(config),
+                config.pyth_token_list_time,
+            )?;
+
+        // a redelegated position might be exposed to its new publisher already
+        utils::risk::validate(
+            stake_account_positions,
+            stake_account_custody.amount,
+            unvested_balance,
+            current_epoch,
+            config.get_unlocking_durations(),
+        )?;
+
+        // the position was removed from next epoch's locked amount when it was closed
+        if let Some(target_account) = maybe_target_account {
+            target_account.add_locking(current_position.amount, current_epoch)?;
+        }
+
+        emit!(UnlockCancelled {
+            stake_account_positions: ctx.accounts.stake_account_positions.key(),
+            index,
+            target_with_parameters: current_position.target_with_parameters,
+            amount: current_position.amount,
+            epoch: current_epoch,
+        });
+
+        Ok(())
+    }
+
+    /// Moves `amount` from an integrity pool position to a new position with a different
+    /// publisher without waiting for the unlocking period. A locked amount also stays in an
+    /// unlocking position with the old publisher, so it can still be slashed for the epochs it
+    /// was delegated to the old publisher. Until that position is unlocked, the moved tokens are
+    /// exposed to both publishers, but they only count once towards the integrity pool
+    /// exposure.
+    pub fn redelegate_position(
+        ctx: Context<RedelegatePosition>,
+        index: u16,
+        amount: u64,
+        target_with_parameters: TargetWithParameters,
+        new_target_with_parameters: TargetWithParameters,
+    ) -> Result<()> {
+        if amount == 0 {
+            return Err(error!(ErrorCode::ClosePositionWithZero));
+        }
+
+        let i: usize = index.into();
+        let stake_account_positions =
+            &mut DynamicPositionArray::load_mut(&ctx.accounts.stake_account_positions)?;
+        let stake_account_custody = &ctx.accounts.stake_account_custody;
+        let config = &ctx.accounts.config;
+        let current_epoch = get_current_epoch(config)?;
+
+        require!(
+            matches!(
+                target_with_parameters,
+                TargetWithParameters::IntegrityPool { .. }
+            ) && matches!(
+                new_target_with_parameters,
+                TargetWithParameters::IntegrityPool { .. }
+            ) && target_with_parameters != new_target_with_parameters,
+            ErrorCode::InvalidRedelegationTarget
+        );
+        require!(
+            ctx.accounts.pool_authority.key() == config.pool_authority,
+            ErrorCode::InvalidPoolAuthority
+        );
+
+        ctx.accounts
+            .stake_account_metadata
+            .check_is_llc_member(&config.agreement_hash)?;
+
+        let mut current_position: Position = stake_account_positions
+            .read_position(i)?
+            .ok_or_else(|| error!(ErrorCode::PositionNotInUse))?;
+
+        if current_position.target_with_parameters != target_with_parameters {
+            return Err(error!(ErrorCode::WrongTarget));
+        }
+
+        let remaining_amount = current_position
+            .amount
+            .checked_sub(amount)
+            .ok_or_else(|| error!(ErrorCode::AmountBiggerThanPosition))?;
+
+        let unlocking_duration = config
+            .get_unlocking_durations()
+            .get(target_with_parameters.get_target());
+        let redelegation_marker = stake_account_positions.read_redelegation_marker(i)?;
+
+        // the new position only takes the place of a position that stops being delegated at the
+        // epoch it starts being delegated
+        let new_redelegation_marker = match current_position.get_current_position(
+            current_epoch,
+            stake_account_positions.get_unlocking_durations(i, config.get_unlocking_durations())?,
+        )? {
+            PositionState::LOCKED => {
+                let unlocking_redelegation_marker = RedelegationMarker {
+                    unlocking: true,
+                    ..redelegation_marker
+                };
+                if remaining_amount == 0 {
+                    current_position.unlocking_start = Some(current_epoch + 1);
+                    stake_account_positions.write_position(i, &current_position)?;
+                    stake_account_positions
+                        .write_unlocking_duration(i, Some(unlocking_duration))?;
+                    stake_account_positions
+                        .write_redelegation_marker(i, unlocking_redelegation_marker)?;
+                } else {
+                    current_position.amount = remaining_amount;
+                    stake_account_positions.write_position(i, &current_position)?;
+
+                    let j = stake_account_positions
+                        .reserve_new_index(&mut ctx.accounts.stake_account_metadata.next_index)?;
+                    stake_account_positions.write_position(
+                        j,
+                        &Position {
+                            amount,
+                            target_with_parameters: current_position.target_with_parameters,
+                            activation_epoch: current_position.activation_epoch,
+                            unlocking_start: Some(current_epoch + 1),
+                        },
+                    )?;
+                    stake_account_positions
+                        .write_unlocking_duration(j, Some(unlocking_duration))?;
+                    stake_account_positions
+                        .write_redelegation_marker(j, unlocking_redelegation_marker)?;
+                }
+                RedelegationMarker {
+                    unlocking:  false,
+                    activating: true,
+                }
+            }
+            PositionState::LOCKING => {
+                if remaining_amount == 0 {
+                    stake_account_positions
+                        .make_none(i, &mut ctx.accounts.stake_account_metadata.next_index)?;
+                } else {
+                    current_position.amount = remaining_amount;
+                    stake_account_positions.write_position(i, &current_position)?;
+                }
+                // if the position was created by a redelegation, the new position takes its place
+                RedelegationMarker {
+                    unlocking:  false,
+                    activating: redelegation_marker.activating,
+                }
+            }
+            PositionState::UNLOCKING | PositionState::PREUNLOCKING | PositionState::UNLOCKED => {
+                return Err(error!(ErrorCode::AlreadyUnlocking));
+            }
+        };
+
+        let j = stake_account_positions
+            .reserve_new_index(&mut ctx.accounts.stake_account_metadata.next_index)?;
+        stake_account_positions.write_position(
+            j,
+            &Position {
+                amount,
+                target_with_parameters: new_target_with_parameters,
+                activation_epoch: current_epoch + 1,
+                unlocking_start: None,
+            },
+        )?;
+        stake_account_positions.write_redelegation_marker(j, new_redelegation_marker)?;
+
+        let unvested_balance = ctx
+            .accounts
+            .stake_account_metadata
+            .lock
+            .get_unvested_balance(
+                utils::clock::get_current_time(config),
+                config.pyth_token_list_time,
+            )?;
+
+        utils::risk::validate(
+            stake_account_positions,
+            stake_account_custody.amount,
+            unvested_balance,
+            current_epoch,
+            config.get_unlocking_durations(),
+        )?;
+
         stake_account_positions.adjust_rent_if_needed(&ctx.accounts.owner)?;
 
+        emit!(PositionRedelegated {
+            stake_account_positions: ctx.accounts.stake_account_positions.key(),
+            index,
+            new_index: j.try_into()?,
+            target_with_parameters,
+            new_target_with_parameters,
+            amount,
+            epoch: current_epoch,
+        });
+
         Ok(())
     }
 
@@ -438,6 +1106,7 @@ pub mod staking {
             remaining_balance,
             unvested_balance,
             current_epoch,
+            config.get_unlocking_durations(),
         )
         .is_err()
         {
@@ -460,12 +1129,152 @@ pub mod staking {
             ctx.accounts.stake_account_custody.amount,
             unvested_balance,
             current_epoch,
+            config.get_unlocking_durations(),
         )
         .is_err()
         {
             return Err(error!(ErrorCode::InsufficientWithdrawableBalance));
         }
 
+        emit!(StakeWithdrawn {
+            stake_account_positions: ctx.accounts.stake_account_positions.key(),
+            amount,
+            epoch: current_epoch,
+        });
+
+        Ok(())
+    }
+
+    /** Closes an empty stake account and sends the rent of its accounts to the owner.
+     *
+     * The voter record and the split request are closed too if they exist. The delegation
+     * records of the integrity pool need to be closed before, since they can't be closed
+     * once the positions account is gone, and the stake account of a publisher can't be
+     * closed. Accounts created before the delegation records were counted need to check them
+     * with `check_delegation_records` first.
+     */
+    pub fn close_stake_account(ctx: Context<CloseStakeAccount>) -> Result<()> {
+        require!(
+            DynamicPositionArray::load(&ctx.accounts.stake_account_positions)?
+                .get_next_index(ctx.accounts.stake_account_metadata.next_index)?
+                == 0,
+            ErrorCode::CloseWithStake
+        );
+        require!(
+            ctx.accounts.stake_account_custody.amount == 0,
+            ErrorCode::CloseWithBalance
+        );
+        // the delegation records can't be closed without the positions account
+        require!(
+            ctx.accounts.stake_account_metadata.delegation_record_count == 0,
+            ErrorCode::CloseWithDelegationRecords
+        );
+
+        // delegation records created before the count existed aren't counted
+        require!(
+            usize::from(
+                ctx.accounts
+                    .stake_account_metadata
+                    .delegation_records_checked
+            ) == POOL_DATA_MAX_PUBLISHERS,
+            ErrorCode::UncheckedDelegationRecords
+        );
+
+        // the pool authority's program sends the rewards of publishers to their stake accounts
+        let pool_data = &ctx.accounts.pool_data;
+        check_pool_data(&ctx.accounts.pool_config, pool_data)?;
+        let publisher_stake_accounts_end =
+            POOL_DATA_PUBLISHER_STAKE_ACCOUNTS_OFFSET + 32 * POOL_DATA_MAX_PUBLISHERS;
+        require!(
+            !pool_data
+                .try_borrow_data()?
+                .get(POOL_DATA_PUBLISHER_STAKE_ACCOUNTS_OFFSET..publisher_stake_accounts_end)
+                .ok_or_else(|| error!(ErrorCode::InvalidPoolData))?
+                .chunks_exact(32)
+                .any(|key| key == ctx.accounts.stake_account_positions.key().as_ref()),
+            ErrorCode::ClosePublisherStakeAccount
+        );
+
+        // the voter record and the split request only exist if the account voted or requested a
+        // split
+        for account in [
+            &ctx.accounts.voter_record,
+            &ctx.accounts.stake_account_split_request,
+        ] {
+            if account.owner == &crate::ID {
+                **ctx.accounts.owner.try_borrow_mut_lamports()? += account.lamports();
+                **account.try_borrow_mut_lamports()? = 0;
+                account.assign(&anchor_lang::system_program::ID);
+                account.realloc(0, false)?;
+            }
+        }
+
+        close_account(CpiContext::from(&*ctx.accounts).with_signer(&[&[
+            AUTHORITY_SEED.as_bytes(),
+            ctx.accounts.stake_account_positions.key().as_ref(),
+            &[ctx.accounts.stake_account_metadata.authority_bump],
+        ]]))?;
+
+        Ok(())
+    }
+
+    /** Checks the delegation records of a stake account created before they were counted, so
+     * that it can be closed.
+     *
+     * The remaining accounts are the delegation records of the next publishers of the pool
+     * data, in order, starting from the first unchecked one. Each of them needs to be empty
+     * or counted, records get counted the next time they're advanced. The check can be
+     * spread over several transactions.
+     */
+    pub fn check_delegation_records(ctx: Context<CheckDelegationRecords>) -> Result<()> {
+        let pool_data = &ctx.accounts.pool_data;
+        check_pool_data(&ctx.accounts.pool_config, pool_data)?;
+        let pool_data = pool_data.try_borrow_data()?;
+        let pool_program = ctx.accounts.pool_config.owner;
+        let stake_account_positions = ctx.accounts.stake_account_positions.key();
+        let stake_account_metadata = &mut ctx.accounts.stake_account_metadata;
+
+        let mut delegation_records = ctx.remaining_accounts.iter();
+        while usize::from(stake_account_metadata.delegation_records_checked)
+            < POOL_DATA_MAX_PUBLISHERS
+        {
+            let offset = POOL_DATA_PUBLISHERS_OFFSET
+                + 32 * usize::from(stake_account_metadata.delegation_records_checked);
+            let publisher = pool_data
+                .get(offset..offset + 32)
+                .ok_or_else(|| error!(ErrorCode::InvalidPoolData))?;
+
+            // empty publisher slots don't have delegation records
+            if publisher != Pubkey::default().as_ref() {
+                let Some(delegation_record) = delegation_records.next() else {
+                    break;
+                };
+                let (delegation_record_address, _) = Pubkey::find_program_address(
+                    &[
+                        DELEGATION_RECORD_SEED.as_bytes(),
+                        publisher,
+                        stake_account_positions.as_ref(),
+                    ],
+                    pool_program,
+                );
+                require_keys_eq!(
+                    delegation_record.key(),
+                    delegation_record_address,
+                    ErrorCode::InvalidDelegationRecord
+                );
+                require!(
+                    delegation_record.data_is_empty()
+                        || (delegation_record.owner == pool_program
+                            && delegation_record
+                                .try_borrow_data()?
+                                .get(DELEGATION_RECORD_REGISTERED_OFFSET)
+                                == Some(&1)),
+                    ErrorCode::UnregisteredDelegationRecord
+                );
+            }
+            stake_account_metadata.delegation_records_checked += 1;
+        }
+
         Ok(())
     }
 
@@ -502,6 +1311,7 @@ pub mod staking {
             stake_account_custody.amount,
             unvested_balance,
             current_epoch,
+            config.get_unlocking_durations(),
         )?;
 
         let epoch_of_snapshot: u64;
@@ -565,17 +1375,87 @@ pub mod staking {
             return Err(error!(ErrorCode::InvalidVotingEpoch));
         }
 
+        ctx.accounts
+            .stake_account_metadata
+            .check_can_vote(epoch_of_snapshot)?;
+
         voter_record.voter_weight = compute_voter_weight(
             stake_account_positions,
             epoch_of_snapshot,
             governance_target.get_current_amount_locked(epoch_of_snapshot)?,
             MAX_VOTER_WEIGHT,
+            config.get_unlocking_durations(),
         )?;
         voter_record.voter_weight_expiry = Some(Clock::get()?.slot);
 
         Ok(())
     }
 
+    /// Returns the amount of tokens that can be withdrawn from the stake account right now
+    pub fn get_withdrawable_balance(ctx: Context<GetWithdrawableBalance>) -> Result<u64> {
+        let stake_account_positions =
+            &DynamicPositionArray::load(&ctx.accounts.stake_account_positions)?;
+        let config = &ctx.accounts.config;
+
+        let unvested_balance = ctx
+            .accounts
+            .stake_account_metadata
+            .lock
+            .get_unvested_balance(
+                utils::clock::get_current_time(config),
+                config.pyth_token_list_time,
+            )?;
+
+        utils::risk::validate(
+            stake_account_positions,
+            ctx.accounts.stake_account_custody.amount,
+            unvested_balance,
+            get_current_epoch(config)?,
+            config.get_unlocking_durations(),
+        )
+    }
+
+    /// Returns the voter weight that `update_voter_weight` would compute for a snapshot at
+    /// `epoch`, which has to be the current epoch or the previous one
+    pub fn get_voter_weight_at_epoch(
+        ctx: Context<GetVoterWeightAtEpoch>,
+        epoch: u64,
+    ) -> Result<u64> {
+        let stake_account_positions =
+            &DynamicPositionArray::load(&ctx.accounts.stake_account_positions)?;
+        let config = &ctx.accounts.config;
+        let current_epoch = get_current_epoch(config)?;
+
+        if !(epoch <= current_epoch && current_epoch - epoch <= 1) {
+            return Err(error!(ErrorCode::InvalidVotingEpoch));
+        }
+
+        ctx.accounts
+            .stake_account_metadata
+            .check_is_llc_member(&config.agreement_hash)?;
+        ctx.accounts.stake_account_metadata.check_can_vote(epoch)?;
+
+        compute_voter_weight(
+            stake_account_positions,
+            epoch,
+            ctx.accounts
+                .governance_target
+                .get_current_amount_locked(epoch)?,
+            MAX_VOTER_WEIGHT,
+            config.get_unlocking_durations(),
+        )
+    }
+
+    /// Returns the amounts of the positions of the stake account grouped by position state
+    pub fn get_position_summary(ctx: Context<GetPositionSummary>) -> Result<LockedBalanceSummary> {
+        let config = &ctx.accounts.config;
+        DynamicPositionArray::load(&ctx.accounts.stake_account_positions)?
+            .get_locked_balance_summary(
+                get_current_epoch(config)?,
+                config.get_unlocking_durations(),
+            )
+    }
+
     pub fn update_max_voter_weight(ctx: Context<UpdateMaxVoterWeight>) -> Result<()> {
         let config = &ctx.accounts.config;
         let max_voter_record = &mut ctx.accounts.max_voter_record;
@@ -625,32 +1505,74 @@ pub mod staking {
      * request at a time.
      * In the first step, the user requests a split by specifying the `amount` of tokens
      * they want to give to the other user and the `recipient`'s pubkey.
+     * The request can be accepted during `SPLIT_REQUEST_DURATION` epochs, a new request
+     * replaces the previous one.
      */
     pub fn request_split(ctx: Context<RequestSplit>, amount: u64, recipient: Pubkey) -> Result<()> {
-        ctx.accounts.stake_account_split_request.amount = amount;
-        ctx.accounts.stake_account_split_request.recipient = recipient;
-        Ok(())
+        let current_epoch = get_current_epoch(&ctx.accounts.config)?;
+
+        SplitRequest {
+            amount,
+            recipient,
+            expiry_epoch: current_epoch + SPLIT_REQUEST_DURATION - 1,
+        }
+        .save(
+            &ctx.accounts.stake_account_split_request,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &[&[
+                SPLIT_REQUEST.as_bytes(),
+                ctx.accounts.stake_account_positions.key().as_ref(),
+                &[ctx.bumps.stake_account_split_request],
+            ]],
+        )
     }
 
+    /**
+     * Withdraws the pending split request of a stake account and sends its rent back to the
+     * owner.
+     */
+    pub fn cancel_split(ctx: Context<CancelSplit>) -> Result<()> {
+        let split_request = &ctx.accounts.stake_account_split_request;
+        let owner = &ctx.accounts.owner;
+
+        **owner.try_borrow_mut_lamports()? += split_request.lamports();
+        **split_request.try_borrow_mut_lamports()? = 0;
+        split_request.assign(&anchor_lang::system_program::ID);
+        split_request.realloc(0, false)?;
+        Ok(())
+    }
 
     /**
      * A split request can only be accepted by the `pda_authority` from
      * the config account. If accepted, `amount` tokens are transferred to a new stake account
      * owned by the `recipient` and the split request is reset (by setting `amount` to 0).
+     * The same share of every position is moved to the new stake account. Accounts with
+     * integrity pool positions can only be split through the pool authority, which creates the
+     * delegation records of the new stake account.
      * The recipient of a transfer can't vote during the epoch of the transfer.
      *
      * The `pda_authority` must explicitly approve both the amount of tokens and recipient, and
-     * these parameters must match the request (in the `split_request` account).
+     * these parameters must match the request (in the `split_request` account). Expired
+     * requests can't be accepted.
      */
     pub fn accept_split(ctx: Context<AcceptSplit>, amount: u64, recipient: Pubkey) -> Result<()> {
         let config = &ctx.accounts.config;
         let current_epoch = get_current_epoch(config)?;
 
-        let split_request = &ctx.accounts.source_stake_account_split_request;
+        let split_request = &mut SplitRequest::try_deserialize_legacy(
+            &ctx.accounts
+                .source_stake_account_split_request
+                .try_borrow_data()?,
+        )?;
         require!(
             split_request.amount == amount && split_request.recipient == recipient,
             ErrorCode::InvalidApproval
         );
+        require!(
+            !split_request.is_expired(current_epoch),
+            ErrorCode::SplitRequestExpired
+        );
 
         // Initialize new accounts
         ctx.accounts.new_stake_account_metadata.initialize(
@@ -665,9 +1587,6 @@ pub mod staking {
         new_stake_account_positions.set_owner(&split_request.recipient)?;
 
         // Pre-check invariants
-        // Note that the accept operation requires the positions account to be empty, which should
-        // trivially pass this invariant check. However, we explicitly check invariants
-        // everywhere else, so may as well check in this operation also.
         let source_stake_account_positions =
             &mut DynamicPositionArray::load_mut(&ctx.accounts.source_stake_account_positions)?;
 
@@ -682,16 +1601,9 @@ pub mod staking {
                     config.pyth_token_list_time,
                 )?,
             current_epoch,
+            config.get_unlocking_durations(),
         )?;
 
-        // Check that there aren't any positions (i.e., staked tokens) in the source account.
-        // This check allows us to create an empty positions account on behalf of the recipient and
-        // not worry about moving positions from the source account to the new account.
-        require!(
-            ctx.accounts.source_stake_account_metadata.next_index == 0,
-            ErrorCode::SplitWithStake
-        );
-
         require!(split_request.amount > 0, ErrorCode::SplitZeroTokens);
 
         // Split vesting account
@@ -710,6 +1622,29 @@ pub mod staking {
             .new_stake_account_metadata
             .set_lock(new_vesting_schedule);
 
+        // the pool authority needs to split the delegation records of integrity pool positions
+        if ctx.accounts.pool_authority.is_none() {
+            require_eq!(
+                source_stake_account_positions
+                    .get_integrity_pool_exposure(current_epoch, config.get_unlocking_durations())?,
+                0,
+                ErrorCode::SplitWithDelegation
+            );
+        }
+
+        // Split positions, the amounts locked in the governance target don't change
+        source_stake_account_positions.split_positions(
+            new_stake_account_positions,
+            &mut ctx.accounts.source_stake_account_metadata.next_index,
+            &mut ctx.accounts.new_stake_account_metadata.next_index,
+            split_request.amount,
+            ctx.accounts.source_stake_account_custody.amount,
+            current_epoch,
+            config.get_unlocking_durations(),
+        )?;
+        new_stake_account_positions.adjust_rent_if_needed(&ctx.accounts.pda_authority)?;
+        // the moved positions might have voted already
+        ctx.accounts.new_stake_account_metadata.transfer_epoch = Some(current_epoch);
 
         transfer(
             CpiContext::from(&*ctx.accounts).with_signer(&[&[
@@ -736,6 +1671,7 @@ pub mod staking {
                     config.pyth_token_list_time,
                 )?,
             current_epoch,
+            config.get_unlocking_durations(),
         )?;
 
         utils::risk::validate(
@@ -749,10 +1685,25 @@ pub mod staking {
                     config.pyth_token_list_time,
                 )?,
             current_epoch,
+            config.get_unlocking_durations(),
         )?;
 
         // Delete current request
-        ctx.accounts.source_stake_account_split_request.amount = 0;
+        split_request.amount = 0;
+        split_request.save(
+            &ctx.accounts.source_stake_account_split_request,
+            &ctx.accounts.pda_authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &[],
+        )?;
+
+        emit!(SplitAccepted {
+            source_stake_account_positions: ctx.accounts.source_stake_account_positions.key(),
+            new_stake_account_positions: ctx.accounts.new_stake_account_positions.key(),
+            recipient,
+            amount,
+            epoch: current_epoch,
+        });
 
         Ok(())
     }
@@ -766,6 +1717,28 @@ pub mod staking {
     pub fn join_dao_llc(ctx: Context<JoinDaoLlc>, _agreement_hash: [u8; 32]) -> Result<()> {
         ctx.accounts.stake_account_metadata.signed_agreement_hash =
             Some(ctx.accounts.config.agreement_hash);
+
+        emit!(JoinedDaoLlc {
+            stake_account_positions: ctx.accounts.stake_account_positions.key(),
+            agreement_hash:          ctx.accounts.config.agreement_hash,
+            epoch:                   get_current_epoch(&ctx.accounts.config)?,
+        });
+
+        Ok(())
+    }
+
+    /// Sets the operator of the stake account, or revokes it if `operator` is `None`. The
+    /// operator can create, close and re-lock positions, including integrity pool delegations,
+    /// but can't withdraw tokens, split or transfer the account.
+    pub fn set_operator(ctx: Context<SetOperator>, operator: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.stake_account_metadata.operator = operator;
+
+        emit!(OperatorSet {
+            stake_account_positions: ctx.accounts.stake_account_positions.key(),
+            operator,
+            epoch: get_current_epoch(&ctx.accounts.config)?,
+        });
+
         Ok(())
     }
 
@@ -778,72 +1751,159 @@ pub mod staking {
     pub fn recover_account(ctx: Context<RecoverAccount>) -> Result<()> {
         // Check that there aren't any positions (i.e., staked tokens) in the account.
         // Transferring accounts with staked tokens might lead to double voting
+        let stake_account_positions =
+            &mut DynamicPositionArray::load_mut(&ctx.accounts.stake_account_positions)?;
         require!(
-            ctx.accounts.stake_account_metadata.next_index == 0,
+            stake_account_positions
+                .get_next_index(ctx.accounts.stake_account_metadata.next_index)?
+                == 0,
             ErrorCode::RecoverWithStake
         );
 
         let new_owner = ctx.accounts.owner.owner;
+        let previous_owner = ctx.accounts.stake_account_metadata.owner;
 
         ctx.accounts.stake_account_metadata.owner = new_owner;
-        let stake_account_positions =
-            &mut DynamicPositionArray::load_mut(&ctx.accounts.stake_account_positions)?;
+        ctx.accounts.stake_account_metadata.operator = None;
         stake_account_positions.set_owner(&new_owner)?;
         ctx.accounts.voter_record.governing_token_owner = new_owner;
 
+        emit!(AccountRecovered {
+            stake_account_positions: ctx.accounts.stake_account_positions.key(),
+            previous_owner,
+            new_owner,
+            epoch: get_current_epoch(&ctx.accounts.config)?,
+        });
+
         Ok(())
     }
 
     /** Transfers a user's stake account to a new owner provided by the `governance_authority`.
      *
      * This functionality addresses the scenario where a user doesn't have access to their owner
-     * key. Only accounts without any staked tokens can be transferred.
+     * key. The positions of the account are kept. To prevent double voting, the account can't
+     * vote on proposals whose snapshot was taken up to the epoch of the transfer.
      */
     pub fn transfer_account(ctx: Context<TransferAccount>) -> Result<()> {
-        // Check that there aren't any positions (i.e., staked tokens) in the account.
-        // Transferring accounts with staked tokens might lead to double voting
-        require!(
-            ctx.accounts.stake_account_metadata.next_index == 0,
-            ErrorCode::RecoverWithStake
-        );
+        let current_epoch = get_current_epoch(&ctx.accounts.config)?;
 
         let new_owner = ctx.accounts.new_owner.key();
+        let previous_owner = ctx.accounts.stake_account_metadata.owner;
         ctx.accounts.stake_account_metadata.owner = new_owner;
+        ctx.accounts.stake_account_metadata.transfer_epoch = Some(current_epoch);
+        // the operator was chosen by the previous owner
+        ctx.accounts.stake_account_metadata.operator = None;
         let stake_account_positions =
             &mut DynamicPositionArray::load_mut(&ctx.accounts.stake_account_positions)?;
         stake_account_positions.set_owner(&new_owner)?;
         ctx.accounts.voter_record.governing_token_owner = new_owner;
 
+        emit!(AccountTransferred {
+            stake_account_positions: ctx.accounts.stake_account_positions.key(),
+            previous_owner,
+            new_owner,
+            epoch: current_epoch,
+        });
+
+        Ok(())
+    }
+
+    /** Replaces the vesting schedule of a stake account with one that vests faster, for example
+     * after an acquisition. This can only be done by the `governance_authority`.
+     *
+     * The new schedule's unvested balance can't be higher than the current one at any time from
+     * now on.
+     */
+    pub fn accelerate_vesting(
+        ctx: Context<AccelerateVesting>,
+        lock: VestingSchedule,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let stake_account_metadata = &mut ctx.accounts.stake_account_metadata;
+        let previous_lock = stake_account_metadata.lock;
+
+        require!(
+            lock.vests_faster_than(
+                &previous_lock,
+                utils::clock::get_current_time(config),
+                config.pyth_token_list_time,
+            )?,
+            ErrorCode::InvalidVestingAcceleration
+        );
+        stake_account_metadata.set_lock(lock);
+
+        emit!(VestingAccelerated {
+            stake_account_positions: ctx.accounts.stake_account_positions.key(),
+            previous_lock,
+            new_lock: lock,
+            epoch: get_current_epoch(config)?,
+        });
+
         Ok(())
     }
 
+    /// Slashes the positions of a stake account delegated to `publisher`, returns the locked and
+    /// preunlocking amounts slashed. The positions that took the place of slashed positions in a
+    /// redelegation are capped too, use `slash_account_v2` to get the caps.
     pub fn slash_account(
         ctx: Context<SlashAccount>,
         // a number between 0 and 1 with 6 decimals of precision
         // TODO: use fract64 instead of u64
         slash_ratio: u64,
     ) -> Result<(u64, u64)> {
+        let SlashedAmounts {
+            locked_slashed,
+            preunlocking_slashed,
+            ..
+        } = slash_stake_account(ctx.accounts, slash_ratio)?;
+        Ok((locked_slashed, preunlocking_slashed))
+    }
+
+    /// Like `slash_account`, but also returns the amounts by which the positions of other
+    /// publishers were capped
+    pub fn slash_account_v2(
+        ctx: Context<SlashAccount>,
+        // a number between 0 and 1 with 6 decimals of precision
+        slash_ratio: u64,
+    ) -> Result<SlashedAmounts> {
+        slash_stake_account(ctx.accounts, slash_ratio)
+    }
+
+    /** Claws back the unvested tokens of a stake account, for example when a grant is
+     * terminated. The unvested balance is transferred to the `treasury` and the account becomes
+     * fully vested.
+     *
+     * The voting positions are reduced so that the governance exposure stays within the custody
+     * balance. Integrity pool positions are backed by vested tokens, so they don't change.
+     */
+    pub fn clawback(ctx: Context<Clawback>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let current_epoch = get_current_epoch(config)?;
+
         let stake_account_positions =
             &mut DynamicPositionArray::load_mut(&ctx.accounts.stake_account_positions)?;
-        let governance_target_account = &mut ctx.accounts.governance_target_account;
-        let publisher = &ctx.accounts.publisher;
 
-        let next_index = &mut ctx.accounts.stake_account_metadata.next_index;
-
-        let current_epoch = get_current_epoch(&ctx.accounts.config)?;
+        let unvested_balance = ctx
+            .accounts
+            .stake_account_metadata
+            .lock
+            .get_unvested_balance(
+                utils::clock::get_current_time(config),
+                config.pyth_token_list_time,
+            )?;
+        let clawback_amount = unvested_balance.min(ctx.accounts.stake_account_custody.amount);
 
-        let SlashedAmounts {
-            total_slashed,
-            locked_slashed,
-            preunlocking_slashed,
-        } = stake_account_positions.slash_positions(
+        stake_account_positions.reduce_governance_exposure(
             current_epoch,
-            next_index,
+            &mut ctx.accounts.stake_account_metadata.next_index,
             ctx.accounts.stake_account_custody.amount,
-            publisher.key,
-            slash_ratio,
-            governance_target_account,
+            clawback_amount,
+            &mut ctx.accounts.governance_target_account,
+            config.get_unlocking_durations(),
         )?;
+        ctx.accounts
+            .stake_account_metadata
+            .set_lock(VestingSchedule::FullyVested);
 
         transfer(
             CpiContext::from(&*ctx.accounts).with_signer(&[&[
@@ -851,10 +1911,28 @@ pub mod staking {
                 ctx.accounts.stake_account_positions.key().as_ref(),
                 &[ctx.accounts.stake_account_metadata.authority_bump],
             ]]),
-            total_slashed,
+            clawback_amount,
         )?;
 
-        Ok((locked_slashed, preunlocking_slashed))
+        ctx.accounts.stake_account_custody.reload()?;
+
+        // Post-check
+        utils::risk::validate(
+            stake_account_positions,
+            ctx.accounts.stake_account_custody.amount,
+            0,
+            current_epoch,
+            config.get_unlocking_durations(),
+        )?;
+
+        emit!(UnvestedTokensClawedBack {
+            stake_account_positions: ctx.accounts.stake_account_positions.key(),
+            treasury:                ctx.accounts.treasury.key(),
+            amount:                  clawback_amount,
+            epoch:                   current_epoch,
+        });
+
+        Ok(())
     }
 
     // Hack to allow exporting the Position type in the IDL
@@ -862,3 +1940,46 @@ pub mod staking {
         Ok(())
     }
 }
+
+/// Shared by the versions of `slash_account`, which only differ in what they return
+fn slash_stake_account(accounts: &mut SlashAccount, slash_ratio: u64) -> Result<SlashedAmounts> {
+    let stake_account_positions =
+        &mut DynamicPositionArray::load_mut(&accounts.stake_account_positions)?;
+    let governance_target_account = &mut accounts.governance_target_account;
+    let publisher = &accounts.publisher;
+
+    let next_index = &mut accounts.stake_account_metadata.next_index;
+
+    let current_epoch = get_current_epoch(&accounts.config)?;
+
+    let slashed_amounts = stake_account_positions.slash_positions(
+        current_epoch,
+        next_index,
+        accounts.stake_account_custody.amount,
+        publisher.key,
+        slash_ratio,
+        governance_target_account,
+        accounts.config.get_unlocking_durations(),
+    )?;
+
+    transfer(
+        CpiContext::from(&*accounts).with_signer(&[&[
+            AUTHORITY_SEED.as_bytes(),
+            accounts.stake_account_positions.key().as_ref(),
+            &[accounts.stake_account_metadata.authority_bump],
+        ]]),
+        slashed_amounts.total_slashed,
+    )?;
+
+    emit!(AccountSlashed {
+        stake_account_positions: accounts.stake_account_positions.key(),
+        publisher: publisher.key(),
+        slash_ratio,
+        total_slashed: slashed_amounts.total_slashed,
+        locked_slashed: slashed_amounts.locked_slashed,
+        preunlocking_slashed: slashed_amounts.preunlocking_slashed,
+        epoch: current_epoch,
+    });
+
+    Ok(slashed_amounts)
+}
@@ -1,4 +1,5 @@
 use {
+    crate::state::positions::UnlockingDurations,
     anchor_lang::prelude::*,
     borsh::BorshSchema,
 };
@@ -28,10 +29,40 @@ pub struct GlobalConfig {
     pub mock_clock_time: i64, /* this field needs to be greater than 0 otherwise the API
                                * will use real time */
     pub pool_authority:  Pubkey,
+
+    /// Number of epochs positions stay in the UNLOCKING state, `None` means
+    /// `UNLOCKING_DURATION`.
+    pub voting_unlocking_duration:         Option<u64>,
+    pub integrity_pool_unlocking_duration: Option<u64>,
+
+    /// `epoch_duration` applies from `epoch_duration_change_time`, which is the start of epoch
+    /// `epoch_offset`. Both are set by `update_epoch_duration` so that epoch numbers stay
+    /// continuous when the duration changes.
+    pub epoch_offset:               u64,
+    pub epoch_duration_change_time: i64,
+
+    /// The epoch parameters that applied before the last change of the epoch duration, so that
+    /// times from before the change (e.g. the start of a proposal) map to the right epoch.
+    pub previous_epoch_duration:             u64,
+    pub previous_epoch_offset:               u64,
+    pub previous_epoch_duration_change_time: i64,
+
+    /// Keys nominated to replace the authorities, a nominated key becomes the authority once it
+    /// accepts the nomination.
+    pub pending_governance_authority: Option<Pubkey>,
+    pub pending_pda_authority:        Option<Pubkey>,
+    pub pending_pool_authority:       Option<Pubkey>,
 }
 
 impl GlobalConfig {
     pub const LEN: usize = 10240;
+
+    pub fn get_unlocking_durations(&self) -> UnlockingDurations {
+        UnlockingDurations::new(
+            self.voting_unlocking_duration,
+            self.integrity_pool_unlocking_duration,
+        )
+    }
 }
 
 #[cfg(test)]
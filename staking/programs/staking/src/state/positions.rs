@@ -16,7 +16,11 @@ use {
     arrayref::array_ref,
     solana_program::system_instruction,
     std::{
-        convert::TryInto,
+        collections::BTreeMap,
+        convert::{
+            TryFrom,
+            TryInto,
+        },
         fmt::{
             self,
             Debug,
@@ -26,6 +30,21 @@ use {
 
 // Intentionally make the buffer for positions bigger than it needs for migrations
 pub const POSITION_BUFFER_SIZE: usize = 200;
+// A serialized `Option<Position>` takes at most 59 bytes, followed by the redelegation marker and
+// the unlocking duration
+pub const POSITION_BUFFER_SIZE_V2: usize = 72;
+
+// Accounts with the V2 layout have this byte right after `PositionData`, in the V1 layout this is
+// the `Option` tag of the first position, which is 0 or 1
+pub const POSITIONS_V2_VERSION: u8 = 2;
+// version (1 byte), padding (1 byte), next_index (2 bytes), reserved (4 bytes)
+pub const POSITIONS_V2_HEADER_LEN: usize = 8;
+// The last bytes of every position buffer store the unlocking duration of the position as an
+// `Option<u32>`. It's set when the position starts unlocking, so that updating the unlocking
+// durations of the config doesn't change the state of positions that are already unlocking.
+pub const UNLOCKING_DURATION_LEN: usize = 5;
+// The bytes right before the unlocking duration store the `RedelegationMarker` of the position
+pub const REDELEGATION_MARKER_LEN: usize = 2;
 
 /// The header of DynamicPositionArray
 #[account(zero_copy)]
@@ -38,34 +57,83 @@ impl PositionData {
     pub const LEN: usize = 8 + 32;
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PositionsLayout {
+    /// Positions of `POSITION_BUFFER_SIZE` bytes, `next_index` is stored in
+    /// `StakeAccountMetadataV2` so there can't be more than 255 positions
+    V1,
+    /// Positions of `POSITION_BUFFER_SIZE_V2` bytes, `next_index` is a `u16` stored in the
+    /// header of the positions account
+    V2,
+}
+
+impl PositionsLayout {
+    pub fn positions_offset(&self) -> usize {
+        match self {
+            PositionsLayout::V1 => PositionData::LEN,
+            PositionsLayout::V2 => PositionData::LEN + POSITIONS_V2_HEADER_LEN,
+        }
+    }
+
+    pub fn position_buffer_size(&self) -> usize {
+        match self {
+            PositionsLayout::V1 => POSITION_BUFFER_SIZE,
+            PositionsLayout::V2 => POSITION_BUFFER_SIZE_V2,
+        }
+    }
+
+    pub fn data_len(&self, position_capacity: usize) -> usize {
+        self.positions_offset() + position_capacity * self.position_buffer_size()
+    }
+}
+
 /// This account stores a user's positions in a dynamic sized array.
-/// Its first 40 bytes are `PositionData` (including discriminator) and the rest is a
-/// variable-length slice of `[u8; POSITION_BUFFER_SIZE]`. Each element of the array can be
-/// deserialized into an `Option<Position>`. The old invariant is maintained: For `i < next_index`,
-/// `positions[i] == Some` For `i >= next_index`, `positions[i] == None`
-/// Other invariants are that `data_len() == 40 + n * POSITION_BUFFER_SIZE` where n is an integer
-/// and that `data_len() >= 40 + next_index * POSITION_BUFFER_SIZE`.
+/// Its first 40 bytes are `PositionData` (including discriminator). In the V1 layout the rest is a
+/// variable-length slice of `[u8; POSITION_BUFFER_SIZE]`, in the V2 layout it's a header of
+/// `POSITIONS_V2_HEADER_LEN` bytes followed by a slice of `[u8; POSITION_BUFFER_SIZE_V2]`. Each
+/// element of the array can be deserialized into an `Option<Position>`. The old invariant is
+/// maintained: For `i < next_index`, `positions[i] == Some` For `i >= next_index`,
+/// `positions[i] == None`
+/// Other invariants are that `data_len() == positions_offset + n * position_buffer_size` where n
+/// is an integer and that `data_len() >= positions_offset + next_index * position_buffer_size`.
+/// The `next_index` arguments of the methods are `StakeAccountMetadataV2.next_index`, they're
+/// only used by accounts with the V1 layout.
 /// It stores account info to get access to the data and resize.
 pub struct DynamicPositionArray<'a> {
     pub acc_info: AccountInfo<'a>,
 }
 
 impl<'a> DynamicPositionArray<'a> {
-    fn get_positions_slice(&self) -> Result<&mut [[u8; POSITION_BUFFER_SIZE]]> {
-        let position_capacity = self.get_position_capacity();
+    fn get_positions_slice(&self) -> Result<&mut [u8]> {
+        let layout = self.get_layout()?;
+        let positions_len = self.get_position_capacity() * layout.position_buffer_size();
         unsafe {
             Ok(std::slice::from_raw_parts_mut(
-                self.acc_info.try_borrow_mut_data()?[PositionData::LEN..].as_mut_ptr()
-                    as *mut [u8; POSITION_BUFFER_SIZE],
-                position_capacity,
+                self.acc_info.try_borrow_mut_data()?[layout.positions_offset()..].as_mut_ptr(),
+                positions_len,
             ))
         }
     }
 
+    fn get_position_buffer(&self, i: usize) -> Result<&mut [u8]> {
+        let position_buffer_size = self.get_layout()?.position_buffer_size();
+        self.get_positions_slice()?
+            .get_mut(i * position_buffer_size..(i + 1) * position_buffer_size)
+            .ok_or_else(|| error!(ErrorCode::PositionOutOfBounds))
+    }
+
     fn data_len(&self) -> usize {
         self.acc_info.data_len()
     }
 
+    pub fn get_layout(&self) -> Result<PositionsLayout> {
+        let data = self.acc_info.try_borrow_data()?;
+        match data.get(PositionData::LEN) {
+            Some(&POSITIONS_V2_VERSION) => Ok(PositionsLayout::V2),
+            _ => Ok(PositionsLayout::V1),
+        }
+    }
+
     pub fn load_init(account_loader: &AccountLoader<'a, PositionData>) -> Result<Self> {
         let acc_info = account_loader.to_account_info();
         if !acc_info.is_writable {
@@ -84,7 +152,9 @@ impl<'a> DynamicPositionArray<'a> {
             }
         }
 
-        Ok(Self { acc_info })
+        let mut result = Self { acc_info };
+        result.write_v2_header()?;
+        Ok(result)
     }
 
     pub fn load_mut(account_loader: &AccountLoader<'a, PositionData>) -> Result<Self> {
@@ -112,6 +182,52 @@ impl<'a> DynamicPositionArray<'a> {
         Ok(Self { acc_info })
     }
 
+    /// Writes an empty V2 header, the account needs to be empty
+    fn write_v2_header(&mut self) -> Result<()> {
+        let header_end = PositionsLayout::V2.positions_offset();
+        if self.data_len() < header_end {
+            self.acc_info.realloc(header_end, false)?;
+        }
+
+        let mut data = self.acc_info.try_borrow_mut_data()?;
+        data[PositionData::LEN..header_end].fill(0);
+        data[PositionData::LEN] = POSITIONS_V2_VERSION;
+        Ok(())
+    }
+
+    /// Rewrites an account with the V1 layout in the V2 layout, every position keeps its index
+    pub fn migrate_to_v2(&mut self, next_index: &mut u8) -> Result<()> {
+        require!(
+            self.get_layout()? == PositionsLayout::V1,
+            ErrorCode::PositionsAlreadyMigrated
+        );
+
+        let positions = (0..usize::from(*next_index))
+            .map(|i| {
+                Ok((
+                    self.read_position(i)?,
+                    self.read_redelegation_marker(i)?,
+                    self.read_unlocking_duration(i)?,
+                ))
+            })
+            .collect::<Result<Vec<(Option<Position>, RedelegationMarker, Option<u64>)>>>()?;
+
+        self.acc_info
+            .realloc(PositionsLayout::V2.data_len(positions.len()), false)?;
+        self.write_v2_header()?;
+        self.set_next_index(next_index, positions.len())?;
+        *next_index = 0;
+
+        for (i, (position, redelegation_marker, unlocking_duration)) in
+            positions.into_iter().enumerate()
+        {
+            position.try_write(self.get_position_buffer(i)?)?;
+            self.write_redelegation_marker(i, redelegation_marker)?;
+            self.write_unlocking_duration(i, unlocking_duration)?;
+        }
+        Ok(())
+    }
+
     pub fn adjust_rent_if_needed(&self, payer: &AccountInfo<'a>) -> Result<()> {
         let rent = Rent::get()?;
         let amount_required = rent.minimum_balance(self.data_len());
@@ -148,54 +264,175 @@ impl<'a> DynamicPositionArray<'a> {
     }
 
     pub fn get_position_capacity(&self) -> usize {
-        self.acc_info.data_len().saturating_sub(PositionData::LEN) / POSITION_BUFFER_SIZE
+        self.get_layout().map_or(0, |layout| {
+            self.data_len().saturating_sub(layout.positions_offset())
+                / layout.position_buffer_size()
+        })
+    }
+
+    /// Number of positions in the array, `next_index` is only used by the V1 layout
+    pub fn get_next_index(&self, next_index: u8) -> Result<usize> {
+        match self.get_layout()? {
+            PositionsLayout::V1 => Ok(usize::from(next_index)),
+            PositionsLayout::V2 => {
+                let data = self.acc_info.try_borrow_data()?;
+                Ok(usize::from(u16::from_le_bytes(*array_ref![
+                    data,
+                    PositionData::LEN + 2,
+                    2
+                ])))
+            }
+        }
+    }
+
+    fn set_next_index(&mut self, next_index: &mut u8, value: usize) -> Result<()> {
+        match self.get_layout()? {
+            PositionsLayout::V1 => {
+                *next_index =
+                    u8::try_from(value).map_err(|_| error!(ErrorCode::TooManyPositions))?;
+            }
+            PositionsLayout::V2 => {
+                let value =
+                    u16::try_from(value).map_err(|_| error!(ErrorCode::TooManyPositions))?;
+                let mut data = self.acc_info.try_borrow_mut_data()?;
+                data[PositionData::LEN + 2..PositionData::LEN + 4]
+                    .copy_from_slice(&value.to_le_bytes());
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the amount of `position` to an existing position with the same target, the same
+    /// `activation_epoch` and no `unlocking_start`. If there's none, `position` is written to a new
+    /// index. Positions that take the place of another position in a redelegation are left alone.
+    /// Returns the index of the position that holds the amount.
+    pub fn add_position(&mut self, next_index: &mut u8, position: &Position) -> Result<usize> {
+        if position.unlocking_start.is_none() {
+            for i in 0..self.get_next_index(*next_index)? {
+                if let Some(mut existing_position) = self.read_position(i)? {
+                    if existing_position.target_with_parameters == position.target_with_parameters
+                        && existing_position.activation_epoch == position.activation_epoch
+                        && existing_position.unlocking_start.is_none()
+                        && !self.read_redelegation_marker(i)?.activating
+                    {
+                        existing_position.amount = existing_position
+                            .amount
+                            .checked_add(position.amount)
+                            .ok_or_else(|| error!(ErrorCode::GenericOverflow))?;
+                        self.write_position(i, &existing_position)?;
+                        return Ok(i);
+                    }
+                }
+            }
+        }
+
+        let i = self.reserve_new_index(next_index)?;
+        self.write_position(i, position)?;
+        Ok(i)
     }
 
     /// Finds first index available for a new position, increments the internal counter
     pub fn reserve_new_index(&mut self, next_index: &mut u8) -> Result<usize> {
         let position_capacity: usize = self.get_position_capacity();
-        let res = usize::from(*next_index);
-        *next_index = next_index
-            .checked_add(1)
-            .ok_or_else(|| error!(ErrorCode::TooManyPositions))?;
+        let res = self.get_next_index(*next_index)?;
+        self.set_next_index(next_index, res + 1)?;
 
         if res == position_capacity {
             self.realloc(next_index)?;
         }
+        // the buffer might hold the unlocking duration of a position that was removed
+        self.write_redelegation_marker(res, RedelegationMarker::default())?;
+        self.write_unlocking_duration(res, None)?;
         Ok(res)
     }
 
     pub fn realloc(&mut self, next_index: &u8) -> Result<()> {
-        self.acc_info.realloc(
-            PositionData::LEN + usize::from(*next_index) * POSITION_BUFFER_SIZE,
-            false,
-        )?;
+        let layout = self.get_layout()?;
+        self.acc_info
+            .realloc(layout.data_len(self.get_next_index(*next_index)?), false)?;
         Ok(())
     }
 
     // Makes position at index i none, and swaps positions to preserve the invariant
     pub fn make_none(&mut self, i: usize, next_index: &mut u8) -> Result<()> {
-        if usize::from(*next_index) <= i {
+        let last_index = self.get_next_index(*next_index)?;
+        if last_index <= i {
             return Err(error!(ErrorCode::PositionOutOfBounds));
         }
-        *next_index -= 1;
+        let last_index = last_index - 1;
+        self.set_next_index(next_index, last_index)?;
+
+        let position_buffer_size = self.get_layout()?.position_buffer_size();
         let positions = self.get_positions_slice()?;
-        positions[i] = positions[usize::from(*next_index)];
-        None::<Option<Position>>.try_write(&mut positions[usize::from(*next_index)])
+        positions.copy_within(
+            last_index * position_buffer_size..(last_index + 1) * position_buffer_size,
+            i * position_buffer_size,
+        );
+        None::<Option<Position>>.try_write(self.get_position_buffer(last_index)?)
     }
 
     pub fn write_position(&mut self, i: usize, &position: &Position) -> Result<()> {
-        let positions = self.get_positions_slice()?;
-        Some(position).try_write(&mut positions[i])
+        Some(position).try_write(self.get_position_buffer(i)?)
     }
 
     pub fn read_position(&self, i: usize) -> Result<Option<Position>> {
-        let positions = self.get_positions_slice()?;
-        Option::<Position>::try_read(
-            positions
-                .get(i)
-                .ok_or_else(|| error!(ErrorCode::PositionOutOfBounds))?,
-        )
+        Option::<Position>::try_read(self.get_position_buffer(i)?)
+    }
+
+    fn get_unlocking_duration_buffer(&self, i: usize) -> Result<&mut [u8]> {
+        let buffer = self.get_position_buffer(i)?;
+        let start = buffer.len() - UNLOCKING_DURATION_LEN;
+        Ok(&mut buffer[start..])
+    }
+
+    /// The unlocking duration stored when the position at index i started unlocking, positions
+    /// that started unlocking before it was stored don't have one
+    pub fn read_unlocking_duration(&self, i: usize) -> Result<Option<u64>> {
+        Ok(Option::<u32>::try_read(self.get_unlocking_duration_buffer(i)?)?.map(u64::from))
+    }
+
+    pub fn write_unlocking_duration(
+        &mut self,
+        i: usize,
+        unlocking_duration: Option<u64>,
+    ) -> Result<()> {
+        unlocking_duration
+            .map(u32::try_from)
+            .transpose()
+            .map_err(|_| error!(ErrorCode::InvalidUnlockingDuration))?
+            .try_write(self.get_unlocking_duration_buffer(i)?)
+    }
+
+    fn get_redelegation_marker_buffer(&self, i: usize) -> Result<&mut [u8]> {
+        let buffer = self.get_position_buffer(i)?;
+        let end = buffer.len() - UNLOCKING_DURATION_LEN;
+        Ok(&mut buffer[end - REDELEGATION_MARKER_LEN..end])
+    }
+
+    pub fn read_redelegation_marker(&self, i: usize) -> Result<RedelegationMarker> {
+        RedelegationMarker::try_read(self.get_redelegation_marker_buffer(i)?)
+    }
+
+    pub fn write_redelegation_marker(
+        &mut self,
+        i: usize,
+        redelegation_marker: RedelegationMarker,
+    ) -> Result<()> {
+        redelegation_marker.try_write(self.get_redelegation_marker_buffer(i)?)
+    }
+
+    /// The unlocking durations that apply to the position at index i: its stored unlocking
+    /// duration if it has one, `unlocking_durations` otherwise
+    pub fn get_unlocking_durations(
+        &self,
+        i: usize,
+        unlocking_durations: UnlockingDurations,
+    ) -> Result<UnlockingDurations> {
+        Ok(self
+            .read_unlocking_duration(i)?
+            .map_or(unlocking_durations, |unlocking_duration| {
+                UnlockingDurations::new(Some(unlocking_duration), Some(unlocking_duration))
+            }))
     }
 
     pub fn has_target_with_parameters_exposure(
@@ -212,12 +449,19 @@ impl<'a> DynamicPositionArray<'a> {
         Ok(false)
     }
 
-    pub fn get_target_exposure(&self, target: &Target, current_epoch: u64) -> Result<u64> {
+    pub fn get_target_exposure(
+        &self,
+        target: &Target,
+        current_epoch: u64,
+        unlocking_durations: UnlockingDurations,
+    ) -> Result<u64> {
         let mut exposure: u64 = 0;
         for i in 0..self.get_position_capacity() {
             if let Some(position) = self.read_position(i)? {
+                let unlocking_durations = self.get_unlocking_durations(i, unlocking_durations)?;
                 if position.target_with_parameters.get_target() == *target
-                    && position.get_current_position(current_epoch)? != PositionState::UNLOCKED
+                    && position.get_current_position(current_epoch, unlocking_durations)?
+                        != PositionState::UNLOCKED
                 {
                     exposure = exposure
                         .checked_add(position.amount)
@@ -228,6 +472,114 @@ impl<'a> DynamicPositionArray<'a> {
         Ok(exposure)
     }
 
+    /// Like `get_target_exposure` for the integrity pool, but the old and new positions of a
+    /// redelegation are only counted once, the new position takes the place of the old one until
+    /// it's unlocked.
+    pub fn get_integrity_pool_exposure(
+        &self,
+        current_epoch: u64,
+        unlocking_durations: UnlockingDurations,
+    ) -> Result<u64> {
+        let mut exposure: u64 = 0;
+        for i in 0..self.get_position_capacity() {
+            if let Some(position) = self.read_position(i)? {
+                if position.target_with_parameters.get_target() != Target::IntegrityPool {
+                    continue;
+                }
+                let unlocking_durations = self.get_unlocking_durations(i, unlocking_durations)?;
+                if position.get_current_position(current_epoch, unlocking_durations)?
+                    == PositionState::UNLOCKED
+                {
+                    continue;
+                }
+                exposure = exposure
+                    .checked_add(position.amount)
+                    .ok_or_else(|| error!(ErrorCode::GenericOverflow))?;
+            }
+        }
+
+        for redelegated_amount in self
+            .get_redelegated_amounts(current_epoch, unlocking_durations)?
+            .values()
+        {
+            exposure -= redelegated_amount;
+        }
+        Ok(exposure)
+    }
+
+    /// The amounts of the integrity pool positions that stop being delegated at an epoch because
+    /// of a redelegation and are covered by the positions that start being delegated at the same
+    /// epoch because of a redelegation, by epoch. Other positions that stop and start being
+    /// delegated at the same epoch aren't netted.
+    fn get_redelegated_amounts(
+        &self,
+        current_epoch: u64,
+        unlocking_durations: UnlockingDurations,
+    ) -> Result<BTreeMap<u64, u64>> {
+        let mut unlocking_amounts: BTreeMap<u64, u64> = BTreeMap::new();
+        let mut activating_amounts: BTreeMap<u64, u64> = BTreeMap::new();
+        for i in 0..self.get_position_capacity() {
+            if let Some(position) = self.read_position(i)? {
+                if position.target_with_parameters.get_target() != Target::IntegrityPool {
+                    continue;
+                }
+                let unlocking_durations = self.get_unlocking_durations(i, unlocking_durations)?;
+                if position.get_current_position(current_epoch, unlocking_durations)?
+                    == PositionState::UNLOCKED
+                {
+                    continue;
+                }
+                let redelegation_marker = self.read_redelegation_marker(i)?;
+                if let (Some(unlocking_start), true) =
+                    (position.unlocking_start, redelegation_marker.unlocking)
+                {
+                    *unlocking_amounts.entry(unlocking_start).or_default() += position.amount;
+                }
+                if redelegation_marker.activating {
+                    *activating_amounts
+                        .entry(position.activation_epoch)
+                        .or_default() += position.amount;
+                }
+            }
+        }
+
+        Ok(unlocking_amounts
+            .into_iter()
+            .filter_map(|(epoch, unlocking_amount)| {
+                activating_amounts
+                    .get(&epoch)
+                    .map(|activating_amount| (epoch, unlocking_amount.min(*activating_amount)))
+            })
+            .collect())
+    }
+
+    /// Adds up the amounts of the positions grouped by position state, the unlocked positions are
+    /// left out
+    pub fn get_locked_balance_summary(
+        &self,
+        current_epoch: u64,
+        unlocking_durations: UnlockingDurations,
+    ) -> Result<LockedBalanceSummary> {
+        let mut summary = LockedBalanceSummary::default();
+        for i in 0..self.get_position_capacity() {
+            if let Some(position) = self.read_position(i)? {
+                let unlocking_durations = self.get_unlocking_durations(i, unlocking_durations)?;
+                let balance =
+                    match position.get_current_position(current_epoch, unlocking_durations)? {
+                        PositionState::LOCKING => &mut summary.locking,
+                        PositionState::LOCKED => &mut summary.locked,
+                        PositionState::PREUNLOCKING => &mut summary.preunlocking,
+                        PositionState::UNLOCKING => &mut summary.unlocking,
+                        PositionState::UNLOCKED => continue,
+                    };
+                *balance = balance
+                    .checked_add(position.amount)
+                    .ok_or_else(|| error!(ErrorCode::GenericOverflow))?;
+            }
+        }
+        Ok(summary)
+    }
+
     /// This function is used to reduce the number of positions in the array by merging equivalent
     /// positions. Sometimes some positions have the same `target_with_parameters`,
     /// `activation_epoch` and `unlocking_start`. These can obviously be merged, but this is not
@@ -243,18 +595,40 @@ impl<'a> DynamicPositionArray<'a> {
         current_epoch: u64,
         next_index: &mut u8,
         target_with_parameters: TargetWithParameters,
+        unlocking_durations: UnlockingDurations,
     ) -> Result<()> {
-        let mut i = usize::from(*next_index);
+        let mut i = self.get_next_index(*next_index)?;
         while i >= 1 {
             i -= 1;
             if let Some(position) = self.read_position(i)? {
                 if position.target_with_parameters == target_with_parameters {
-                    if position.get_current_position(current_epoch)? == PositionState::UNLOCKED {
+                    let unlocking_duration = self.read_unlocking_duration(i)?;
+                    let redelegation_marker = self.read_redelegation_marker(i)?;
+                    let unlocking_durations =
+                        self.get_unlocking_durations(i, unlocking_durations)?;
+                    if position.get_current_position(current_epoch, unlocking_durations)?
+                        == PositionState::UNLOCKED
+                    {
                         self.make_none(i, next_index)?;
                     } else {
                         for j in 0..i {
                             if let Some(mut other_position) = self.read_position(j)? {
-                                if position.is_equivalent(&other_position, current_epoch) {
+                                // positions that unlock after different durations stop being
+                                // equivalent at some point, and redelegated positions need to
+                                // keep the epochs of their redelegation
+                                if self.read_unlocking_duration(j)? == unlocking_duration
+                                    && self.read_redelegation_marker(j)? == redelegation_marker
+                                    && (redelegation_marker == RedelegationMarker::default()
+                                        || (other_position.activation_epoch
+                                            == position.activation_epoch
+                                            && other_position.unlocking_start
+                                                == position.unlocking_start))
+                                    && position.is_equivalent(
+                                        &other_position,
+                                        current_epoch,
+                                        unlocking_durations,
+                                    )
+                                {
                                     self.make_none(i, next_index)?;
                                     other_position.amount += position.amount;
                                     other_position.activation_epoch = std::cmp::min(
@@ -274,6 +648,71 @@ impl<'a> DynamicPositionArray<'a> {
         Ok(())
     }
 
+    /// Moves a share of `transferred_amount / total_amount` of every voting position to
+    /// `destination`, keeping the activation and unlocking epochs of the positions. The moved
+    /// amounts are rounded down as a whole, so the positions that stay in this array never exceed
+    /// the share of the balance that stays in the source account.
+    /// Integrity pool positions are moved too, the caller makes sure the pool authority splits
+    /// their delegation records.
+    pub fn split_positions(
+        &mut self,
+        destination: &mut DynamicPositionArray,
+        next_index: &mut u8,
+        destination_next_index: &mut u8,
+        transferred_amount: u64,
+        total_amount: u64,
+        current_epoch: u64,
+        unlocking_durations: UnlockingDurations,
+    ) -> Result<()> {
+        let mut cumulative_amount: u128 = 0;
+        let mut cumulative_transferred: u64 = 0;
+
+        let mut i: usize = 0;
+        while i < self.get_next_index(*next_index)? {
+            let mut position = self
+                .read_position(i)?
+                .ok_or_else(|| error!(ErrorCode::PositionNotInUse))?;
+
+            // unlocked positions don't back anything, they stay in the source account
+            let unlocking_durations = self.get_unlocking_durations(i, unlocking_durations)?;
+            if position.get_current_position(current_epoch, unlocking_durations)?
+                == PositionState::UNLOCKED
+            {
+                i += 1;
+                continue;
+            }
+
+            cumulative_amount += u128::from(position.amount);
+            let to_transfer: u64 = u64::try_from(
+                cumulative_amount * u128::from(transferred_amount) / u128::from(total_amount),
+            )? - cumulative_transferred;
+            cumulative_transferred += to_transfer;
+
+            if to_transfer > 0 {
+                let j = destination.reserve_new_index(destination_next_index)?;
+                destination.write_position(
+                    j,
+                    &Position {
+                        amount: to_transfer,
+                        ..position
+                    },
+                )?;
+                destination.write_redelegation_marker(j, self.read_redelegation_marker(i)?)?;
+                destination.write_unlocking_duration(j, self.read_unlocking_duration(i)?)?;
+            }
+
+            if to_transfer == position.amount {
+                self.make_none(i, next_index)?;
+                continue;
+            }
+
+            position.amount -= to_transfer;
+            self.write_position(i, &position)?;
+            i += 1;
+        }
+        Ok(())
+    }
+
     pub fn slash_positions(
         &mut self,
         current_epoch: u64,
@@ -282,6 +721,7 @@ impl<'a> DynamicPositionArray<'a> {
         publisher: &Pubkey,
         slash_ratio: u64,
         governance_target_account: &mut TargetMetadata,
+        unlocking_durations: UnlockingDurations,
     ) -> Result<SlashedAmounts> {
         require_gte!(1_000_000, slash_ratio, ErrorCode::InvalidSlashRatio);
 
@@ -289,14 +729,19 @@ impl<'a> DynamicPositionArray<'a> {
         let mut unlocking_slashed = 0;
         let mut preunlocking_slashed = 0;
 
+        let redelegated_amounts =
+            self.get_redelegated_amounts(current_epoch, unlocking_durations)?;
 
         let mut i: usize = 0;
-        while i < usize::from(*next_index) {
+        while i < self.get_next_index(*next_index)? {
             let position = self.read_position(i)?;
 
             if let Some(position_data) = position {
-                let prev_state = position_data.get_current_position(current_epoch - 1)?;
-                let current_state = position_data.get_current_position(current_epoch)?;
+                let unlocking_durations = self.get_unlocking_durations(i, unlocking_durations)?;
+                let prev_state =
+                    position_data.get_current_position(current_epoch - 1, unlocking_durations)?;
+                let current_state =
+                    position_data.get_current_position(current_epoch, unlocking_durations)?;
                 if matches!(
                     position_data.target_with_parameters,
                     TargetWithParameters::IntegrityPool { publisher: publisher_pubkey } if publisher_pubkey == *publisher,
@@ -343,17 +788,140 @@ impl<'a> DynamicPositionArray<'a> {
             i += 1;
         }
 
-        let governance_exposure = self.get_target_exposure(&Target::Voting, current_epoch)?;
+        let redelegation_caps = self.cap_redelegations(
+            current_epoch,
+            next_index,
+            publisher,
+            redelegated_amounts,
+            unlocking_durations,
+        )?;
 
         let total_slashed = locked_slashed + unlocking_slashed + preunlocking_slashed;
+        self.reduce_governance_exposure(
+            current_epoch,
+            next_index,
+            custody_account_amount,
+            total_slashed,
+            governance_target_account,
+            unlocking_durations,
+        )?;
+
+        Ok(SlashedAmounts {
+            total_slashed,
+            locked_slashed,
+            preunlocking_slashed,
+            redelegation_caps,
+        })
+    }
+
+    /// Reduces the positions of other publishers that took the place of the slashed positions of
+    /// `slashed_publisher` in a redelegation. The slashed tokens left the custody account, so they
+    /// can't back the new positions anymore. `redelegated_amounts` are the redelegated amounts
+    /// before slashing, the positions are reduced by how much these went down.
+    fn cap_redelegations(
+        &mut self,
+        current_epoch: u64,
+        next_index: &mut u8,
+        slashed_publisher: &Pubkey,
+        redelegated_amounts: BTreeMap<u64, u64>,
+        unlocking_durations: UnlockingDurations,
+    ) -> Result<Vec<RedelegationCap>> {
+        let slashed_redelegated_amounts =
+            self.get_redelegated_amounts(current_epoch, unlocking_durations)?;
+        let mut to_cap: BTreeMap<u64, u64> = redelegated_amounts
+            .into_iter()
+            .map(|(epoch, amount)| {
+                let slashed_amount = slashed_redelegated_amounts
+                    .get(&epoch)
+                    .copied()
+                    .unwrap_or_default();
+                (epoch, amount.saturating_sub(slashed_amount))
+            })
+            .filter(|(_, amount)| *amount > 0)
+            .collect();
+
+        let mut redelegation_caps: Vec<RedelegationCap> = vec![];
+        let mut i: usize = 0;
+        while i < self.get_next_index(*next_index)? && !to_cap.is_empty() {
+            if let Some(mut position) = self.read_position(i)? {
+                let unlocking_durations = self.get_unlocking_durations(i, unlocking_durations)?;
+                let current_state =
+                    position.get_current_position(current_epoch, unlocking_durations)?;
+                let remaining = to_cap.get_mut(&position.activation_epoch);
+                match (position.target_with_parameters, remaining) {
+                    (TargetWithParameters::IntegrityPool { publisher }, Some(remaining))
+                        if publisher != *slashed_publisher
+                            && current_state != PositionState::UNLOCKED
+                            && self.read_redelegation_marker(i)?.activating =>
+                    {
+                        let capped = (*remaining).min(position.amount);
+                        *remaining -= capped;
+                        if *remaining == 0 {
+                            to_cap.remove(&position.activation_epoch);
+                        }
+
+                        let redelegation_cap = match redelegation_caps
+                            .iter()
+                            .position(|cap| cap.publisher == publisher)
+                        {
+                            Some(j) => &mut redelegation_caps[j],
+                            None => {
+                                redelegation_caps.push(RedelegationCap {
+                                    publisher,
+                                    ..Default::default()
+                                });
+                                redelegation_caps.last_mut().unwrap()
+                            }
+                        };
+                        match current_state {
+                            PositionState::LOCKING => redelegation_cap.locking += capped,
+                            PositionState::LOCKED => redelegation_cap.locked += capped,
+                            PositionState::PREUNLOCKING => redelegation_cap.preunlocking += capped,
+                            PositionState::UNLOCKING | PositionState::UNLOCKED => {}
+                        }
+
+                        if capped == position.amount {
+                            self.make_none(i, next_index)?;
+                            continue;
+                        }
+                        position.amount -= capped;
+                        self.write_position(i, &position)?;
+                    }
+                    _ => {}
+                }
+            }
+            i += 1;
+        }
+        Ok(redelegation_caps)
+    }
+
+    /// Reduces the voting positions so that the governance exposure stays within the custody
+    /// balance after `removed_amount` tokens leave the custody account. The governance target is
+    /// updated accordingly.
+    pub fn reduce_governance_exposure(
+        &mut self,
+        current_epoch: u64,
+        next_index: &mut u8,
+        custody_account_amount: u64,
+        removed_amount: u64,
+        governance_target_account: &mut TargetMetadata,
+        unlocking_durations: UnlockingDurations,
+    ) -> Result<()> {
+        let governance_exposure =
+            self.get_target_exposure(&Target::Voting, current_epoch, unlocking_durations)?;
+
         if let Some(mut remaining) =
-            (governance_exposure + total_slashed).checked_sub(custody_account_amount)
+            (governance_exposure + removed_amount).checked_sub(custody_account_amount)
         {
             let mut i = 0;
-            while i < usize::from(*next_index) && remaining > 0 {
+            while i < self.get_next_index(*next_index)? && remaining > 0 {
                 if let Some(position) = self.read_position(i)? {
-                    let prev_state = position.get_current_position(current_epoch - 1)?;
-                    let current_state = position.get_current_position(current_epoch)?;
+                    let unlocking_durations =
+                        self.get_unlocking_durations(i, unlocking_durations)?;
+                    let prev_state =
+                        position.get_current_position(current_epoch - 1, unlocking_durations)?;
+                    let current_state =
+                        position.get_current_position(current_epoch, unlocking_durations)?;
 
                     if position.target_with_parameters == TargetWithParameters::Voting
                         && current_state != PositionState::UNLOCKED
@@ -405,19 +973,46 @@ impl<'a> DynamicPositionArray<'a> {
             }
         }
 
-        Ok(SlashedAmounts {
-            total_slashed,
-            locked_slashed,
-            preunlocking_slashed,
-        })
+        Ok(())
     }
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Default, PartialEq, Eq)]
 pub struct SlashedAmounts {
     pub total_slashed:        u64,
     pub locked_slashed:       u64,
     pub preunlocking_slashed: u64,
+    pub redelegation_caps:    Vec<RedelegationCap>,
+}
+
+/// Marks the positions created by a redelegation. `unlocking` is set on the position that stops
+/// being delegated to the old publisher and `activating` on the position that starts being
+/// delegated to the new publisher, only these are netted against each other.
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RedelegationMarker {
+    pub unlocking:  bool,
+    pub activating: bool,
+}
+
+/// The amounts by which the positions of a publisher were reduced because they took the place of
+/// slashed positions in a redelegation, grouped by position state
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RedelegationCap {
+    pub publisher:    Pubkey,
+    pub locking:      u64,
+    pub locked:       u64,
+    pub preunlocking: u64,
+}
+
+#[wasm_bindgen]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LockedBalanceSummary {
+    pub locking:      u64,
+    pub locked:       u64,
+    pub unlocking:    u64,
+    pub preunlocking: u64,
 }
+
 pub struct DynamicPositionArrayAccount {
     pub key:      Pubkey,
     pub lamports: u64,
@@ -535,13 +1130,48 @@ impl TargetWithParameters {
     }
 }
 
+/// The number of epochs positions of each target spend in the UNLOCKING state before they are
+/// UNLOCKED
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnlockingDurations {
+    pub voting:         u64,
+    pub integrity_pool: u64,
+}
+
+impl Default for UnlockingDurations {
+    fn default() -> Self {
+        UnlockingDurations::new(None, None)
+    }
+}
+
+impl UnlockingDurations {
+    /// `None` means `UNLOCKING_DURATION`
+    pub fn new(voting: Option<u64>, integrity_pool: Option<u64>) -> Self {
+        UnlockingDurations {
+            voting:         voting.unwrap_or(UNLOCKING_DURATION),
+            integrity_pool: integrity_pool.unwrap_or(UNLOCKING_DURATION),
+        }
+    }
+
+    pub fn get(&self, target: Target) -> u64 {
+        match target {
+            Target::Voting => self.voting,
+            Target::IntegrityPool => self.integrity_pool,
+        }
+    }
+}
+
 impl Position {
     /// Managing the state of a position is tricky because we can only update the data when a user
     /// makes a transaction but many of the state transitions take effect later, e.g. at the
     /// next epoch boundary. In order to get the actual current state, we need the current
     /// epoch. This encapsulates that logic so that other parts of the code can use the actual
     /// state.
-    pub fn get_current_position(&self, current_epoch: u64) -> Result<PositionState> {
+    pub fn get_current_position(
+        &self,
+        current_epoch: u64,
+        unlocking_durations: UnlockingDurations,
+    ) -> Result<PositionState> {
         if current_epoch < self.activation_epoch {
             Ok(PositionState::LOCKING)
         } else {
@@ -550,7 +1180,9 @@ impl Position {
                 Some(unlocking_start) => {
                     let has_activated: bool = self.activation_epoch <= current_epoch;
                     let unlock_started: bool = unlocking_start <= current_epoch;
-                    let unlock_ended: bool = unlocking_start + UNLOCKING_DURATION <= current_epoch;
+                    let unlock_ended: bool = unlocking_start
+                        + unlocking_durations.get(self.target_with_parameters.get_target())
+                        <= current_epoch;
 
                     if has_activated && !unlock_started {
                         Ok(PositionState::PREUNLOCKING)
@@ -571,10 +1203,16 @@ impl Position {
      * pool, therefore `pool_authority` should ensure rewards have been claimed before
      * allowing merging positions.
      */
-    pub fn is_equivalent(&self, other: &Position, current_epoch: u64) -> bool {
-        self.get_current_position(current_epoch) == other.get_current_position(current_epoch)
-            && self.get_current_position(current_epoch.saturating_sub(1))
-                == other.get_current_position(current_epoch.saturating_sub(1))
+    pub fn is_equivalent(
+        &self,
+        other: &Position,
+        current_epoch: u64,
+        unlocking_durations: UnlockingDurations,
+    ) -> bool {
+        self.get_current_position(current_epoch, unlocking_durations)
+            == other.get_current_position(current_epoch, unlocking_durations)
+            && self.get_current_position(current_epoch.saturating_sub(1), unlocking_durations)
+                == other.get_current_position(current_epoch.saturating_sub(1), unlocking_durations)
             && self.target_with_parameters == other.target_with_parameters
     }
 
@@ -611,11 +1249,19 @@ pub mod tests {
                 Position,
                 PositionData,
                 PositionState,
+                PositionsLayout,
+                RedelegationMarker,
                 SlashedAmounts,
                 Target,
                 TargetWithParameters,
                 TryBorsh,
+                UnlockingDurations,
+                POSITIONS_V2_HEADER_LEN,
+                POSITIONS_V2_VERSION,
                 POSITION_BUFFER_SIZE,
+                POSITION_BUFFER_SIZE_V2,
+                REDELEGATION_MARKER_LEN,
+                UNLOCKING_DURATION_LEN,
             },
             target::TargetMetadata,
         },
@@ -642,21 +1288,31 @@ pub mod tests {
             target_with_parameters: TargetWithParameters::Voting,
             amount:                 10,
         };
-        assert_eq!(PositionState::LOCKING, p.get_current_position(0).unwrap());
-        assert_eq!(PositionState::LOCKING, p.get_current_position(7).unwrap());
+        let unlocking_durations = UnlockingDurations::default();
+        assert_eq!(
+            PositionState::LOCKING,
+            p.get_current_position(0, unlocking_durations).unwrap()
+        );
+        assert_eq!(
+            PositionState::LOCKING,
+            p.get_current_position(7, unlocking_durations).unwrap()
+        );
         assert_eq!(
             PositionState::PREUNLOCKING,
-            p.get_current_position(8).unwrap()
+            p.get_current_position(8, unlocking_durations).unwrap()
         );
         assert_eq!(
             PositionState::PREUNLOCKING,
-            p.get_current_position(11).unwrap()
+            p.get_current_position(11, unlocking_durations).unwrap()
         );
         assert_eq!(
             PositionState::UNLOCKING,
-            p.get_current_position(12).unwrap()
+            p.get_current_position(12, unlocking_durations).unwrap()
+        );
+        assert_eq!(
+            PositionState::UNLOCKED,
+            p.get_current_position(13, unlocking_durations).unwrap()
         );
-        assert_eq!(PositionState::UNLOCKED, p.get_current_position(13).unwrap());
     }
 
     #[test]
@@ -667,12 +1323,70 @@ pub mod tests {
             target_with_parameters: TargetWithParameters::Voting,
             amount:                 10,
         };
-        assert_eq!(PositionState::LOCKING, p.get_current_position(0).unwrap());
-        assert_eq!(PositionState::LOCKING, p.get_current_position(7).unwrap());
-        assert_eq!(PositionState::LOCKED, p.get_current_position(8).unwrap());
-        assert_eq!(PositionState::LOCKED, p.get_current_position(11).unwrap());
-        assert_eq!(PositionState::LOCKED, p.get_current_position(300).unwrap());
+        let unlocking_durations = UnlockingDurations::default();
+        assert_eq!(
+            PositionState::LOCKING,
+            p.get_current_position(0, unlocking_durations).unwrap()
+        );
+        assert_eq!(
+            PositionState::LOCKING,
+            p.get_current_position(7, unlocking_durations).unwrap()
+        );
+        assert_eq!(
+            PositionState::LOCKED,
+            p.get_current_position(8, unlocking_durations).unwrap()
+        );
+        assert_eq!(
+            PositionState::LOCKED,
+            p.get_current_position(11, unlocking_durations).unwrap()
+        );
+        assert_eq!(
+            PositionState::LOCKED,
+            p.get_current_position(300, unlocking_durations).unwrap()
+        );
+    }
+
+    #[test]
+    fn lifecycle_unlocking_duration_per_target() {
+        let unlocking_durations = UnlockingDurations {
+            voting:         1,
+            integrity_pool: 3,
+        };
+        let voting = Position {
+            activation_epoch:       8,
+            unlocking_start:        Some(12),
+            target_with_parameters: TargetWithParameters::Voting,
+            amount:                 10,
+        };
+        let integrity_pool = Position {
+            target_with_parameters: TargetWithParameters::IntegrityPool {
+                publisher: Pubkey::new_unique(),
+            },
+            ..voting
+        };
+
+        assert_eq!(
+            PositionState::UNLOCKED,
+            voting
+                .get_current_position(13, unlocking_durations)
+                .unwrap()
+        );
+        for epoch in 12..15 {
+            assert_eq!(
+                PositionState::UNLOCKING,
+                integrity_pool
+                    .get_current_position(epoch, unlocking_durations)
+                    .unwrap()
+            );
+        }
+        assert_eq!(
+            PositionState::UNLOCKED,
+            integrity_pool
+                .get_current_position(15, unlocking_durations)
+                .unwrap()
+        );
     }
+
     #[test]
     #[allow(deprecated)]
     fn test_serialized_size() {
@@ -683,6 +1397,25 @@ pub mod tests {
             anchor_lang::solana_program::borsh::get_packed_len::<Option<Position>>()
                 < POSITION_BUFFER_SIZE
         );
+        assert!(
+            anchor_lang::solana_program::borsh::get_packed_len::<Option<Position>>()
+                < POSITION_BUFFER_SIZE_V2
+        );
+        // The redelegation marker and the unlocking duration are stored after the position
+        assert!(
+            anchor_lang::solana_program::borsh::get_packed_len::<Option<Position>>()
+                + REDELEGATION_MARKER_LEN
+                + UNLOCKING_DURATION_LEN
+                <= POSITION_BUFFER_SIZE_V2
+        );
+        assert_eq!(
+            anchor_lang::solana_program::borsh::get_packed_len::<Option<u32>>(),
+            UNLOCKING_DURATION_LEN
+        );
+        assert_eq!(
+            RedelegationMarker::default().try_to_vec().unwrap().len(),
+            REDELEGATION_MARKER_LEN
+        );
     }
 
     #[test]
@@ -726,6 +1459,274 @@ pub mod tests {
             .unwrap());
     }
 
+    #[test]
+    fn test_v2_layout() {
+        let mut fixture = DynamicPositionArrayAccount::default();
+        fixture.data[PositionData::LEN] = POSITIONS_V2_VERSION;
+        let mut position_data = fixture.to_dynamic_position_array();
+        assert_eq!(position_data.get_layout().unwrap(), PositionsLayout::V2);
+        assert_eq!(
+            position_data.get_position_capacity(),
+            (20040 - POSITIONS_V2_HEADER_LEN - PositionData::LEN) / POSITION_BUFFER_SIZE_V2
+        );
+
+        let mut next_index: u8 = 0;
+        for i in 0..270 {
+            let position = Position {
+                activation_epoch:       1,
+                unlocking_start:        Some(2),
+                target_with_parameters: TargetWithParameters::IntegrityPool {
+                    publisher: Pubkey::new_unique(),
+                },
+                amount:                 i,
+            };
+            let index = position_data.reserve_new_index(&mut next_index).unwrap();
+            assert_eq!(index, i as usize);
+            position_data.write_position(index, &position).unwrap();
+        }
+        assert_eq!(next_index, 0);
+        assert_eq!(position_data.get_next_index(next_index).unwrap(), 270);
+
+        for i in 0..270 {
+            assert_eq!(
+                position_data.read_position(i).unwrap().unwrap().amount,
+                i as u64
+            );
+        }
+        assert!(position_data.read_position(270).unwrap().is_none());
+
+        position_data.make_none(0, &mut next_index).unwrap();
+        assert_eq!(position_data.get_next_index(next_index).unwrap(), 269);
+        assert_eq!(position_data.read_position(0).unwrap().unwrap().amount, 269);
+        assert!(position_data.read_position(269).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_unlocking_duration() {
+        let mut fixture = DynamicPositionArrayAccount::default();
+        let mut position_data = fixture.to_dynamic_position_array();
+        let mut next_index: u8 = 0;
+        let unlocking_durations = UnlockingDurations::default();
+
+        let position = Position {
+            activation_epoch:       1,
+            unlocking_start:        Some(5),
+            target_with_parameters: TargetWithParameters::Voting,
+            amount:                 10,
+        };
+        for _ in 0..2 {
+            let i = position_data.reserve_new_index(&mut next_index).unwrap();
+            position_data.write_position(i, &position).unwrap();
+        }
+        position_data.write_unlocking_duration(1, Some(3)).unwrap();
+
+        // the first position uses the unlocking duration of the config
+        assert_eq!(position_data.read_unlocking_duration(0).unwrap(), None);
+        assert_eq!(position_data.read_unlocking_duration(1).unwrap(), Some(3));
+        assert_eq!(
+            position_data
+                .get_unlocking_durations(0, unlocking_durations)
+                .unwrap(),
+            unlocking_durations
+        );
+        assert_eq!(
+            position_data
+                .get_target_exposure(&Target::Voting, 6, unlocking_durations)
+                .unwrap(),
+            10
+        );
+        assert_eq!(
+            position_data
+                .get_target_exposure(&Target::Voting, 8, unlocking_durations)
+                .unwrap(),
+            0
+        );
+
+        // the unlocking duration moves with the position
+        position_data.make_none(0, &mut next_index).unwrap();
+        assert_eq!(position_data.read_unlocking_duration(0).unwrap(), Some(3));
+
+        // a new position doesn't inherit the unlocking duration of a removed one
+        position_data.make_none(0, &mut next_index).unwrap();
+        position_data.write_unlocking_duration(0, Some(3)).unwrap();
+        let i = position_data.reserve_new_index(&mut next_index).unwrap();
+        assert_eq!(i, 0);
+        assert_eq!(position_data.read_unlocking_duration(0).unwrap(), None);
+
+        assert!(position_data
+            .write_unlocking_duration(0, Some(u64::from(u32::MAX) + 1))
+            .is_err());
+    }
+
+    #[test]
+    fn test_redelegation_marker() {
+        let mut fixture = DynamicPositionArrayAccount::default();
+        let mut position_data = fixture.to_dynamic_position_array();
+        let mut next_index: u8 = 0;
+        let unlocking_durations = UnlockingDurations::default();
+        let first_publisher = TargetWithParameters::IntegrityPool {
+            publisher: Pubkey::new_unique(),
+        };
+        let second_publisher = TargetWithParameters::IntegrityPool {
+            publisher: Pubkey::new_unique(),
+        };
+
+        // a position closed at epoch 2 and a new position created at epoch 2
+        for position in [
+            Position {
+                activation_epoch:       1,
+                unlocking_start:        Some(3),
+                target_with_parameters: first_publisher,
+                amount:                 10,
+            },
+            Position {
+                activation_epoch:       3,
+                unlocking_start:        None,
+                target_with_parameters: second_publisher,
+                amount:                 10,
+            },
+        ] {
+            let i = position_data.reserve_new_index(&mut next_index).unwrap();
+            position_data.write_position(i, &position).unwrap();
+        }
+
+        // they aren't a redelegation, so both count
+        assert_eq!(
+            position_data
+                .get_integrity_pool_exposure(2, unlocking_durations)
+                .unwrap(),
+            20
+        );
+
+        // the marker doesn't overlap the position or the unlocking duration
+        position_data.write_unlocking_duration(0, Some(1)).unwrap();
+        position_data
+            .write_redelegation_marker(
+                0,
+                RedelegationMarker {
+                    unlocking:  true,
+                    activating: false,
+                },
+            )
+            .unwrap();
+        position_data
+            .write_redelegation_marker(
+                1,
+                RedelegationMarker {
+                    unlocking:  false,
+                    activating: true,
+                },
+            )
+            .unwrap();
+        assert_eq!(position_data.read_position(0).unwrap().unwrap().amount, 10);
+        assert_eq!(position_data.read_unlocking_duration(0).unwrap(), Some(1));
+
+        // the redelegated tokens only count once
+        assert_eq!(
+            position_data
+                .get_integrity_pool_exposure(2, unlocking_durations)
+                .unwrap(),
+            10
+        );
+
+        // a new position doesn't inherit the marker of a removed one
+        position_data.make_none(0, &mut next_index).unwrap();
+        assert!(
+            position_data
+                .read_redelegation_marker(0)
+                .unwrap()
+                .activating
+        );
+        position_data.make_none(0, &mut next_index).unwrap();
+        let i = position_data.reserve_new_index(&mut next_index).unwrap();
+        assert_eq!(i, 0);
+        assert_eq!(
+            position_data.read_redelegation_marker(0).unwrap(),
+            RedelegationMarker::default()
+        );
+    }
+
+    #[test]
+    fn test_add_position() {
+        let mut fixture = DynamicPositionArrayAccount::default();
+        let mut position_data = fixture.to_dynamic_position_array();
+        let mut next_index: u8 = 0;
+
+        let voting = Position {
+            activation_epoch:       3,
+            unlocking_start:        None,
+            target_with_parameters: TargetWithParameters::Voting,
+            amount:                 10,
+        };
+        let integrity_pool = Position {
+            target_with_parameters: TargetWithParameters::IntegrityPool {
+                publisher: Pubkey::new_unique(),
+            },
+            ..voting
+        };
+
+        assert_eq!(
+            position_data
+                .add_position(&mut next_index, &voting)
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            position_data
+                .add_position(&mut next_index, &integrity_pool)
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            position_data
+                .add_position(&mut next_index, &voting)
+                .unwrap(),
+            0
+        );
+        assert_eq!(next_index, 2);
+        assert_eq!(position_data.read_position(0).unwrap().unwrap().amount, 20);
+        assert_eq!(position_data.read_position(1).unwrap().unwrap().amount, 10);
+
+        // positions with a different activation epoch aren't merged
+        let next_epoch = Position {
+            activation_epoch: 4,
+            ..voting
+        };
+        assert_eq!(
+            position_data
+                .add_position(&mut next_index, &next_epoch)
+                .unwrap(),
+            2
+        );
+
+        // unlocking positions aren't merged
+        let unlocking = Position {
+            unlocking_start: Some(4),
+            ..voting
+        };
+        position_data.write_position(0, &unlocking).unwrap();
+        assert_eq!(
+            position_data
+                .add_position(&mut next_index, &voting)
+                .unwrap(),
+            3
+        );
+        assert_eq!(next_index, 4);
+        assert_eq!(position_data.read_position(0).unwrap().unwrap(), unlocking);
+        assert_eq!(position_data.read_position(3).unwrap().unwrap(), voting);
+
+        let max_amount = Position {
+            amount: u64::MAX,
+            ..voting
+        };
+        assert_eq!(
+            position_data
+                .add_position(&mut next_index, &max_amount)
+                .unwrap_err(),
+            crate::error::ErrorCode::GenericOverflow.into()
+        );
+    }
+
     // A vector of DataOperation will be tested on both our struct and on a HashSet
     #[derive(Clone, Debug)]
     enum DataOperation {
@@ -869,9 +1870,11 @@ pub mod tests {
             u64,
         > = HashMap::new();
         for &position in positions.iter() {
-            let current_state = position.get_current_position(epoch).unwrap();
+            let current_state = position
+                .get_current_position(epoch, UnlockingDurations::default())
+                .unwrap();
             let previous_state = position
-                .get_current_position(epoch.saturating_sub(1))
+                .get_current_position(epoch.saturating_sub(1), UnlockingDurations::default())
                 .unwrap();
 
             if current_state != PositionState::UNLOCKED {
@@ -900,10 +1903,16 @@ pub mod tests {
                 TargetWithParameters::IntegrityPool {
                     publisher: FIRST_PUBLISHER,
                 },
+                UnlockingDurations::default(),
             )
             .unwrap();
         dynamic_position_array
-            .merge_target_positions(epoch, &mut next_index, TargetWithParameters::Voting)
+            .merge_target_positions(
+                epoch,
+                &mut next_index,
+                TargetWithParameters::Voting,
+                UnlockingDurations::default(),
+            )
             .unwrap();
         dynamic_position_array
             .merge_target_positions(
@@ -912,6 +1921,7 @@ pub mod tests {
                 TargetWithParameters::IntegrityPool {
                     publisher: SECOND_PUBLISHER,
                 },
+                UnlockingDurations::default(),
             )
             .unwrap();
 
@@ -923,9 +1933,11 @@ pub mod tests {
         > = HashMap::new();
         for i in 0..next_index {
             if let Some(position) = dynamic_position_array.read_position(i as usize).unwrap() {
-                let current_state = position.get_current_position(epoch).unwrap();
+                let current_state = position
+                    .get_current_position(epoch, UnlockingDurations::default())
+                    .unwrap();
                 let previous_state = position
-                    .get_current_position(epoch.saturating_sub(1))
+                    .get_current_position(epoch.saturating_sub(1), UnlockingDurations::default())
                     .unwrap();
 
                 if hash_set.contains(&(
@@ -962,7 +1974,12 @@ pub mod tests {
 
 
     #[quickcheck]
-    fn slash_position(positions: Vec<Position>, epoch: u8, slash_ratio: u64) -> bool {
+    fn slash_position(
+        positions: Vec<Position>,
+        redelegation_markers: Vec<(bool, bool)>,
+        epoch: u8,
+        slash_ratio: u64,
+    ) -> bool {
         let epoch = ((epoch % 7) + 1) as u64;
         let mut fixture = DynamicPositionArrayAccount::default();
         let mut dynamic_position_array = fixture.to_dynamic_position_array();
@@ -976,10 +1993,12 @@ pub mod tests {
             (TargetWithParameters, PositionState, PositionState),
             u64,
         > = HashMap::new();
-        for &position in positions.iter() {
-            let current_state = position.get_current_position(epoch).unwrap();
+        for (k, &position) in positions.iter().enumerate() {
+            let current_state = position
+                .get_current_position(epoch, UnlockingDurations::default())
+                .unwrap();
             let previous_state = position
-                .get_current_position(epoch.saturating_sub(1))
+                .get_current_position(epoch.saturating_sub(1), UnlockingDurations::default())
                 .unwrap();
 
             pre_position_buckets
@@ -1012,6 +2031,17 @@ pub mod tests {
             dynamic_position_array
                 .write_position(index, &position)
                 .unwrap();
+            if let Some(&(unlocking, activating)) = redelegation_markers.get(k) {
+                dynamic_position_array
+                    .write_redelegation_marker(
+                        index,
+                        RedelegationMarker {
+                            unlocking,
+                            activating,
+                        },
+                    )
+                    .unwrap();
+            }
         }
 
         let mut governance_target_account = {
@@ -1118,11 +2148,12 @@ pub mod tests {
                 total_slashed,
                 locked_slashed,
                 preunlocking_slashed,
+                redelegation_caps: vec![],
             }
         };
 
         let governance_exposure = dynamic_position_array
-            .get_target_exposure(&Target::Voting, epoch)
+            .get_target_exposure(&Target::Voting, epoch, UnlockingDurations::default())
             .unwrap();
 
         let publisher_1_exposure = pre_position_buckets
@@ -1157,6 +2188,7 @@ pub mod tests {
             total_slashed,
             locked_slashed,
             preunlocking_slashed,
+            redelegation_caps,
         } = dynamic_position_array
             .slash_positions(
                 epoch,
@@ -1165,6 +2197,7 @@ pub mod tests {
                 &FIRST_PUBLISHER,
                 slash_ratio,
                 &mut governance_target_account,
+                UnlockingDurations::default(),
             )
             .unwrap();
 
@@ -1175,9 +2208,11 @@ pub mod tests {
         > = HashMap::new();
         for i in 0..next_index {
             if let Some(position) = dynamic_position_array.read_position(i as usize).unwrap() {
-                let current_state = position.get_current_position(epoch).unwrap();
+                let current_state = position
+                    .get_current_position(epoch, UnlockingDurations::default())
+                    .unwrap();
                 let previous_state = position
-                    .get_current_position(epoch.saturating_sub(1))
+                    .get_current_position(epoch.saturating_sub(1), UnlockingDurations::default())
                     .unwrap();
 
                 post_position_buckets
@@ -1222,7 +2257,7 @@ pub mod tests {
 
         // check governance exposure has been reduced by the correct amount
         let post_governance_exposure = dynamic_position_array
-            .get_target_exposure(&Target::Voting, epoch)
+            .get_target_exposure(&Target::Voting, epoch, UnlockingDurations::default())
             .unwrap();
 
         if post_governance_exposure
@@ -1286,13 +2321,19 @@ pub mod tests {
         let mut post_amount_slashed_preunlocking = 0;
 
         for (target, prev_state, curr_state) in pre_position_buckets.keys() {
-            // slashing doesn't affect this target
+            // slashing doesn't affect this target, unless its positions took the place of slashed
+            // positions in a redelegation
             if (target
                 == &TargetWithParameters::IntegrityPool {
                     publisher: SECOND_PUBLISHER,
                 })
-                && pre_position_buckets.get(&(*target, *prev_state, *curr_state))
-                    != post_position_buckets.get(&(*target, *prev_state, *curr_state))
+                && (if redelegation_caps.is_empty() {
+                    pre_position_buckets.get(&(*target, *prev_state, *curr_state))
+                        != post_position_buckets.get(&(*target, *prev_state, *curr_state))
+                } else {
+                    pre_position_buckets.get(&(*target, *prev_state, *curr_state))
+                        < post_position_buckets.get(&(*target, *prev_state, *curr_state))
+                })
             {
                 return false;
             }
@@ -1380,4 +2421,178 @@ pub mod tests {
 
         true
     }
+
+    #[quickcheck]
+    fn split_positions(positions: Vec<Position>, epoch: u8, transferred_amount: u64) -> bool {
+        let epoch = ((epoch % 7) + 1) as u64;
+        let mut fixture = DynamicPositionArrayAccount::default();
+        let mut dynamic_position_array = fixture.to_dynamic_position_array();
+        let mut destination_fixture = DynamicPositionArrayAccount::default();
+        let mut destination = destination_fixture.to_dynamic_position_array();
+        let mut next_index: u8 = 0;
+        let mut destination_next_index: u8 = 0;
+
+        let mut pre_position_buckets: HashMap<(PositionState, PositionState), u64> = HashMap::new();
+        let mut exposure: u64 = 0;
+        for &position in positions.iter() {
+            let position = Position {
+                target_with_parameters: TargetWithParameters::Voting,
+                ..position
+            };
+            let current_state = position
+                .get_current_position(epoch, UnlockingDurations::default())
+                .unwrap();
+            let previous_state = position
+                .get_current_position(epoch.saturating_sub(1), UnlockingDurations::default())
+                .unwrap();
+            if current_state != PositionState::UNLOCKED {
+                exposure += position.amount;
+            }
+            pre_position_buckets
+                .entry((previous_state, current_state))
+                .and_modify(|e| *e += position.amount)
+                .or_insert(position.amount);
+
+            let index = dynamic_position_array
+                .reserve_new_index(&mut next_index)
+                .unwrap();
+            dynamic_position_array
+                .write_position(index, &position)
+                .unwrap();
+        }
+
+        let total_amount = exposure + 1;
+        let transferred_amount = transferred_amount % (total_amount + 1);
+        dynamic_position_array
+            .split_positions(
+                &mut destination,
+                &mut next_index,
+                &mut destination_next_index,
+                transferred_amount,
+                total_amount,
+                epoch,
+                UnlockingDurations::default(),
+            )
+            .unwrap();
+
+        let mut post_position_buckets: HashMap<(PositionState, PositionState), u64> =
+            HashMap::new();
+        for (positions, next_index) in [
+            (&dynamic_position_array, next_index),
+            (&destination, destination_next_index),
+        ] {
+            for i in 0..next_index {
+                let position = positions.read_position(i as usize).unwrap().unwrap();
+                let current_state = position
+                    .get_current_position(epoch, UnlockingDurations::default())
+                    .unwrap();
+                let previous_state = position
+                    .get_current_position(epoch.saturating_sub(1), UnlockingDurations::default())
+                    .unwrap();
+                post_position_buckets
+                    .entry((previous_state, current_state))
+                    .and_modify(|e| *e += position.amount)
+                    .or_insert(position.amount);
+            }
+        }
+        // empty positions don't count
+        pre_position_buckets.retain(|_, amount| *amount > 0);
+        post_position_buckets.retain(|_, amount| *amount > 0);
+        if pre_position_buckets != post_position_buckets {
+            return false;
+        }
+
+        // the exposure is split in the same proportion as the balance, rounding down the
+        // exposure of the destination
+        let destination_exposure = destination
+            .get_target_exposure(&Target::Voting, epoch, UnlockingDurations::default())
+            .unwrap();
+        let source_exposure = dynamic_position_array
+            .get_target_exposure(&Target::Voting, epoch, UnlockingDurations::default())
+            .unwrap();
+        destination_exposure
+            == (u128::from(exposure) * u128::from(transferred_amount) / u128::from(total_amount))
+                as u64
+            && source_exposure + destination_exposure == exposure
+            && destination_exposure <= transferred_amount
+            && source_exposure <= total_amount - transferred_amount
+    }
+
+    #[test]
+    fn split_positions_with_delegation() {
+        let mut fixture = DynamicPositionArrayAccount::default();
+        let mut dynamic_position_array = fixture.to_dynamic_position_array();
+        let mut destination_fixture = DynamicPositionArrayAccount::default();
+        let mut destination = destination_fixture.to_dynamic_position_array();
+        let mut next_index: u8 = 0;
+        let mut destination_next_index: u8 = 0;
+
+        let index = dynamic_position_array
+            .reserve_new_index(&mut next_index)
+            .unwrap();
+        dynamic_position_array
+            .write_position(
+                index,
+                &Position {
+                    activation_epoch:       1,
+                    unlocking_start:        Some(2),
+                    target_with_parameters: TargetWithParameters::IntegrityPool {
+                        publisher: FIRST_PUBLISHER,
+                    },
+                    amount:                 10,
+                },
+            )
+            .unwrap();
+
+        // unlocked integrity pool positions stay in the source account
+        dynamic_position_array
+            .split_positions(
+                &mut destination,
+                &mut next_index,
+                &mut destination_next_index,
+                5,
+                10,
+                3,
+                UnlockingDurations::default(),
+            )
+            .unwrap();
+        assert_eq!(next_index, 1);
+        assert_eq!(destination_next_index, 0);
+
+        dynamic_position_array
+            .split_positions(
+                &mut destination,
+                &mut next_index,
+                &mut destination_next_index,
+                4,
+                10,
+                2,
+                UnlockingDurations::default(),
+            )
+            .unwrap();
+        assert_eq!(next_index, 1);
+        assert_eq!(destination_next_index, 1);
+        assert_eq!(
+            dynamic_position_array.read_position(0).unwrap().unwrap(),
+            Position {
+                activation_epoch:       1,
+                unlocking_start:        Some(2),
+                target_with_parameters: TargetWithParameters::IntegrityPool {
+                    publisher: FIRST_PUBLISHER,
+                },
+                amount:                 6,
+            }
+        );
+        assert_eq!(
+            destination.read_position(0).unwrap().unwrap(),
+            Position {
+                activation_epoch:       1,
+                unlocking_start:        Some(2),
+                target_with_parameters: TargetWithParameters::IntegrityPool {
+                    publisher: FIRST_PUBLISHER,
+                },
+                amount:                 4,
+            }
+        );
+    }
 }
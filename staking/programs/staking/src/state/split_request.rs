@@ -1,17 +1,114 @@
 use {
+    crate::utils::account::create_or_grow_account,
     anchor_lang::prelude::*,
     borsh::BorshSchema,
 };
 
+/// Number of epochs during which a split request can be accepted, including the epoch of the
+/// request
+pub const SPLIT_REQUEST_DURATION: u64 = 4;
+
 #[account]
-#[derive(Default, BorshSchema)]
+#[derive(Default, BorshSchema, Debug, PartialEq, Eq)]
 pub struct SplitRequest {
-    pub amount:    u64,
-    pub recipient: Pubkey,
+    pub amount:       u64,
+    pub recipient:    Pubkey,
+    pub expiry_epoch: u64,
 }
 
 impl SplitRequest {
     pub const LEN: usize = 8 // Discriminant
                          + 8 // Amount
-                         + 32; // Recipient
+                         + 32 // Recipient
+                         + 8; // Expiry epoch
+
+    // length of the requests created before the expiry epoch was introduced
+    pub const LEGACY_LEN: usize = 8 + 8 + 32;
+
+    pub fn is_expired(&self, current_epoch: u64) -> bool {
+        current_epoch > self.expiry_epoch
+    }
+
+    /// Deserializes a request, requests created before the expiry epoch was introduced are
+    /// expired, they're stale and have to be requested again
+    pub fn try_deserialize_legacy(data: &[u8]) -> Result<Self> {
+        if data.len() >= Self::LEN {
+            return Self::try_deserialize(&mut &data[..]);
+        }
+        if data.len() != Self::LEGACY_LEN {
+            return Err(anchor_lang::error::ErrorCode::AccountDidNotDeserialize.into());
+        }
+        let mut padded = data.to_vec();
+        padded.extend_from_slice(&0u64.to_le_bytes());
+        Self::try_deserialize(&mut &padded[..])
+    }
+
+    /// Writes the request to its account, the account is created if it doesn't exist and
+    /// requests created before the expiry epoch was introduced are resized
+    pub fn save<'info>(
+        &self,
+        account: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        create_or_grow_account(
+            account,
+            payer,
+            system_program,
+            Self::LEN,
+            &crate::ID,
+            signer_seeds,
+        )?;
+
+        let mut data = account.try_borrow_mut_data()?;
+        self.try_serialize(&mut &mut data[..])
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use {
+        crate::state::split_request::SplitRequest,
+        anchor_lang::{
+            prelude::*,
+            Discriminator,
+        },
+    };
+
+    #[test]
+    fn test_deserialize_legacy() {
+        let split_request = SplitRequest {
+            amount:       1,
+            recipient:    Pubkey::new_unique(),
+            expiry_epoch: 2,
+        };
+        let mut data = vec![];
+        split_request.try_serialize(&mut data).unwrap();
+        assert_eq!(
+            SplitRequest::try_deserialize_legacy(&data).unwrap(),
+            split_request
+        );
+
+        // requests created before the expiry epoch was introduced are expired
+        let legacy_split_request =
+            SplitRequest::try_deserialize_legacy(&data[..SplitRequest::LEGACY_LEN]).unwrap();
+        assert_eq!(legacy_split_request.amount, 1);
+        assert_eq!(legacy_split_request.recipient, split_request.recipient);
+        assert!(legacy_split_request.is_expired(1));
+
+        assert!(
+            SplitRequest::try_deserialize_legacy(&data[..SplitRequest::LEGACY_LEN - 1]).is_err()
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn check_size() {
+        assert_eq!(
+            anchor_lang::solana_program::borsh::get_packed_len::<SplitRequest>()
+                + SplitRequest::discriminator().len(),
+            SplitRequest::LEN
+        );
+    }
 }
@@ -1,5 +1,6 @@
 use {
     crate::{
+        context::POOL_DATA_MAX_PUBLISHERS,
         error::ErrorCode,
         state::vesting::VestingSchedule,
     },
@@ -16,19 +17,38 @@ use {
 #[account]
 #[derive(BorshSchema)]
 pub struct StakeAccountMetadataV2 {
-    pub metadata_bump:         u8,
-    pub custody_bump:          u8,
-    pub authority_bump:        u8,
-    pub voter_bump:            u8,
-    pub owner:                 Pubkey,
-    pub lock:                  VestingSchedule,
-    pub next_index:            u8,
-    pub _deprecated:           Option<u64>,
-    pub signed_agreement_hash: Option<[u8; 32]>,
+    pub metadata_bump:              u8,
+    pub custody_bump:               u8,
+    pub authority_bump:             u8,
+    pub voter_bump:                 u8,
+    pub owner:                      Pubkey,
+    pub lock:                       VestingSchedule,
+    // only used by positions accounts with the V1 layout, see `DynamicPositionArray`
+    pub next_index:                 u8,
+    pub _deprecated:                Option<u64>,
+    pub signed_agreement_hash:      Option<[u8; 32]>,
+    // epoch of the last transfer of the account, the account can't vote with snapshots taken
+    // up to this epoch to prevent double voting
+    pub transfer_epoch:             Option<u64>,
+    // key allowed to manage the positions of the account on behalf of the owner, it can't move
+    // tokens out of the account
+    pub operator:                   Option<Pubkey>,
+    // number of delegation records of the pool authority's program for this account, the account
+    // can't be closed while there are some
+    pub delegation_record_count:    u32,
+    // number of publishers of the pool data whose delegation record is known to be counted,
+    // accounts created before the count existed need to check all of them before being closed
+    pub delegation_records_checked: u16,
 }
 
 impl StakeAccountMetadataV2 {
-    pub const LEN: usize = 200;
+    // accounts created before the operator was introduced are 200 bytes long and get reallocated
+    // when an operator is set
+    pub const LEN: usize = 240;
+
+    pub fn is_owner_or_operator(&self, signer: &Pubkey) -> bool {
+        self.owner == *signer || self.operator == Some(*signer)
+    }
 
     pub fn check_is_llc_member(&self, expected_agreement_hash: &[u8; 32]) -> Result<()> {
         if let Some(agreement_hash) = self.signed_agreement_hash {
@@ -39,6 +59,16 @@ impl StakeAccountMetadataV2 {
 
         err!(ErrorCode::NotLlcMember)
     }
+
+    pub fn check_can_vote(&self, epoch_of_snapshot: u64) -> Result<()> {
+        if let Some(transfer_epoch) = self.transfer_epoch {
+            if epoch_of_snapshot <= transfer_epoch {
+                return err!(ErrorCode::VoteDuringTransferEpoch);
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl StakeAccountMetadataV2 {
@@ -57,6 +87,11 @@ impl StakeAccountMetadataV2 {
         self.next_index = 0;
         self._deprecated = None;
         self.signed_agreement_hash = None;
+        self.transfer_epoch = None;
+        self.operator = None;
+        self.delegation_record_count = 0;
+        // the delegation records of new accounts are counted from the start
+        self.delegation_records_checked = POOL_DATA_MAX_PUBLISHERS as u16;
     }
 
     pub fn set_lock(&mut self, lock: VestingSchedule) {
@@ -88,15 +123,19 @@ pub mod tests {
     #[test]
     fn check_is_llc_member() {
         let stake_account_metadata_llc_member = StakeAccountMetadataV2 {
-            metadata_bump:         0,
-            custody_bump:          0,
-            authority_bump:        0,
-            voter_bump:            0,
-            owner:                 Pubkey::default(),
-            lock:                  VestingSchedule::FullyVested,
-            next_index:            0,
-            _deprecated:           None,
-            signed_agreement_hash: Some([0; 32]),
+            metadata_bump:              0,
+            custody_bump:               0,
+            authority_bump:             0,
+            voter_bump:                 0,
+            owner:                      Pubkey::default(),
+            lock:                       VestingSchedule::FullyVested,
+            next_index:                 0,
+            _deprecated:                None,
+            signed_agreement_hash:      Some([0; 32]),
+            transfer_epoch:             None,
+            operator:                   None,
+            delegation_record_count:    0,
+            delegation_records_checked: 0,
         };
         assert!(stake_account_metadata_llc_member
             .check_is_llc_member(&[0; 32])
@@ -108,18 +147,75 @@ pub mod tests {
 
 
         let stake_account_metadata_non_llc_member = StakeAccountMetadataV2 {
-            metadata_bump:         0,
-            custody_bump:          0,
-            authority_bump:        0,
-            voter_bump:            0,
-            owner:                 Pubkey::default(),
-            lock:                  VestingSchedule::FullyVested,
-            next_index:            0,
-            _deprecated:           None,
-            signed_agreement_hash: None,
+            metadata_bump:              0,
+            custody_bump:               0,
+            authority_bump:             0,
+            voter_bump:                 0,
+            owner:                      Pubkey::default(),
+            lock:                       VestingSchedule::FullyVested,
+            next_index:                 0,
+            _deprecated:                None,
+            signed_agreement_hash:      None,
+            transfer_epoch:             None,
+            operator:                   None,
+            delegation_record_count:    0,
+            delegation_records_checked: 0,
         };
         assert!(stake_account_metadata_non_llc_member
             .check_is_llc_member(&[0; 32])
             .is_err());
     }
+
+    #[test]
+    fn check_can_vote() {
+        let mut stake_account_metadata = StakeAccountMetadataV2 {
+            metadata_bump:              0,
+            custody_bump:               0,
+            authority_bump:             0,
+            voter_bump:                 0,
+            owner:                      Pubkey::default(),
+            lock:                       VestingSchedule::FullyVested,
+            next_index:                 0,
+            _deprecated:                None,
+            signed_agreement_hash:      None,
+            transfer_epoch:             None,
+            operator:                   None,
+            delegation_record_count:    0,
+            delegation_records_checked: 0,
+        };
+        assert!(stake_account_metadata.check_can_vote(0).is_ok());
+
+        stake_account_metadata.transfer_epoch = Some(10);
+        assert!(stake_account_metadata.check_can_vote(9).is_err());
+        assert!(stake_account_metadata.check_can_vote(10).is_err());
+        assert!(stake_account_metadata.check_can_vote(11).is_ok());
+    }
+
+    #[test]
+    fn check_is_owner_or_operator() {
+        let owner = Pubkey::new_unique();
+        let operator = Pubkey::new_unique();
+        let mut stake_account_metadata = StakeAccountMetadataV2 {
+            metadata_bump: 0,
+            custody_bump: 0,
+            authority_bump: 0,
+            voter_bump: 0,
+            owner,
+            lock: VestingSchedule::FullyVested,
+            next_index: 0,
+            _deprecated: None,
+            signed_agreement_hash: None,
+            transfer_epoch: None,
+            operator: None,
+            delegation_record_count: 0,
+            delegation_records_checked: 0,
+        };
+        assert!(stake_account_metadata.is_owner_or_operator(&owner));
+        assert!(!stake_account_metadata.is_owner_or_operator(&operator));
+
+        stake_account_metadata.operator = Some(operator);
+        assert!(stake_account_metadata.is_owner_or_operator(&owner));
+        assert!(stake_account_metadata.is_owner_or_operator(&operator));
+        assert!(!stake_account_metadata.is_owner_or_operator(&Pubkey::new_unique()));
+    }
 }
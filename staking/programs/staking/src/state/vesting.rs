@@ -34,6 +34,35 @@ pub enum VestingSchedule {
         period_duration: u64,
         num_periods:     u64,
     },
+    /// Same as PeriodicVesting, but nothing vests before the end of the first (cliff_periods)
+    /// periods, when the amounts of these periods vest at once
+    PeriodicVestingWithCliff {
+        initial_balance: u64,
+        start_date:      i64,
+        period_duration: u64,
+        num_periods:     u64,
+        cliff_periods:   u64,
+    },
+    /// Each of the first (num_tranches) tranches vests its amount at its time
+    Tranches {
+        num_tranches: u8,
+        tranches:     [VestingTranche; MAX_VESTING_TRANCHES],
+    },
+}
+
+/// The number of tranches is bounded so that the schedule fits in the stake account metadata
+pub const MAX_VESTING_TRANCHES: usize = 6;
+
+/// Maximum number of vesting events of the current schedule that are checked when comparing
+/// schedules, to bound the compute used by the comparison
+pub const MAX_COMPARED_VESTING_EVENTS: usize = 256;
+
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, BorshSchema, PartialEq, Default,
+)]
+pub struct VestingTranche {
+    pub time:   i64,
+    pub amount: u64,
 }
 
 #[wasm_bindgen]
@@ -89,6 +118,38 @@ impl VestingSchedule {
                 },
                 None,
             ) => Ok(initial_balance),
+            (
+                VestingSchedule::PeriodicVestingWithCliff {
+                    initial_balance,
+                    start_date,
+                    period_duration,
+                    num_periods,
+                    cliff_periods,
+                },
+                _,
+            ) => Ok(VestingSchedule::periodic_vesting_with_cliff_helper(
+                current_time,
+                initial_balance,
+                start_date,
+                period_duration,
+                num_periods,
+                cliff_periods,
+            )),
+            (
+                VestingSchedule::Tranches {
+                    num_tranches,
+                    tranches,
+                },
+                _,
+            ) => tranches
+                .iter()
+                .take(num_tranches.into())
+                .filter(|tranche| tranche.time > current_time)
+                .try_fold(0u64, |unvested, tranche| {
+                    unvested
+                        .checked_add(tranche.amount)
+                        .ok_or_else(|| error!(ErrorCode::GenericOverflow))
+                }),
         }
     }
 
@@ -132,9 +193,94 @@ impl VestingSchedule {
                 // No vesting events until the token listing date is determined.
                 Ok(None)
             }
+            (
+                VestingSchedule::PeriodicVestingWithCliff {
+                    initial_balance,
+                    start_date,
+                    period_duration,
+                    num_periods,
+                    cliff_periods,
+                },
+                _,
+            ) => VestingSchedule::next_vesting_with_cliff_helper(
+                current_time,
+                initial_balance,
+                start_date,
+                period_duration,
+                num_periods,
+                cliff_periods,
+            ),
+            (
+                VestingSchedule::Tranches {
+                    num_tranches,
+                    tranches,
+                },
+                _,
+            ) => {
+                let tranches = tranches.iter().take(num_tranches.into());
+                let next_time = tranches
+                    .clone()
+                    .map(|tranche| tranche.time)
+                    .filter(|time| *time > current_time)
+                    .min();
+
+                match next_time {
+                    Some(time) => Ok(Some(VestingEvent {
+                        time,
+                        amount: tranches.filter(|tranche| tranche.time == time).try_fold(
+                            0u64,
+                            |amount, tranche| {
+                                amount
+                                    .checked_add(tranche.amount)
+                                    .ok_or_else(|| error!(ErrorCode::GenericOverflow))
+                            },
+                        )?,
+                    })),
+                    None => Ok(None),
+                }
+            }
         }
     }
 
+    /// Checks that `self` vests at least as fast as `current`, i.e. that its unvested balance is
+    /// lower or equal at every time from `current_time` on.
+    /// Unvested balances never increase, so it's enough to compare them now and at every vesting
+    /// event of `current`. Schedules that depend on an unknown listing time or with more than
+    /// `MAX_COMPARED_VESTING_EVENTS` future events are only accepted if `self` is fully vested.
+    pub fn vests_faster_than(
+        &self,
+        current: &VestingSchedule,
+        current_time: i64,
+        token_list_time: Option<i64>,
+    ) -> Result<bool> {
+        if *self == VestingSchedule::FullyVested {
+            return Ok(true);
+        }
+
+        if token_list_time.is_none()
+            && (matches!(self, VestingSchedule::PeriodicVestingAfterListing { .. })
+                || matches!(current, VestingSchedule::PeriodicVestingAfterListing { .. }))
+        {
+            return Ok(false);
+        }
+
+        let mut time = current_time;
+        for _ in 0..=MAX_COMPARED_VESTING_EVENTS {
+            if self.get_unvested_balance(time, token_list_time)?
+                > current.get_unvested_balance(time, token_list_time)?
+            {
+                return Ok(false);
+            }
+
+            match current.get_next_vesting(time, token_list_time)? {
+                Some(event) => time = event.time,
+                None => return Ok(true),
+            }
+        }
+
+        Ok(false)
+    }
+
     /// This is essentially the unvested balance calculation for periodic vesting.
     /// Factor this out because linear vesting is the same as periodic vesting with a period of 1
     fn periodic_vesting_helper(
@@ -234,6 +380,83 @@ impl VestingSchedule {
         }))
     }
 
+    /// Unvested balance calculation for periodic vesting with a cliff. Until `cliff_periods`
+    /// periods have passed, nothing vests.
+    fn periodic_vesting_with_cliff_helper(
+        current_time: i64,
+        initial_balance: u64,
+        start_date: i64,
+        period_duration: u64,
+        num_periods: u64,
+        cliff_periods: u64,
+    ) -> u64 {
+        if current_time < start_date {
+            return initial_balance;
+        }
+
+        let time_passed: u64 = current_time
+            .checked_sub(start_date)
+            .unwrap()
+            .try_into()
+            .unwrap();
+        if time_passed / period_duration < cliff_periods {
+            initial_balance
+        } else {
+            Self::periodic_vesting_helper(
+                current_time,
+                initial_balance,
+                start_date,
+                period_duration,
+                num_periods,
+            )
+        }
+    }
+
+    /// Calculate the time when the next token vest occurs for a periodic vesting schedule with a
+    /// cliff. Before the cliff, the next vest is the cliff itself.
+    fn next_vesting_with_cliff_helper(
+        current_time: i64,
+        initial_balance: u64,
+        start_date: i64,
+        period_duration: u64,
+        num_periods: u64,
+        cliff_periods: u64,
+    ) -> Result<Option<VestingEvent>> {
+        let cliff_date = start_date
+            .checked_add(
+                cliff_periods
+                    .checked_mul(period_duration)
+                    .ok_or_else(|| error!(ErrorCode::GenericOverflow))?
+                    .try_into()
+                    .map_err(|_| error!(ErrorCode::GenericOverflow))?,
+            )
+            .ok_or_else(|| error!(ErrorCode::GenericOverflow))?;
+
+        if cliff_periods == 0 || current_time >= cliff_date {
+            return Self::next_vesting_helper(
+                current_time,
+                initial_balance,
+                start_date,
+                period_duration,
+                num_periods,
+            );
+        }
+
+        let amount = initial_balance.saturating_sub(Self::periodic_vesting_with_cliff_helper(
+            cliff_date,
+            initial_balance,
+            start_date,
+            period_duration,
+            num_periods,
+            cliff_periods,
+        ));
+
+        Ok(Some(VestingEvent {
+            time: cliff_date,
+            amount,
+        }))
+    }
+
     pub fn split_vesting_schedule(
         &self,
         transferred_amount: u64,
@@ -296,6 +519,87 @@ impl VestingSchedule {
                     num_periods:     *num_periods,
                 },
             )),
+            VestingSchedule::PeriodicVestingWithCliff {
+                initial_balance,
+                start_date,
+                period_duration,
+                num_periods,
+                cliff_periods,
+            } => Ok((
+                VestingSchedule::PeriodicVestingWithCliff {
+                    initial_balance: (u128::from(remaining_amount) * u128::from(*initial_balance)
+                        / u128::from(total_amount))
+                    .try_into()?,
+                    start_date:      *start_date,
+                    period_duration: *period_duration,
+                    num_periods:     *num_periods,
+                    cliff_periods:   *cliff_periods,
+                },
+                VestingSchedule::PeriodicVestingWithCliff {
+                    initial_balance: (u128::from(transferred_amount)
+                        * u128::from(*initial_balance)
+                        / u128::from(total_amount))
+                    .try_into()?,
+                    start_date:      *start_date,
+                    period_duration: *period_duration,
+                    num_periods:     *num_periods,
+                    cliff_periods:   *cliff_periods,
+                },
+            )),
+            VestingSchedule::Tranches {
+                num_tranches,
+                tranches,
+            } => {
+                // We split the amount that is still unvested after each tranche rather than the
+                // amount of each tranche, so that the unvested balance of both new schedules is
+                // the unvested balance of the original schedule rounded down at all times
+                let used_tranches = usize::from(*num_tranches).min(MAX_VESTING_TRANCHES);
+                let mut sorted_tranches = *tranches;
+                sorted_tranches[..used_tranches].sort_by_key(|tranche| tranche.time);
+
+                let mut remaining_tranches = sorted_tranches;
+                let mut transferred_tranches = sorted_tranches;
+                let mut unvested: u128 = sorted_tranches[..used_tranches]
+                    .iter()
+                    .map(|tranche| u128::from(tranche.amount))
+                    .sum();
+                let mut remaining_unvested =
+                    unvested * u128::from(remaining_amount) / u128::from(total_amount);
+                let mut transferred_unvested =
+                    unvested * u128::from(transferred_amount) / u128::from(total_amount);
+
+                for ((tranche, remaining_tranche), transferred_tranche) in sorted_tranches
+                    [..used_tranches]
+                    .iter()
+                    .zip(remaining_tranches.iter_mut())
+                    .zip(transferred_tranches.iter_mut())
+                {
+                    unvested -= u128::from(tranche.amount);
+                    let next_remaining_unvested =
+                        unvested * u128::from(remaining_amount) / u128::from(total_amount);
+                    let next_transferred_unvested =
+                        unvested * u128::from(transferred_amount) / u128::from(total_amount);
+
+                    remaining_tranche.amount =
+                        (remaining_unvested - next_remaining_unvested).try_into()?;
+                    transferred_tranche.amount =
+                        (transferred_unvested - next_transferred_unvested).try_into()?;
+
+                    remaining_unvested = next_remaining_unvested;
+                    transferred_unvested = next_transferred_unvested;
+                }
+
+                Ok((
+                    VestingSchedule::Tranches {
+                        num_tranches: *num_tranches,
+                        tranches:     remaining_tranches,
+                    },
+                    VestingSchedule::Tranches {
+                        num_tranches: *num_tranches,
+                        tranches:     transferred_tranches,
+                    },
+                ))
+            }
         }
     }
 }
@@ -309,7 +613,11 @@ pub mod tests {
                 self,
                 PeriodicVesting,
                 PeriodicVestingAfterListing,
+                PeriodicVestingWithCliff,
+                Tranches,
             },
+            VestingTranche,
+            MAX_VESTING_TRANCHES,
         },
         quickcheck::TestResult,
         quickcheck_macros::quickcheck,
@@ -503,6 +811,236 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_period_with_cliff() {
+        let v = PeriodicVestingWithCliff {
+            initial_balance: 20,
+            start_date:      5,
+            period_duration: 3,
+            num_periods:     7,
+            cliff_periods:   2,
+        };
+        let periodic = PeriodicVesting {
+            initial_balance: 20,
+            start_date:      5,
+            period_duration: 3,
+            num_periods:     7,
+        };
+
+        // nothing vests before the cliff, then the first two periods vest at once
+        for t in 0..11 {
+            assert_eq!(v.get_unvested_balance(t, None).unwrap(), 20);
+            assert_eq!(
+                v.get_next_vesting(t, None).unwrap(),
+                Some(VestingEvent {
+                    time:   11,
+                    amount: 6,
+                })
+            );
+        }
+
+        // after the cliff, the schedule is the same as without a cliff
+        for t in 11..30 {
+            assert_eq!(
+                v.get_unvested_balance(t, None).unwrap(),
+                periodic.get_unvested_balance(t, None).unwrap()
+            );
+            assert_eq!(
+                v.get_next_vesting(t, None).unwrap(),
+                periodic.get_next_vesting(t, None).unwrap()
+            );
+        }
+        assert_eq!(v.get_unvested_balance(11, None).unwrap(), 14);
+        assert_eq!(v.get_unvested_balance(5 + 7 * 3, None).unwrap(), 0);
+        assert_eq!(v.get_next_vesting(5 + 7 * 3, None).unwrap(), None);
+
+        // a cliff longer than the schedule vests everything at the cliff
+        let v = PeriodicVestingWithCliff {
+            initial_balance: 20,
+            start_date:      5,
+            period_duration: 3,
+            num_periods:     7,
+            cliff_periods:   10,
+        };
+        assert_eq!(v.get_unvested_balance(5 + 7 * 3, None).unwrap(), 20);
+        assert_eq!(
+            v.get_next_vesting(5 + 7 * 3, None).unwrap(),
+            Some(VestingEvent {
+                time:   35,
+                amount: 20,
+            })
+        );
+        assert_eq!(v.get_unvested_balance(35, None).unwrap(), 0);
+        assert_eq!(v.get_next_vesting(35, None).unwrap(), None);
+
+        // without a cliff, the schedule is periodic vesting
+        let v = PeriodicVestingWithCliff {
+            initial_balance: 20,
+            start_date:      5,
+            period_duration: 3,
+            num_periods:     7,
+            cliff_periods:   0,
+        };
+        for t in 0..30 {
+            assert_eq!(
+                v.get_unvested_balance(t, None).unwrap(),
+                periodic.get_unvested_balance(t, None).unwrap()
+            );
+            assert_eq!(
+                v.get_next_vesting(t, None).unwrap(),
+                periodic.get_next_vesting(t, None).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_tranches() {
+        let mut tranches = [VestingTranche::default(); MAX_VESTING_TRANCHES];
+        tranches[0] = VestingTranche {
+            time:   10,
+            amount: 5,
+        };
+        tranches[1] = VestingTranche {
+            time:   5,
+            amount: 3,
+        };
+        tranches[2] = VestingTranche {
+            time:   10,
+            amount: 2,
+        };
+        // only the first num_tranches tranches are used
+        tranches[3] = VestingTranche {
+            time:   20,
+            amount: 100,
+        };
+        let v = Tranches {
+            num_tranches: 3,
+            tranches,
+        };
+
+        for t in 0..5 {
+            assert_eq!(v.get_unvested_balance(t, None).unwrap(), 10);
+            assert_eq!(
+                v.get_next_vesting(t, None).unwrap(),
+                Some(VestingEvent {
+                    time:   5,
+                    amount: 3,
+                })
+            );
+        }
+        for t in 5..10 {
+            assert_eq!(v.get_unvested_balance(t, None).unwrap(), 7);
+            assert_eq!(
+                v.get_next_vesting(t, None).unwrap(),
+                Some(VestingEvent {
+                    time:   10,
+                    amount: 7,
+                })
+            );
+        }
+        for t in 10..30 {
+            assert_eq!(v.get_unvested_balance(t, None).unwrap(), 0);
+            assert_eq!(v.get_next_vesting(t, None).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn test_vests_faster_than() {
+        let current = PeriodicVesting {
+            initial_balance: 20,
+            start_date:      5,
+            period_duration: 3,
+            num_periods:     4,
+        };
+
+        assert!(VestingSchedule::FullyVested
+            .vests_faster_than(&current, 0, None)
+            .unwrap());
+        assert!(current.vests_faster_than(&current, 0, None).unwrap());
+        assert!(!current
+            .vests_faster_than(&VestingSchedule::FullyVested, 0, None)
+            .unwrap());
+
+        // fewer periods
+        let faster = PeriodicVesting {
+            initial_balance: 20,
+            start_date:      5,
+            period_duration: 3,
+            num_periods:     2,
+        };
+        assert!(faster.vests_faster_than(&current, 0, None).unwrap());
+        assert!(!current.vests_faster_than(&faster, 0, None).unwrap());
+
+        // vests later, but only after the current time
+        let later = PeriodicVesting {
+            initial_balance: 20,
+            start_date:      8,
+            period_duration: 3,
+            num_periods:     4,
+        };
+        assert!(!later.vests_faster_than(&current, 0, None).unwrap());
+        assert!(later.vests_faster_than(&current, 100, None).unwrap());
+
+        // a cliff delays vesting
+        let cliff = PeriodicVestingWithCliff {
+            initial_balance: 20,
+            start_date:      5,
+            period_duration: 3,
+            num_periods:     4,
+            cliff_periods:   2,
+        };
+        assert!(current.vests_faster_than(&cliff, 0, None).unwrap());
+        assert!(!cliff.vests_faster_than(&current, 0, None).unwrap());
+        assert!(cliff.vests_faster_than(&current, 11, None).unwrap());
+
+        // tranches that vest each period of the current schedule at its start
+        let mut tranches = [VestingTranche::default(); MAX_VESTING_TRANCHES];
+        for (i, tranche) in tranches.iter_mut().take(4).enumerate() {
+            *tranche = VestingTranche {
+                time:   5 + 3 * i as i64,
+                amount: 5,
+            };
+        }
+        let tranches = Tranches {
+            num_tranches: 4,
+            tranches,
+        };
+        assert!(tranches.vests_faster_than(&current, 0, None).unwrap());
+        assert!(!current.vests_faster_than(&tranches, 0, None).unwrap());
+
+        // the listing time must be known to compare schedules that depend on it
+        let after_listing = PeriodicVestingAfterListing {
+            initial_balance: 20,
+            period_duration: 3,
+            num_periods:     4,
+        };
+        assert!(!after_listing
+            .vests_faster_than(&after_listing, 0, None)
+            .unwrap());
+        assert!(after_listing
+            .vests_faster_than(&after_listing, 0, Some(5))
+            .unwrap());
+        assert!(after_listing
+            .vests_faster_than(&current, 0, Some(5))
+            .unwrap());
+        assert!(!after_listing
+            .vests_faster_than(&current, 0, Some(6))
+            .unwrap());
+        assert!(VestingSchedule::FullyVested
+            .vests_faster_than(&after_listing, 0, None)
+            .unwrap());
+
+        // too many vesting events to compare
+        let long = PeriodicVesting {
+            initial_balance: 1_000,
+            start_date:      0,
+            period_duration: 1,
+            num_periods:     1_000,
+        };
+        assert!(!long.vests_faster_than(&long, 0, None).unwrap());
+        assert!(long.vests_faster_than(&long, 1_000 - 256, None).unwrap());
+    }
+
     #[test]
     #[should_panic]
     fn test_overflow() {
@@ -738,6 +1276,126 @@ pub mod tests {
         TestResult::passed()
     }
 
+    #[quickcheck]
+    fn test_split_props_cliff_and_tranches(
+        transferred: u64,
+        total: u64,
+        initial_balance: u64,
+    ) -> TestResult {
+        if transferred > total || total == 0 {
+            return TestResult::discard();
+        }
+        let received = total - transferred;
+
+        let mut tranches = [VestingTranche::default(); MAX_VESTING_TRANCHES];
+        tranches[0] = VestingTranche {
+            time:   START_TIMESTAMP + 7,
+            amount: initial_balance / 3,
+        };
+        tranches[1] = VestingTranche {
+            time:   START_TIMESTAMP + 1,
+            amount: initial_balance % 3,
+        };
+        tranches[2] = VestingTranche {
+            time:   START_TIMESTAMP + 3,
+            amount: initial_balance / 3,
+        };
+        tranches[3] = VestingTranche {
+            time:   START_TIMESTAMP + 7,
+            amount: initial_balance / 3,
+        };
+
+        for schedule in [
+            PeriodicVestingWithCliff {
+                initial_balance,
+                // all of these fields should be preserved in the result
+                start_date: START_TIMESTAMP,
+                period_duration: PERIOD_DURATION,
+                num_periods: NUM_PERIODS,
+                cliff_periods: 2,
+            },
+            Tranches {
+                num_tranches: 4,
+                tranches,
+            },
+        ] {
+            let (remaining_schedule, transferred_schedule) =
+                schedule.split_vesting_schedule(transferred, total).unwrap();
+
+            match (schedule, remaining_schedule, transferred_schedule) {
+                (
+                    PeriodicVestingWithCliff {
+                        start_date,
+                        period_duration,
+                        num_periods,
+                        cliff_periods,
+                        ..
+                    },
+                    PeriodicVestingWithCliff {
+                        start_date: r_start_date,
+                        period_duration: r_period_duration,
+                        num_periods: r_num_periods,
+                        cliff_periods: r_cliff_periods,
+                        ..
+                    },
+                    PeriodicVestingWithCliff {
+                        start_date: t_start_date,
+                        period_duration: t_period_duration,
+                        num_periods: t_num_periods,
+                        cliff_periods: t_cliff_periods,
+                        ..
+                    },
+                ) => {
+                    assert_eq!(
+                        (start_date, period_duration, num_periods, cliff_periods),
+                        (
+                            r_start_date,
+                            r_period_duration,
+                            r_num_periods,
+                            r_cliff_periods
+                        )
+                    );
+                    assert_eq!(
+                        (start_date, period_duration, num_periods, cliff_periods),
+                        (
+                            t_start_date,
+                            t_period_duration,
+                            t_num_periods,
+                            t_cliff_periods
+                        )
+                    );
+                }
+                (Tranches { .. }, Tranches { .. }, Tranches { .. }) => {}
+                _ => {
+                    panic!("Test failed");
+                }
+            }
+
+            for timestamp in 0..(START_TIMESTAMP + (PERIOD_DURATION * NUM_PERIODS + 1) as i64) {
+                let initial_unvested = schedule.get_unvested_balance(timestamp, None).unwrap();
+                let remaining_unvested = remaining_schedule
+                    .get_unvested_balance(timestamp, None)
+                    .unwrap();
+                let transferred_unvested = transferred_schedule
+                    .get_unvested_balance(timestamp, None)
+                    .unwrap();
+
+                assert!(
+                    initial_unvested.saturating_sub(2)
+                        <= (remaining_unvested + transferred_unvested)
+                        && (remaining_unvested + transferred_unvested) <= initial_unvested
+                );
+
+                if initial_unvested <= total {
+                    assert!(transferred_unvested <= transferred);
+                    assert!(remaining_unvested <= received);
+                }
+            }
+        }
+
+        TestResult::passed()
+    }
+
     fn test_split_helper(
         transferred: u64,
         total: u64,
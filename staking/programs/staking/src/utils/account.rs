@@ -0,0 +1,65 @@
+use {
+    crate::{
+        context::POOL_CONFIG_POOL_DATA_OFFSET,
+        error::ErrorCode,
+    },
+    anchor_lang::prelude::*,
+    std::convert::TryInto,
+};
+
+/// Makes `account` a rent exempt account of at least `space` bytes owned by `owner`. The account
+/// is created if it doesn't exist and grown if it's smaller. The rent is topped up first, the
+/// account might have been funded before it was created.
+pub fn create_or_grow_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    owner: &Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let lamports = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(account.lamports());
+    if lamports > 0 {
+        let cpi_accounts = anchor_lang::system_program::Transfer {
+            from: payer.clone(),
+            to:   account.clone(),
+        };
+        let cpi_ctx = CpiContext::new(system_program.clone(), cpi_accounts);
+        anchor_lang::system_program::transfer(cpi_ctx, lamports)?;
+    }
+
+    if account.data_is_empty() {
+        let cpi_accounts = anchor_lang::system_program::Allocate {
+            account_to_allocate: account.clone(),
+        };
+        let cpi_ctx =
+            CpiContext::new(system_program.clone(), cpi_accounts).with_signer(signer_seeds);
+        anchor_lang::system_program::allocate(cpi_ctx, space.try_into()?)?;
+
+        let cpi_accounts = anchor_lang::system_program::Assign {
+            account_to_assign: account.clone(),
+        };
+        let cpi_ctx =
+            CpiContext::new(system_program.clone(), cpi_accounts).with_signer(signer_seeds);
+        anchor_lang::system_program::assign(cpi_ctx, owner)?;
+    } else if account.data_len() < space {
+        account.realloc(space, false)?;
+    }
+    Ok(())
+}
+
+/// Checks that `pool_data` is the pool data of `pool_config`, an account of the pool authority's
+/// program that stores the address of the pool data.
+pub fn check_pool_data(pool_config: &AccountInfo, pool_data: &AccountInfo) -> Result<()> {
+    require!(
+        pool_data.owner == pool_config.owner
+            && pool_config
+                .try_borrow_data()?
+                .get(POOL_CONFIG_POOL_DATA_OFFSET..POOL_CONFIG_POOL_DATA_OFFSET + 32)
+                == Some(pool_data.key.as_ref()),
+        ErrorCode::InvalidPoolData
+    );
+    Ok(())
+}
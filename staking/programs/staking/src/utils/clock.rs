@@ -13,17 +13,102 @@ use {
 pub const UNLOCKING_DURATION: u64 = 1; // 1 epoch
 
 /// Computes Pyth clock.
-/// Right now it's just the current Unix timestamp divided by the epoch length
+/// Epochs are `epoch_duration` long, counting from `epoch_duration_change_time` which is the start
+/// of epoch `epoch_offset`. Until the epoch duration is changed both are 0, so the epoch is the
+/// current Unix timestamp divided by the epoch length.
 pub fn get_current_epoch(global_config: &GlobalConfig) -> Result<u64> {
     let now_ts: i64 = get_current_time(global_config);
     time_to_epoch(global_config, now_ts)
 }
 
+/// Times before the last change of the epoch duration use the previous epoch duration, times
+/// before the change preceding it can't be mapped to an epoch anymore.
 pub fn time_to_epoch(global_config: &GlobalConfig, now_ts: UnixTimestamp) -> Result<u64> {
-    TryInto::<u64>::try_into(now_ts)
-        .map_err(|_| ErrorCode::GenericOverflow)?
-        .checked_div(global_config.epoch_duration)
-        .ok_or_else(|| error!(ErrorCode::ZeroEpochDuration))
+    if now_ts >= global_config.epoch_duration_change_time {
+        return time_to_epoch_since_change(
+            global_config.epoch_duration,
+            global_config.epoch_offset,
+            global_config.epoch_duration_change_time,
+            now_ts,
+        );
+    }
+
+    require_gte!(
+        now_ts,
+        global_config.previous_epoch_duration_change_time,
+        ErrorCode::TimeBeforeEpochDurationChange
+    );
+    time_to_epoch_since_change(
+        global_config.previous_epoch_duration,
+        global_config.previous_epoch_offset,
+        global_config.previous_epoch_duration_change_time,
+        now_ts,
+    )
+}
+
+fn time_to_epoch_since_change(
+    epoch_duration: u64,
+    epoch_offset: u64,
+    epoch_duration_change_time: UnixTimestamp,
+    now_ts: UnixTimestamp,
+) -> Result<u64> {
+    let epoch_duration = get_epoch_duration(epoch_duration)?;
+    let elapsed_epochs = now_ts
+        .checked_sub(epoch_duration_change_time)
+        .ok_or_else(|| error!(ErrorCode::GenericOverflow))?
+        .div_euclid(epoch_duration);
+
+    TryInto::<i64>::try_into(epoch_offset)
+        .ok()
+        .and_then(|epoch_offset| epoch_offset.checked_add(elapsed_epochs))
+        .and_then(|epoch| TryInto::<u64>::try_into(epoch).ok())
+        .ok_or_else(|| error!(ErrorCode::GenericOverflow))
+}
+
+/// Epochs before the last change of the epoch duration use the previous epoch duration.
+pub fn epoch_to_time(global_config: &GlobalConfig, epoch: u64) -> Result<UnixTimestamp> {
+    if epoch >= global_config.epoch_offset {
+        return epoch_to_time_since_change(
+            global_config.epoch_duration,
+            global_config.epoch_offset,
+            global_config.epoch_duration_change_time,
+            epoch,
+        );
+    }
+
+    epoch_to_time_since_change(
+        global_config.previous_epoch_duration,
+        global_config.previous_epoch_offset,
+        global_config.previous_epoch_duration_change_time,
+        epoch,
+    )
+}
+
+fn epoch_to_time_since_change(
+    epoch_duration: u64,
+    epoch_offset: u64,
+    epoch_duration_change_time: UnixTimestamp,
+    epoch: u64,
+) -> Result<UnixTimestamp> {
+    let epoch_duration = get_epoch_duration(epoch_duration)?;
+    let epoch = TryInto::<i64>::try_into(epoch).map_err(|_| ErrorCode::GenericOverflow)?;
+    let epoch_offset =
+        TryInto::<i64>::try_into(epoch_offset).map_err(|_| ErrorCode::GenericOverflow)?;
+
+    epoch
+        .checked_sub(epoch_offset)
+        .and_then(|elapsed_epochs| elapsed_epochs.checked_mul(epoch_duration))
+        .and_then(|elapsed_time| elapsed_time.checked_add(epoch_duration_change_time))
+        .ok_or_else(|| error!(ErrorCode::GenericOverflow))
+}
+
+fn get_epoch_duration(epoch_duration: u64) -> Result<i64> {
+    match epoch_duration {
+        0 => err!(ErrorCode::ZeroEpochDuration),
+        epoch_duration => {
+            TryInto::<i64>::try_into(epoch_duration).map_err(|_| error!(ErrorCode::GenericOverflow))
+        }
+    }
 }
 
 // As an extra form of defense to make sure we're not using the mock clock
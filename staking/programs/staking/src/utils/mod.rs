@@ -1,3 +1,4 @@
+pub mod account;
 pub mod clock;
 pub mod risk;
 pub mod voter_weight;
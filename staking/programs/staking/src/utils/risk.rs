@@ -3,6 +3,7 @@ use {
         state::positions::{
             DynamicPositionArray,
             Target,
+            UnlockingDurations,
         },
         ErrorCode::{
             TokensNotYetVested,
@@ -24,11 +25,15 @@ pub fn validate(
     total_balance: u64,
     unvested_balance: u64,
     current_epoch: u64,
+    unlocking_durations: UnlockingDurations,
 ) -> Result<u64> {
-    let governance_exposure: u64 =
-        stake_account_positions.get_target_exposure(&Target::Voting, current_epoch)?;
+    let governance_exposure: u64 = stake_account_positions.get_target_exposure(
+        &Target::Voting,
+        current_epoch,
+        unlocking_durations,
+    )?;
     let integrity_pool_exposure: u64 =
-        stake_account_positions.get_target_exposure(&Target::IntegrityPool, current_epoch)?;
+        stake_account_positions.get_integrity_pool_exposure(current_epoch, unlocking_durations)?;
 
     let vested_balance = total_balance
         .checked_sub(unvested_balance)
@@ -71,7 +76,9 @@ pub mod tests {
                 DynamicPositionArrayAccount,
                 Position,
                 PositionState,
+                RedelegationMarker,
                 TargetWithParameters,
+                UnlockingDurations,
             },
             utils::risk::validate,
         },
@@ -118,17 +125,27 @@ pub mod tests {
                 pd.read_position(0)
                     .unwrap()
                     .unwrap()
-                    .get_current_position(current_epoch)
+                    .get_current_position(current_epoch, UnlockingDurations::default())
                     .unwrap(),
                 desired_state
             );
-            assert_eq!(validate(&pd, 15, 0, current_epoch).unwrap(), 5); // 10 staked
-            assert_eq!(validate(&pd, 10, 0, current_epoch).unwrap(), 0); // 10 staked, the limit
-            assert_eq!(validate(&pd, 13, 3, current_epoch).unwrap(), 0); // 3 locked, 10 staked
-            assert!(validate(&pd, 9, 0, current_epoch).is_err()); // 9 tokens but needs 10 staked, should fail
-            assert!(validate(&pd, 13, 4, current_epoch).is_err()); // 4 locked, 9 unlocked but
-                                                                   // needs 10 for staking,
-                                                                   // should fail
+            assert_eq!(
+                validate(&pd, 15, 0, current_epoch, UnlockingDurations::default()).unwrap(),
+                5
+            ); // 10 staked
+            assert_eq!(
+                validate(&pd, 10, 0, current_epoch, UnlockingDurations::default()).unwrap(),
+                0
+            ); // 10 staked, the limit
+            assert_eq!(
+                validate(&pd, 13, 3, current_epoch, UnlockingDurations::default()).unwrap(),
+                0
+            ); // 3 locked, 10 staked
+
+            // 9 tokens but needs 10 staked, should fail
+            assert!(validate(&pd, 9, 0, current_epoch, UnlockingDurations::default()).is_err());
+            // 4 locked, 9 unlocked but needs 10 for staking, should fail
+            assert!(validate(&pd, 13, 4, current_epoch, UnlockingDurations::default()).is_err());
         }
 
         let (current_epoch, desired_state) = (51u64, PositionState::UNLOCKED);
@@ -136,15 +153,117 @@ pub mod tests {
             pd.read_position(0)
                 .unwrap()
                 .unwrap()
-                .get_current_position(current_epoch)
+                .get_current_position(current_epoch, UnlockingDurations::default())
                 .unwrap(),
             desired_state
         );
-        assert_eq!(validate(&pd, 15, 0, current_epoch).unwrap(), 15);
-        assert_eq!(validate(&pd, 10, 0, current_epoch).unwrap(), 10);
-        assert_eq!(validate(&pd, 13, 3, current_epoch).unwrap(), 10);
-        assert_eq!(validate(&pd, 9, 0, current_epoch).unwrap(), 9);
-        assert_eq!(validate(&pd, 13, 4, current_epoch).unwrap(), 9);
+        assert_eq!(
+            validate(&pd, 15, 0, current_epoch, UnlockingDurations::default()).unwrap(),
+            15
+        );
+        assert_eq!(
+            validate(&pd, 10, 0, current_epoch, UnlockingDurations::default()).unwrap(),
+            10
+        );
+        assert_eq!(
+            validate(&pd, 13, 3, current_epoch, UnlockingDurations::default()).unwrap(),
+            10
+        );
+        assert_eq!(
+            validate(&pd, 9, 0, current_epoch, UnlockingDurations::default()).unwrap(),
+            9
+        );
+        assert_eq!(
+            validate(&pd, 13, 4, current_epoch, UnlockingDurations::default()).unwrap(),
+            9
+        );
+    }
+
+    #[test]
+    fn test_redelegated() {
+        let mut fixture = DynamicPositionArrayAccount::default();
+        let mut pd = fixture.to_dynamic_position_array();
+        // 10 tokens redelegated at epoch 4 and 7 of them redelegated again at epoch 5
+        pd.write_position(
+            0,
+            &Position {
+                activation_epoch:       1,
+                amount:                 10,
+                target_with_parameters: TargetWithParameters::IntegrityPool {
+                    publisher: Pubkey::new_unique(),
+                },
+                unlocking_start:        Some(5),
+            },
+        )
+        .unwrap();
+        pd.write_position(
+            1,
+            &Position {
+                activation_epoch:       5,
+                amount:                 3,
+                target_with_parameters: TargetWithParameters::IntegrityPool {
+                    publisher: Pubkey::new_unique(),
+                },
+                unlocking_start:        None,
+            },
+        )
+        .unwrap();
+        pd.write_position(
+            2,
+            &Position {
+                activation_epoch:       5,
+                amount:                 7,
+                target_with_parameters: TargetWithParameters::IntegrityPool {
+                    publisher: Pubkey::new_unique(),
+                },
+                unlocking_start:        Some(6),
+            },
+        )
+        .unwrap();
+        pd.write_position(
+            3,
+            &Position {
+                activation_epoch:       6,
+                amount:                 7,
+                target_with_parameters: TargetWithParameters::IntegrityPool {
+                    publisher: Pubkey::new_unique(),
+                },
+                unlocking_start:        None,
+            },
+        )
+        .unwrap();
+
+        // without the redelegation markers, the positions are unrelated
+        assert!(validate(&pd, 10, 0, 5, UnlockingDurations::default()).is_err());
+
+        for (i, unlocking, activating) in [
+            (0, true, false),
+            (1, false, true),
+            (2, true, true),
+            (3, false, true),
+        ] {
+            pd.write_redelegation_marker(
+                i,
+                RedelegationMarker {
+                    unlocking,
+                    activating,
+                },
+            )
+            .unwrap();
+        }
+
+        // the new positions take the place of the old ones, so 10 tokens are enough
+        for current_epoch in 4..8 {
+            assert_eq!(
+                validate(&pd, 10, 0, current_epoch, UnlockingDurations::default()).unwrap(),
+                0
+            );
+            assert_eq!(
+                validate(&pd, 15, 0, current_epoch, UnlockingDurations::default()).unwrap(),
+                5
+            );
+            assert!(validate(&pd, 9, 0, current_epoch, UnlockingDurations::default()).is_err());
+        }
     }
 
     #[test]
@@ -175,12 +294,21 @@ pub mod tests {
         )
         .unwrap();
         let current_epoch = 44;
-        assert_eq!(validate(&pd, 10, 0, current_epoch).unwrap(), 3);
-        assert_eq!(validate(&pd, 7, 0, current_epoch).unwrap(), 0);
-        assert_eq!(validate(&pd, 7, 4, current_epoch).unwrap(), 0);
-        assert!(validate(&pd, 6, 0, current_epoch).is_err());
+        assert_eq!(
+            validate(&pd, 10, 0, current_epoch, UnlockingDurations::default()).unwrap(),
+            3
+        );
+        assert_eq!(
+            validate(&pd, 7, 0, current_epoch, UnlockingDurations::default()).unwrap(),
+            0
+        );
+        assert_eq!(
+            validate(&pd, 7, 4, current_epoch, UnlockingDurations::default()).unwrap(),
+            0
+        );
+        assert!(validate(&pd, 6, 0, current_epoch, UnlockingDurations::default()).is_err());
         // only 2 vested:
-        assert!(validate(&pd, 10, 8, current_epoch).is_err());
+        assert!(validate(&pd, 10, 8, current_epoch, UnlockingDurations::default()).is_err());
     }
     #[test]
     fn test_double_integrity_pool() {
@@ -212,11 +340,17 @@ pub mod tests {
         )
         .unwrap();
         let current_epoch = 44;
-        assert_eq!(validate(&pd, 10, 0, current_epoch).unwrap(), 0);
-        assert_eq!(validate(&pd, 12, 0, current_epoch).unwrap(), 2);
-        assert!(validate(&pd, 12, 4, current_epoch).is_err());
-        assert!(validate(&pd, 9, 0, current_epoch).is_err());
-        assert!(validate(&pd, 20, 11, current_epoch).is_err());
+        assert_eq!(
+            validate(&pd, 10, 0, current_epoch, UnlockingDurations::default()).unwrap(),
+            0
+        );
+        assert_eq!(
+            validate(&pd, 12, 0, current_epoch, UnlockingDurations::default()).unwrap(),
+            2
+        );
+        assert!(validate(&pd, 12, 4, current_epoch, UnlockingDurations::default()).is_err());
+        assert!(validate(&pd, 9, 0, current_epoch, UnlockingDurations::default()).is_err());
+        assert!(validate(&pd, 20, 11, current_epoch, UnlockingDurations::default()).is_err());
     }
     #[test]
     fn test_multiple_integrity_pool() {
@@ -237,7 +371,10 @@ pub mod tests {
             .unwrap();
         }
         let current_epoch = 44;
-        assert_eq!(validate(&pd, 50, 0, current_epoch).unwrap(), 0);
+        assert_eq!(
+            validate(&pd, 50, 0, current_epoch, UnlockingDurations::default()).unwrap(),
+            0
+        );
         // Now we have 6 integrity pool positions, so 50 tokens is not enough
         pd.write_position(
             7,
@@ -251,10 +388,16 @@ pub mod tests {
             },
         )
         .unwrap();
-        assert!(validate(&pd, 50, 0, current_epoch).is_err());
+        assert!(validate(&pd, 50, 0, current_epoch, UnlockingDurations::default()).is_err());
         // But 60 should be
-        assert_eq!(validate(&pd, 60, 0, current_epoch).unwrap(), 0);
-        assert_eq!(validate(&pd, 65, 0, current_epoch).unwrap(), 5);
+        assert_eq!(
+            validate(&pd, 60, 0, current_epoch, UnlockingDurations::default()).unwrap(),
+            0
+        );
+        assert_eq!(
+            validate(&pd, 65, 0, current_epoch, UnlockingDurations::default()).unwrap(),
+            5
+        );
     }
     #[test]
     fn test_multiple_voting() {
@@ -273,10 +416,19 @@ pub mod tests {
             .unwrap();
         }
         let current_epoch = 44;
-        assert_eq!(validate(&pd, 100, 0, current_epoch).unwrap(), 50);
-        assert_eq!(validate(&pd, 50, 0, current_epoch).unwrap(), 0);
-        assert_eq!(validate(&pd, 60, 51, current_epoch).unwrap(), 9);
-        assert!(validate(&pd, 49, 0, current_epoch).is_err());
+        assert_eq!(
+            validate(&pd, 100, 0, current_epoch, UnlockingDurations::default()).unwrap(),
+            50
+        );
+        assert_eq!(
+            validate(&pd, 50, 0, current_epoch, UnlockingDurations::default()).unwrap(),
+            0
+        );
+        assert_eq!(
+            validate(&pd, 60, 51, current_epoch, UnlockingDurations::default()).unwrap(),
+            9
+        );
+        assert!(validate(&pd, 49, 0, current_epoch, UnlockingDurations::default()).is_err());
     }
 
     #[test]
@@ -297,7 +449,14 @@ pub mod tests {
         }
         // Overflows in the total governance computation
         let current_epoch = 44;
-        assert!(validate(&pd, u64::MAX, 0, current_epoch).is_err());
+        assert!(validate(
+            &pd,
+            u64::MAX,
+            0,
+            current_epoch,
+            UnlockingDurations::default()
+        )
+        .is_err());
     }
 
     #[test]
@@ -320,7 +479,14 @@ pub mod tests {
         }
         let current_epoch = 44;
         // Overflows in the aggregation computation
-        assert!(validate(&pd, u64::MAX, 0, current_epoch).is_err());
+        assert!(validate(
+            &pd,
+            u64::MAX,
+            0,
+            current_epoch,
+            UnlockingDurations::default()
+        )
+        .is_err());
     }
 
     #[test]
@@ -374,11 +540,23 @@ pub mod tests {
         )
         .unwrap();
 
-        assert_eq!(validate(&pd, 10, 6, current_epoch).unwrap(), 0);
-        assert_eq!(validate(&pd, 10, 4, current_epoch).unwrap(), 0);
-        assert_eq!(validate(&pd, 11, 7, current_epoch).unwrap(), 0);
-        assert_eq!(validate(&pd, 11, 6, current_epoch).unwrap(), 1);
-        assert!(validate(&pd, 10, 7, current_epoch).is_err()); // breaks the integrity pool inequality
-        assert!(validate(&pd, 4, 0, current_epoch).is_err()); // breaks the voting inequality
+        assert_eq!(
+            validate(&pd, 10, 6, current_epoch, UnlockingDurations::default()).unwrap(),
+            0
+        );
+        assert_eq!(
+            validate(&pd, 10, 4, current_epoch, UnlockingDurations::default()).unwrap(),
+            0
+        );
+        assert_eq!(
+            validate(&pd, 11, 7, current_epoch, UnlockingDurations::default()).unwrap(),
+            0
+        );
+        assert_eq!(
+            validate(&pd, 11, 6, current_epoch, UnlockingDurations::default()).unwrap(),
+            1
+        );
+        assert!(validate(&pd, 10, 7, current_epoch, UnlockingDurations::default()).is_err()); // breaks the integrity pool inequality
+        assert!(validate(&pd, 4, 0, current_epoch, UnlockingDurations::default()).is_err()); // breaks the voting inequality
     }
 }
@@ -4,6 +4,7 @@ use {
         state::positions::{
             DynamicPositionArray,
             PositionState,
+            UnlockingDurations,
         },
     },
     anchor_lang::prelude::*,
@@ -15,11 +16,15 @@ pub fn compute_voter_weight(
     current_epoch: u64,
     current_locked: u64,
     total_supply: u64,
+    unlocking_durations: UnlockingDurations,
 ) -> Result<u64> {
     let mut raw_voter_weight = 0u64;
     for i in 0..stake_account_positions.get_position_capacity() {
         if let Some(position) = stake_account_positions.read_position(i)? {
-            match position.get_current_position(current_epoch)? {
+            match position.get_current_position(
+                current_epoch,
+                stake_account_positions.get_unlocking_durations(i, unlocking_durations)?,
+            )? {
                 PositionState::LOCKED | PositionState::PREUNLOCKING => {
                     if position.is_voting() {
                         // position.amount is trusted, so I don't think this can overflow,
@@ -47,6 +52,7 @@ pub mod tests {
                 DynamicPositionArrayAccount,
                 Position,
                 TargetWithParameters,
+                UnlockingDurations,
             },
             utils::voter_weight::compute_voter_weight,
         },
@@ -103,19 +109,19 @@ pub mod tests {
         )
         .unwrap();
 
-        let weight = compute_voter_weight(&pd, 0, 100, 150).unwrap();
+        let weight = compute_voter_weight(&pd, 0, 100, 150, UnlockingDurations::default()).unwrap();
         assert_eq!(weight, 0);
 
-        let weight = compute_voter_weight(&pd, 1, 100, 150).unwrap();
+        let weight = compute_voter_weight(&pd, 1, 100, 150, UnlockingDurations::default()).unwrap();
         assert_eq!(weight, 7 * 150 / 100);
 
-        let weight = compute_voter_weight(&pd, 2, 100, 150).unwrap();
+        let weight = compute_voter_weight(&pd, 2, 100, 150, UnlockingDurations::default()).unwrap();
         assert_eq!(weight, 12 * 150 / 100);
 
-        let weight = compute_voter_weight(&pd, 3, 100, 150).unwrap();
+        let weight = compute_voter_weight(&pd, 3, 100, 150, UnlockingDurations::default()).unwrap();
         assert_eq!(weight, 8 * 150 / 100);
 
-        let weight = compute_voter_weight(&pd, 4, 100, 150).unwrap();
+        let weight = compute_voter_weight(&pd, 4, 100, 150, UnlockingDurations::default()).unwrap();
         assert_eq!(weight, 3 * 150 / 100);
     }
 
@@ -134,7 +140,14 @@ pub mod tests {
         )
         .unwrap();
 
-        let weight = compute_voter_weight(&pd, 1, u64::MAX / 2, u64::MAX).unwrap();
+        let weight = compute_voter_weight(
+            &pd,
+            1,
+            u64::MAX / 2,
+            u64::MAX,
+            UnlockingDurations::default(),
+        )
+        .unwrap();
         assert_eq!(weight, u64::MAX);
     }
 
@@ -153,7 +166,8 @@ pub mod tests {
         )
         .unwrap();
 
-        let weight = compute_voter_weight(&pd, 1, 0, u64::MAX).unwrap();
+        let weight =
+            compute_voter_weight(&pd, 1, 0, u64::MAX, UnlockingDurations::default()).unwrap();
         assert_eq!(weight, 0);
     }
 }
@@ -3,11 +3,14 @@ use {
     crate::{
         error::ErrorCode,
         state::{
+            global_config::GlobalConfig,
             max_voter_weight_record::MAX_VOTER_WEIGHT,
             positions::{
                 DynamicPositionArrayAccount,
+                LockedBalanceSummary,
                 PositionData,
                 PositionState,
+                UnlockingDurations,
             },
             target::TargetMetadata,
             vesting::VestingEvent,
@@ -32,14 +35,6 @@ pub struct WasmPositionData {
     wrapped: Vec<u8>,
 }
 
-#[wasm_bindgen]
-pub struct LockedBalanceSummary {
-    pub locking:      u64,
-    pub locked:       u64,
-    pub unlocking:    u64,
-    pub preunlocking: u64,
-}
-
 
 #[wasm_bindgen]
 impl WasmPositionData {
@@ -50,25 +45,37 @@ impl WasmPositionData {
         })
     }
 
+    /// The unlocking durations are the ones of the global config, they can be omitted if they
+    /// aren't set
     #[wasm_bindgen(js_name=getPositionState)]
     pub fn get_position_state(
         &self,
         index: u16,
         current_epoch: u64,
+        voting_unlocking_duration: Option<u64>,
+        integrity_pool_unlocking_duration: Option<u64>,
     ) -> Result<PositionState, JsValue> {
-        convert_error(self.get_position_state_impl(index, current_epoch))
+        convert_error(self.get_position_state_impl(
+            index,
+            current_epoch,
+            UnlockingDurations::new(voting_unlocking_duration, integrity_pool_unlocking_duration),
+        ))
     }
     fn get_position_state_impl(
         &self,
         index: u16,
         current_epoch: u64,
+        unlocking_durations: UnlockingDurations,
     ) -> anchor_lang::Result<PositionState> {
         let mut account = DynamicPositionArrayAccount::default_with_data(&self.wrapped);
-        account
-            .to_dynamic_position_array()
+        let position_array = account.to_dynamic_position_array();
+        position_array
             .read_position(index as usize)?
             .ok_or_else(|| error!(ErrorCode::PositionNotInUse))?
-            .get_current_position(current_epoch)
+            .get_current_position(
+                current_epoch,
+                position_array.get_unlocking_durations(index as usize, unlocking_durations)?,
+            )
     }
     #[wasm_bindgen(js_name=isPositionVoting)]
     pub fn is_position_voting(&self, index: u16) -> Result<bool, JsValue> {
@@ -91,61 +98,34 @@ impl WasmPositionData {
     pub fn get_locked_balance_summary(
         &self,
         current_epoch: u64,
+        voting_unlocking_duration: Option<u64>,
+        integrity_pool_unlocking_duration: Option<u64>,
     ) -> Result<LockedBalanceSummary, JsValue> {
-        convert_error(self.get_locked_balance_summary_impl(current_epoch))
+        convert_error(self.get_locked_balance_summary_impl(
+            current_epoch,
+            UnlockingDurations::new(voting_unlocking_duration, integrity_pool_unlocking_duration),
+        ))
     }
     fn get_locked_balance_summary_impl(
         &self,
         current_epoch: u64,
+        unlocking_durations: UnlockingDurations,
     ) -> anchor_lang::Result<LockedBalanceSummary> {
         let mut account = DynamicPositionArrayAccount::default_with_data(&self.wrapped);
-        let positions = account.to_dynamic_position_array();
-
-        let mut locking: u64 = 0;
-        let mut locked: u64 = 0;
-        let mut unlocking: u64 = 0;
-        let mut preunlocking: u64 = 0;
-
-        for i in 0..positions.get_position_capacity() {
-            if let Some(position) = positions.read_position(i)? {
-                match position.get_current_position(current_epoch)? {
-                    PositionState::LOCKING => {
-                        locking = locking
-                            .checked_add(position.amount)
-                            .ok_or(error!(ErrorCode::GenericOverflow))?;
-                    }
-                    PositionState::LOCKED => {
-                        locked = locked
-                            .checked_add(position.amount)
-                            .ok_or(error!(ErrorCode::GenericOverflow))?;
-                    }
-                    PositionState::PREUNLOCKING => {
-                        preunlocking = preunlocking
-                            .checked_add(position.amount)
-                            .ok_or(error!(ErrorCode::GenericOverflow))?;
-                    }
-                    PositionState::UNLOCKING => {
-                        unlocking = unlocking
-                            .checked_add(position.amount)
-                            .ok_or(error!(ErrorCode::GenericOverflow))?;
-                    }
-                    _ => {}
-                }
-            }
-        }
-        Ok(LockedBalanceSummary {
-            locking,
-            locked,
-            unlocking,
-            preunlocking,
-        })
+        account
+            .to_dynamic_position_array()
+            .get_locked_balance_summary(current_epoch, unlocking_durations)
     }
 
+    /// The unlocking durations are the ones of the global config, they can be omitted if they
+    /// aren't set
     #[wasm_bindgen(js_name=getVoterWeight)]
     pub fn get_voter_weight(
         &self,
         current_epoch: u64,
         current_locked: u64,
+        voting_unlocking_duration: Option<u64>,
+        integrity_pool_unlocking_duration: Option<u64>,
     ) -> Result<u64, JsValue> {
         let mut account = DynamicPositionArrayAccount::default_with_data(&self.wrapped);
         convert_error(crate::utils::voter_weight::compute_voter_weight(
@@ -153,6 +133,7 @@ impl WasmPositionData {
             current_epoch,
             current_locked,
             MAX_VOTER_WEIGHT,
+            UnlockingDurations::new(voting_unlocking_duration, integrity_pool_unlocking_duration),
         ))
     }
 }
@@ -226,6 +207,30 @@ fn get_unvested_balance_impl(
     vs.get_unvested_balance(current_time, tokenListingTime)
 }
 
+/// Maps a Unix time to an epoch with the epoch parameters of the global config (globalConfigBorsh),
+/// they change when governance updates the epoch duration
+#[wasm_bindgen(js_name=timeToEpoch)]
+pub fn time_to_epoch(globalConfigBorsh: &[u8], unixTime: i64) -> Result<u64, JsValue> {
+    convert_error(time_to_epoch_impl(globalConfigBorsh, unixTime))
+}
+fn time_to_epoch_impl(global_config_borsh: &[u8], unix_time: i64) -> anchor_lang::Result<u64> {
+    let mut ptr = global_config_borsh;
+    let global_config = GlobalConfig::deserialize(&mut ptr)?;
+    crate::utils::clock::time_to_epoch(&global_config, unix_time)
+}
+
+/// Returns the Unix time at which an epoch starts with the epoch parameters of the global config
+/// (globalConfigBorsh)
+#[wasm_bindgen(js_name=epochToTime)]
+pub fn epoch_to_time(globalConfigBorsh: &[u8], epoch: u64) -> Result<i64, JsValue> {
+    convert_error(epoch_to_time_impl(globalConfigBorsh, epoch))
+}
+fn epoch_to_time_impl(global_config_borsh: &[u8], epoch: u64) -> anchor_lang::Result<i64> {
+    let mut ptr = global_config_borsh;
+    let global_config = GlobalConfig::deserialize(&mut ptr)?;
+    crate::utils::clock::epoch_to_time(&global_config, epoch)
+}
+
 #[wasm_bindgen(js_name=getUnixTime)]
 /// Deserializes the contents of the SYSVAR_CLOCK account (onChainSerialized), returning the
 /// Unix time field
@@ -291,6 +296,22 @@ impl Constants {
     pub fn POSITION_BUFFER_SIZE() -> usize {
         crate::state::positions::POSITION_BUFFER_SIZE
     }
+    #[wasm_bindgen]
+    pub fn POSITION_BUFFER_SIZE_V2() -> usize {
+        crate::state::positions::POSITION_BUFFER_SIZE_V2
+    }
+    #[wasm_bindgen]
+    pub fn POSITIONS_V2_VERSION() -> u8 {
+        crate::state::positions::POSITIONS_V2_VERSION
+    }
+    #[wasm_bindgen]
+    pub fn POSITIONS_V2_HEADER_LEN() -> usize {
+        crate::state::positions::POSITIONS_V2_HEADER_LEN
+    }
+    #[wasm_bindgen]
+    pub fn MAX_VESTING_TRANCHES() -> usize {
+        crate::state::vesting::MAX_VESTING_TRANCHES
+    }
 }
 
 #[wasm_bindgen]
@@ -30,7 +30,8 @@ describe("config", async () => {
   const config = readAnchorConfig(ANCHOR_CONFIG_PATH);
   const pdaAuthority = pdaAuthorityKeypair.publicKey;
   const governanceProgram = new PublicKey(config.programs.localnet.governance);
-  const poolAuthority = PublicKey.unique();
+  const poolAuthorityKeypair = new Keypair();
+  const poolAuthority = poolAuthorityKeypair.publicKey;
 
   let program: Program<Staking>;
   let controller: CustomAbortController;
@@ -75,6 +76,16 @@ describe("config", async () => {
         agreementHash: getDummyAgreementHash(),
         mockClockTime: new BN(10),
         poolAuthority,
+        votingUnlockingDuration: null,
+        integrityPoolUnlockingDuration: null,
+        epochOffset: new BN(0),
+        epochDurationChangeTime: new BN(0),
+        previousEpochDuration: new BN(0),
+        previousEpochOffset: new BN(0),
+        previousEpochDurationChangeTime: new BN(0),
+        pendingGovernanceAuthority: null,
+        pendingPdaAuthority: null,
+        pendingPoolAuthority: null,
       })
       .rpc();
 
@@ -108,6 +119,16 @@ describe("config", async () => {
         agreementHash: getDummyAgreementHash(),
         mockClockTime: new BN(10),
         poolAuthority,
+        votingUnlockingDuration: null,
+        integrityPoolUnlockingDuration: null,
+        epochOffset: new BN(0),
+        epochDurationChangeTime: new BN(0),
+        previousEpochDuration: new BN(0),
+        previousEpochOffset: new BN(0),
+        previousEpochDurationChangeTime: new BN(0),
+        pendingGovernanceAuthority: null,
+        pendingPdaAuthority: null,
+        pendingPoolAuthority: null,
       })
     );
   });
@@ -135,6 +156,16 @@ describe("config", async () => {
         agreementHash: getDummyAgreementHash(),
         mockClockTime: new BN(15),
         poolAuthority,
+        votingUnlockingDuration: null,
+        integrityPoolUnlockingDuration: null,
+        epochOffset: new BN(0),
+        epochDurationChangeTime: new BN(0),
+        previousEpochDuration: new BN(0),
+        previousEpochOffset: new BN(0),
+        previousEpochDurationChangeTime: new BN(0),
+        pendingGovernanceAuthority: null,
+        pendingPdaAuthority: null,
+        pendingPoolAuthority: null,
       })
     );
 
@@ -158,6 +189,16 @@ describe("config", async () => {
         agreementHash: getDummyAgreementHash(),
         mockClockTime: new BN(30),
         poolAuthority,
+        votingUnlockingDuration: null,
+        integrityPoolUnlockingDuration: null,
+        epochOffset: new BN(0),
+        epochDurationChangeTime: new BN(0),
+        previousEpochDuration: new BN(0),
+        previousEpochOffset: new BN(0),
+        previousEpochDurationChangeTime: new BN(0),
+        pendingGovernanceAuthority: null,
+        pendingPdaAuthority: null,
+        pendingPoolAuthority: null,
       })
     );
   });
@@ -185,6 +226,16 @@ describe("config", async () => {
         agreementHash: getDummyAgreementHash(),
         mockClockTime: new BN(30),
         poolAuthority,
+        votingUnlockingDuration: null,
+        integrityPoolUnlockingDuration: null,
+        epochOffset: new BN(0),
+        epochDurationChangeTime: new BN(0),
+        previousEpochDuration: new BN(0),
+        previousEpochOffset: new BN(0),
+        previousEpochDurationChangeTime: new BN(0),
+        pendingGovernanceAuthority: null,
+        pendingPdaAuthority: null,
+        pendingPoolAuthority: null,
       })
     );
 
@@ -208,6 +259,16 @@ describe("config", async () => {
         agreementHash: getDummyAgreementHash(),
         mockClockTime: new BN(30),
         poolAuthority,
+        votingUnlockingDuration: null,
+        integrityPoolUnlockingDuration: null,
+        epochOffset: new BN(0),
+        epochDurationChangeTime: new BN(0),
+        previousEpochDuration: new BN(0),
+        previousEpochOffset: new BN(0),
+        previousEpochDurationChangeTime: new BN(0),
+        pendingGovernanceAuthority: null,
+        pendingPdaAuthority: null,
+        pendingPoolAuthority: null,
       })
     );
   });
@@ -233,6 +294,16 @@ describe("config", async () => {
         agreementHash: getDummyAgreementHash(),
         mockClockTime: new BN(30),
         poolAuthority,
+        votingUnlockingDuration: null,
+        integrityPoolUnlockingDuration: null,
+        epochOffset: new BN(0),
+        epochDurationChangeTime: new BN(0),
+        previousEpochDuration: new BN(0),
+        previousEpochOffset: new BN(0),
+        previousEpochDurationChangeTime: new BN(0),
+        pendingGovernanceAuthority: null,
+        pendingPdaAuthority: null,
+        pendingPoolAuthority: null,
       })
     );
 
@@ -316,7 +387,7 @@ describe("config", async () => {
     // Airdrops are not instant unfortunately, wait
     await new Promise((resolve) => setTimeout(resolve, 2000));
 
-    // pda_authority updates pda_authority to the holder of governance_authority
+    // pda_authority nominates the holder of governance_authority
     await pdaConnection.program.methods
       .updatePdaAuthority(program.provider.publicKey)
       .rpc();
@@ -325,6 +396,43 @@ describe("config", async () => {
       configAccount
     );
 
+    assert.equal(
+      JSON.stringify(configAccountData),
+      JSON.stringify({
+        bump,
+        governanceAuthority: program.provider.publicKey,
+        pythTokenMint: pythMintAccount.publicKey,
+        pythGovernanceRealm,
+        removedUnlockingDuration: 0,
+        epochDuration: new BN(3600),
+        freeze: false,
+        pdaAuthority: pdaAuthority,
+        governanceProgram,
+        pythTokenListTime: null,
+        agreementHash: getDummyAgreementHash(),
+        mockClockTime: new BN(30),
+        poolAuthority,
+        votingUnlockingDuration: null,
+        integrityPoolUnlockingDuration: null,
+        epochOffset: new BN(0),
+        epochDurationChangeTime: new BN(0),
+        previousEpochDuration: new BN(0),
+        previousEpochOffset: new BN(0),
+        previousEpochDurationChangeTime: new BN(0),
+        pendingGovernanceAuthority: null,
+        pendingPdaAuthority: program.provider.publicKey,
+        pendingPoolAuthority: null,
+      })
+    );
+
+    // the nominated authority becomes the pda_authority once it accepts
+    await program.methods
+      .acceptPdaAuthority()
+      .accounts({ newPdaAuthority: program.provider.publicKey })
+      .rpc();
+
+    configAccountData = await program.account.globalConfig.fetch(configAccount);
+
     assert.equal(
       JSON.stringify(configAccountData),
       JSON.stringify({
@@ -341,11 +449,25 @@ describe("config", async () => {
         agreementHash: getDummyAgreementHash(),
         mockClockTime: new BN(30),
         poolAuthority,
+        votingUnlockingDuration: null,
+        integrityPoolUnlockingDuration: null,
+        epochOffset: new BN(0),
+        epochDurationChangeTime: new BN(0),
+        previousEpochDuration: new BN(0),
+        previousEpochOffset: new BN(0),
+        previousEpochDurationChangeTime: new BN(0),
+        pendingGovernanceAuthority: null,
+        pendingPdaAuthority: null,
+        pendingPoolAuthority: null,
       })
     );
 
     // the authority gets returned to the original pda_authority
     await program.methods.updatePdaAuthority(pdaAuthority).rpc();
+    await pdaConnection.program.methods
+      .acceptPdaAuthority()
+      .accounts({ newPdaAuthority: pdaAuthority })
+      .rpc();
 
     configAccountData = await program.account.globalConfig.fetch(configAccount);
 
@@ -365,12 +487,23 @@ describe("config", async () => {
         agreementHash: getDummyAgreementHash(),
         mockClockTime: new BN(30),
         poolAuthority,
+        votingUnlockingDuration: null,
+        integrityPoolUnlockingDuration: null,
+        epochOffset: new BN(0),
+        epochDurationChangeTime: new BN(0),
+        previousEpochDuration: new BN(0),
+        previousEpochOffset: new BN(0),
+        previousEpochDurationChangeTime: new BN(0),
+        pendingGovernanceAuthority: null,
+        pendingPdaAuthority: null,
+        pendingPoolAuthority: null,
       })
     );
   });
 
   it("updates pool authority", async () => {
-    const newPoolAuthority = PublicKey.unique();
+    const newPoolAuthorityKeypair = new Keypair();
+    const newPoolAuthority = newPoolAuthorityKeypair.publicKey;
     await program.methods.updatePoolAuthority(newPoolAuthority).rpc();
 
     let configAccountData = await program.account.globalConfig.fetch(
@@ -387,16 +520,77 @@ describe("config", async () => {
         removedUnlockingDuration: 0,
         epochDuration: new BN(3600),
         freeze: false,
-        pdaAuthority,
+        pdaAuthority: pdaAuthority,
+        governanceProgram,
+        pythTokenListTime: null,
+        agreementHash: getDummyAgreementHash(),
+        mockClockTime: new BN(30),
+        poolAuthority,
+        votingUnlockingDuration: null,
+        integrityPoolUnlockingDuration: null,
+        epochOffset: new BN(0),
+        epochDurationChangeTime: new BN(0),
+        previousEpochDuration: new BN(0),
+        previousEpochOffset: new BN(0),
+        previousEpochDurationChangeTime: new BN(0),
+        pendingGovernanceAuthority: null,
+        pendingPdaAuthority: null,
+        pendingPoolAuthority: newPoolAuthority,
+      })
+    );
+
+    // only the nominated authority can accept
+    await expectFail(
+      program.methods
+        .acceptPoolAuthority()
+        .accounts({ newPoolAuthority: poolAuthority })
+        .signers([poolAuthorityKeypair]),
+      "The signer is not the nominated authority"
+    );
+
+    await program.methods
+      .acceptPoolAuthority()
+      .accounts({ newPoolAuthority })
+      .signers([newPoolAuthorityKeypair])
+      .rpc();
+
+    configAccountData = await program.account.globalConfig.fetch(configAccount);
+
+    assert.equal(
+      JSON.stringify(configAccountData),
+      JSON.stringify({
+        bump,
+        governanceAuthority: program.provider.publicKey,
+        pythTokenMint: pythMintAccount.publicKey,
+        pythGovernanceRealm,
+        removedUnlockingDuration: 0,
+        epochDuration: new BN(3600),
+        freeze: false,
+        pdaAuthority: pdaAuthority,
         governanceProgram,
         pythTokenListTime: null,
         agreementHash: getDummyAgreementHash(),
         mockClockTime: new BN(30),
         poolAuthority: newPoolAuthority,
+        votingUnlockingDuration: null,
+        integrityPoolUnlockingDuration: null,
+        epochOffset: new BN(0),
+        epochDurationChangeTime: new BN(0),
+        previousEpochDuration: new BN(0),
+        previousEpochOffset: new BN(0),
+        previousEpochDurationChangeTime: new BN(0),
+        pendingGovernanceAuthority: null,
+        pendingPdaAuthority: null,
+        pendingPoolAuthority: null,
       })
     );
 
     await program.methods.updatePoolAuthority(poolAuthority).rpc();
+    await program.methods
+      .acceptPoolAuthority()
+      .accounts({ newPoolAuthority: poolAuthority })
+      .signers([poolAuthorityKeypair])
+      .rpc();
 
     configAccountData = await program.account.globalConfig.fetch(configAccount);
 
@@ -410,12 +604,22 @@ describe("config", async () => {
         removedUnlockingDuration: 0,
         epochDuration: new BN(3600),
         freeze: false,
-        pdaAuthority,
+        pdaAuthority: pdaAuthority,
         governanceProgram,
         pythTokenListTime: null,
         agreementHash: getDummyAgreementHash(),
         mockClockTime: new BN(30),
         poolAuthority,
+        votingUnlockingDuration: null,
+        integrityPoolUnlockingDuration: null,
+        epochOffset: new BN(0),
+        epochDurationChangeTime: new BN(0),
+        previousEpochDuration: new BN(0),
+        previousEpochOffset: new BN(0),
+        previousEpochDurationChangeTime: new BN(0),
+        pendingGovernanceAuthority: null,
+        pendingPdaAuthority: null,
+        pendingPoolAuthority: null,
       })
     );
   });
@@ -447,6 +651,16 @@ describe("config", async () => {
         agreementHash: getDummyAgreementHash2(),
         mockClockTime: new BN(30),
         poolAuthority,
+        votingUnlockingDuration: null,
+        integrityPoolUnlockingDuration: null,
+        epochOffset: new BN(0),
+        epochDurationChangeTime: new BN(0),
+        previousEpochDuration: new BN(0),
+        previousEpochOffset: new BN(0),
+        previousEpochDurationChangeTime: new BN(0),
+        pendingGovernanceAuthority: null,
+        pendingPdaAuthority: null,
+        pendingPoolAuthority: null,
       })
     );
   });
@@ -103,7 +103,7 @@ describe("pool authority", async () => {
     let stakeAccount = await stakeConnection.getMainAccount(
       stakeConnection.userPublicKey()
     );
-    assert.equal(stakeAccount.stakeAccountMetadata.nextIndex, 2); // Expecting 2 positions
+    assert.equal(stakeAccount.getNextIndex(), 2); // Expecting 2 positions
     assert.deepEqual(
       stakeAccount.stakeAccountPositionsJs.positions[0].targetWithParameters,
       { voting: {} }
@@ -206,7 +206,7 @@ describe("pool authority", async () => {
     stakeAccount = await stakeConnection.getMainAccount(
       stakeConnection.userPublicKey()
     );
-    assert.equal(stakeAccount.stakeAccountMetadata.nextIndex, 1); // Only 1 position left
+    assert.equal(stakeAccount.getNextIndex(), 1); // Only 1 position left
     assert.deepEqual(
       stakeAccount.stakeAccountPositionsJs.positions[0].targetWithParameters,
       { voting: {} }
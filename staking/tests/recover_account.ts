@@ -76,6 +76,16 @@ describe("recover account", async () => {
         agreementHash: getDummyAgreementHash(),
         mockClockTime: new BN(10),
         poolAuthority: PublicKey.unique(),
+        votingUnlockingDuration: null,
+        integrityPoolUnlockingDuration: null,
+        epochOffset: new BN(0),
+        epochDurationChangeTime: new BN(0),
+        previousEpochDuration: new BN(0),
+        previousEpochOffset: new BN(0),
+        previousEpochDurationChangeTime: new BN(0),
+        pendingGovernanceAuthority: null,
+        pendingPdaAuthority: null,
+        pendingPoolAuthority: null,
       })
       .rpc();
 
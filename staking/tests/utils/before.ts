@@ -381,6 +381,16 @@ export function makeDefaultConfig(
     pdaAuthority,
     agreementHash: getDummyAgreementHash(),
     poolAuthority: PublicKey.unique(),
+    votingUnlockingDuration: null,
+    integrityPoolUnlockingDuration: null,
+    epochOffset: new BN(0),
+    epochDurationChangeTime: new BN(0),
+    previousEpochDuration: new BN(0),
+    previousEpochOffset: new BN(0),
+    previousEpochDurationChangeTime: new BN(0),
+    pendingGovernanceAuthority: null,
+    pendingPdaAuthority: null,
+    pendingPoolAuthority: null,
   };
 }
 
@@ -520,7 +530,8 @@ export async function standardSetup(portNumber: number): Promise<{
     console.log("Lookup table address: ", lookupTableAddress.toBase58());
   }
 
-  // Give the power back to the people
+  // Give the power back to the people, the governance becomes the governance authority once a
+  // proposal accepts the nomination, until then the user stays the governance authority
   await program.methods
     .updateGovernanceAuthority(globalConfig.governanceAuthority)
     .accounts({ governanceSigner: user })